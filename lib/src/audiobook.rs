@@ -0,0 +1,269 @@
+//! Audiobooks split over many files, or in a single `m4b`, played as one book.
+//!
+//! Every `m4b` file is a book by itself, and so is every directory or file directly inside one of
+//! the `audiobook_dir` of the config. How far a book was listened to and its bookmarks are kept in
+//! the music database.
+
+use crate::track::Track;
+use crate::utils::filetype_supported;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Audiobook {
+    /// The directory or single file of the book
+    pub path: String,
+    pub title: String,
+    /// The files in playing order, with their duration
+    pub files: Vec<(String, Duration)>,
+}
+
+/// How far a book was listened to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BookProgress {
+    /// [`Audiobook::path`] of the book
+    pub book: String,
+    pub title: String,
+    /// The file listened to last, and where in it
+    pub file: String,
+    pub position: Duration,
+    /// Time listened up to `position` in the whole book
+    pub elapsed: Duration,
+    pub duration: Duration,
+    /// Seconds since the unix epoch
+    pub last_played: u64,
+}
+
+/// A place in a book the user gave a name to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    /// Set by the database, ignored when adding
+    pub id: i64,
+    /// [`Audiobook::path`] of the book
+    pub book: String,
+    pub name: String,
+    pub file: String,
+    pub position: Duration,
+    /// Seconds since the unix epoch
+    pub created: u64,
+}
+
+impl Audiobook {
+    /// The path of the book `file` belongs to, if it belongs to one.
+    pub fn of_file(file: &str, audiobook_dirs: &[String]) -> Option<PathBuf> {
+        let path = Path::new(file);
+        if is_m4b(path) {
+            return Some(path.to_path_buf());
+        }
+        audiobook_dirs.iter().find_map(|dir| {
+            let dir = PathBuf::from(shellexpand::tilde(dir).as_ref());
+            let first = path.strip_prefix(&dir).ok()?.components().next()?;
+            Some(dir.join(first))
+        })
+    }
+
+    /// Read the files of the book at `path`, as returned by [`Self::of_file`].
+    pub fn open(path: &Path) -> Self {
+        let (title, mut files) = if path.is_dir() {
+            let files: Vec<String> = walkdir::WalkDir::new(path)
+                .follow_links(true)
+                .into_iter()
+                .filter_map(std::result::Result::ok)
+                .filter(|f| f.file_type().is_file())
+                .map(|f| f.path().to_string_lossy().to_string())
+                .filter(|f| filetype_supported(f))
+                .collect();
+            (path.file_name(), files)
+        } else {
+            (path.file_stem(), vec![path.to_string_lossy().to_string()])
+        };
+        files.sort_by(|a, b| natural_cmp(a, b));
+
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let duration = Track::read_from_path(&file, true)
+                    .map(|track| track.duration())
+                    .unwrap_or_default();
+                (file, duration)
+            })
+            .collect();
+
+        Self {
+            path: path.to_string_lossy().to_string(),
+            title: title.map_or_else(String::new, |t| t.to_string_lossy().to_string()),
+            files,
+        }
+    }
+
+    pub fn contains(&self, file: &str) -> bool {
+        self.files.iter().any(|(f, _)| f == file)
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.files.iter().map(|(_, d)| *d).sum()
+    }
+
+    /// Time listened up to `position` in `file`, counting all the files before it.
+    pub fn elapsed(&self, file: &str, position: Duration) -> Duration {
+        let before: Duration = self
+            .files
+            .iter()
+            .take_while(|(f, _)| f != file)
+            .map(|(_, d)| *d)
+            .sum();
+        before + position
+    }
+
+    /// The progress to store when listening to `position` in `file` right now.
+    pub fn progress(&self, file: &str, position: Duration) -> BookProgress {
+        BookProgress {
+            book: self.path.clone(),
+            title: self.title.clone(),
+            file: file.to_string(),
+            position,
+            elapsed: self.elapsed(file, position),
+            duration: self.duration(),
+            last_played: now(),
+        }
+    }
+}
+
+impl BookProgress {
+    /// Listened part of the book, from 0 to 100.
+    pub fn percent(&self) -> f64 {
+        if self.duration.is_zero() {
+            return 0.0;
+        }
+        (self.elapsed.as_secs_f64() / self.duration.as_secs_f64() * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+impl Bookmark {
+    pub fn new(book: String, name: String, file: String, position: Duration) -> Self {
+        Self {
+            id: 0,
+            book,
+            name,
+            file,
+            position,
+            created: now(),
+        }
+    }
+}
+
+fn is_m4b(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m4b"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Compare the way a person would, with "2" before "10" and ignoring case.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let ordering = match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a);
+                let y = take_number(&mut b);
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                a.next();
+                b.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> String {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        number.push(c);
+    }
+    number
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(files: &[(&str, u64)]) -> Audiobook {
+        Audiobook {
+            path: "/books/Book".to_string(),
+            title: "Book".to_string(),
+            files: files
+                .iter()
+                .map(|(f, secs)| ((*f).to_string(), Duration::from_secs(*secs)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn sorts_naturally() {
+        let mut files = vec![
+            "Part 10.mp3",
+            "part 2.mp3",
+            "Part 1.mp3",
+            "Part 02b.mp3",
+            "Intro.mp3",
+        ];
+        files.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            files,
+            vec![
+                "Intro.mp3",
+                "Part 1.mp3",
+                "part 2.mp3",
+                "Part 02b.mp3",
+                "Part 10.mp3"
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_spans_files() {
+        let book = book(&[("/books/Book/1.mp3", 600), ("/books/Book/2.mp3", 400)]);
+        assert_eq!(book.duration(), Duration::from_secs(1000));
+
+        let progress = book.progress("/books/Book/2.mp3", Duration::from_secs(150));
+        assert_eq!(progress.elapsed, Duration::from_secs(750));
+        assert!((progress.percent() - 75.0).abs() < f64::EPSILON);
+        assert!(book.contains("/books/Book/1.mp3"));
+        assert!(!book.contains("/books/Other/1.mp3"));
+    }
+
+    #[test]
+    fn finds_the_book_of_a_file() {
+        let dirs = vec!["/books".to_string()];
+        assert_eq!(
+            Audiobook::of_file("/books/Book/CD 1/01.mp3", &dirs),
+            Some(PathBuf::from("/books/Book"))
+        );
+        assert_eq!(
+            Audiobook::of_file("/books/Single.mp3", &dirs),
+            Some(PathBuf::from("/books/Single.mp3"))
+        );
+        assert_eq!(
+            Audiobook::of_file("/music/Whole.m4b", &dirs),
+            Some(PathBuf::from("/music/Whole.m4b"))
+        );
+        assert_eq!(Audiobook::of_file("/music/Song.mp3", &dirs), None);
+    }
+}
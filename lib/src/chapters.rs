@@ -0,0 +1,751 @@
+//! Chapters of long episodes and audiobooks.
+//!
+//! They come from the tags of a file (ID3 `CHAP` / `CTOC` frames, MP4 chapter tracks and Nero
+//! `chpl` boxes) or from a podcast feed (Podlove Simple Chapters and `podcast:chapters` JSON).
+
+use anyhow::{anyhow, Result};
+use lofty::FileType;
+use rss::extension::ExtensionMap;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// Going to the previous chapter this far into a chapter goes to its start instead.
+const PREVIOUS_RESTARTS_AFTER: Duration = Duration::from_secs(3);
+/// Biggest `moov` box read into memory when looking for chapters.
+const MAX_MOOV_LEN: u64 = 64 * 1024 * 1024;
+/// Most samples read from a chapter track, which has one for every chapter.
+const MAX_CHAPTER_SAMPLES: usize = 4096;
+/// Biggest chapter track sample, a 16 bit text length and the text.
+const MAX_CHAPTER_SAMPLE_LEN: u32 = 2 + u16::MAX as u32;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chapter {
+    /// Where the chapter starts, from the start of the track
+    pub start: Duration,
+    pub title: String,
+}
+
+/// Where the podcast feed says the chapters of an episode are.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct FeedChapters {
+    /// Podlove Simple Chapters in the feed itself
+    pub chapters: Vec<Chapter>,
+    /// A `podcast:chapters` JSON file to fetch
+    pub url: Option<String>,
+}
+
+/// Index of the chapter playing at `position`.
+pub fn current_index(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    chapters
+        .iter()
+        .rposition(|chapter| chapter.start <= position)
+}
+
+/// Start of the chapter after the one playing at `position`.
+pub fn next_start(chapters: &[Chapter], position: Duration) -> Option<Duration> {
+    chapters
+        .iter()
+        .map(|chapter| chapter.start)
+        .find(|start| *start > position)
+}
+
+/// Start of the chapter before the one playing at `position`, or of the one playing if it has
+/// been playing for a few seconds.
+pub fn previous_start(chapters: &[Chapter], position: Duration) -> Option<Duration> {
+    let index = current_index(chapters, position)?;
+    let start = chapters[index].start;
+    if position.saturating_sub(start) >= PREVIOUS_RESTARTS_AFTER || index == 0 {
+        return Some(start);
+    }
+    Some(chapters[index - 1].start)
+}
+
+/// Read the chapters of a local file, an empty list if it has none or cannot be read.
+pub fn read_from_path(path: &Path, file_type: FileType) -> Vec<Chapter> {
+    let chapters = match file_type {
+        FileType::Mpeg | FileType::Aiff | FileType::Wav => id3::Tag::read_from_path(path)
+            .map(|tag| from_id3(&tag))
+            .map_err(anyhow::Error::from),
+        FileType::Mp4 => File::open(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| from_mp4(&mut BufReader::new(file))),
+        _ => Ok(Vec::new()),
+    };
+    chapters.unwrap_or_else(|e| {
+        debug!("no chapters read from {}: {e}", path.display());
+        Vec::new()
+    })
+}
+
+/// Chapters from the `CHAP` frames of an ID3 tag.
+///
+/// With a top level `CTOC` frame, only the chapters it lists are taken.
+pub fn from_id3(tag: &id3::Tag) -> Vec<Chapter> {
+    let toc = tag.tables_of_contents().find(|toc| toc.top_level);
+    let mut chapters: Vec<Chapter> = tag
+        .chapters()
+        .filter(|chap| toc.map_or(true, |toc| toc.elements.contains(&chap.element_id)))
+        .map(|chap| Chapter {
+            start: Duration::from_millis(u64::from(chap.start_time)),
+            title: chap
+                .frames
+                .iter()
+                .find(|frame| frame.id() == "TIT2")
+                .and_then(|frame| frame.content().text())
+                .unwrap_or(chap.element_id.as_str())
+                .to_string(),
+        })
+        .collect();
+    sort(&mut chapters);
+    chapters
+}
+
+/// Chapters of a MP4 file, from a `QuickTime` chapter track or else a Nero `chpl` box.
+pub fn from_mp4<R: Read + Seek>(reader: &mut R) -> Result<Vec<Chapter>> {
+    let moov = read_moov(reader)?;
+
+    let mut chapters = match chapter_track_samples(&moov) {
+        Some(samples) => {
+            let mut chapters = Vec::with_capacity(samples.len());
+            for (start, offset, len) in samples {
+                reader.seek(SeekFrom::Start(offset))?;
+                let mut sample = vec![0; len];
+                reader.read_exact(&mut sample)?;
+                chapters.push(Chapter {
+                    start,
+                    title: sample_text(&sample),
+                });
+            }
+            chapters
+        }
+        None => find_box(&moov, &[b"udta", b"chpl"])
+            .and_then(parse_chpl)
+            .unwrap_or_default(),
+    };
+    sort(&mut chapters);
+    Ok(chapters)
+}
+
+/// Find the chapters of a feed item, in the Podlove `chapters` element or the URL of the
+/// `podcast:chapters` one.
+///
+/// The namespaces are told apart by their content, so any prefix works.
+pub fn from_feed_extensions(extensions: &ExtensionMap) -> FeedChapters {
+    let mut feed_chapters = FeedChapters::default();
+    for element in extensions
+        .values()
+        .filter_map(|names| names.get("chapters"))
+        .flatten()
+    {
+        if let Some(children) = element.children.get("chapter") {
+            let mut chapters: Vec<Chapter> = children
+                .iter()
+                .filter_map(|chapter| {
+                    Some(Chapter {
+                        start: parse_npt(chapter.attrs.get("start")?)?,
+                        title: chapter.attrs.get("title").cloned().unwrap_or_default(),
+                    })
+                })
+                .collect();
+            sort(&mut chapters);
+            feed_chapters.chapters = chapters;
+        } else if let Some(url) = element.attrs.get("url") {
+            feed_chapters.url = Some(url.clone());
+        }
+    }
+    feed_chapters
+}
+
+/// Parse a `podcast:chapters` JSON file, leaving out the chapters not meant for the table of
+/// contents.
+pub fn from_json(json: &str) -> Result<Vec<Chapter>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let list = value["chapters"]
+        .as_array()
+        .ok_or_else(|| anyhow!("no chapters in the json"))?;
+
+    let mut chapters: Vec<Chapter> = list
+        .iter()
+        .filter(|chapter| chapter["toc"].as_bool() != Some(false))
+        .filter_map(|chapter| {
+            let start = chapter["startTime"].as_f64()?;
+            Some(Chapter {
+                start: Duration::try_from_secs_f64(start).ok()?,
+                title: chapter["title"].as_str().unwrap_or_default().to_string(),
+            })
+        })
+        .collect();
+    sort(&mut chapters);
+    Ok(chapters)
+}
+
+/// Download and parse a `podcast:chapters` JSON file.
+pub fn fetch_json(url: &str) -> Result<Vec<Chapter>> {
+    let client = crate::http::blocking_client_builder().build()?;
+    let json = client.get(url).send()?.error_for_status()?.text()?;
+    from_json(&json)
+}
+
+/// Serialize for the `chapters` column of the podcast database.
+pub fn to_db(chapters: &[Chapter]) -> Option<String> {
+    if chapters.is_empty() {
+        return None;
+    }
+    serde_json::to_string(chapters).ok()
+}
+
+/// Deserialize the `chapters` column of the podcast database.
+pub fn from_db(value: Option<String>) -> Vec<Chapter> {
+    value
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Order by start and number the chapters without a title.
+fn sort(chapters: &mut Vec<Chapter>) {
+    chapters.sort_by_key(|chapter| chapter.start);
+    chapters.dedup_by(|a, b| a.start == b.start && a.title == b.title);
+    for (i, chapter) in chapters.iter_mut().enumerate() {
+        if chapter.title.trim().is_empty() {
+            chapter.title = format!("Chapter {}", i + 1);
+        }
+    }
+}
+
+/// Parse a normal play time like `01:02:03.5`, `02:03` or `123.5`.
+fn parse_npt(value: &str) -> Option<Duration> {
+    let mut secs = 0.0;
+    for part in value.trim().split(':') {
+        let part: f64 = part.parse().ok()?;
+        secs = secs * 60.0 + part;
+    }
+    Duration::try_from_secs_f64(secs).ok()
+}
+
+/// Read the content of the top level `moov` box.
+fn read_moov<R: Read + Seek>(reader: &mut R) -> Result<Vec<u8>> {
+    loop {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        let size = u64::from(u32::from_be_bytes([
+            header[0], header[1], header[2], header[3],
+        ]));
+        let (content_len, to_end) = match size {
+            0 => (0, true),
+            1 => {
+                let mut large_size = [0; 8];
+                reader.read_exact(&mut large_size)?;
+                (u64::from_be_bytes(large_size).saturating_sub(16), false)
+            }
+            _ => (size.saturating_sub(8), false),
+        };
+
+        if &header[4..] == b"moov" {
+            if to_end {
+                let mut moov = Vec::new();
+                reader.take(MAX_MOOV_LEN).read_to_end(&mut moov)?;
+                return Ok(moov);
+            }
+            if content_len > MAX_MOOV_LEN {
+                return Err(anyhow!("moov box too big"));
+            }
+            let mut moov = vec![0; usize::try_from(content_len)?];
+            reader.read_exact(&mut moov)?;
+            return Ok(moov);
+        }
+        if to_end {
+            return Err(anyhow!("no moov box"));
+        }
+        reader.seek(SeekFrom::Current(i64::try_from(content_len)?))?;
+    }
+}
+
+/// The boxes directly in `data`, as type and content.
+fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let size = usize::try_from(read_u32(rest, 0)?).ok()?;
+        let kind = rest.get(4..8)?;
+        let (start, end) = match size {
+            0 => (8, rest.len()),
+            1 => (16, usize::try_from(read_u64(rest, 8)?).ok()?),
+            _ => (8, size),
+        };
+        let content = rest.get(start..end)?;
+        rest = &rest[end..];
+        Some((kind, content))
+    })
+}
+
+/// Content of the first box at `path` below `data`.
+fn find_box<'a>(data: &'a [u8], path: &[&[u8; 4]]) -> Option<&'a [u8]> {
+    let (first, rest) = path.split_first()?;
+    let (_, content) = boxes(data).find(|(kind, _)| kind == first)?;
+    if rest.is_empty() {
+        Some(content)
+    } else {
+        find_box(content, rest)
+    }
+}
+
+/// Parse a Nero `chpl` box, the starts are in 100ns units.
+fn parse_chpl(data: &[u8]) -> Option<Vec<Chapter>> {
+    let version = *data.first()?;
+    // version, flags and in version 1 four more unknown bytes
+    let mut pos = if version == 0 { 4 } else { 8 };
+    let count = *data.get(pos)?;
+    pos += 1;
+
+    let mut chapters = Vec::with_capacity(usize::from(count));
+    for _ in 0..count {
+        let start = read_u64(data, pos)?;
+        let title_len = usize::from(*data.get(pos + 8)?);
+        let title = data.get(pos + 9..pos + 9 + title_len)?;
+        pos += 9 + title_len;
+        chapters.push(Chapter {
+            start: Duration::from_nanos(start.saturating_mul(100)),
+            title: String::from_utf8_lossy(title).into_owned(),
+        });
+    }
+    Some(chapters)
+}
+
+/// Start, file offset and length of each sample of the track a `tref/chap` box points at.
+fn chapter_track_samples(moov: &[u8]) -> Option<Vec<(Duration, u64, usize)>> {
+    let traks: Vec<&[u8]> = boxes(moov)
+        .filter(|(kind, _)| *kind == b"trak")
+        .map(|(_, trak)| trak)
+        .collect();
+    let chap = traks
+        .iter()
+        .find_map(|trak| find_box(trak, &[b"tref", b"chap"]))?;
+    let chapter_ids: Vec<u32> = chap
+        .chunks_exact(4)
+        .filter_map(|id| read_u32(id, 0))
+        .collect();
+    let trak = traks
+        .iter()
+        .find(|trak| track_id(trak).is_some_and(|id| chapter_ids.contains(&id)))?;
+
+    let timescale = {
+        let mdhd = find_box(trak, &[b"mdia", b"mdhd"])?;
+        if mdhd.first()? == &1 {
+            read_u32(mdhd, 20)?
+        } else {
+            read_u32(mdhd, 12)?
+        }
+    };
+    let stbl = find_box(trak, &[b"mdia", b"minf", b"stbl"])?;
+
+    let stsz = find_box(stbl, &[b"stsz"])?;
+    let fixed_size = read_u32(stsz, 4)?;
+    // the count is not trusted further than the sizes in the box go
+    let sample_count = if fixed_size == 0 {
+        (read_u32(stsz, 8)? as usize).min(stsz.len().saturating_sub(12) / 4)
+    } else {
+        read_u32(stsz, 8)? as usize
+    }
+    .min(MAX_CHAPTER_SAMPLES);
+
+    // sample durations, run length encoded
+    let stts = find_box(stbl, &[b"stts"])?;
+    let mut starts = Vec::with_capacity(sample_count);
+    let mut time = 0_u64;
+    'stts: for entry in 0..read_u32(stts, 4)? as usize {
+        let count = read_u32(stts, 8 + entry * 8)?;
+        let delta = read_u32(stts, 12 + entry * 8)?;
+        for _ in 0..count {
+            if starts.len() >= sample_count {
+                break 'stts;
+            }
+            starts.push(Duration::from_nanos(
+                u64::try_from(u128::from(time) * 1_000_000_000 / u128::from(timescale.max(1)))
+                    .ok()?,
+            ));
+            time += u64::from(delta);
+        }
+    }
+
+    let size = |sample: usize| {
+        if fixed_size == 0 {
+            read_u32(stsz, 12 + sample * 4)
+        } else {
+            Some(fixed_size)
+        }
+    };
+
+    let chunk_offsets: Vec<u64> = if let Some(stco) = find_box(stbl, &[b"stco"]) {
+        (0..read_u32(stco, 4)? as usize)
+            .map(|i| read_u32(stco, 8 + i * 4).map(u64::from))
+            .collect::<Option<_>>()?
+    } else {
+        let co64 = find_box(stbl, &[b"co64"])?;
+        (0..read_u32(co64, 4)? as usize)
+            .map(|i| read_u64(co64, 8 + i * 8))
+            .collect::<Option<_>>()?
+    };
+
+    // first chunk (counted from 1) and samples per chunk, until the next entry
+    let chunk_runs = find_box(stbl, &[b"stsc"])?;
+    let stsc_entries: Vec<(u32, u32)> = (0..read_u32(chunk_runs, 4)? as usize)
+        .map(|i| {
+            Some((
+                read_u32(chunk_runs, 8 + i * 12)?,
+                read_u32(chunk_runs, 12 + i * 12)?,
+            ))
+        })
+        .collect::<Option<_>>()?;
+
+    let mut samples = Vec::with_capacity(sample_count);
+    let mut sample = 0;
+    for (chunk, chunk_offset) in chunk_offsets.iter().enumerate() {
+        let chunk = u32::try_from(chunk + 1).ok()?;
+        let per_chunk = stsc_entries
+            .iter()
+            .rev()
+            .find(|(first, _)| *first <= chunk)
+            .map_or(0, |(_, per_chunk)| *per_chunk);
+        let mut offset = *chunk_offset;
+        for _ in 0..per_chunk {
+            if sample >= sample_count.min(starts.len()) {
+                return Some(samples);
+            }
+            let len = size(sample)?;
+            if len > MAX_CHAPTER_SAMPLE_LEN {
+                return None;
+            }
+            samples.push((starts[sample], offset, len as usize));
+            offset += u64::from(len);
+            sample += 1;
+        }
+    }
+    Some(samples)
+}
+
+/// Id of a track from its `tkhd` box.
+fn track_id(trak: &[u8]) -> Option<u32> {
+    let tkhd = find_box(trak, &[b"tkhd"])?;
+    if tkhd.first()? == &1 {
+        read_u32(tkhd, 20)
+    } else {
+        read_u32(tkhd, 12)
+    }
+}
+
+/// The text of a sample of a `QuickTime` text track, UTF-8 or UTF-16 with a byte order mark.
+fn sample_text(sample: &[u8]) -> String {
+    let len = read_u16(sample, 0).map_or(0, usize::from);
+    let text = sample.get(2..2 + len).unwrap_or_default();
+    match text {
+        [0xfe, 0xff, utf16 @ ..] | [0xff, 0xfe, utf16 @ ..] => {
+            let big_endian = text[0] == 0xfe;
+            let units: Vec<u16> = utf16
+                .chunks_exact(2)
+                .map(|unit| {
+                    if big_endian {
+                        u16::from_be_bytes([unit[0], unit[1]])
+                    } else {
+                        u16::from_le_bytes([unit[0], unit[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(text).into_owned(),
+    }
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::TagLike;
+    use pretty_assertions::assert_eq;
+    use std::io::Cursor;
+
+    fn chapter(secs: u64, title: &str) -> Chapter {
+        Chapter {
+            start: Duration::from_secs(secs),
+            title: title.to_string(),
+        }
+    }
+
+    fn mp4_box(kind: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut data = u32::try_from(content.len() + 8)
+            .unwrap()
+            .to_be_bytes()
+            .to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(content);
+        data
+    }
+
+    fn full_box(kind: &[u8], fields: &[u32]) -> Vec<u8> {
+        let mut content = vec![0; 4];
+        for field in fields {
+            content.extend_from_slice(&field.to_be_bytes());
+        }
+        mp4_box(kind, &content)
+    }
+
+    #[test]
+    fn next_and_previous() {
+        let chapters = vec![chapter(0, "a"), chapter(60, "b"), chapter(120, "c")];
+        let at = Duration::from_secs;
+
+        assert_eq!(current_index(&chapters, at(59)), Some(0));
+        assert_eq!(current_index(&chapters, at(60)), Some(1));
+        assert_eq!(next_start(&chapters, at(60)), Some(at(120)));
+        assert_eq!(next_start(&chapters, at(121)), None);
+
+        // a few seconds in goes back to the previous chapter, later to the start of this one
+        assert_eq!(previous_start(&chapters, at(61)), Some(at(0)));
+        assert_eq!(previous_start(&chapters, at(70)), Some(at(60)));
+        assert_eq!(previous_start(&chapters, at(1)), Some(at(0)));
+        assert_eq!(previous_start(&[], at(1)), None);
+    }
+
+    #[test]
+    fn id3_chapters_in_toc_order() {
+        let mut tag = id3::Tag::new();
+        for (id, start, title) in [("ch1", 90_000, "Second"), ("ch0", 0, "First")] {
+            tag.add_frame(id3::frame::Chapter {
+                element_id: id.to_string(),
+                start_time: start,
+                end_time: start + 90_000,
+                start_offset: u32::MAX,
+                end_offset: u32::MAX,
+                frames: vec![id3::Frame::text("TIT2", title)],
+            });
+        }
+        tag.add_frame(id3::frame::Chapter {
+            element_id: "hidden".to_string(),
+            start_time: 30_000,
+            end_time: 40_000,
+            start_offset: u32::MAX,
+            end_offset: u32::MAX,
+            frames: Vec::new(),
+        });
+        tag.add_frame(id3::frame::TableOfContents {
+            element_id: "toc".to_string(),
+            top_level: true,
+            ordered: true,
+            elements: vec!["ch0".to_string(), "ch1".to_string()],
+            frames: Vec::new(),
+        });
+
+        let mut data = Vec::new();
+        tag.write_to(&mut data, id3::Version::Id3v24).unwrap();
+        let tag = id3::Tag::read_from2(Cursor::new(data)).unwrap();
+        assert_eq!(
+            from_id3(&tag),
+            vec![chapter(0, "First"), chapter(90, "Second")]
+        );
+    }
+
+    #[test]
+    fn mp4_chapter_track() {
+        let texts: [&[u8]; 2] = [b"Intro", b"\xfe\xff\x00M\x00a\x00i\x00n"];
+        let mut samples = Vec::new();
+        for text in texts {
+            samples.extend_from_slice(&u16::try_from(text.len()).unwrap().to_be_bytes());
+            samples.extend_from_slice(text);
+        }
+
+        let audio = mp4_box(
+            b"trak",
+            &[
+                full_box(b"tkhd", &[0, 0, 1]),
+                mp4_box(b"tref", &mp4_box(b"chap", &2u32.to_be_bytes())),
+            ]
+            .concat(),
+        );
+        let build = |mdat_offset: u32| {
+            let stbl = [
+                // 1 sample of 2.5s, 1 of 10s at 1000 per second
+                full_box(b"stts", &[2, 1, 2500, 1, 10_000]),
+                full_box(b"stsz", &[0, 2, 7, 12]),
+                full_box(b"stsc", &[1, 1, 2, 1]),
+                full_box(b"stco", &[1, mdat_offset]),
+            ]
+            .concat();
+            let mdia = [
+                full_box(b"mdhd", &[0, 0, 1000, 12_500]),
+                mp4_box(b"minf", &mp4_box(b"stbl", &stbl)),
+            ]
+            .concat();
+            let text = mp4_box(
+                b"trak",
+                &[full_box(b"tkhd", &[0, 0, 2]), mp4_box(b"mdia", &mdia)].concat(),
+            );
+            mp4_box(b"moov", &[audio.clone(), text].concat())
+        };
+
+        let ftyp = mp4_box(b"ftyp", b"M4A \0\0\0\0");
+        let moov_len = build(0).len();
+        let mdat_offset = u32::try_from(ftyp.len() + moov_len + 8).unwrap();
+        let file = [ftyp, build(mdat_offset), mp4_box(b"mdat", &samples)].concat();
+
+        assert_eq!(
+            from_mp4(&mut Cursor::new(file)).unwrap(),
+            vec![
+                Chapter {
+                    start: Duration::ZERO,
+                    title: "Intro".to_string()
+                },
+                Chapter {
+                    start: Duration::from_millis(2500),
+                    title: "Main".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn mp4_chapter_track_counts_capped() {
+        let audio = mp4_box(
+            b"trak",
+            &[
+                full_box(b"tkhd", &[0, 0, 1]),
+                mp4_box(b"tref", &mp4_box(b"chap", &2u32.to_be_bytes())),
+            ]
+            .concat(),
+        );
+        // the counts claim far more samples than there are sizes for
+        let stbl = [
+            full_box(b"stts", &[1, u32::MAX, 1000]),
+            full_box(b"stsz", &[0, u32::MAX, 7, 12]),
+            full_box(b"stsc", &[1, 1, u32::MAX, 1]),
+            full_box(b"stco", &[1, 100]),
+        ]
+        .concat();
+        let mdia = [
+            full_box(b"mdhd", &[0, 0, 1000, 2000]),
+            mp4_box(b"minf", &mp4_box(b"stbl", &stbl)),
+        ]
+        .concat();
+        let text = mp4_box(
+            b"trak",
+            &[full_box(b"tkhd", &[0, 0, 2]), mp4_box(b"mdia", &mdia)].concat(),
+        );
+
+        assert_eq!(
+            chapter_track_samples(&[audio, text].concat()),
+            Some(vec![
+                (Duration::ZERO, 100, 7),
+                (Duration::from_secs(1), 107, 12)
+            ])
+        );
+    }
+
+    #[test]
+    fn mp4_chapter_track_huge_sample() {
+        let audio = mp4_box(
+            b"trak",
+            &[
+                full_box(b"tkhd", &[0, 0, 1]),
+                mp4_box(b"tref", &mp4_box(b"chap", &2u32.to_be_bytes())),
+            ]
+            .concat(),
+        );
+        let stbl = [
+            full_box(b"stts", &[1, 1, 1000]),
+            full_box(b"stsz", &[0, 1, u32::MAX]),
+            full_box(b"stsc", &[1, 1, 1, 1]),
+            full_box(b"stco", &[1, 0]),
+        ]
+        .concat();
+        let mdia = [
+            full_box(b"mdhd", &[0, 0, 1000, 1000]),
+            mp4_box(b"minf", &mp4_box(b"stbl", &stbl)),
+        ]
+        .concat();
+        let text = mp4_box(
+            b"trak",
+            &[full_box(b"tkhd", &[0, 0, 2]), mp4_box(b"mdia", &mdia)].concat(),
+        );
+        let moov = mp4_box(b"moov", &[audio, text].concat());
+
+        assert_eq!(chapter_track_samples(&moov[8..]), None);
+        let file = [mp4_box(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+        assert_eq!(from_mp4(&mut Cursor::new(file)).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn mp4_nero_chapters() {
+        let mut chpl = vec![1, 0, 0, 0, 0, 0, 0, 0, 2];
+        for (start, title) in [(0_u64, "One"), (600_000_000, "Two")] {
+            chpl.extend_from_slice(&start.to_be_bytes());
+            chpl.push(u8::try_from(title.len()).unwrap());
+            chpl.extend_from_slice(title.as_bytes());
+        }
+        let moov = mp4_box(b"moov", &mp4_box(b"udta", &mp4_box(b"chpl", &chpl)));
+        let file = [mp4_box(b"ftyp", b"M4A \0\0\0\0"), moov].concat();
+
+        assert_eq!(
+            from_mp4(&mut Cursor::new(file)).unwrap(),
+            vec![chapter(0, "One"), chapter(60, "Two")]
+        );
+    }
+
+    #[test]
+    fn feed_chapters() {
+        let feed = r#"<rss version="2.0" xmlns:psc="http://podlove.org/simple-chapters"
+            xmlns:podcast="https://podcastindex.org/namespace/1.0"><channel>
+            <item><title>Podlove</title><psc:chapters version="1.2">
+                <psc:chapter start="00:00:00.000" title="Welcome" />
+                <psc:chapter start="01:02:03.5" title="Later" />
+                <psc:chapter start="12:30" />
+            </psc:chapters></item>
+            <item><title>Json</title>
+            <podcast:chapters url="https://example.com/chapters.json" type="application/json+chapters" />
+            </item></channel></rss>"#;
+        let channel = rss::Channel::read_from(feed.as_bytes()).unwrap();
+
+        let podlove = from_feed_extensions(channel.items()[0].extensions());
+        assert_eq!(
+            podlove.chapters,
+            vec![
+                chapter(0, "Welcome"),
+                chapter(750, "Chapter 2"),
+                Chapter {
+                    start: Duration::from_millis(3_723_500),
+                    title: "Later".to_string()
+                },
+            ]
+        );
+        assert_eq!(podlove.url, None);
+
+        let json = from_feed_extensions(channel.items()[1].extensions());
+        assert!(json.chapters.is_empty());
+        assert_eq!(
+            json.url.as_deref(),
+            Some("https://example.com/chapters.json")
+        );
+
+        let chapters = from_json(
+            r#"{"version": "1.2.0", "chapters": [
+                {"startTime": 0, "title": "Intro"},
+                {"startTime": 30.5, "title": "Ad", "toc": false},
+                {"startTime": 90, "title": "Topic"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(chapters, vec![chapter(0, "Intro"), chapter(90, "Topic")]);
+        assert_eq!(from_db(to_db(&chapters)), chapters);
+    }
+}
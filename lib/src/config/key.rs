@@ -40,13 +40,27 @@ pub struct Keys {
     pub global_lyric_adjust_backward: BindingForEvent,
     pub global_player_speed_up: BindingForEvent,
     pub global_player_speed_down: BindingForEvent,
+    pub global_player_ab_loop_set_a: BindingForEvent,
+    pub global_player_ab_loop_set_b: BindingForEvent,
+    pub global_player_ab_loop_clear: BindingForEvent,
+    pub global_player_chapter_next: BindingForEvent,
+    pub global_player_chapter_previous: BindingForEvent,
+    pub global_audiobook_bookmark: BindingForEvent,
+    pub global_audiobook_show: BindingForEvent,
     pub global_lyric_cycle: BindingForEvent,
     pub global_layout_treeview: BindingForEvent,
     pub global_layout_database: BindingForEvent,
     pub global_player_toggle_gapless: BindingForEvent,
+    pub global_player_output_device: BindingForEvent,
+    pub global_player_switch_backend: BindingForEvent,
+    pub global_player_radio_go_live: BindingForEvent,
+    pub global_player_radio_record: BindingForEvent,
+    pub global_visualizer_cycle: BindingForEvent,
     pub global_config_open: BindingForEvent,
     pub global_save_playlist: BindingForEvent,
     pub global_layout_podcast: BindingForEvent,
+    pub global_layout_radio: BindingForEvent,
+    pub global_radio_history: BindingForEvent,
     pub global_xywh_move_left: BindingForEvent,
     pub global_xywh_move_right: BindingForEvent,
     pub global_xywh_move_up: BindingForEvent,
@@ -85,6 +99,13 @@ pub struct Keys {
     pub podcast_search_add_feed: BindingForEvent,
     pub podcast_refresh_feed: BindingForEvent,
     pub podcast_refresh_all_feeds: BindingForEvent,
+    pub radio_search: BindingForEvent,
+    pub radio_favorite: BindingForEvent,
+    pub radio_delete_favorite: BindingForEvent,
+    pub radio_sort_cycle: BindingForEvent,
+    pub radio_history_search_youtube: BindingForEvent,
+    pub radio_history_search_lyric: BindingForEvent,
+    pub radio_history_export: BindingForEvent,
 }
 
 impl Keys {
@@ -112,13 +133,27 @@ impl Keys {
             .chain(once(self.global_lyric_adjust_backward))
             .chain(once(self.global_player_speed_up))
             .chain(once(self.global_player_speed_down))
+            .chain(once(self.global_player_ab_loop_set_a))
+            .chain(once(self.global_player_ab_loop_set_b))
+            .chain(once(self.global_player_ab_loop_clear))
+            .chain(once(self.global_player_chapter_next))
+            .chain(once(self.global_player_chapter_previous))
+            .chain(once(self.global_audiobook_bookmark))
+            .chain(once(self.global_audiobook_show))
             .chain(once(self.global_lyric_cycle))
             .chain(once(self.global_layout_treeview))
             .chain(once(self.global_layout_database))
             .chain(once(self.global_player_toggle_gapless))
+            .chain(once(self.global_player_output_device))
+            .chain(once(self.global_player_switch_backend))
+            .chain(once(self.global_player_radio_go_live))
+            .chain(once(self.global_player_radio_record))
+            .chain(once(self.global_visualizer_cycle))
             .chain(once(self.global_config_open))
             .chain(once(self.global_save_playlist))
             .chain(once(self.global_layout_podcast))
+            .chain(once(self.global_layout_radio))
+            .chain(once(self.global_radio_history))
             .chain(once(self.global_xywh_move_left))
             .chain(once(self.global_xywh_move_right))
             .chain(once(self.global_xywh_move_up))
@@ -170,17 +205,36 @@ impl Keys {
             .chain(once(self.podcast_episode_delete_file))
     }
 
+    fn iter_radio(&self) -> impl Iterator<Item = BindingForEvent> {
+        once(self.radio_search)
+            .chain(once(self.radio_favorite))
+            .chain(once(self.radio_delete_favorite))
+            .chain(once(self.radio_sort_cycle))
+    }
+
+    fn iter_radio_history(&self) -> impl Iterator<Item = BindingForEvent> {
+        once(self.radio_history_search_youtube)
+            .chain(once(self.radio_history_search_lyric))
+            .chain(once(self.radio_history_export))
+    }
+
     pub fn has_unique_elements(&self) -> bool {
         let mut uniq_global = HashSet::new();
         let mut uniq_library = HashSet::new();
         let mut uniq_playlist = HashSet::new();
         let mut uniq_podcast = HashSet::new();
         let mut uniq_episode = HashSet::new();
+        let mut uniq_radio = HashSet::new();
+        let mut uniq_radio_history = HashSet::new();
         self.iter_global().all(move |x| uniq_global.insert(x))
             && self.iter_library().all(move |x| uniq_library.insert(x))
             && self.iter_playlist().all(move |x| uniq_playlist.insert(x))
             && self.iter_podcast().all(move |x| uniq_podcast.insert(x))
             && self.iter_episode().all(move |x| uniq_episode.insert(x))
+            && self.iter_radio().all(move |x| uniq_radio.insert(x))
+            && self
+                .iter_radio_history()
+                .all(move |x| uniq_radio_history.insert(x))
     }
 }
 
@@ -420,6 +474,34 @@ impl Default for Keys {
                 code: Key::Char('b'),
                 modifier: KeyModifiers::CONTROL,
             },
+            global_player_ab_loop_set_a: BindingForEvent {
+                code: Key::Char('['),
+                modifier: KeyModifiers::NONE,
+            },
+            global_player_ab_loop_set_b: BindingForEvent {
+                code: Key::Char(']'),
+                modifier: KeyModifiers::NONE,
+            },
+            global_player_ab_loop_clear: BindingForEvent {
+                code: Key::Char('\\'),
+                modifier: KeyModifiers::NONE,
+            },
+            global_player_chapter_next: BindingForEvent {
+                code: Key::Char('}'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            global_player_chapter_previous: BindingForEvent {
+                code: Key::Char('{'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            global_audiobook_bookmark: BindingForEvent {
+                code: Key::Char('k'),
+                modifier: KeyModifiers::CONTROL,
+            },
+            global_audiobook_show: BindingForEvent {
+                code: Key::Char('a'),
+                modifier: KeyModifiers::CONTROL,
+            },
 
             global_lyric_adjust_forward: BindingForEvent {
                 code: Key::Char('F'),
@@ -517,6 +599,26 @@ impl Default for Keys {
                 code: Key::Char('g'),
                 modifier: KeyModifiers::CONTROL,
             },
+            global_player_output_device: BindingForEvent {
+                code: Key::Char('O'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            global_player_switch_backend: BindingForEvent {
+                code: Key::Char('E'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            global_player_radio_go_live: BindingForEvent {
+                code: Key::Char('l'),
+                modifier: KeyModifiers::CONTROL,
+            },
+            global_player_radio_record: BindingForEvent {
+                code: Key::Char('r'),
+                modifier: KeyModifiers::CONTROL,
+            },
+            global_visualizer_cycle: BindingForEvent {
+                code: Key::Char('V'),
+                modifier: KeyModifiers::SHIFT,
+            },
             global_config_open: BindingForEvent {
                 code: Key::Char('C'),
                 modifier: KeyModifiers::SHIFT,
@@ -581,6 +683,42 @@ impl Default for Keys {
                 code: Key::Char('D'),
                 modifier: KeyModifiers::SHIFT,
             },
+            global_layout_radio: BindingForEvent {
+                code: Key::Char('4'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_search: BindingForEvent {
+                code: Key::Char('/'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_favorite: BindingForEvent {
+                code: Key::Char('a'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_delete_favorite: BindingForEvent {
+                code: Key::Char('d'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_sort_cycle: BindingForEvent {
+                code: Key::Char('o'),
+                modifier: KeyModifiers::NONE,
+            },
+            global_radio_history: BindingForEvent {
+                code: Key::Char('H'),
+                modifier: KeyModifiers::SHIFT,
+            },
+            radio_history_search_youtube: BindingForEvent {
+                code: Key::Char('s'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_history_search_lyric: BindingForEvent {
+                code: Key::Char('t'),
+                modifier: KeyModifiers::NONE,
+            },
+            radio_history_export: BindingForEvent {
+                code: Key::Char('e'),
+                modifier: KeyModifiers::NONE,
+            },
             global_xywh_move_left: BindingForEvent {
                 code: Key::Left,
                 modifier: CONTROL_SHIFT,
@@ -34,6 +34,7 @@ use image::DynamicImage;
 pub use key::{BindingForEvent, Keys, ALT_SHIFT, CONTROL_ALT, CONTROL_ALT_SHIFT, CONTROL_SHIFT};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 pub use theme::{load_alacritty, ColorTermusic, StyleColorSymbol};
@@ -58,6 +59,19 @@ lazy_static! {
         path.push(Path::new("podcast"));
         path.as_path().to_string_lossy().to_string()
     };
+    static ref STREAM_CACHE_DIR: String = {
+        let mut path = dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from(shellexpand::tilde("~/.cache").to_string()));
+        path.push(Path::new("termusic"));
+        path.push(Path::new("streams"));
+        path.as_path().to_string_lossy().to_string()
+    };
+    static ref RADIO_RECORD_DIR: String = {
+        let mut path = dirs::audio_dir()
+            .unwrap_or_else(|| PathBuf::from(shellexpand::tilde("~/Music").to_string()));
+        path.push(Path::new("radio"));
+        path.as_path().to_string_lossy().to_string()
+    };
 }
 
 #[derive(Clone, Copy, Default, Deserialize, Serialize, Debug)]
@@ -234,6 +248,25 @@ impl Xywh {
         Self::safe_guard_width_or_height(height, term_height * 2)
     }
 
+    /// Where a square picture goes, as `(x, y, width, height)` in cells.
+    ///
+    /// The visualizer is drawn there when it takes the place of the album art.
+    #[must_use]
+    pub fn square_area(&self, term_width: u32, term_height: u32) -> (u32, u32, u32, u32) {
+        let width = (self.width_between_1_100 * term_width / 100).min(term_width);
+        let (absolute_x, absolute_y) = (
+            self.x_between_1_100 * term_width / 100,
+            self.y_between_1_100 * term_height / 100,
+        );
+        // a cell is about twice as high as wide, like in `calculate_xywh`
+        (
+            self.align.x(absolute_x, width),
+            self.align.y(absolute_y, width),
+            width,
+            width / 2,
+        )
+    }
+
     pub fn get_terminal_size_u32() -> (u32, u32) {
         let (term_width, term_height) = viuer::terminal_size();
         (u32::from(term_width), u32::from(term_height))
@@ -276,6 +309,176 @@ impl std::fmt::Display for LastPosition {
     }
 }
 
+/// Sample rate conversion quality used by the rusty backend when the decoded audio does not
+/// match the output device rate.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum ResampleQuality {
+    /// Linear interpolation, cheapest but prone to aliasing.
+    Linear,
+    /// Windowed sinc with a short kernel.
+    Low,
+    /// Windowed sinc with a medium kernel.
+    Medium,
+    /// Windowed sinc with a long kernel.
+    High,
+}
+
+impl std::fmt::Display for ResampleQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let quality = match self {
+            Self::Linear => "linear",
+            Self::Low => "low(sinc, 8 zero crossings)",
+            Self::Medium => "medium(sinc, 16 zero crossings)",
+            Self::High => "high(sinc, 32 zero crossings)",
+        };
+        write!(f, "{quality}")
+    }
+}
+
+/// Sample format of the raw PCM written to `player_pipe_path`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum PipeSampleFormat {
+    /// Signed 16 bit, little endian.
+    S16Le,
+    /// Signed 32 bit, little endian.
+    S32Le,
+    /// 32 bit float, little endian.
+    F32Le,
+}
+
+impl std::fmt::Display for PipeSampleFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = match self {
+            Self::S16Le => "s16le",
+            Self::S32Le => "s32le",
+            Self::F32Le => "f32le",
+        };
+        write!(f, "{format}")
+    }
+}
+
+/// The playback backends, whether or not they are compiled in.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub enum BackendKind {
+    Rusty,
+    Mpv,
+    GStreamer,
+}
+
+impl std::fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let backend = match self {
+            Self::Rusty => "rusty",
+            Self::Mpv => "mpv",
+            Self::GStreamer => "gstreamer",
+        };
+        write!(f, "{backend}")
+    }
+}
+
+impl std::str::FromStr for BackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "rusty" => Ok(Self::Rusty),
+            "mpv" => Ok(Self::Mpv),
+            "gstreamer" | "gst" => Ok(Self::GStreamer),
+            _ => bail!("unknown backend \"{s}\""),
+        }
+    }
+}
+
+/// Format of the HTTP stream served on `player_http_stream_port`.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum HttpStreamFormat {
+    /// Uncompressed 16 bit PCM.
+    Wav,
+    /// Lossless, encoded with `ffmpeg`.
+    Flac,
+    /// Encoded with `ffmpeg`, the most widely supported by players and streaming clients.
+    Mp3,
+}
+
+impl std::fmt::Display for HttpStreamFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Mp3 => "mp3",
+        };
+        write!(f, "{format}")
+    }
+}
+
+/// Where the TUI shows the visualizer.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum VisualizerPosition {
+    Hidden,
+    /// In place of the lyric pane.
+    Lyric,
+    /// Next to the lyric pane, which gets half of its width.
+    BesideLyric,
+    /// In place of the album art.
+    AlbumArt,
+}
+
+impl VisualizerPosition {
+    /// The next position, for cycling through them with a key.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Hidden => Self::Lyric,
+            Self::Lyric => Self::BesideLyric,
+            Self::BesideLyric => Self::AlbumArt,
+            Self::AlbumArt => Self::Hidden,
+        }
+    }
+}
+
+impl std::fmt::Display for VisualizerPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let position = match self {
+            Self::Hidden => "hidden",
+            Self::Lyric => "lyric",
+            Self::BesideLyric => "beside lyric",
+            Self::AlbumArt => "album art",
+        };
+        write!(f, "{position}")
+    }
+}
+
+/// How the TUI draws the visualizer.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum VisualizerStyle {
+    /// Frequency bands as bars.
+    Bars,
+    /// The waveform.
+    Oscilloscope,
+}
+
+impl std::fmt::Display for VisualizerStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let style = match self {
+            Self::Bars => "bars",
+            Self::Oscilloscope => "oscilloscope",
+        };
+        write!(f, "{style}")
+    }
+}
+
+/// Where the rusty backend sends the audio it plays, only settable from the server command line.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum AudioSink {
+    /// The audio output device, see `player_output_device`.
+    #[default]
+    Device,
+    /// Discard the audio, played `speed` times faster than real time.
+    Null { speed: u16 },
+    /// Write the audio to a WAV file, played `speed` times faster than real time.
+    Wav { path: PathBuf, speed: u16 },
+}
+
 #[derive(Clone, Deserialize, Serialize, Debug)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct Settings {
@@ -288,6 +491,8 @@ pub struct Settings {
     pub disable_discord_rpc_from_cli: bool,
     #[serde(skip)]
     pub max_depth_cli: usize,
+    #[serde(skip)]
+    pub audio_sink_from_cli: AudioSink,
     pub player_port: u16,
     pub player_loop_mode: Loop,
     pub player_volume: u16,
@@ -306,6 +511,70 @@ pub struct Settings {
     pub kill_daemon_when_quit: bool,
     pub player_use_mpris: bool,
     pub player_use_discord: bool,
+    pub player_resample_quality: ResampleQuality,
+    /// Name of the audio output device, empty for the system default
+    pub player_output_device: String,
+    /// FIFO the rusty backend also writes raw PCM to, "-" for stdout, empty to disable
+    pub player_pipe_path: String,
+    pub player_pipe_format: PipeSampleFormat,
+    pub player_pipe_sample_rate: u32,
+    pub player_pipe_channels: u16,
+    /// Only write to `player_pipe_path`, without playing on the audio output device
+    pub player_pipe_only: bool,
+    /// Serve what is playing as an Icecast compatible HTTP stream
+    pub player_http_stream_enable: bool,
+    /// Address the HTTP stream listens on, `0.0.0.0` to let other devices connect
+    pub player_http_stream_address: String,
+    pub player_http_stream_port: u16,
+    /// Falls back to wav if `ffmpeg` is not found
+    pub player_http_stream_format: HttpStreamFormat,
+    /// Clients served at the same time, the ones connecting beyond get `503 Service Unavailable`
+    pub player_http_stream_max_clients: usize,
+    /// Needs a backend providing spectrum data, currently rusty
+    pub visualizer_position: VisualizerPosition,
+    pub visualizer_style: VisualizerStyle,
+    /// Every directory or file directly inside is an audiobook, `m4b` files are anywhere
+    pub audiobook_dir: Vec<String>,
+    /// KiB of a podcast or stream downloaded before it plays, and of the next one before it is
+    /// queued for gapless playback
+    pub player_prefetch_kb: u64,
+    /// Milliseconds the rusty backend ramps the volume over on pause, resume, stop, skip and
+    /// seek, 0 to cut hard
+    pub player_fade_ms: u64,
+    /// Backend to play files with these extensions with, like `ape = "Mpv"`, the one the server
+    /// started with or was switched to plays the rest
+    pub player_backend_by_extension: BTreeMap<String, BackendKind>,
+    /// Where the rusty backend keeps podcasts and other streams it played, so playing or seeking
+    /// in them again does not download them again
+    pub player_cache_dir: String,
+    /// MiB the cache may take up before the least recently played streams are removed, 0 to not
+    /// cache streams
+    pub player_cache_max_mb: u64,
+    /// Move podcast episodes that were streamed to the end into the podcast directory, as if they
+    /// were downloaded
+    pub podcast_cached_as_downloaded: bool,
+    /// Minutes of a live radio the rusty backend keeps to go back in, or to play on from after a
+    /// pause
+    pub player_radio_timeshift_minutes: u64,
+    /// Where recordings of radios go, a file for every song
+    pub player_radio_record_dir: String,
+    /// Leave out of a recording the songs it only has a part of, the ones playing when it starts
+    /// and stops
+    pub player_radio_record_skip_partial: bool,
+    /// Server of the radio-browser directory the radio view searches for stations
+    pub radio_browser_url: String,
+    /// Proxy for everything fetched over the network, like `http://proxy.example.com:3128`, empty
+    /// to use the one in `HTTP_PROXY` and `HTTPS_PROXY`, if any
+    pub http_proxy: String,
+    /// Empty for `termusic/<version>`
+    pub http_user_agent: String,
+    pub http_connect_timeout_secs: u64,
+    /// Seconds a request may take, except for streams and podcast downloads, 0 for no limit
+    pub http_timeout_secs: u64,
+    /// Headers sent with every request, like `X-Token = "secret"`
+    pub http_headers: BTreeMap<String, String>,
+    /// PEM file of certificates to trust besides those of the system, like the one of a proxy
+    pub http_ca_file: String,
     pub album_photo_xywh: Xywh,
     pub style_color_symbol: StyleColorSymbol,
     pub keys: Keys,
@@ -336,6 +605,7 @@ impl Default for Settings {
             disable_album_art_from_cli: false,
             disable_discord_rpc_from_cli: false,
             max_depth_cli: 4,
+            audio_sink_from_cli: AudioSink::Device,
             podcast_simultanious_download: 3,
             podcast_dir: PODCAST_DIR.to_string(),
             podcast_max_retries: 3,
@@ -343,6 +613,37 @@ impl Default for Settings {
             kill_daemon_when_quit: true,
             player_use_mpris: true,
             player_use_discord: true,
+            player_resample_quality: ResampleQuality::Medium,
+            player_output_device: String::new(),
+            player_pipe_path: String::new(),
+            player_pipe_format: PipeSampleFormat::S16Le,
+            player_pipe_sample_rate: 48000,
+            player_pipe_channels: 2,
+            player_pipe_only: false,
+            player_http_stream_enable: false,
+            player_http_stream_address: "127.0.0.1".to_string(),
+            player_http_stream_port: 50102,
+            player_http_stream_format: HttpStreamFormat::Mp3,
+            player_http_stream_max_clients: 8,
+            visualizer_position: VisualizerPosition::Hidden,
+            visualizer_style: VisualizerStyle::Bars,
+            audiobook_dir: Vec::new(),
+            player_prefetch_kb: 256,
+            player_fade_ms: 100,
+            player_backend_by_extension: BTreeMap::new(),
+            player_cache_dir: STREAM_CACHE_DIR.to_string(),
+            player_cache_max_mb: 1024,
+            podcast_cached_as_downloaded: false,
+            player_radio_timeshift_minutes: 30,
+            player_radio_record_dir: RADIO_RECORD_DIR.to_string(),
+            player_radio_record_skip_partial: true,
+            radio_browser_url: "https://all.api.radio-browser.info".to_string(),
+            http_proxy: String::new(),
+            http_user_agent: String::new(),
+            http_connect_timeout_secs: 10,
+            http_timeout_secs: 30,
+            http_headers: BTreeMap::new(),
+            http_ca_file: String::new(),
             player_port: 50101,
         }
     }
@@ -374,4 +675,13 @@ impl Settings {
         *self = config;
         Ok(())
     }
+
+    /// The backend preferred for the file at `location` in `player_backend_by_extension`.
+    pub fn backend_for(&self, location: &str) -> Option<BackendKind> {
+        let ext = Path::new(location).extension()?.to_str()?;
+        self.player_backend_by_extension
+            .iter()
+            .find(|(preferred, _)| preferred.eq_ignore_ascii_case(ext))
+            .map(|(_, backend)| *backend)
+    }
 }
@@ -0,0 +1,259 @@
+//! The HTTP clients everything termusic fetches over the network is fetched with: feeds,
+//! episodes, streams, `YouTube` searches, lyrics and covers.
+//!
+//! They all go through the proxy and send the user agent and headers of the config, and trust its
+//! CA bundle, once [`configure`] was called with it. The mpv and gstreamer backends fetch streams
+//! themselves, with the [`backend_options`] as of when they start.
+
+use crate::config::Settings;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Proxy};
+use std::time::Duration;
+
+lazy_static! {
+    static ref OPTIONS: RwLock<Options> = RwLock::new(Options::default());
+}
+
+#[derive(Debug, Clone)]
+struct Options {
+    proxy: Option<Proxy>,
+    proxy_url: Option<String>,
+    user_agent: String,
+    connect_timeout: Duration,
+    timeout: Option<Duration>,
+    headers: HeaderMap,
+    certificates: Vec<Certificate>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            proxy_url: None,
+            user_agent: default_user_agent(),
+            connect_timeout: Duration::from_secs(10),
+            timeout: Some(Duration::from_secs(30)),
+            headers: HeaderMap::new(),
+            certificates: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    fn from_settings(config: &Settings) -> Result<Self> {
+        let proxy = if config.http_proxy.is_empty() {
+            None
+        } else {
+            Some(
+                Proxy::all(&config.http_proxy)
+                    .with_context(|| format!("invalid proxy url {}", config.http_proxy))?,
+            )
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.http_headers {
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("invalid header name {name}"))?,
+                HeaderValue::from_str(value)
+                    .with_context(|| format!("invalid value of header {name}"))?,
+            );
+        }
+
+        let mut certificates = Vec::new();
+        if !config.http_ca_file.is_empty() {
+            let path = shellexpand::tilde(&config.http_ca_file).to_string();
+            let pem = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading CA bundle {path}"))?;
+            for block in pem_blocks(&pem) {
+                certificates.push(
+                    Certificate::from_pem(block.as_bytes())
+                        .with_context(|| format!("invalid certificate in CA bundle {path}"))?,
+                );
+            }
+        }
+
+        Ok(Self {
+            proxy,
+            proxy_url: (!config.http_proxy.is_empty()).then(|| config.http_proxy.clone()),
+            user_agent: if config.http_user_agent.is_empty() {
+                default_user_agent()
+            } else {
+                config.http_user_agent.clone()
+            },
+            connect_timeout: Duration::from_secs(config.http_connect_timeout_secs),
+            timeout: (config.http_timeout_secs > 0)
+                .then(|| Duration::from_secs(config.http_timeout_secs)),
+            headers,
+            certificates,
+        })
+    }
+
+    fn backend_options(&self) -> BackendOptions {
+        BackendOptions {
+            proxy: self.proxy_url.clone(),
+            user_agent: self.user_agent.clone(),
+            headers: self
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The proxy, user agent and headers of the config, for the backends fetching streams with their
+/// own HTTP client. The timeouts and the CA bundle are left to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendOptions {
+    /// `None` to use the one in `HTTP_PROXY` and `HTTPS_PROXY`, if any
+    pub proxy: Option<String>,
+    pub user_agent: String,
+    /// Names and values, the names in lowercase
+    pub headers: Vec<(String, String)>,
+}
+
+/// The certificates of a PEM bundle, each with its `BEGIN` and `END` line.
+fn pem_blocks(pem: &str) -> Vec<&str> {
+    const END: &str = "-----END CERTIFICATE-----";
+    let mut blocks = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find("-----BEGIN CERTIFICATE-----") {
+        let Some(length) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + length + END.len();
+        blocks.push(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    blocks
+}
+
+/// `termusic/<version>`, the user agent when the config has none.
+#[must_use]
+pub fn default_user_agent() -> String {
+    format!("termusic/{}", crate::VERSION)
+}
+
+/// Make the clients made from now on with the proxy, user agent, timeouts, headers and CA bundle
+/// of `config`.
+///
+/// # Errors
+///
+/// if the proxy url or a header is not valid, or the CA bundle can not be read, in which case the
+/// settings before are kept
+pub fn configure(config: &Settings) -> Result<()> {
+    *OPTIONS.write() = Options::from_settings(config)?;
+    Ok(())
+}
+
+/// The options of [`configure`] the mpv and gstreamer backends can use.
+#[must_use]
+pub fn backend_options() -> BackendOptions {
+    OPTIONS.read().backend_options()
+}
+
+/// A builder of async clients, for streams.
+///
+/// Only connecting times out, as a stream goes on for as long as it is listened to.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let options = OPTIONS.read().clone();
+    let mut builder = reqwest::Client::builder()
+        .user_agent(options.user_agent)
+        .default_headers(options.headers)
+        .connect_timeout(options.connect_timeout);
+    if let Some(proxy) = options.proxy {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in options.certificates {
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder
+}
+
+/// A builder of blocking clients, for feeds, APIs and downloads.
+///
+/// Requests time out after `http_timeout_secs`, which a download of something large has to turn
+/// off with `timeout(None)`.
+pub fn blocking_client_builder() -> reqwest::blocking::ClientBuilder {
+    let options = OPTIONS.read().clone();
+    let mut builder = reqwest::blocking::Client::builder()
+        .user_agent(options.user_agent)
+        .default_headers(options.headers)
+        .connect_timeout(options.connect_timeout)
+        .timeout(options.timeout);
+    if let Some(proxy) = options.proxy {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in options.certificates {
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_settings() {
+        let mut config = Settings::default();
+        config
+            .http_headers
+            .insert("X-Token".to_string(), "secret".to_string());
+        let options = Options::from_settings(&config).unwrap();
+        assert_eq!(options.headers["x-token"], "secret");
+        assert_eq!(options.user_agent, default_user_agent());
+
+        config
+            .http_headers
+            .insert("Not a header".to_string(), String::new());
+        assert!(Options::from_settings(&config).is_err());
+
+        config.http_headers.clear();
+        config.http_proxy = "not a url".to_string();
+        assert!(Options::from_settings(&config).is_err());
+    }
+
+    #[test]
+    fn passes_the_settings_on_to_the_backends() {
+        let options = Options::default().backend_options();
+        assert_eq!(options.proxy, None);
+        assert_eq!(options.user_agent, default_user_agent());
+        assert!(options.headers.is_empty());
+
+        let config = Settings {
+            http_proxy: "http://proxy.example.com:3128".to_string(),
+            http_user_agent: "player/1.0".to_string(),
+            http_headers: [("X-Token".to_string(), "a, b".to_string())].into(),
+            ..Settings::default()
+        };
+        let options = Options::from_settings(&config).unwrap().backend_options();
+        assert_eq!(
+            options,
+            BackendOptions {
+                proxy: Some("http://proxy.example.com:3128".to_string()),
+                user_agent: "player/1.0".to_string(),
+                headers: vec![("x-token".to_string(), "a, b".to_string())],
+            }
+        );
+    }
+
+    #[test]
+    fn splits_a_pem_bundle() {
+        let pem = "comment\n-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n\
+                   -----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----\n";
+        assert_eq!(
+            pem_blocks(pem),
+            [
+                "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----",
+                "-----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----",
+            ]
+        );
+    }
+}
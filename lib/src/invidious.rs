@@ -26,11 +26,7 @@ use rand::seq::SliceRandom;
 use serde_json::Value;
 // left for debug
 // use std::io::Write;
-use reqwest::{
-    blocking::{Client, ClientBuilder},
-    StatusCode,
-};
-use std::time::Duration;
+use reqwest::{blocking::Client, StatusCode};
 
 const INVIDIOUS_INSTANCE_LIST: [&str; 7] = [
     "https://vid.puffyan.us",
@@ -76,7 +72,9 @@ pub struct YoutubeVideo {
 
 impl Default for Instance {
     fn default() -> Self {
-        let client = Client::new();
+        let client = crate::http::blocking_client_builder()
+            .build()
+            .unwrap_or_default();
         let domain = Some(String::new());
         let query = Some(String::new());
 
@@ -91,9 +89,7 @@ impl Default for Instance {
 #[allow(unused)]
 impl Instance {
     pub fn new(query: &str) -> Result<(Self, Vec<YoutubeVideo>)> {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(10))
-            .build()?;
+        let client = crate::http::blocking_client_builder().build()?;
 
         let mut domain = String::new();
         let mut domains = vec![];
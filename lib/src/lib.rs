@@ -4,7 +4,12 @@
 #![warn(rust_2018_idioms)]
 #![warn(clippy::pedantic)]
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+pub mod audiobook;
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+pub mod chapters;
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 pub mod config;
+pub mod http;
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 pub mod invidious;
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
@@ -12,6 +17,10 @@ pub mod playlist;
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 pub mod podcast;
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+pub mod radio_browser;
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
+pub mod radio_history;
+#[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 pub mod songtag;
 #[allow(clippy::missing_errors_doc, clippy::must_use_candidate)]
 pub mod sqlite;
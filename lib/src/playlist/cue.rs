@@ -0,0 +1,206 @@
+//! Decode cue sheets, which split one or more audio files, mostly whole lossless rips, into tracks
+
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+
+/// Frames of a cue sheet time in a second
+const FRAMES_PER_SEC: u64 = 75;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CueSheet {
+    /// Album title
+    pub title: Option<String>,
+    /// Album artist
+    pub performer: Option<String>,
+    pub genre: Option<String>,
+    pub date: Option<String>,
+    pub tracks: Vec<CueTrack>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CueTrack {
+    pub number: u32,
+    /// The audio file the track is part of, as written in the sheet unless read with [`read`]
+    pub file: String,
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// Start in `file`, at `INDEX 01`
+    pub start: Duration,
+    /// Where the next track in the same file starts, `None` to play until the end of `file`
+    pub end: Option<Duration>,
+}
+
+/// The part of an audio file a track of a cue sheet is, see [`Track::cue`](crate::track::Track::cue).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CueSection {
+    /// Path of the cue sheet
+    pub sheet: String,
+    pub number: u32,
+    pub start: Duration,
+    pub end: Option<Duration>,
+}
+
+/// Read the cue sheet at `path`, with the audio files resolved relative to it.
+///
+/// Sheets are often not written in UTF-8, those are read as latin-1.
+pub fn read(path: &Path) -> Result<CueSheet> {
+    let bytes = std::fs::read(path)?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+    let content = match std::str::from_utf8(bytes) {
+        Ok(content) => content.to_string(),
+        Err(_) => bytes.iter().map(|b| char::from(*b)).collect(),
+    };
+
+    let mut sheet = decode(&content);
+    let base = path.parent().unwrap_or_else(|| Path::new(""));
+    for track in &mut sheet.tracks {
+        track.file = base.join(&track.file).to_string_lossy().to_string();
+    }
+    Ok(sheet)
+}
+
+pub fn decode(content: &str) -> CueSheet {
+    let mut sheet = CueSheet::default();
+    let mut file: Option<String> = None;
+    // the track the lines belong to, `None` before the first one or in a data track
+    let mut track: Option<CueTrack> = None;
+    let mut in_track = false;
+    // only a track with an `INDEX 01` is played
+    let mut started = false;
+
+    for line in content.lines() {
+        let (command, rest) = split_word(line.trim());
+        match command.to_uppercase().as_str() {
+            "FILE" => {
+                push_track(&mut sheet, track.take(), started);
+                in_track = false;
+                // the file type follows the name
+                let name = rest.trim_end().rsplit_once(char::is_whitespace);
+                file = Some(argument(name.map_or(rest, |(name, _)| name)));
+            }
+            "TRACK" => {
+                push_track(&mut sheet, track.take(), started);
+                in_track = true;
+                started = false;
+                let (number, kind) = split_word(rest);
+                if let (Ok(number), Some(file), true) = (
+                    number.parse(),
+                    &file,
+                    kind.trim().eq_ignore_ascii_case("AUDIO"),
+                ) {
+                    track = Some(CueTrack {
+                        number,
+                        file: file.clone(),
+                        title: None,
+                        performer: None,
+                        start: Duration::ZERO,
+                        end: None,
+                    });
+                }
+            }
+            "INDEX" => {
+                let (index, time) = split_word(rest);
+                if let (Some(track), "01", Some(time)) = (&mut track, index, parse_time(time)) {
+                    track.start = time;
+                    started = true;
+                }
+            }
+            "TITLE" | "PERFORMER" => {
+                let value = Some(argument(rest));
+                let is_title = command.eq_ignore_ascii_case("TITLE");
+                match (&mut track, in_track, is_title) {
+                    (Some(track), _, true) => track.title = value,
+                    (Some(track), _, false) => track.performer = value,
+                    (None, false, true) => sheet.title = value,
+                    (None, false, false) => sheet.performer = value,
+                    (None, true, _) => {}
+                }
+            }
+            "REM" => {
+                let (key, value) = split_word(rest);
+                match key.to_uppercase().as_str() {
+                    "GENRE" => sheet.genre = Some(argument(value)),
+                    "DATE" => sheet.date = Some(argument(value)),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    push_track(&mut sheet, track, started);
+
+    // a track ends where the next one in the same file starts
+    let ends: Vec<Option<Duration>> = sheet
+        .tracks
+        .windows(2)
+        .map(|pair| {
+            (pair[0].file == pair[1].file && pair[1].start > pair[0].start).then_some(pair[1].start)
+        })
+        .chain([None])
+        .collect();
+    for (track, end) in sheet.tracks.iter_mut().zip(ends) {
+        track.end = end;
+    }
+    sheet
+}
+
+/// The place of track `number` of the cue sheet at `sheet`, as kept in playlists and the database.
+pub fn location(sheet: &str, number: u32) -> String {
+    format!("{sheet}#{number}")
+}
+
+/// The cue sheet and track number of a [`location`].
+pub fn parse_location(location: &str) -> Option<(&str, u32)> {
+    let (sheet, number) = location.rsplit_once('#')?;
+    if !is_cue(sheet) {
+        return None;
+    }
+    Some((sheet, number.parse().ok()?))
+}
+
+/// The file behind `location`, the cue sheet for a track of one.
+pub fn location_file(location: &str) -> &str {
+    parse_location(location).map_or(location, |(sheet, _)| sheet)
+}
+
+pub fn is_cue(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"))
+}
+
+fn push_track(sheet: &mut CueSheet, track: Option<CueTrack>, started: bool) {
+    if let Some(track) = track.filter(|_| started) {
+        sheet.tracks.push(track);
+    }
+}
+
+/// The first word of `line` and what follows it.
+fn split_word(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    line.split_once(char::is_whitespace).unwrap_or((line, ""))
+}
+
+/// A value, which is quoted when it has spaces, though not in every sheet.
+fn argument(rest: &str) -> String {
+    let rest = rest.trim();
+    match rest.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or_default().to_string(),
+        None => rest.to_string(),
+    }
+}
+
+/// A `mm:ss:ff` time, with 75 frames to the second.
+fn parse_time(time: &str) -> Option<Duration> {
+    let mut parts = time.trim().splitn(3, ':').map(str::parse::<u64>);
+    let (Some(Ok(minutes)), Some(Ok(seconds)), Some(Ok(frames))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(
+        Duration::from_secs(minutes * 60 + seconds)
+            + Duration::from_nanos(frames * 1_000_000_000 / FRAMES_PER_SEC),
+    )
+}
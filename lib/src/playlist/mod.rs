@@ -1,8 +1,11 @@
 //! This is a very simple url extractor for different kinds of playlist formats: M3U, PLS, ASX, XSPF
 //!
 //! It is not optimized yet and does create a lot of strings on the way.
+//!
+//! Cue sheets are in [`cue`], as they list parts of audio files instead of urls.
 
 mod asx;
+pub mod cue;
 mod m3u;
 mod pls;
 mod xspf;
@@ -76,7 +79,52 @@ pub fn decode(content: &str) -> Result<Vec<String>, Box<dyn Error>> {
     let v: Vec<String> = set.into_iter().collect();
     Ok(v)
 }
-#[allow(unused)]
+
+/// Whether `url` is of a playlist by its extension, as radio stations hand out to their streams.
+pub fn is_playlist_url(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    let extension = url
+        .path()
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase());
+    matches!(
+        extension.as_deref(),
+        Some("pls" | "m3u" | "m3u8" | "asx" | "xspf")
+    )
+}
+
+/// The urls of the streams in the playlist `content` fetched from `base`, in the order they are
+/// to be tried. Relative urls are resolved against `base`, and those that are not http dropped.
+///
+/// An HLS playlist is a stream itself, so it has none.
+pub fn stream_urls(content: &str, base: &reqwest::Url) -> Vec<String> {
+    if is_content_hls(content) {
+        return Vec::new();
+    }
+    let Ok(items) = decode(content) else {
+        return Vec::new();
+    };
+    let mut urls: Vec<String> = Vec::new();
+    for item in items.iter().map(|item| item.trim()) {
+        if item.is_empty() {
+            continue;
+        }
+        let Ok(url) = base.join(item) else {
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            continue;
+        }
+        let url = url.to_string();
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
 pub fn is_content_hls(content: &str) -> bool {
     if content.contains("EXT-X-STREAM-INF") {
         return true;
@@ -89,6 +137,8 @@ pub fn is_content_hls(content: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     #[test]
     fn xspf() {
         let s = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -186,4 +236,102 @@ Title=mytitle
         assert!(items[0].url == "http://this.is.an.example");
         assert!(items[0].title == "mytitle");
     }
+
+    #[test]
+    fn radio_playlist() {
+        assert!(super::is_playlist_url(
+            "http://radio.example/listen.PLS?sid=1"
+        ));
+        assert!(super::is_playlist_url("https://radio.example/live.m3u"));
+        assert!(!super::is_playlist_url("http://radio.example/live.mp3"));
+        assert!(!super::is_playlist_url("http://radio.example:8000/stream"));
+
+        let base = reqwest::Url::parse("http://radio.example/tunein/station.pls").unwrap();
+        let urls = super::stream_urls(
+            "[playlist]
+NumberOfEntries=3
+File2=http://backup.example:8000/stream
+File1=http://main.example:8000/stream
+File3=http://main.example:8000/stream
+",
+            &base,
+        );
+        assert_eq!(
+            urls,
+            [
+                "http://main.example:8000/stream",
+                "http://backup.example:8000/stream"
+            ]
+        );
+
+        let urls = super::stream_urls("#EXTM3U\n\nlive/high.aac\nftp://x.example/a\n", &base);
+        assert_eq!(urls, ["http://radio.example/tunein/live/high.aac"]);
+        let hls = "#EXTM3U\n#EXT-X-TARGETDURATION:10\nsegment1.aac\n";
+        assert!(super::stream_urls(hls, &base).is_empty());
+    }
+
+    #[test]
+    fn cue() {
+        let sheet = crate::playlist::cue::decode(
+            r#"REM GENRE Jazz
+REM DATE 1959
+PERFORMER "Some Band"
+TITLE "Some Album"
+FILE "Some Album.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "First"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Second"
+    PERFORMER Guest Artist
+    INDEX 00 04:58:50
+    INDEX 01 05:00:37
+  TRACK 03 AUDIO
+    TITLE "No Start"
+FILE bonus.wav WAVE
+  TRACK 04 AUDIO
+    TITLE "Bonus"
+    INDEX 01 00:00:00
+"#,
+        );
+        assert_eq!(sheet.title.as_deref(), Some("Some Album"));
+        assert_eq!(sheet.performer.as_deref(), Some("Some Band"));
+        assert_eq!(sheet.genre.as_deref(), Some("Jazz"));
+        assert_eq!(sheet.date.as_deref(), Some("1959"));
+        assert_eq!(sheet.tracks.len(), 3);
+
+        let second = Duration::from_millis(300_493);
+        assert_eq!(sheet.tracks[0].file, "Some Album.flac");
+        assert_eq!(sheet.tracks[0].title.as_deref(), Some("First"));
+        assert_eq!(sheet.tracks[0].performer, None);
+        assert_eq!(sheet.tracks[0].start, Duration::ZERO);
+        assert_eq!(
+            sheet.tracks[0].end.map(|end| end.as_millis()),
+            Some(second.as_millis())
+        );
+
+        assert_eq!(sheet.tracks[1].number, 2);
+        assert_eq!(sheet.tracks[1].performer.as_deref(), Some("Guest Artist"));
+        assert_eq!(sheet.tracks[1].start.as_millis(), second.as_millis());
+        // the last track of a file plays until its end
+        assert_eq!(sheet.tracks[1].end, None);
+
+        assert_eq!(sheet.tracks[2].number, 4);
+        assert_eq!(sheet.tracks[2].file, "bonus.wav");
+        assert_eq!(sheet.tracks[2].end, None);
+    }
+
+    #[test]
+    fn cue_location() {
+        use crate::playlist::cue::{location, location_file, parse_location};
+
+        let loc = location("/music/Album #1/Album.cue", 3);
+        assert_eq!(loc, "/music/Album #1/Album.cue#3");
+        assert_eq!(parse_location(&loc), Some(("/music/Album #1/Album.cue", 3)));
+        assert_eq!(location_file(&loc), "/music/Album #1/Album.cue");
+
+        assert_eq!(parse_location("/music/Song #3.flac"), None);
+        assert_eq!(parse_location("/music/Album.cue#x"), None);
+        assert_eq!(location_file("/music/Song #3.flac"), "/music/Song #3.flac");
+    }
 }
@@ -1,6 +1,6 @@
 //! Decode File and Title parts from simple playlist PLS files
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub struct PlaylistItem {
     pub title: String,
@@ -11,7 +11,8 @@ pub fn decode(content: &str) -> Vec<PlaylistItem> {
     let lines = content.lines();
     let mut list = vec![];
     let mut found_pls = false;
-    let mut map_urls = HashMap::new();
+    // by number, as players try the entries in order
+    let mut map_urls = BTreeMap::new();
     let mut map_title = HashMap::new();
     let mut default_title = "";
     for line in lines {
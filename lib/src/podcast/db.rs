@@ -1,16 +1,17 @@
 use anyhow::{anyhow, Context, Result};
 use std::path::{Path, PathBuf};
 
+use crate::chapters::{self, Chapter};
 use crate::track::Track;
 use ahash::AHashMap;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use semver::Version;
 use std::time::Duration;
 
-use super::{Episode, EpisodeNoId, NewEpisode, Podcast, PodcastNoId};
+use super::{EpData, Episode, EpisodeNoId, NewEpisode, Podcast, PodcastNoId};
 
 lazy_static! {
     /// Regex for removing "A", "An", and "The" from the beginning of
@@ -47,6 +48,7 @@ impl Database {
             conn: Some(conn),
         };
         db_conn.create()?;
+        db_conn.add_missing_columns()?;
 
         {
             let conn = db_conn
@@ -130,6 +132,8 @@ impl Database {
                 hidden INTEGER,
                 last_position INTERGER,
                 image_url TEXT,
+                chapters TEXT,
+                chapters_url TEXT,
                 FOREIGN KEY(podcast_id) REFERENCES podcasts(id) ON DELETE CASCADE
             );",
             params![],
@@ -159,6 +163,31 @@ impl Database {
         Ok(())
     }
 
+    /// Adds the columns of the episodes table that came after it was
+    /// first created, so that older databases keep working.
+    fn add_missing_columns(&self) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or(anyhow!("Error connecting to database."))?;
+
+        let mut stmt = conn.prepare("PRAGMA table_info(episodes);")?;
+        let columns: Vec<String> = stmt
+            .query_map(params![], |row| row.get("name"))?
+            .collect::<Result<_, _>>()?;
+
+        for (column, column_type) in [("chapters", "TEXT"), ("chapters_url", "TEXT")] {
+            if !columns.iter().any(|c| c == column) {
+                conn.execute(
+                    &format!("ALTER TABLE episodes ADD COLUMN {column} {column_type};"),
+                    params![],
+                )
+                .with_context(|| format!("Could not add column {column} to episodes"))?;
+            }
+        }
+        Ok(())
+    }
+
     /// If version stored in database is less than the current version
     /// of the app, this updates the value stored in the database to
     /// match.
@@ -244,8 +273,9 @@ impl Database {
 
         let mut stmt = conn.prepare_cached(
             "INSERT INTO episodes (podcast_id, title, url, guid,
-                description, pubdate, duration, played, hidden, last_position, image_url)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+                description, pubdate, duration, played, hidden, last_position, image_url,
+                chapters, chapters_url)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
         )?;
         stmt.execute(params![
             podcast_id,
@@ -259,6 +289,8 @@ impl Database {
             false,
             0,
             episode.image_url,
+            chapters::to_db(&episode.chapters),
+            episode.chapters_url,
         ])?;
         Ok(conn.last_insert_rowid())
     }
@@ -427,10 +459,13 @@ impl Database {
 
             if let Some(id) = existing_id {
                 if update {
+                    // chapters fetched from `chapters_url` are kept while the
+                    // feed has none itself
                     let mut stmt = tx.prepare_cached(
                         "UPDATE episodes SET title = ?, url = ?,
                                 guid = ?, description = ?, pubdate = ?,
-                                duration = ? WHERE id = ?;",
+                                duration = ?, chapters = COALESCE(?, chapters),
+                                chapters_url = ? WHERE id = ?;",
                     )?;
                     stmt.execute(params![
                         new_ep.title,
@@ -439,6 +474,8 @@ impl Database {
                         new_ep.description,
                         new_pd,
                         new_ep.duration,
+                        chapters::to_db(&new_ep.chapters),
+                        new_ep.chapters_url,
                         id,
                     ])?;
                     update_ep.push(id);
@@ -478,6 +515,8 @@ impl Database {
             && new_ep.guid == old_ep.guid
             && new_ep.description == old_ep.description
             && new_ep.duration == old_ep.duration
+            && (new_ep.chapters.is_empty() || new_ep.chapters == old_ep.chapters)
+            && new_ep.chapters_url == old_ep.chapters_url
             && pd_match)
         {
             return true;
@@ -616,12 +655,44 @@ impl Database {
                 played: row.get("played")?,
                 last_position: row.get("last_position")?,
                 image_url: row.get("image_url")?,
+                chapters: chapters::from_db(row.get("chapters")?),
+                chapters_url: row.get("chapters_url")?,
             })
         })?;
         let episodes = episode_iter.flatten().collect();
         Ok(episodes)
     }
 
+    /// The episode with `url` and the title of its podcast, when the episode is not downloaded.
+    pub fn get_undownloaded_episode(&self, url: &str) -> Result<Option<(EpData, String)>> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or(anyhow!("Error connecting to database."))?;
+        let mut stmt = conn.prepare_cached(
+            "SELECT episodes.id AS id, episodes.podcast_id AS podcast_id,
+                    episodes.title AS title, episodes.pubdate AS pubdate,
+                    podcasts.title AS podcast_title FROM episodes
+                    JOIN podcasts ON podcasts.id = episodes.podcast_id
+                    LEFT JOIN files ON episodes.id = files.episode_id
+                    WHERE episodes.url = ? AND files.path IS NULL;",
+        )?;
+        let episode = stmt
+            .query_row(params![url], |row| {
+                let ep_data = EpData {
+                    id: row.get("id")?,
+                    pod_id: row.get("podcast_id")?,
+                    title: row.get("title")?,
+                    url: url.to_string(),
+                    pubdate: convert_date(&row.get("pubdate")),
+                    file_path: None,
+                };
+                Ok((ep_data, row.get("podcast_title")?))
+            })
+            .optional()?;
+        Ok(episode)
+    }
+
     /// Deletes all rows in all tables
     #[allow(clippy::missing_panics_doc)]
     pub fn clear_db(&self) -> Result<()> {
@@ -678,6 +749,22 @@ impl Database {
         .expect("update last position failed.");
         // error!("set last position as {}", last_position.as_secs());
     }
+
+    /// Stores the chapters fetched for the episode of `track`.
+    pub fn set_chapters(&self, track: &Track, chapters: &[Chapter]) -> Result<()> {
+        let conn = self
+            .conn
+            .as_ref()
+            .ok_or(anyhow!("conn is not available for set chapters."))?;
+        conn.execute(
+            "UPDATE episodes SET chapters = ?1 WHERE url = ?2",
+            params![
+                chapters::to_db(chapters),
+                track.file().unwrap_or("Unknown File Name").to_string(),
+            ],
+        )?;
+        Ok(())
+    }
 }
 
 /// Helper function converting an (optional) Unix timestamp to a
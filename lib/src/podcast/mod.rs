@@ -3,6 +3,7 @@
 #[allow(unused)]
 pub mod db;
 
+use crate::chapters::{self, Chapter};
 use crate::config::Settings;
 use crate::types::{Msg, PCMsg};
 use crate::utils::StringUtils;
@@ -12,7 +13,6 @@ use db::Database;
 use lazy_static::lazy_static;
 use opml::{Body, Head, Outline, OPML};
 use regex::{Match, Regex};
-use reqwest::blocking::ClientBuilder;
 use rfc822_sanitizer::parse_from_rfc2822_with_fallback;
 use rss::{Channel, Item};
 use sanitize_filename::{sanitize_with_options, Options};
@@ -25,7 +25,6 @@ use std::sync::{
     Arc, Mutex,
 };
 use std::thread;
-use std::time::Duration;
 
 // How many columns we need, minimum, before we display the
 // (unplayed/total) after the podcast title
@@ -153,6 +152,9 @@ pub struct Episode {
     pub played: bool,
     pub last_position: Option<i64>,
     pub image_url: Option<String>,
+    pub chapters: Vec<Chapter>,
+    /// `podcast:chapters` JSON to fetch when the feed has no chapters itself
+    pub chapters_url: Option<String>,
 }
 
 impl Episode {
@@ -255,6 +257,8 @@ pub struct EpisodeNoId {
     pub pubdate: Option<DateTime<Utc>>,
     pub duration: Option<i64>,
     pub image_url: Option<String>,
+    pub chapters: Vec<Chapter>,
+    pub chapters_url: Option<String>,
 }
 
 /// Struct holding data about an individual podcast episode, specifically
@@ -323,9 +327,7 @@ pub fn check_feed(
 /// Given a URL, this attempts to pull the data about a podcast and its
 /// episodes from an RSS feed.
 fn get_feed_data(url: &str, mut max_retries: usize) -> Result<PodcastNoId> {
-    let agent = ClientBuilder::new()
-        .connect_timeout(Duration::from_secs(5))
-        .build()?;
+    let agent = crate::http::blocking_client_builder().build()?;
 
     let request: Result<reqwest::blocking::Response> = loop {
         let response = agent.get(url).send();
@@ -435,6 +437,7 @@ fn parse_episode_data(item: &Item) -> EpisodeNoId {
         duration = duration_to_int(itunes.duration()).map(i64::from);
         image_url = itunes.image().map(std::string::ToString::to_string);
     }
+    let feed_chapters = chapters::from_feed_extensions(item.extensions());
 
     EpisodeNoId {
         title,
@@ -444,6 +447,8 @@ fn parse_episode_data(item: &Item) -> EpisodeNoId {
         pubdate,
         duration,
         image_url,
+        chapters: feed_chapters.chapters,
+        chapters_url: feed_chapters.url,
     }
 }
 
@@ -811,6 +816,27 @@ pub struct EpData {
     pub file_path: Option<PathBuf>,
 }
 
+/// `name` made safe to use as the name of a file or directory.
+pub fn sanitize_file_name(name: &str) -> String {
+    sanitize_with_options(
+        name,
+        Options {
+            truncate: true,
+            windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
+            replacement: "",
+        },
+    )
+}
+
+/// The name of the file an episode is downloaded to: its title, when it was published, and `ext`.
+pub fn episode_file_name(ep_data: &EpData, ext: &str) -> String {
+    let mut file_name = sanitize_file_name(&ep_data.title);
+    if let Some(pubdate) = ep_data.pubdate {
+        file_name = format!("{file_name}_{}", pubdate.format("%Y%m%d_%H%M%S"));
+    }
+    format!("{file_name}.{ext}")
+}
+
 /// This is the function the main controller uses to indicate new
 /// files to download. It uses the threadpool to start jobs
 /// for every episode to be downloaded. New jobs can be requested
@@ -848,8 +874,9 @@ fn download_job(tx_to_main: &Sender<Msg>, ep: EpData, dest: PathBuf, max_retries
 /// indicating success or failure.
 #[allow(clippy::single_match_else)]
 fn download_file(mut ep_data: EpData, destination_path: PathBuf, mut max_retries: usize) -> PCMsg {
-    let agent = ClientBuilder::new()
-        .connect_timeout(Duration::from_secs(10))
+    // episodes can take longer to download than any timeout
+    let agent = crate::http::blocking_client_builder()
+        .timeout(None)
         .build()
         .expect("reqwest client build failed");
 
@@ -892,21 +919,8 @@ fn download_file(mut ep_data: EpData, destination_path: PathBuf, mut max_retries
         // _ => "mp3", // assume .mp3 unless we figure out otherwise
     };
 
-    let mut file_name = sanitize_with_options(
-        &ep_data.title,
-        Options {
-            truncate: true,
-            windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
-            replacement: "",
-        },
-    );
-
-    if let Some(pubdate) = ep_data.pubdate {
-        file_name = format!("{file_name}_{}", pubdate.format("%Y%m%d_%H%M%S"));
-    }
-
     let mut file_path = destination_path;
-    file_path.push(format!("{file_name}.{ext}"));
+    file_path.push(episode_file_name(&ep_data, ext));
 
     let dst = File::create(&file_path);
     if dst.is_err() {
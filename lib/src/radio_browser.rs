@@ -0,0 +1,315 @@
+//! A client of the [radio-browser](https://api.radio-browser.info) directory of internet radio
+//! stations, or of any server with its API.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fmt;
+
+/// How many stations a search finds at most
+const SEARCH_LIMIT: u32 = 100;
+
+/// A station of the directory.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Station {
+    /// The id the directory knows the station by
+    pub uuid: String,
+    pub name: String,
+    /// The stream, or a playlist of it
+    pub url: String,
+    pub homepage: String,
+    /// Comma separated, like `jazz,smooth jazz`
+    pub tags: String,
+    pub country: String,
+    pub codec: String,
+    /// kbit/s, 0 if not known
+    pub bitrate: u32,
+    pub votes: u64,
+    pub clicks: u64,
+}
+
+/// A station as the API returns it.
+#[derive(Deserialize)]
+struct ApiStation {
+    stationuuid: String,
+    name: String,
+    url: String,
+    #[serde(default)]
+    url_resolved: String,
+    #[serde(default)]
+    homepage: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    country: String,
+    #[serde(default)]
+    codec: String,
+    #[serde(default)]
+    bitrate: u32,
+    #[serde(default)]
+    votes: u64,
+    #[serde(default)]
+    clickcount: u64,
+}
+
+impl From<ApiStation> for Station {
+    fn from(station: ApiStation) -> Self {
+        // the directory already resolved playlists to the stream in them
+        let url = if station.url_resolved.is_empty() {
+            station.url
+        } else {
+            station.url_resolved
+        };
+        Self {
+            uuid: station.stationuuid,
+            name: station.name.trim().to_string(),
+            url,
+            homepage: station.homepage,
+            tags: station.tags,
+            country: station.country,
+            codec: station.codec,
+            bitrate: station.bitrate,
+            votes: station.votes,
+            clicks: station.clickcount,
+        }
+    }
+}
+
+/// What the stations found are sorted by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// The most voted for first
+    #[default]
+    Votes,
+    /// The most listened to first
+    Clicks,
+    Name,
+    /// The highest bitrate first
+    Bitrate,
+}
+
+impl Order {
+    /// The order after this one, to go through them all.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Votes => Self::Clicks,
+            Self::Clicks => Self::Name,
+            Self::Name => Self::Bitrate,
+            Self::Bitrate => Self::Votes,
+        }
+    }
+
+    /// The `order` parameter of the API
+    const fn api_name(self) -> &'static str {
+        match self {
+            Self::Votes => "votes",
+            Self::Clicks => "clickcount",
+            Self::Name => "name",
+            Self::Bitrate => "bitrate",
+        }
+    }
+}
+
+impl fmt::Display for Order {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Votes => write!(f, "popularity"),
+            Self::Clicks => write!(f, "listeners"),
+            Self::Name => write!(f, "name"),
+            Self::Bitrate => write!(f, "bitrate"),
+        }
+    }
+}
+
+/// The stations to search for, every field that is not empty has to match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Query {
+    pub name: String,
+    pub tag: String,
+    /// The name of a country, or its two letter code
+    pub country: String,
+    pub codec: String,
+    pub order: Order,
+}
+
+impl Query {
+    /// A query from what was typed into the search, like `jazz tag:smooth country:DE codec:mp3`,
+    /// where the words without a field are the name. A field given twice takes the last value.
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut name = Vec::new();
+        for word in input.split_whitespace() {
+            let (field, value) = match word.split_once(':') {
+                Some(("tag", value)) => (&mut query.tag, value),
+                Some(("country", value)) => (&mut query.country, value),
+                Some(("codec", value)) => (&mut query.codec, value),
+                _ => {
+                    name.push(word);
+                    continue;
+                }
+            };
+            *field = value.to_string();
+        }
+        query.name = name.join(" ");
+        query
+    }
+
+    /// The parameters of the search request.
+    fn params(&self) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        for (key, value) in [
+            ("name", &self.name),
+            ("tag", &self.tag),
+            ("codec", &self.codec),
+        ] {
+            if !value.is_empty() {
+                params.push((key, value.clone()));
+            }
+        }
+        if self.country.len() == 2 {
+            params.push(("countrycode", self.country.to_uppercase()));
+        } else if !self.country.is_empty() {
+            params.push(("country", self.country.clone()));
+        }
+        params.push(("order", self.order.api_name().to_string()));
+        params.push(("reverse", (self.order != Order::Name).to_string()));
+        params.push(("hidebroken", "true".to_string()));
+        params.push(("limit", SEARCH_LIMIT.to_string()));
+        params
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut words = Vec::new();
+        if !self.name.is_empty() {
+            words.push(self.name.clone());
+        }
+        for (key, value) in [
+            ("tag", &self.tag),
+            ("country", &self.country),
+            ("codec", &self.codec),
+        ] {
+            if !value.is_empty() {
+                words.push(format!("{key}:{value}"));
+            }
+        }
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+/// Searches the directory at a base url, like `https://all.api.radio-browser.info`.
+pub struct Client {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl Client {
+    pub fn new(base_url: &str) -> Result<Self> {
+        let client = crate::http::blocking_client_builder().build()?;
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    /// The stations that match `query`, in its order.
+    pub fn search(&self, query: &Query) -> Result<Vec<Station>> {
+        let url = format!("{}/json/stations/search", self.base_url);
+        let stations: Vec<ApiStation> = self
+            .client
+            .get(&url)
+            .query(&query.params())
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .with_context(|| format!("searching {}", self.base_url))?
+            .json()
+            .context("reading the stations found")?;
+        Ok(stations.into_iter().map(Station::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_a_query() {
+        let query = Query::parse("smooth  jazz tag:chill country:DE codec:mp3 tag:lounge");
+        assert_eq!(
+            query,
+            Query {
+                name: "smooth jazz".to_string(),
+                tag: "lounge".to_string(),
+                country: "DE".to_string(),
+                codec: "mp3".to_string(),
+                order: Order::Votes,
+            }
+        );
+        assert_eq!(
+            query.to_string(),
+            "smooth jazz tag:lounge country:DE codec:mp3"
+        );
+        assert_eq!(Order::Bitrate.next(), Order::Votes);
+    }
+
+    #[test]
+    fn searches_a_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let body = r#"[
+                {"stationuuid": "1", "name": " Jazz FM ", "url": "http://jazz.example/listen.pls",
+                 "url_resolved": "http://jazz.example:8000/live", "homepage": "http://jazz.example",
+                 "tags": "jazz,smooth", "country": "Germany", "codec": "MP3", "bitrate": 128,
+                 "votes": 42, "clickcount": 7, "favicon": ""},
+                {"stationuuid": "2", "name": "Other", "url": "http://other.example/stream",
+                 "url_resolved": ""}
+            ]"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            request_line
+        });
+
+        let client = Client::new(&base_url).unwrap();
+        let mut query = Query::parse("jazz country:de");
+        query.order = Order::Name;
+        let stations = client.search(&query).unwrap();
+
+        let request_line = server.join().unwrap();
+        assert!(request_line.starts_with("GET /json/stations/search?name=jazz&countrycode=DE&order=name&reverse=false&hidebroken=true&limit=100 "));
+        assert_eq!(stations.len(), 2);
+        assert_eq!(
+            stations[0],
+            Station {
+                uuid: "1".to_string(),
+                name: "Jazz FM".to_string(),
+                url: "http://jazz.example:8000/live".to_string(),
+                homepage: "http://jazz.example".to_string(),
+                tags: "jazz,smooth".to_string(),
+                country: "Germany".to_string(),
+                codec: "MP3".to_string(),
+                bitrate: 128,
+                votes: 42,
+                clicks: 7,
+            }
+        );
+        assert_eq!(stations[1].url, "http://other.example/stream");
+    }
+}
@@ -0,0 +1,90 @@
+//! The titles radio stations announced while they played, as kept in the music database.
+
+use chrono::{DateTime, Local, Utc};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A title a station announced, like `Artist - Song`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RadioTitle {
+    pub station: String,
+    pub title: String,
+    /// Seconds since the unix epoch
+    pub played_at: u64,
+}
+
+impl RadioTitle {
+    /// `title` announced by `station` now.
+    pub fn new(station: &str, title: &str) -> Self {
+        Self {
+            station: station.to_string(),
+            title: title.to_string(),
+            played_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// When it played in local time, like `2024-05-01 13:37`.
+    pub fn played_at_local(&self) -> String {
+        played_at(self.played_at)
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+    }
+}
+
+fn played_at(secs: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp(i64::try_from(secs).unwrap_or(i64::MAX), 0).unwrap_or_default()
+}
+
+/// `titles` as CSV with a header, the time in RFC 3339 so spreadsheets and scripts read it alike.
+pub fn to_csv(titles: &[RadioTitle]) -> String {
+    let mut csv = String::from("played_at,station,title\r\n");
+    csv.extend(titles.iter().map(|title| {
+        format!(
+            "{},{},{}\r\n",
+            played_at(title.played_at).to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            csv_field(&title.station),
+            csv_field(&title.title)
+        )
+    }));
+    csv
+}
+
+/// `field` quoted if it has to be, as RFC 4180 has it.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn exports_csv() {
+        let titles = [
+            RadioTitle {
+                station: "Jazz FM".to_string(),
+                title: "Miles Davis - So What".to_string(),
+                played_at: 1_700_000_000,
+            },
+            RadioTitle {
+                station: "Rock, Live".to_string(),
+                title: "The \"Band\" - Song".to_string(),
+                played_at: 1_700_000_300,
+            },
+        ];
+        assert_eq!(
+            to_csv(&titles),
+            "played_at,station,title\r\n\
+             2023-11-14T22:13:20Z,Jazz FM,Miles Davis - So What\r\n\
+             2023-11-14T22:18:20Z,\"Rock, Live\",\"The \"\"Band\"\" - Song\"\r\n"
+        );
+    }
+}
@@ -28,8 +28,7 @@ use anyhow::{anyhow, bail, Result};
 use bytes::Buf;
 use lofty::Picture;
 use model::{to_lyric, to_lyric_id_accesskey, to_pic_url, to_song_info, to_song_url};
-use reqwest::blocking::{Client, ClientBuilder};
-use std::time::Duration;
+use reqwest::blocking::Client;
 
 static URL_SEARCH_KUGOU: &str = "http://mobilecdn.kugou.com/api/v3/search/song";
 static URL_LYRIC_SEARCH_KUGOU: &str = "http://krcs.kugou.com/search";
@@ -42,8 +41,7 @@ pub struct Api {
 
 impl Api {
     pub fn new() -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(10))
+        let client = crate::http::blocking_client_builder()
             .build()
             .expect("failed to build reqwest client.");
 
@@ -162,6 +162,15 @@ impl Lyric {
         result
     }
 
+    /// The lines without their time, as plain text.
+    pub fn as_plain_text(&self) -> String {
+        self.unsynced_captions
+            .iter()
+            .map(|line| line.text.trim())
+            .collect::<Vec<_>>()
+            .join(EOL)
+    }
+
     pub fn merge_adjacent(&mut self) {
         let mut unsynced_captions = self.unsynced_captions.clone();
         let mut offset = 1;
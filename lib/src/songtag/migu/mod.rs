@@ -28,8 +28,7 @@ use bytes::Buf;
 use lofty::Picture;
 use model::{to_lyric, to_pic_url, to_song_info};
 // use std::io::Write;
-use reqwest::blocking::{Client, ClientBuilder};
-use std::time::Duration;
+use reqwest::blocking::Client;
 
 static URL_SEARCH_MIGU: &str = "https://m.music.migu.cn/migu/remoting/scr_search_tag";
 static URL_LYRIC_MIGU: &str = "https://music.migu.cn/v3/api/music/audioPlayer/getLyric";
@@ -41,8 +40,7 @@ pub struct Api {
 
 impl Api {
     pub fn new() -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(10))
+        let client = crate::http::blocking_client_builder()
             .build()
             .expect("client build error");
 
@@ -11,10 +11,10 @@ use lazy_static::lazy_static;
 use lofty::Picture;
 use model::{to_lyric, to_song_info, to_song_url, Method, Parse, SongUrl};
 use regex::Regex;
-use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::blocking::Client;
 // use std::io::Write;
 use bytes::Buf;
-use std::{collections::HashMap, time::Duration};
+use std::collections::HashMap;
 
 lazy_static! {
     static ref _CSRF: Regex = Regex::new(r"_csrf=(?P<csrf>[^(;|$)]+)").unwrap();
@@ -57,8 +57,7 @@ enum CryptoApi {
 impl Api {
     #[allow(unused)]
     pub fn new() -> Self {
-        let client = ClientBuilder::new()
-            .timeout(Duration::from_secs(10))
+        let client = crate::http::blocking_client_builder()
             .build()
             .expect("build client error");
 
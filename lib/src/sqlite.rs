@@ -22,11 +22,16 @@
  * SOFTWARE.
  */
 // database
+use crate::audiobook::{natural_cmp, BookProgress, Bookmark};
 use crate::config::Settings;
+use crate::playlist::cue;
+use crate::radio_browser::Station;
+use crate::radio_history::RadioTitle;
 use crate::track::Track;
 use crate::utils::{filetype_supported, get_app_config_path, get_pin_yin};
 use parking_lot::Mutex;
 use rusqlite::{params, Connection, Error, Result, Row};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, UNIX_EPOCH};
@@ -128,6 +133,60 @@ impl DataBase {
         )
         .expect("create table tracks failed");
 
+        // not dropped with the tracks, the books are not in the music dir necessarily
+        conn.execute(
+            "create table if not exists audiobooks(
+             book TEXT PRIMARY KEY NOT NULL,
+             title TEXT,
+             file TEXT NOT NULL,
+             position INTEGER,
+             elapsed INTEGER,
+             duration INTEGER,
+             last_played INTEGER
+            )",
+            [],
+        )
+        .expect("create table audiobooks failed");
+
+        conn.execute(
+            "create table if not exists audiobook_bookmarks(
+             id integer primary key,
+             book TEXT NOT NULL,
+             name TEXT,
+             file TEXT NOT NULL,
+             position INTEGER,
+             created INTEGER
+            )",
+            [],
+        )
+        .expect("create table audiobook_bookmarks failed");
+
+        conn.execute(
+            "create table if not exists radio_favorites(
+             uuid TEXT PRIMARY KEY NOT NULL,
+             name TEXT NOT NULL,
+             url TEXT NOT NULL,
+             homepage TEXT,
+             tags TEXT,
+             country TEXT,
+             codec TEXT,
+             bitrate INTEGER
+            )",
+            [],
+        )
+        .expect("create table radio_favorites failed");
+
+        conn.execute(
+            "create table if not exists radio_history(
+             id integer primary key,
+             station TEXT NOT NULL,
+             title TEXT NOT NULL,
+             played_at INTEGER
+            )",
+            [],
+        )
+        .expect("create table radio_history failed");
+
         let max_depth = config.max_depth_cli;
 
         let conn = Arc::new(Mutex::new(conn));
@@ -147,7 +206,7 @@ impl DataBase {
                 track.title().unwrap_or("Unknown Title").to_string(),
                 track.album().unwrap_or("empty").to_string(),
                 track.genre().unwrap_or("no type").to_string(),
-                track.file().unwrap_or("Unknown File").to_string(),
+                track.location().unwrap_or_else(|| "Unknown File".to_string()),
                 track.duration().as_secs(),
                 track.name().unwrap_or_default().to_string(),
                 track.ext().unwrap_or_default().to_string(),
@@ -202,7 +261,7 @@ impl DataBase {
             .flatten()
             .collect();
         for record in vec {
-            let path = Path::new(&record.file);
+            let path = Path::new(cue::location_file(&record.file));
             if path.exists() {
                 continue;
             }
@@ -232,13 +291,31 @@ impl DataBase {
             .max_depth(self.max_depth);
 
         std::thread::spawn(move || -> Result<()> {
-            for record in all_items
+            let items: Vec<walkdir::DirEntry> = all_items
                 .into_iter()
                 .filter_map(std::result::Result::ok)
                 .filter(|f| f.file_type().is_file())
-                .filter(|f| filetype_supported(&f.path().to_string_lossy()))
-            {
+                .collect();
+            // audio files split by a cue sheet are in the database as its tracks
+            let split_by_cue: HashSet<String> = items
+                .iter()
+                .filter(|f| cue::is_cue(&f.path().to_string_lossy()))
+                .filter_map(|f| cue::read(f.path()).ok())
+                .flat_map(|sheet| sheet.tracks.into_iter().map(|track| track.file))
+                .collect();
+
+            for record in &items {
+                let path = record.path().to_string_lossy();
+                let is_cue = cue::is_cue(&path);
+                if !is_cue && (!filetype_supported(&path) || split_by_cue.contains(&*path)) {
+                    continue;
+                }
                 match Self::need_update(&conn, record.path()) {
+                    Ok(true) if is_cue => {
+                        if let Ok(tracks) = Track::read_cue_sheet(record.path(), true) {
+                            track_vec.extend(tracks);
+                        }
+                    }
                     Ok(true) => {
                         if let Ok(track) = Track::read_from_path(record.path(), true) {
                             track_vec.push(track);
@@ -254,6 +331,11 @@ impl DataBase {
                 Self::add_records(&conn, track_vec)?;
             }
 
+            // whole files added before the cue sheet splitting them was
+            if !split_by_cue.is_empty() {
+                Self::delete_records(&conn, split_by_cue.into_iter().collect())?;
+            }
+
             // delete records where local file are missing
 
             match Self::need_delete(&conn) {
@@ -354,20 +436,17 @@ impl DataBase {
     ///
     /// if the connection is unavailable
     pub fn get_last_position(&mut self, track: &Track) -> Result<Duration> {
-        let query = "SELECT last_position FROM tracks WHERE name = ?1";
+        let (column, key) = Self::position_key(track);
+        let query = format!("SELECT last_position FROM tracks WHERE {column} = ?1");
 
         let mut last_position: Duration = Duration::from_secs(0);
         let conn = self.conn.lock();
-        conn.query_row(
-            query,
-            params![track.name().unwrap_or("Unknown File").to_string(),],
-            |row| {
-                let last_position_u64: u64 = row.get(0)?;
-                // error!("last_position_u64 is {last_position_u64}");
-                last_position = Duration::from_secs(last_position_u64);
-                Ok(last_position)
-            },
-        )?;
+        conn.query_row(&query, params![key], |row| {
+            let last_position_u64: u64 = row.get(0)?;
+            // error!("last_position_u64 is {last_position_u64}");
+            last_position = Duration::from_secs(last_position_u64);
+            Ok(last_position)
+        })?;
         // .expect("get last position failed.");
         // error!("get last pos as {}", last_position.as_secs());
         Ok(last_position)
@@ -377,19 +456,23 @@ impl DataBase {
     ///
     /// if the connection is unavailable
     pub fn set_last_position(&mut self, track: &Track, last_position: Duration) {
-        let query = "UPDATE tracks SET last_position = ?1 WHERE name = ?2";
+        let (column, key) = Self::position_key(track);
+        let query = format!("UPDATE tracks SET last_position = ?1 WHERE {column} = ?2");
         let conn = self.conn.lock();
-        conn.execute(
-            query,
-            params![
-                last_position.as_secs(),
-                track.name().unwrap_or("Unknown File Name").to_string(),
-            ],
-        )
-        .expect("update last position failed.");
+        conn.execute(&query, params![last_position.as_secs(), key])
+            .expect("update last position failed.");
         // error!("set last position as {}", last_position.as_secs());
     }
 
+    /// The column and value the last position of `track` is found by, as the tracks of a cue
+    /// sheet share its name.
+    fn position_key(track: &Track) -> (&'static str, String) {
+        match track.cue() {
+            Some(_) => ("file", track.location().unwrap_or_default()),
+            None => ("name", track.name().unwrap_or("Unknown File").to_string()),
+        }
+    }
+
     /// # Panics
     ///
     /// if the connection is unavailable
@@ -412,4 +495,242 @@ impl DataBase {
 
         Err(Error::QueryReturnedNoRows)
     }
+
+    /// # Errors
+    ///
+    /// if the progress cannot be written to the database
+    pub fn set_audiobook_progress(&mut self, progress: &BookProgress) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO audiobooks (book, title, file, position, elapsed, duration, last_played)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                progress.book,
+                progress.title,
+                progress.file,
+                millis(progress.position),
+                millis(progress.elapsed),
+                millis(progress.duration),
+                progress.last_played,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// if `book` has no progress saved, or the database cannot be read
+    pub fn get_audiobook_progress(&mut self, book: &str) -> Result<BookProgress> {
+        let conn = self.conn.lock();
+        conn.query_row(
+            "SELECT * FROM audiobooks WHERE book = ?",
+            [book],
+            Self::book_progress_db,
+        )
+    }
+
+    /// The books listened to, the last played first.
+    ///
+    /// # Errors
+    ///
+    /// if the database cannot be read
+    pub fn get_audiobooks(&mut self) -> Result<Vec<BookProgress>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT * FROM audiobooks ORDER BY last_played DESC")?;
+        let vec: Vec<BookProgress> = stmt
+            .query_map([], Self::book_progress_db)?
+            .flatten()
+            .collect();
+        Ok(vec)
+    }
+
+    /// Forget the progress and bookmarks of `book`.
+    ///
+    /// # Errors
+    ///
+    /// if the progress or the bookmarks cannot be deleted, in which case neither is
+    pub fn delete_audiobook(&mut self, book: &str) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM audiobooks WHERE book = ?", [book])?;
+        tx.execute("DELETE FROM audiobook_bookmarks WHERE book = ?", [book])?;
+        tx.commit()
+    }
+
+    /// # Errors
+    ///
+    /// if the bookmark cannot be written to the database
+    pub fn add_audiobook_bookmark(&mut self, bookmark: &Bookmark) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT INTO audiobook_bookmarks (book, name, file, position, created)
+            values (?1, ?2, ?3, ?4, ?5)",
+            params![
+                bookmark.book,
+                bookmark.name,
+                bookmark.file,
+                millis(bookmark.position),
+                bookmark.created,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The bookmarks of `book`, in the order of the book.
+    ///
+    /// # Errors
+    ///
+    /// if the database cannot be read
+    pub fn get_audiobook_bookmarks(&mut self, book: &str) -> Result<Vec<Bookmark>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT * FROM audiobook_bookmarks WHERE book = ?")?;
+        let mut vec: Vec<Bookmark> = stmt
+            .query_map([book], |row| {
+                Ok(Bookmark {
+                    id: row.get(0)?,
+                    book: row.get(1)?,
+                    name: row.get(2)?,
+                    file: row.get(3)?,
+                    position: Duration::from_millis(row.get(4)?),
+                    created: row.get(5)?,
+                })
+            })?
+            .flatten()
+            .collect();
+        vec.sort_by(|a, b| natural_cmp(&a.file, &b.file).then(a.position.cmp(&b.position)));
+        Ok(vec)
+    }
+
+    /// # Errors
+    ///
+    /// if the bookmark cannot be deleted
+    pub fn delete_audiobook_bookmark(&mut self, id: i64) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM audiobook_bookmarks WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// if the station cannot be written to the database
+    pub fn add_radio_favorite(&mut self, station: &Station) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "INSERT OR REPLACE INTO radio_favorites (uuid, name, url, homepage, tags, country, codec, bitrate)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                station.uuid,
+                station.name,
+                station.url,
+                station.homepage,
+                station.tags,
+                station.country,
+                station.codec,
+                station.bitrate,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The favorite radio stations, by name.
+    ///
+    /// # Errors
+    ///
+    /// if the database cannot be read
+    pub fn get_radio_favorites(&mut self) -> Result<Vec<Station>> {
+        let conn = self.conn.lock();
+        let mut stmt =
+            conn.prepare("SELECT * FROM radio_favorites ORDER BY name COLLATE NOCASE")?;
+        let vec: Vec<Station> = stmt
+            .query_map([], |row| {
+                Ok(Station {
+                    uuid: row.get(0)?,
+                    name: row.get(1)?,
+                    url: row.get(2)?,
+                    homepage: row.get(3)?,
+                    tags: row.get(4)?,
+                    country: row.get(5)?,
+                    codec: row.get(6)?,
+                    bitrate: row.get(7)?,
+                    ..Station::default()
+                })
+            })?
+            .flatten()
+            .collect();
+        Ok(vec)
+    }
+
+    /// # Errors
+    ///
+    /// if the station cannot be deleted
+    pub fn delete_radio_favorite(&mut self, uuid: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM radio_favorites WHERE uuid = ?", [uuid])?;
+        Ok(())
+    }
+
+    /// Record `title`, unless it is what the same station announced last. Returns whether it was
+    /// recorded.
+    ///
+    /// # Errors
+    ///
+    /// if the title cannot be written to the database
+    pub fn add_radio_title(&mut self, title: &RadioTitle) -> Result<bool> {
+        let conn = self.conn.lock();
+        let last: Option<(String, String)> = conn
+            .query_row(
+                "SELECT station, title FROM radio_history ORDER BY id DESC LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+        if last.is_some_and(|(station, last)| station == title.station && last == title.title) {
+            return Ok(false);
+        }
+        conn.execute(
+            "INSERT INTO radio_history (station, title, played_at) values (?1, ?2, ?3)",
+            params![title.station, title.title, title.played_at],
+        )?;
+        Ok(true)
+    }
+
+    /// The titles radio stations announced, the latest first, all of them if `limit` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// if the database cannot be read
+    pub fn get_radio_history(&mut self, limit: Option<u32>) -> Result<Vec<RadioTitle>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT station, title, played_at FROM radio_history ORDER BY id DESC LIMIT ?",
+        )?;
+        let vec: Vec<RadioTitle> = stmt
+            .query_map([limit.map_or(-1, i64::from)], |row| {
+                Ok(RadioTitle {
+                    station: row.get(0)?,
+                    title: row.get(1)?,
+                    played_at: row.get(2)?,
+                })
+            })?
+            .flatten()
+            .collect();
+        Ok(vec)
+    }
+
+    fn book_progress_db(row: &Row<'_>) -> Result<BookProgress> {
+        Ok(BookProgress {
+            book: row.get(0)?,
+            title: row.get(1)?,
+            file: row.get(2)?,
+            position: Duration::from_millis(row.get(3)?),
+            elapsed: Duration::from_millis(row.get(4)?),
+            duration: Duration::from_millis(row.get(5)?),
+            last_played: row.get(6)?,
+        })
+    }
+}
+
+/// Durations are stored in milliseconds.
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
 }
@@ -1,3 +1,5 @@
+use crate::chapters::{self, Chapter};
+use crate::playlist::cue::{self, CueSection, CueSheet, CueTrack};
 use crate::podcast::Episode;
 /**
  * MIT License
@@ -65,6 +67,12 @@ pub struct Track {
     picture: Option<Picture>,
     album_photo: Option<String>,
     file_type: Option<FileType>,
+    /// Chapters, ordered by start
+    chapters: Vec<Chapter>,
+    /// Where the `podcast:chapters` of an episode can be fetched, until they are
+    chapters_url: Option<String>,
+    /// The part of `file` played, for a track of a cue sheet
+    cue: Option<CueSection>,
     // Date
     // Track
     genre: Option<String>,
@@ -78,7 +86,7 @@ pub struct Track {
 
 impl PartialEq for Track {
     fn eq(&self, other: &Self) -> bool {
-        self.file == other.file
+        self.file == other.file && self.cue == other.cue
     }
 }
 
@@ -117,6 +125,9 @@ impl Track {
             picture: None,
             album_photo: ep.image_url.clone(),
             file_type: None,
+            chapters: ep.chapters.clone(),
+            chapters_url: ep.chapters_url.clone(),
+            cue: None,
             genre: None,
             media_type: Some(MediaType::Podcast),
             podcast_localfile,
@@ -185,6 +196,10 @@ impl Track {
                     .and_then(|pl| pl);
                 song.lyric_frames = lyric_frames;
 
+                if let Some(file_type) = file_type {
+                    song.chapters = chapters::read_from_path(path, file_type);
+                }
+
                 // Get the picture (not necessarily the front cover)
                 let mut picture = tag
                     .pictures()
@@ -215,6 +230,75 @@ impl Track {
         Ok(song)
     }
 
+    /// Read a track as kept in playlists and the database, see [`Self::location`].
+    pub fn read_from_location(location: &str, for_db: bool) -> Result<Self> {
+        match cue::parse_location(location) {
+            Some((sheet, number)) => Self::read_from_cue(sheet, number, for_db),
+            None => Self::read_from_path(location, for_db),
+        }
+    }
+
+    /// Read track `number` of the cue sheet at `sheet`.
+    pub fn read_from_cue<P: AsRef<Path>>(sheet: P, number: u32, for_db: bool) -> Result<Self> {
+        let sheet = sheet.as_ref();
+        let cue_sheet = cue::read(sheet)?;
+        let cue_track = cue_sheet
+            .tracks
+            .iter()
+            .find(|track| track.number == number)
+            .with_context(|| format!("no track {number} in {}", sheet.display()))?;
+        let audio = Self::read_from_path(&cue_track.file, for_db)?;
+        Ok(audio.into_cue_track(sheet, &cue_sheet, cue_track))
+    }
+
+    /// Read all tracks of the cue sheet at `sheet`, skipping those of missing audio files.
+    pub fn read_cue_sheet<P: AsRef<Path>>(sheet: P, for_db: bool) -> Result<Vec<Self>> {
+        let sheet = sheet.as_ref();
+        let cue_sheet = cue::read(sheet)?;
+        let mut tracks = Vec::new();
+        // the audio file read last, as most sheets have all tracks in one
+        let mut audio: Option<Self> = None;
+        for cue_track in &cue_sheet.tracks {
+            if audio.as_ref().and_then(Self::file) != Some(cue_track.file.as_str()) {
+                audio = Self::read_from_path(&cue_track.file, for_db).ok();
+            }
+            if let Some(audio) = &audio {
+                tracks.push(audio.clone().into_cue_track(sheet, &cue_sheet, cue_track));
+            }
+        }
+        Ok(tracks)
+    }
+
+    /// Turn the track of a whole audio file into the part of it `cue_track` is.
+    fn into_cue_track(mut self, sheet: &Path, cue_sheet: &CueSheet, cue_track: &CueTrack) -> Self {
+        let end = cue_track.end.unwrap_or(self.duration);
+        self.duration = end.saturating_sub(cue_track.start);
+        self.title = cue_track.title.clone().or(self.title);
+        self.artist = cue_track
+            .performer
+            .clone()
+            .or_else(|| cue_sheet.performer.clone())
+            .or(self.artist);
+        self.album = cue_sheet.title.clone().or(self.album);
+        self.genre = cue_sheet.genre.clone().or(self.genre);
+        // the sheet is what the library shows and tracks changes of
+        let sheet_track = Self::new(sheet);
+        self.name = sheet_track.name;
+        self.directory = sheet_track.directory;
+        self.last_modified = sheet_track.last_modified;
+        // these are timed for the whole file
+        self.chapters.clear();
+        self.lyric_frames.clear();
+        self.parsed_lyric = None;
+        self.cue = Some(CueSection {
+            sheet: sheet.to_string_lossy().to_string(),
+            number: cue_track.number,
+            start: cue_track.start,
+            end: cue_track.end,
+        });
+        self
+    }
+
     pub fn new_radio(url: &str) -> Self {
         let mut track = Self::new(url);
         track.artist = Some("Radio".to_string());
@@ -223,6 +307,14 @@ impl Track {
         track.media_type = Some(MediaType::LiveRadio);
         track
     }
+
+    /// The same radio station played from `url`, the stream its playlist was resolved to.
+    #[must_use]
+    pub fn with_stream(&self, url: &str) -> Self {
+        let mut track = self.clone();
+        track.file = Some(url.to_string());
+        track
+    }
     fn new<P: AsRef<Path>>(path: P) -> Self {
         let p = path.as_ref();
         let directory = Some(get_parent_folder(&p.to_string_lossy()));
@@ -250,6 +342,9 @@ impl Track {
         Self {
             ext,
             file_type: None,
+            chapters: Vec::new(),
+            chapters_url: None,
+            cue: None,
             artist,
             album,
             title,
@@ -347,6 +442,18 @@ impl Track {
         Some(self.lyric_frames.clone())
     }
 
+    pub fn chapters(&self) -> &[Chapter] {
+        &self.chapters
+    }
+
+    pub fn set_chapters(&mut self, chapters: Vec<Chapter>) {
+        self.chapters = chapters;
+    }
+
+    pub fn chapters_url(&self) -> Option<&str> {
+        self.chapters_url.as_deref()
+    }
+
     pub const fn picture(&self) -> Option<&Picture> {
         self.picture.as_ref()
     }
@@ -393,10 +500,25 @@ impl Track {
         self.title = Some(title.to_string());
     }
 
+    /// The audio file or url played, which for a track of a cue sheet is shared with the others.
     pub fn file(&self) -> Option<&str> {
         self.file.as_deref()
     }
 
+    /// Where the track is kept in playlists and the database, which is [`Self::file`] unless it
+    /// is a track of a cue sheet.
+    pub fn location(&self) -> Option<String> {
+        match &self.cue {
+            Some(section) => Some(cue::location(&section.sheet, section.number)),
+            None => self.file.clone(),
+        }
+    }
+
+    /// The part of [`Self::file`] that is played, for a track of a cue sheet.
+    pub const fn cue(&self) -> Option<&CueSection> {
+        self.cue.as_ref()
+    }
+
     pub fn directory(&self) -> Option<&str> {
         self.directory.as_deref()
     }
@@ -430,6 +552,9 @@ impl Track {
     }
 
     pub fn save_tag(&mut self) -> Result<()> {
+        if self.cue.is_some() {
+            bail!("tags of a cue sheet track are in the sheet, not the audio file");
+        }
         match self.file_type {
             Some(FileType::Mpeg) => {
                 if let Some(file_path) = self.file() {
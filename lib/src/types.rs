@@ -1,6 +1,7 @@
 use crate::config::{BindingForEvent, ColorTermusic};
 use crate::invidious::{Instance, YoutubeVideo};
 use crate::podcast::{EpData, PodcastFeed, PodcastNoId};
+use crate::radio_browser::Station;
 use crate::songtag::SongTag;
 use anyhow::{anyhow, Result};
 use image::DynamicImage;
@@ -8,6 +9,7 @@ use image::DynamicImage;
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Msg {
     // AppClose,
+    Audiobook(AudiobookMsg),
     ConfigEditor(ConfigEditorMsg),
     DataBase(DBMsg),
     DeleteConfirmCloseCancel,
@@ -21,11 +23,19 @@ pub enum Msg {
     LayoutTreeView,
     LayoutDataBase,
     LayoutPodCast,
+    LayoutRadio,
     Library(LIMsg),
     LyricMessage(LyricMsg),
     LyricCycle,
     LyricAdjustDelay(i64),
+    OutputDevicePopupShow,
+    OutputDevicePopupCloseCancel,
+    OutputDevicePopupCloseOk(usize),
+    VisualizerCycle,
     PlayerToggleGapless,
+    PlayerSwitchBackend,
+    PlayerRadioGoLive,
+    PlayerRadioRecord,
     PlayerTogglePause,
     PlayerVolumeUp,
     PlayerVolumeDown,
@@ -33,8 +43,15 @@ pub enum Msg {
     PlayerSpeedDown,
     PlayerSeekForward,
     PlayerSeekBackward,
+    PlayerAbLoopSetA,
+    PlayerAbLoopSetB,
+    PlayerAbLoopClear,
+    PlayerChapterNext,
+    PlayerChapterPrevious,
     Playlist(PLMsg),
     Podcast(PCMsg),
+    Radio(RadioMsg),
+    RadioHistory(RadioHistoryMsg),
     QuitPopupCloseCancel,
     QuitPopupCloseOk,
     QuitPopupShow,
@@ -51,6 +68,17 @@ pub enum Msg {
     None,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AudiobookMsg {
+    BookmarkInputShow,
+    BookmarkInputCloseCancel,
+    BookmarkInputCloseOk(String),
+    TablePopupShow,
+    TablePopupCloseCancel,
+    TablePopupCloseOk(usize),
+    TablePopupDelete(usize),
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum XYWHMsg {
     Hide,
@@ -293,6 +321,48 @@ pub enum KFMsg {
     PodcastRefreshFeedBlurUp,
     PodcastRefreshAllFeedsBlurDown,
     PodcastRefreshAllFeedsBlurUp,
+    GlobalPlayerOutputDeviceBlurDown,
+    GlobalPlayerOutputDeviceBlurUp,
+    GlobalVisualizerCycleBlurDown,
+    GlobalVisualizerCycleBlurUp,
+    GlobalPlayerAbLoopSetABlurDown,
+    GlobalPlayerAbLoopSetABlurUp,
+    GlobalPlayerAbLoopSetBBlurDown,
+    GlobalPlayerAbLoopSetBBlurUp,
+    GlobalPlayerAbLoopClearBlurDown,
+    GlobalPlayerAbLoopClearBlurUp,
+    GlobalPlayerChapterNextBlurDown,
+    GlobalPlayerChapterNextBlurUp,
+    GlobalPlayerChapterPreviousBlurDown,
+    GlobalPlayerChapterPreviousBlurUp,
+    GlobalAudiobookBookmarkBlurDown,
+    GlobalAudiobookBookmarkBlurUp,
+    GlobalAudiobookShowBlurDown,
+    GlobalAudiobookShowBlurUp,
+    GlobalPlayerSwitchBackendBlurDown,
+    GlobalPlayerSwitchBackendBlurUp,
+    GlobalPlayerRadioGoLiveBlurDown,
+    GlobalPlayerRadioGoLiveBlurUp,
+    GlobalPlayerRadioRecordBlurDown,
+    GlobalPlayerRadioRecordBlurUp,
+    GlobalLayoutRadioBlurDown,
+    GlobalLayoutRadioBlurUp,
+    RadioSearchBlurDown,
+    RadioSearchBlurUp,
+    RadioFavoriteBlurDown,
+    RadioFavoriteBlurUp,
+    RadioDeleteFavoriteBlurDown,
+    RadioDeleteFavoriteBlurUp,
+    RadioSortCycleBlurDown,
+    RadioSortCycleBlurUp,
+    GlobalRadioHistoryBlurDown,
+    GlobalRadioHistoryBlurUp,
+    RadioHistorySearchYoutubeBlurDown,
+    RadioHistorySearchYoutubeBlurUp,
+    RadioHistorySearchLyricBlurDown,
+    RadioHistorySearchLyricBlurUp,
+    RadioHistoryExportBlurDown,
+    RadioHistoryExportBlurUp,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -360,6 +430,46 @@ pub enum PCMsg {
     SearchError(String),
 }
 
+/// Radio station directory view messages
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RadioMsg {
+    FavoritesBlurDown,
+    FavoritesBlurUp,
+    StationsBlurDown,
+    StationsBlurUp,
+    SearchPopupShow,
+    SearchPopupCloseCancel,
+    SearchPopupCloseOk(String),
+    SearchSuccess(Vec<Station>),
+    SearchError(String),
+    /// Search again sorted by the next order
+    SortCycle,
+    /// Add the station found at INDEX to the playlist
+    StationAdd(usize),
+    /// Add the station found at INDEX to the favorites
+    FavoriteAdd(usize),
+    /// Add the favorite at INDEX to the playlist
+    FavoritePlay(usize),
+    /// Remove the favorite at INDEX
+    FavoriteDelete(usize),
+}
+
+/// Messages of the popup listing the titles radio stations announced
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RadioHistoryMsg {
+    TablePopupShow,
+    TablePopupCloseCancel,
+    /// Search the title at INDEX on youtube
+    SearchYoutube(usize),
+    /// Search the title at INDEX with the lyric providers
+    SearchLyric(usize),
+    /// Lyric found for TITLE
+    LyricFound(String, String),
+    /// No lyric found for TITLE
+    LyricNotFound(String),
+    ExportCsv,
+}
+
 /// Playlist Library View messages
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PLMsg {
@@ -457,6 +567,8 @@ pub enum TFMsg {
 
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
 pub enum Id {
+    AudiobookBookmarkInputPopup,
+    AudiobookTablePopup,
     ConfigEditor(IdConfigEditor),
     DBListCriteria,
     DBListSearchResult,
@@ -474,6 +586,7 @@ pub enum Id {
     Library,
     Lyric,
     MessagePopup,
+    OutputDeviceTablePopup,
     Playlist,
     Podcast,
     PodcastAddPopup,
@@ -482,10 +595,15 @@ pub enum Id {
     FeedDeleteConfirmInputPopup,
     Progress,
     QuitPopup,
+    RadioFavorites,
+    RadioHistoryTablePopup,
+    RadioSearchInputPopup,
+    RadioStations,
     SavePlaylistPopup,
     SavePlaylistLabel,
     SavePlaylistConfirm,
     TagEditor(IdTagEditor),
+    Visualizer,
     YoutubeSearchInputPopup,
     YoutubeSearchTablePopup,
 }
@@ -613,6 +731,27 @@ pub enum IdKey {
     PodcastSearchAddFeed,
     PodcastRefreshFeed,
     PodcastRefreshAllFeeds,
+    GlobalPlayerOutputDevice,
+    GlobalVisualizerCycle,
+    GlobalPlayerAbLoopSetA,
+    GlobalPlayerAbLoopSetB,
+    GlobalPlayerAbLoopClear,
+    GlobalPlayerChapterNext,
+    GlobalPlayerChapterPrevious,
+    GlobalAudiobookBookmark,
+    GlobalAudiobookShow,
+    GlobalPlayerSwitchBackend,
+    GlobalPlayerRadioGoLive,
+    GlobalPlayerRadioRecord,
+    GlobalLayoutRadio,
+    RadioSearch,
+    RadioFavorite,
+    RadioDeleteFavorite,
+    RadioSortCycle,
+    GlobalRadioHistory,
+    RadioHistorySearchYoutube,
+    RadioHistorySearchLyric,
+    RadioHistoryExport,
 }
 pub enum SearchLyricState {
     Finish(Vec<SongTag>),
@@ -1,4 +1,4 @@
-use crate::config::Settings;
+use crate::config::{BackendKind, Settings};
 use anyhow::{anyhow, bail, Result};
 use lazy_static::lazy_static;
 use pinyin::ToPinyin;
@@ -207,36 +207,35 @@ pub fn parse_hex_color(color: &str) -> Option<Color> {
     })
 }
 
+/// Extensions of the files every backend can play.
+const FILETYPES_ALL_BACKENDS: [&str; 14] = [
+    "mkv", "mka", "mp3", "aiff", "aif", "aifc", "flac", "m4a", "m4b", "aac", "opus", "ogg", "wav",
+    "webm",
+];
+/// Extensions of the files only the backends decoding through ffmpeg or gstreamer plugins can play.
+const FILETYPES_EXTERNAL_DECODERS: [&str; 3] = ["ape", "wv", "mpc"];
+
+/// Whether any backend can play `current_node`, see [`filetype_supported_by`].
 pub fn filetype_supported(current_node: &str) -> bool {
+    filetype_supported_by(current_node, None)
+}
+
+/// Whether `backend` can play `current_node`, or any backend if `None`.
+pub fn filetype_supported_by(current_node: &str, backend: Option<BackendKind>) -> bool {
     let p = Path::new(current_node);
 
     if p.starts_with("http") {
         return true;
     }
 
-    #[cfg(any(feature = "mpv", feature = "gst"))]
-    if let Some(ext) = p.extension() {
-        if ext == "opus" {
-            return true;
-        }
-        if ext == "aiff" {
-            return true;
-        }
+    let Some(ext) = p.extension().and_then(OsStr::to_str) else {
+        return false;
+    };
+    if FILETYPES_ALL_BACKENDS.contains(&ext) {
+        return true;
     }
 
-    match p.extension() {
-        Some(ext) if ext == "mkv" || ext == "mka" => true,
-        Some(ext) if ext == "mp3" => true,
-        // Some(ext) if ext == "aiff" => true,
-        Some(ext) if ext == "flac" => true,
-        Some(ext) if ext == "m4a" => true,
-        Some(ext) if ext == "aac" => true,
-        // Some(ext) if ext == "opus" => true,
-        Some(ext) if ext == "ogg" => true,
-        Some(ext) if ext == "wav" => true,
-        Some(ext) if ext == "webm" => true,
-        Some(_) | None => false,
-    }
+    backend != Some(BackendKind::Rusty) && FILETYPES_EXTERNAL_DECODERS.contains(&ext)
 }
 
 pub fn is_playlist(current_node: &str) -> bool {
@@ -377,6 +376,16 @@ fn get_podcast_save_path(config: &Settings) -> Result<PathBuf> {
     Ok(full_path_pathbuf)
 }
 
+/// The directory of the stream cache, with `~` expanded.
+pub fn get_stream_cache_path(config: &Settings) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&config.player_cache_dir).to_string())
+}
+
+/// The directory recordings of radios go to, with `~` expanded.
+pub fn get_radio_record_path(config: &Settings) -> PathBuf {
+    PathBuf::from(shellexpand::tilde(&config.player_radio_record_dir).to_string())
+}
+
 pub fn create_podcast_dir(config: &Settings, pod_title: String) -> Result<PathBuf> {
     match get_podcast_save_path(config) {
         Ok(mut download_path) => {
@@ -499,4 +508,27 @@ mod tests {
         assert_eq!(child.width, 271);
         assert_eq!(child.height, 54);
     }
+
+    #[test]
+    fn test_filetype_supported_by_backend() {
+        assert!(filetype_supported_by(
+            "/music/a.opus",
+            Some(BackendKind::Rusty)
+        ));
+        assert!(filetype_supported_by(
+            "/music/a.aiff",
+            Some(BackendKind::Rusty)
+        ));
+        assert!(!filetype_supported_by(
+            "/music/a.ape",
+            Some(BackendKind::Rusty)
+        ));
+        assert!(filetype_supported_by(
+            "/music/a.ape",
+            Some(BackendKind::Mpv)
+        ));
+        assert!(filetype_supported("/music/a.ape"));
+        assert!(!filetype_supported("/music/a.txt"));
+        assert!(!filetype_supported("/music/opus"));
+    }
 }
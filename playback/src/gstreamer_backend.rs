@@ -37,6 +37,7 @@ use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::time::Duration;
 use termusiclib::config::Settings;
+use termusiclib::http::BackendOptions;
 use termusiclib::track::{MediaType, Track};
 
 /// This trait allows for easy conversion of a path to a URI
@@ -248,6 +249,15 @@ impl GStreamerBackend {
             None
         });
 
+        // streams are fetched by the source playbin makes for them, like souphttpsrc
+        let http = termusiclib::http::backend_options();
+        this.playbin.connect("source-setup", false, move |values| {
+            if let Ok(source) = values[1].get::<Element>() {
+                set_http_options(&source, &http);
+            }
+            None
+        });
+
         this
     }
     pub fn skip_one(&mut self) {
@@ -479,8 +489,30 @@ impl PlayerTrait for GStreamerBackend {
         self.skip_one();
     }
 
-    fn enqueue_next(&mut self, file: &str) {
-        self.enqueue_next(file);
+    fn enqueue_next(&mut self, track: &Track) {
+        if let Some(file) = track.file() {
+            self.enqueue_next(file);
+        }
+    }
+}
+
+/// Have `source` fetch with the proxy, user agent and headers of the config, if it fetches over
+/// HTTP.
+fn set_http_options(source: &Element, http: &BackendOptions) {
+    if let Some(proxy) = &http.proxy {
+        if source.has_property("proxy", None) {
+            source.set_property("proxy", proxy.as_str());
+        }
+    }
+    if source.has_property("user-agent", None) {
+        source.set_property("user-agent", http.user_agent.as_str());
+    }
+    if !http.headers.is_empty() && source.has_property("extra-headers", None) {
+        let mut headers = gst::Structure::new_empty("extra-headers");
+        for (name, value) in &http.headers {
+            headers.set(name.as_str(), value.clone());
+        }
+        source.set_property("extra-headers", headers);
     }
 }
 
@@ -47,6 +47,24 @@ pub mod player {
             }
         }
     }
+
+    impl From<termusiclib::chapters::Chapter> for Chapter {
+        fn from(value: termusiclib::chapters::Chapter) -> Self {
+            Self {
+                start: Some(value.start.into()),
+                title: value.title,
+            }
+        }
+    }
+
+    impl From<Chapter> for termusiclib::chapters::Chapter {
+        fn from(value: Chapter) -> Self {
+            Self {
+                start: value.start.unwrap_or_default().into(),
+                title: value.title,
+            }
+        }
+    }
 }
 
 #[cfg(feature = "gst")]
@@ -59,12 +77,21 @@ mod rusty_backend;
 mod discord;
 mod mpris;
 pub mod playlist;
+mod radio;
+pub mod spectrum;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 pub use playlist::{Playlist, Status};
+use radio::RadioResolver;
+use spectrum::SpectrumSender;
+use termusic_stream::cache::StreamCache;
+use termusic_stream::source::strip_radio_title;
+pub use termusic_stream::source::StreamState;
+pub use termusic_stream::timeshift::TimeshiftProgress;
 // use std::sync::RwLock;
 // use std::sync::{Arc, Mutex};
-use termusiclib::config::{LastPosition, SeekStep, Settings};
+use termusiclib::chapters::{self, Chapter};
+use termusiclib::config::{BackendKind, LastPosition, SeekStep, Settings};
 // use tokio::sync::Mutex;
 // use parking_lot::Mutex;
 // use std::sync::Arc;
@@ -72,16 +99,23 @@ use termusiclib::config::{LastPosition, SeekStep, Settings};
 // #[cfg(not(any(feature = "mpv", feature = "gst")))]
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use termusiclib::audiobook::Audiobook;
 use termusiclib::podcast::db::Database as DBPod;
+use termusiclib::podcast::{episode_file_name, sanitize_file_name};
+use termusiclib::radio_history::RadioTitle;
 use termusiclib::sqlite::DataBase;
 use termusiclib::track::{MediaType, Track};
-use termusiclib::utils::get_app_config_path;
+use termusiclib::utils::{create_podcast_dir, get_app_config_path, get_stream_cache_path};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 #[macro_use]
 extern crate log;
 
+/// How often the progress of an audiobook is stored while it plays.
+const AUDIOBOOK_SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BackendSelect {
     #[cfg(feature = "mpv")]
@@ -101,6 +135,24 @@ pub enum BackendSelect {
     Default,
 }
 
+impl TryFrom<BackendKind> for BackendSelect {
+    type Error = anyhow::Error;
+
+    /// Fails for a backend that is not compiled in.
+    #[allow(unreachable_patterns)]
+    fn try_from(kind: BackendKind) -> Result<Self> {
+        match kind {
+            #[cfg(feature = "mpv")]
+            BackendKind::Mpv => Ok(Self::Mpv),
+            #[cfg(feature = "rusty")]
+            BackendKind::Rusty => Ok(Self::Rusty),
+            #[cfg(feature = "gst")]
+            BackendKind::GStreamer => Ok(Self::GStreamer),
+            _ => bail!("backend \"{kind}\" is not compiled in"),
+        }
+    }
+}
+
 /// Enum to choose backend at runtime
 pub enum Backend {
     #[cfg(feature = "mpv")]
@@ -165,6 +217,32 @@ impl Backend {
         Self::Mpv(mpv_backend::MpvBackend::new(config, cmd_tx))
     }
 
+    /// The backends compiled in, in the order of [`BackendSelect::Default`]
+    #[must_use]
+    pub fn compiled() -> Vec<BackendKind> {
+        [
+            #[cfg(feature = "rusty")]
+            BackendKind::Rusty,
+            #[cfg(feature = "gst")]
+            BackendKind::GStreamer,
+            #[cfg(feature = "mpv")]
+            BackendKind::Mpv,
+        ]
+        .to_vec()
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> BackendKind {
+        match self {
+            #[cfg(feature = "mpv")]
+            Backend::Mpv(_) => BackendKind::Mpv,
+            #[cfg(feature = "rusty")]
+            Backend::Rusty(_) => BackendKind::Rusty,
+            #[cfg(feature = "gst")]
+            Backend::GStreamer(_) => BackendKind::GStreamer,
+        }
+    }
+
     #[must_use]
     pub fn as_player(&self) -> &dyn PlayerTrait {
         match self {
@@ -192,11 +270,30 @@ impl Backend {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum PlayerCmd {
+    AbLoopClear,
+    AbLoopSetA,
+    AbLoopSetB,
     AboutToFinish,
+    /// Go to a file and position of an audiobook
+    AudiobookJump(String, Duration),
+    /// The files of an audiobook read in the background
+    AudiobookOpened(Audiobook),
+    ChapterNext,
+    ChapterPrevious,
+    /// The `podcast:chapters` fetched for the file of a track
+    ChaptersFetched(String, Vec<Chapter>),
     CycleLoop,
     Eos,
     GetProgress,
+    GetOutputDevices,
     PlaySelected,
+    /// Catch up with the live edge of the radio playing
+    RadioGoLive,
+    /// Start or stop recording the radio playing
+    RadioRecord,
+    /// The stream the playlist of a radio station was resolved to in the background, `None` if
+    /// it could not be
+    RadioResolved(String, Option<String>),
     SkipPrevious,
     Pause,
     Play,
@@ -206,9 +303,12 @@ pub enum PlayerCmd {
     ReloadPlaylist,
     SeekBackward,
     SeekForward,
+    SetOutputDevice(String),
     SkipNext,
     SpeedDown,
     SpeedUp,
+    /// Play on with another backend, the next compiled in one if `None`
+    SwitchBackend(Option<BackendKind>),
     Tick,
     ToggleGapless,
     TogglePause,
@@ -216,17 +316,48 @@ pub enum PlayerCmd {
     VolumeUp,
 }
 
+/// The cache podcasts and other streams are kept in, unless `player_cache_max_mb` turns it off.
+#[must_use]
+pub fn stream_cache(config: &Settings) -> Option<StreamCache> {
+    (config.player_cache_max_mb > 0).then(|| {
+        StreamCache::new(
+            get_stream_cache_path(config),
+            config.player_cache_max_mb * 1024 * 1024,
+        )
+    })
+}
+
 #[allow(clippy::module_name_repetitions)]
 pub struct GeneralPlayer {
     pub backend: Backend,
     pub playlist: Playlist,
     pub config: Settings,
     pub current_track_updated: bool,
+    /// A-B repeat on the current track, cleared when the track changes
+    pub ab_loop: AbLoop,
+    /// Whether the backend loops [`Self::ab_loop`] itself
+    ab_loop_in_backend: bool,
+    /// The audiobook the current track belongs to
+    audiobook: Option<Audiobook>,
+    /// The path of the audiobook being opened in the background
+    audiobook_opening: Option<PathBuf>,
+    /// Where to start the next track instead of the stored audiobook progress
+    audiobook_jump: Option<(String, Duration)>,
+    audiobook_saved: Instant,
+    /// The backend started with or switched to, which plays what has no other backend preferred
+    /// in `player_backend_by_extension`
+    default_backend: BackendKind,
     pub mpris: mpris::Mpris,
     pub discord: discord::Rpc,
     pub db: DataBase,
     pub db_podcast: DBPod,
     pub cmd_tx: PlayerCmdSender,
+    /// The streams of the radio station playlists played
+    radio: RadioResolver,
+    /// Whether the track playing was skipped, so its end is no failure of its stream
+    skipped: bool,
+    /// The title the radio playing announced last, which is in the radio history
+    radio_title_recorded: String,
 }
 
 impl GeneralPlayer {
@@ -242,7 +373,9 @@ impl GeneralPlayer {
         cmd_tx: PlayerCmdSender,
     ) -> Result<Self> {
         let backend = Backend::new_select(backend, config, cmd_tx.clone());
-        let playlist = Playlist::new(config).unwrap_or_default();
+        let default_backend = backend.kind();
+        let mut playlist = Playlist::new(config).unwrap_or_default();
+        playlist.set_backend(Some(default_backend));
 
         let db_path = get_app_config_path().with_context(|| "failed to get podcast db path.")?;
 
@@ -258,6 +391,16 @@ impl GeneralPlayer {
             db_podcast,
             cmd_tx,
             current_track_updated: false,
+            ab_loop: AbLoop::default(),
+            ab_loop_in_backend: false,
+            audiobook: None,
+            audiobook_opening: None,
+            audiobook_jump: None,
+            audiobook_saved: Instant::now(),
+            default_backend,
+            radio: RadioResolver::default(),
+            skipped: false,
+            radio_title_recorded: String::new(),
         })
     }
 
@@ -280,6 +423,102 @@ impl GeneralPlayer {
         self.backend.as_player_mut()
     }
 
+    #[must_use]
+    pub const fn backend_kind(&self) -> BackendKind {
+        self.backend.kind()
+    }
+
+    /// The compiled in backend after the one in use, to cycle through them.
+    #[must_use]
+    pub fn next_backend(&self) -> BackendKind {
+        let compiled = Backend::compiled();
+        let current = self.backend_kind();
+        compiled
+            .iter()
+            .position(|kind| *kind == current)
+            .and_then(|index| compiled.get(index + 1))
+            .or_else(|| compiled.first())
+            .copied()
+            .unwrap_or(current)
+    }
+
+    /// Play with the `kind` backend from now on, the current track going on from where it was,
+    /// with the same volume and speed.
+    ///
+    /// # Errors
+    ///
+    /// - if `kind` is not compiled in
+    ///
+    /// # Panics
+    ///
+    /// panics if the [`tokio::runtime::Runtime`] fails to build
+    pub fn switch_backend(&mut self, kind: BackendKind) -> Result<()> {
+        if kind == self.backend_kind() {
+            self.default_backend = kind;
+            self.playlist.set_backend(Some(kind));
+            return Ok(());
+        }
+        let position = self.position();
+        self.replace_backend(kind)?;
+        self.default_backend = kind;
+        self.playlist.set_backend(Some(kind));
+
+        let status = self.playlist.status();
+        let Some(track) = self.playlist.current_track().cloned() else {
+            return Ok(());
+        };
+        if status == Status::Stopped {
+            return Ok(());
+        }
+        self.current_track_updated = true;
+        let wait = async {
+            self.add_and_play(&track).await;
+        };
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create runtime")
+            .block_on(wait);
+        if track.media_type != Some(MediaType::LiveRadio) {
+            self.seek_to(position);
+        }
+        self.ab_loop_apply();
+        #[cfg(feature = "rusty")]
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref mut backend) = self.backend {
+            backend.message_on_end();
+        }
+        if status == Status::Paused {
+            self.get_player_mut().pause();
+        }
+        Ok(())
+    }
+
+    /// The backend to play `track` with, see `default_backend`.
+    fn backend_for(&self, track: &Track) -> BackendKind {
+        track
+            .file()
+            .and_then(|file| self.config.backend_for(file))
+            .filter(|kind| BackendSelect::try_from(*kind).is_ok())
+            .unwrap_or(self.default_backend)
+    }
+
+    /// Tear down the backend in use for a new one of `kind`, which keeps the volume, speed and
+    /// gapless setting.
+    fn replace_backend(&mut self, kind: BackendKind) -> Result<()> {
+        let select = BackendSelect::try_from(kind)?;
+        info!("switching backend from {} to {kind}", self.backend_kind());
+        let mut config = self.config.clone();
+        config.player_volume = self.volume();
+        config.player_speed = self.speed();
+        config.player_gapless = self.gapless();
+        self.get_player_mut().stop();
+        self.backend = Backend::new_select(select, &config, self.cmd_tx.clone());
+        // what was queued for gapless playback went away with the old backend
+        self.playlist.set_next_track(None);
+        Ok(())
+    }
+
     pub fn toggle_gapless(&mut self) -> bool {
         let new_gapless = !self.backend.as_player().gapless();
         self.backend.as_player_mut().set_gapless(new_gapless);
@@ -295,7 +534,16 @@ impl GeneralPlayer {
             self.playlist.set_status(Status::Running);
         }
 
+        if let Some(track) = self.playlist.current_track() {
+            if let Err(e) = self.keep_cached_episode(track) {
+                error!("error keeping the cached episode {:?}: {e}", track.file());
+            }
+        }
+
         self.playlist.proceed();
+        self.ab_loop_clear();
+        self.fetch_chapters();
+        self.audiobook_update();
 
         if let Some(track) = self.playlist.current_track() {
             let track = track.clone();
@@ -313,6 +561,12 @@ impl GeneralPlayer {
             }
 
             self.current_track_updated = true;
+            let kind = self.backend_for(&track);
+            if kind != self.backend_kind() {
+                if let Err(e) = self.replace_backend(kind) {
+                    error!("error switching to the backend for {:?}: {e}", track.file());
+                }
+            }
             let wait = async {
                 self.add_and_play(&track).await;
             };
@@ -332,8 +586,147 @@ impl GeneralPlayer {
         }
     }
 
+    /// Move the episode of `track` out of the stream cache into the podcast directory, as if it
+    /// was downloaded, when `podcast_cached_as_downloaded` is on and all of it was streamed.
+    fn keep_cached_episode(&self, track: &Track) -> Result<()> {
+        if !self.config.podcast_cached_as_downloaded
+            || track.media_type != Some(MediaType::Podcast)
+            || track.podcast_localfile.is_some()
+        {
+            return Ok(());
+        }
+        let (Some(url), Some(cache)) = (track.file(), stream_cache(&self.config)) else {
+            return Ok(());
+        };
+        if cache.complete_file(url).is_none() {
+            return Ok(());
+        }
+        let Some((ep_data, podcast_title)) = self.db_podcast.get_undownloaded_episode(url)? else {
+            return Ok(());
+        };
+
+        // the extension of the url, if it looks like one
+        let ext = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| {
+                let ext = Path::new(url.path()).extension()?.to_str()?.to_lowercase();
+                (!ext.is_empty() && ext.len() <= 4 && ext.chars().all(char::is_alphanumeric))
+                    .then_some(ext)
+            })
+            .unwrap_or_else(|| "mp3".to_string());
+        let mut path = create_podcast_dir(&self.config, sanitize_file_name(&podcast_title))?;
+        path.push(episode_file_name(&ep_data, &ext));
+        if cache.take_complete(url, &path)? {
+            self.db_podcast.insert_file(ep_data.id, &path)?;
+            info!("Kept cached episode {url} as {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Whether the radio station that ended is to be played again from the next stream of its
+    /// playlist, as a live stream only ends when it fails, unless it was skipped.
+    pub fn radio_stream_failed(&mut self) -> bool {
+        if std::mem::take(&mut self.skipped) {
+            return false;
+        }
+        match self.playlist.current_track() {
+            Some(track) if track.media_type == Some(MediaType::LiveRadio) => {
+                let track = track.clone();
+                self.radio.stream_failed(&track)
+            }
+            _ => false,
+        }
+    }
+
+    /// Resolve the playlist of the radio station `track` in the background, as that connects to
+    /// its streams, to be played once it comes back as [`PlayerCmd::RadioResolved`].
+    fn radio_resolve(&mut self, track: &Track) {
+        let Some((url, failed)) = self.radio.start_resolving(track) else {
+            return;
+        };
+        let cmd_tx = self.cmd_tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("resolve radio playlist".into())
+            .spawn(move || {
+                let stream = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime.block_on(radio::resolve(&url, &failed)),
+                    Err(e) => {
+                        error!("error creating the runtime to resolve {url}: {e}");
+                        None
+                    }
+                };
+                // the player may be gone already
+                let _ = cmd_tx.send(PlayerCmd::RadioResolved(url, stream));
+            });
+        if let Err(e) = spawned {
+            error!("error starting to resolve the radio playlist: {e}");
+            if let Some(url) = track.file() {
+                self.radio.resolved_to(url, None);
+            }
+        }
+    }
+
+    /// The playlist at `url` was resolved to `stream`, which plays if its station is still the
+    /// current track, or the playlist itself if it could not be resolved.
+    ///
+    /// # Panics
+    ///
+    /// panics if the [`tokio::runtime::Runtime`] fails to build
+    pub fn radio_resolved(&mut self, url: &str, stream: Option<String>) {
+        if !self.radio.resolved_to(url, stream) || self.playlist.is_stopped() {
+            return;
+        }
+        let Some(track) = self
+            .playlist
+            .current_track()
+            .filter(|track| track.file() == Some(url))
+            .cloned()
+        else {
+            return;
+        };
+        let track = self.radio.resolved(&track).unwrap_or(track);
+        let wait = async {
+            self.get_player_mut().add_and_play(&track).await;
+        };
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create runtime")
+            .block_on(wait);
+        #[cfg(feature = "rusty")]
+        #[allow(irrefutable_let_patterns)]
+        if let Backend::Rusty(ref mut backend) = self.backend {
+            backend.message_on_end();
+        }
+    }
+
+    /// Keep `radio_title` the station playing announced in the radio history, once for every
+    /// title it announces.
+    pub fn radio_history_record(&mut self, radio_title: &str) {
+        let title = strip_radio_title(radio_title);
+        if title.is_empty() || title == self.radio_title_recorded {
+            return;
+        }
+        let Some(track) = self.playlist.current_track() else {
+            return;
+        };
+        let station = track
+            .title()
+            .or_else(|| track.file())
+            .unwrap_or("Unknown Station");
+        if let Err(e) = self.db.add_radio_title(&RadioTitle::new(station, title)) {
+            error!("error recording radio title {title}: {e}");
+        }
+        self.radio_title_recorded = title.to_string();
+    }
+
     fn add_and_play_mpris_discord(&mut self) {
         if let Some(track) = self.playlist.current_track() {
+            self.backend.as_player_mut().set_now_playing(track);
+
             if self.config.player_use_mpris {
                 self.mpris.add_and_play(track);
             }
@@ -343,6 +736,7 @@ impl GeneralPlayer {
             }
         }
     }
+
     pub fn enqueue_next_from_playlist(&mut self) {
         if self.playlist.next_track().is_some() {
             return;
@@ -352,18 +746,25 @@ impl GeneralPlayer {
             Some(t) => t.clone(),
             None => return,
         };
+        // another backend starts it once the current track ended
+        if self.backend_for(&track) != self.backend_kind() {
+            return;
+        }
 
-        self.playlist.set_next_track(Some(&track));
-        if let Some(file) = track.file() {
-            self.get_player_mut().enqueue_next(file);
+        // a radio playlist not resolved yet is resolved when it plays, not to hold up the player
+        let Some(playable) = self.radio.resolved(&track) else {
+            return;
+        };
 
-            info!("Next track enqueued: {:#?}", file);
-        }
+        self.playlist.set_next_track(Some(&track));
+        self.get_player_mut().enqueue_next(&playable);
+        info!("Next track enqueued: {:#?}", track.location());
     }
 
     pub fn next(&mut self) {
         if self.playlist.current_track().is_some() {
             info!("skip route 1 which is in most cases.");
+            self.skipped = true;
             self.playlist.set_next_track(None);
             self.get_player_mut().skip_one();
         } else {
@@ -465,8 +866,236 @@ impl GeneralPlayer {
             .expect("Error in player seek.");
     }
 
+    /// Mark the current position as the start of the A-B repeat.
+    pub fn ab_loop_set_a(&mut self) {
+        let position = self.position();
+        self.ab_loop.set_a(position);
+        self.ab_loop_apply();
+    }
+
+    /// Mark the current position as the end of the A-B repeat and start looping.
+    pub fn ab_loop_set_b(&mut self) {
+        let position = self.position();
+        self.ab_loop.set_b(position);
+        self.ab_loop_apply();
+    }
+
+    pub fn ab_loop_clear(&mut self) {
+        if self.ab_loop != AbLoop::default() {
+            self.ab_loop = AbLoop::default();
+            self.ab_loop_apply();
+        }
+    }
+
+    fn ab_loop_apply(&mut self) {
+        let range = self.ab_loop.range();
+        self.ab_loop_in_backend = self.get_player_mut().set_ab_loop(range);
+    }
+
+    /// Seek back to A once B is passed, for backends that do not loop themselves.
+    pub fn ab_loop_check(&mut self) {
+        if self.ab_loop_in_backend {
+            return;
+        }
+        if let Some((a, b)) = self.ab_loop.range() {
+            if self.position() >= b {
+                self.seek_to(a);
+            }
+        }
+    }
+
+    /// Seek to the start of the next chapter of the current track.
+    pub fn chapter_next(&mut self) {
+        let position = self.position();
+        let start = self
+            .playlist
+            .current_track()
+            .and_then(|track| chapters::next_start(track.chapters(), position));
+        if let Some(start) = start {
+            self.seek_to(start);
+        }
+    }
+
+    /// Seek to the start of the previous chapter, or of the current one once it played for a
+    /// few seconds.
+    pub fn chapter_previous(&mut self) {
+        let position = self.position();
+        let start = self
+            .playlist
+            .current_track()
+            .and_then(|track| chapters::previous_start(track.chapters(), position));
+        if let Some(start) = start {
+            self.seek_to(start);
+        }
+    }
+
+    /// Fetch the `podcast:chapters` of the current episode in the background, if it has no
+    /// chapters yet. They come back as [`PlayerCmd::ChaptersFetched`].
+    fn fetch_chapters(&self) {
+        let Some(track) = self.playlist.current_track() else {
+            return;
+        };
+        if !track.chapters().is_empty() {
+            return;
+        }
+        let (Some(url), Some(file)) = (track.chapters_url(), track.file()) else {
+            return;
+        };
+        let (url, file) = (url.to_string(), file.to_string());
+        let cmd_tx = self.cmd_tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("fetch chapters".into())
+            .spawn(move || match chapters::fetch_json(&url) {
+                Ok(chapters) => {
+                    // the player may be gone already
+                    let _ = cmd_tx.send(PlayerCmd::ChaptersFetched(file, chapters));
+                }
+                Err(e) => warn!("error fetching chapters from {url}: {e}"),
+            });
+        if let Err(e) = spawned {
+            error!("error starting to fetch chapters: {e}");
+        }
+    }
+
+    /// Store the chapters fetched for `file`, returns whether they belong to the current track.
+    pub fn chapters_fetched(&mut self, file: &str, chapters: Vec<Chapter>) -> bool {
+        let Some(track) = self.playlist.current_track_as_mut() else {
+            return false;
+        };
+        if track.file() != Some(file) {
+            return false;
+        }
+        if let Err(e) = self.db_podcast.set_chapters(track, &chapters) {
+            error!("error storing chapters: {e}");
+        }
+        track.set_chapters(chapters);
+        true
+    }
+
+    /// Follow the audiobook of the current track. The files of a newly started book are read in
+    /// the background, as that reads the tags of every one of them, and come back as
+    /// [`PlayerCmd::AudiobookOpened`].
+    fn audiobook_update(&mut self) {
+        let Some(file) = self.playlist.current_track().and_then(Track::file) else {
+            self.audiobook = None;
+            self.audiobook_opening = None;
+            return;
+        };
+        if self
+            .audiobook
+            .as_ref()
+            .is_some_and(|book| book.contains(file))
+        {
+            return;
+        }
+        self.audiobook = None;
+        let path = Audiobook::of_file(file, &self.config.audiobook_dir);
+        if path == self.audiobook_opening {
+            return;
+        }
+        self.audiobook_opening = path.clone();
+        let Some(path) = path else {
+            return;
+        };
+
+        let cmd_tx = self.cmd_tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("open audiobook".into())
+            .spawn(move || {
+                // the player may be gone already
+                let _ = cmd_tx.send(PlayerCmd::AudiobookOpened(Audiobook::open(&path)));
+            });
+        if let Err(e) = spawned {
+            error!("error starting to open the audiobook: {e}");
+            self.audiobook_opening = None;
+        }
+    }
+
+    /// Keep the audiobook opened in the background, if it is still the one of the current track.
+    pub fn audiobook_opened(&mut self, book: Audiobook) {
+        if self.audiobook_opening.as_deref() != Some(Path::new(&book.path)) {
+            return;
+        }
+        self.audiobook_opening = None;
+        if self
+            .playlist
+            .current_track()
+            .and_then(Track::file)
+            .is_some_and(|file| book.contains(file))
+        {
+            self.audiobook = Some(book);
+        }
+    }
+
+    /// Store how far into its audiobook the current track is.
+    fn audiobook_save(&mut self) {
+        let (Some(book), Some(file)) = (
+            &self.audiobook,
+            self.playlist.current_track().and_then(Track::file),
+        ) else {
+            return;
+        };
+        if !book.contains(file) {
+            return;
+        }
+        let progress = book.progress(file, self.get_player().position());
+        if let Err(e) = self.db.set_audiobook_progress(&progress) {
+            error!("error saving audiobook progress: {e}");
+        }
+        self.audiobook_saved = Instant::now();
+    }
+
+    /// Store the audiobook progress every few seconds while playing.
+    pub fn audiobook_tick(&mut self) {
+        if self.audiobook.is_some()
+            && self.playlist.status() == Status::Running
+            && self.audiobook_saved.elapsed() >= AUDIOBOOK_SAVE_INTERVAL
+        {
+            self.audiobook_save();
+        }
+    }
+
+    /// Go to `position` in `file` of an audiobook, right away if it is playing, otherwise once it
+    /// starts.
+    pub fn audiobook_jump(&mut self, file: String, position: Duration) {
+        if self.playlist.current_track().and_then(Track::file) == Some(file.as_str()) {
+            self.seek_to(position);
+        } else {
+            self.audiobook_jump = Some((file, position));
+        }
+    }
+
+    /// Seek to where the current track was left in its audiobook, returns whether it did.
+    fn audiobook_restore(&mut self) -> bool {
+        let Some(file) = self
+            .playlist
+            .current_track()
+            .and_then(Track::file)
+            .map(ToString::to_string)
+        else {
+            return false;
+        };
+        let position = match self.audiobook_jump.take() {
+            Some((jump_file, position)) if jump_file == file => Some(position),
+            // the book may still be opening, its path is all that is needed here
+            _ => Audiobook::of_file(&file, &self.config.audiobook_dir)
+                .and_then(|book| self.db.get_audiobook_progress(&book.to_string_lossy()).ok())
+                .filter(|progress| progress.file == file)
+                .map(|progress| progress.position),
+        };
+        match position {
+            Some(position) if !position.is_zero() => {
+                self.get_player_mut().seek_to(position);
+                true
+            }
+            _ => false,
+        }
+    }
+
     #[allow(clippy::cast_sign_loss)]
     pub fn player_save_last_position(&mut self) {
+        // audiobooks are remembered whatever the setting
+        self.audiobook_save();
         match self.config.player_remember_last_played_position {
             LastPosition::Yes => {
                 if let Some(track) = self.playlist.current_track() {
@@ -503,6 +1132,9 @@ impl GeneralPlayer {
 
     // #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
     pub fn player_restore_last_position(&mut self) {
+        if self.audiobook_restore() {
+            return;
+        }
         let mut restored = false;
         match self.config.player_remember_last_played_position {
             LastPosition::Yes => {
@@ -562,7 +1194,11 @@ impl GeneralPlayer {
 #[async_trait]
 impl PlayerTrait for GeneralPlayer {
     async fn add_and_play(&mut self, current_track: &Track) {
-        self.get_player_mut().add_and_play(current_track).await;
+        self.skipped = false;
+        match self.radio.resolved(current_track) {
+            Some(track) => self.get_player_mut().add_and_play(&track).await,
+            None => self.radio_resolve(current_track),
+        }
     }
     fn volume(&self) -> u16 {
         self.get_player().volume()
@@ -614,6 +1250,7 @@ impl PlayerTrait for GeneralPlayer {
         self.playlist.set_status(Status::Stopped);
         self.playlist.set_next_track(None);
         self.playlist.clear_current_track();
+        self.ab_loop_clear();
         self.get_player_mut().stop();
     }
 
@@ -630,6 +1267,7 @@ impl PlayerTrait for GeneralPlayer {
     }
 
     fn skip_one(&mut self) {
+        self.skipped = true;
         self.get_player_mut().skip_one();
     }
 
@@ -637,8 +1275,47 @@ impl PlayerTrait for GeneralPlayer {
         self.get_player().position()
     }
 
-    fn enqueue_next(&mut self, file: &str) {
-        self.get_player_mut().enqueue_next(file);
+    fn enqueue_next(&mut self, track: &Track) {
+        self.get_player_mut().enqueue_next(track);
+    }
+
+    fn output_devices(&self) -> Vec<String> {
+        self.get_player().output_devices()
+    }
+
+    fn set_output_device(&mut self, name: &str) {
+        // a gapless next track queued on the old device is lost, let it be queued again
+        self.playlist.set_next_track(None);
+        self.config.player_output_device = name.to_string();
+        self.get_player_mut().set_output_device(name);
+    }
+
+    fn spectrum_sender(&self) -> Option<SpectrumSender> {
+        self.get_player().spectrum_sender()
+    }
+
+    fn stream_state(&self) -> StreamState {
+        self.get_player().stream_state()
+    }
+
+    fn set_ab_loop(&mut self, range: Option<(Duration, Duration)>) -> bool {
+        self.get_player_mut().set_ab_loop(range)
+    }
+
+    fn radio_timeshift(&self) -> Option<TimeshiftProgress> {
+        self.get_player().radio_timeshift()
+    }
+
+    fn radio_go_live(&mut self) {
+        self.get_player_mut().radio_go_live();
+    }
+
+    fn toggle_radio_record(&mut self) -> bool {
+        self.get_player_mut().toggle_radio_record()
+    }
+
+    fn radio_recording(&self) -> bool {
+        self.get_player().radio_recording()
     }
 }
 
@@ -653,6 +1330,43 @@ pub struct PlayerProgress {
     pub total_duration: Option<PlayerTimeUnit>,
 }
 
+/// The marks of an A-B repeat, the section between them is played over and over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AbLoop {
+    pub a: Option<PlayerTimeUnit>,
+    pub b: Option<PlayerTimeUnit>,
+}
+
+impl AbLoop {
+    /// Start the loop at `position`, dropping B if it is not after it anymore.
+    pub fn set_a(&mut self, position: PlayerTimeUnit) {
+        self.a = Some(position);
+        if self.b.map_or(false, |b| b <= position) {
+            self.b = None;
+        }
+    }
+
+    /// End the loop at `position`, starting it at the beginning of the track if A is not set.
+    ///
+    /// Ignored if `position` is not after A.
+    pub fn set_b(&mut self, position: PlayerTimeUnit) {
+        let a = self.a.unwrap_or_default();
+        if position > a {
+            self.a = Some(a);
+            self.b = Some(position);
+        }
+    }
+
+    /// A and B, once both are set.
+    #[must_use]
+    pub fn range(&self) -> Option<(PlayerTimeUnit, PlayerTimeUnit)> {
+        match (self.a, self.b) {
+            (Some(a), Some(b)) if a < b => Some((a, b)),
+            _ => None,
+        }
+    }
+}
+
 impl From<crate::player::PlayerTime> for PlayerProgress {
     fn from(value: crate::player::PlayerTime) -> Self {
         Self {
@@ -707,5 +1421,58 @@ pub trait PlayerTrait {
     fn position(&self) -> PlayerTimeUnit {
         self.get_progress().position
     }
-    fn enqueue_next(&mut self, file: &str);
+    /// Queue `track` to play right after the current one.
+    ///
+    /// Only the rusty backend plays just the [`Track::cue`] part of a track of a cue sheet, the
+    /// others play its whole file.
+    fn enqueue_next(&mut self, track: &Track);
+    /// Get the names of the audio output devices this backend can play on.
+    ///
+    /// Backends that do not support choosing a device return an empty list.
+    fn output_devices(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Switch to the audio output device called `name`, continuing the current track.
+    ///
+    /// An empty name selects the default device. Backends that do not support choosing a
+    /// device ignore this.
+    fn set_output_device(&mut self, _name: &str) {}
+    /// Tell the backend which track is playing now, for outputs that show it to others.
+    fn set_now_playing(&mut self, _track: &Track) {}
+    /// Where the spectrum of the played audio is published, `None` if the backend does not
+    /// analyze it.
+    fn spectrum_sender(&self) -> Option<SpectrumSender> {
+        None
+    }
+    /// How the connection of the streamed track is doing. Backends that do not stream with
+    /// `termusic-stream` leave reconnecting to their own library and report it connected.
+    fn stream_state(&self) -> StreamState {
+        StreamState::Connected
+    }
+    /// Play the section between the two positions of the current track over and over, or play
+    /// on with `None`.
+    ///
+    /// Returns `false` if the backend cannot, the player then seeks back with
+    /// [`PlayerTrait::seek_to`] once the end of the section is passed.
+    fn set_ab_loop(&mut self, _range: Option<(Duration, Duration)>) -> bool {
+        false
+    }
+    /// Where the radio playing is between the start of what can be gone back to of it and its
+    /// live edge, `None` if the backend cannot go back in a radio, or does not know yet.
+    ///
+    /// Seeking in a radio moves within that window.
+    fn radio_timeshift(&self) -> Option<TimeshiftProgress> {
+        None
+    }
+    /// Catch up with the live edge of the radio playing, after pausing it or seeking back in it.
+    fn radio_go_live(&mut self) {}
+    /// Start recording the radio playing to a file for every song, or stop it. Returns whether it
+    /// is recording now, never if the backend cannot record.
+    fn toggle_radio_record(&mut self) -> bool {
+        false
+    }
+    /// Whether the radio playing is being recorded.
+    fn radio_recording(&self) -> bool {
+        false
+    }
 }
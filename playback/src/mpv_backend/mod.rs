@@ -64,6 +64,8 @@ enum PlayerInternalCmd {
     Speed(i32),
     Stop,
     Volume(u16),
+    /// End the event loop, which destroys mpv
+    Quit,
 }
 
 impl MpvBackend {
@@ -96,6 +98,22 @@ impl MpvBackend {
         mpv.set_property("gapless-audio", gapless_setting)
             .expect("gapless setting failed");
 
+        let http = termusiclib::http::backend_options();
+        if let Some(proxy) = &http.proxy {
+            mpv.set_property("http-proxy", proxy.as_str()).ok();
+        }
+        mpv.set_property("user-agent", http.user_agent.as_str())
+            .ok();
+        // appended one by one, as a comma in a value would split it setting them all at once
+        for (name, value) in &http.headers {
+            let field = format!("{name}: {value}");
+            mpv.command(
+                "change-list",
+                &["http-header-fields", "append", &quoted(&field)],
+            )
+            .ok();
+        }
+
         let cmd_tx_inside = command_tx.clone();
         // let mut time_pos: i64 = 0;
         std::thread::Builder::new()
@@ -243,6 +261,10 @@ impl MpvBackend {
                                     error!("error sending eos: {e}");
                                 }
                             }
+                            PlayerInternalCmd::Quit => {
+                                mpv.command("stop", &[""]).ok();
+                                break;
+                            }
                         }
                     }
 
@@ -292,6 +314,18 @@ fn format_duration(dur: Duration) -> String {
     format!("{secs}.{milli}")
 }
 
+/// `arg` as one argument of a command string, in double quotes with the quotes and backslashes in
+/// it escaped
+fn quoted(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Drop for MpvBackend {
+    fn drop(&mut self) {
+        self.command_tx.send(PlayerInternalCmd::Quit).ok();
+    }
+}
+
 #[async_trait]
 impl PlayerTrait for MpvBackend {
     async fn add_and_play(&mut self, current_item: &Track) {
@@ -389,7 +423,20 @@ impl PlayerTrait for MpvBackend {
         self.skip_one();
     }
 
-    fn enqueue_next(&mut self, file: &str) {
-        self.enqueue_next(file);
+    fn enqueue_next(&mut self, track: &Track) {
+        if let Some(file) = track.file() {
+            self.enqueue_next(file);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quoted;
+
+    #[test]
+    fn quotes_a_command_argument() {
+        assert_eq!(quoted("x-token: a, b"), r#""x-token: a, b""#);
+        assert_eq!(quoted(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
     }
 }
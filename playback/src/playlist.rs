@@ -4,15 +4,17 @@ use rand::seq::SliceRandom;
 use rand::thread_rng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use termusiclib::playlist::cue;
 use termusiclib::podcast::{db::Database as DBPod, Episode};
 use termusiclib::track::MediaType;
 use termusiclib::{
-    config::{Loop, Settings},
+    config::{BackendKind, Loop, Settings},
     track::Track,
-    utils::{filetype_supported, get_app_config_path, get_parent_folder},
+    utils::{filetype_supported_by, get_app_config_path, get_parent_folder},
 };
 
 #[derive(Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Debug)]
@@ -65,6 +67,8 @@ pub struct Playlist {
     loop_mode: Loop,
     config: Settings,
     need_proceed_to_next: bool,
+    /// The backend playing the tracks, `None` where it is not known, like in the tui
+    backend: Option<BackendKind>,
 }
 
 impl Playlist {
@@ -87,6 +91,7 @@ impl Playlist {
             config: config.clone(),
             next_track_index: 0,
             need_proceed_to_next: false,
+            backend: None,
         })
     }
 
@@ -139,7 +144,7 @@ impl Playlist {
             .get_podcasts()
             .with_context(|| "failed to get podcasts from db.")?;
         for line in lines {
-            if let Ok(track) = Track::read_from_path(&line, false) {
+            if let Ok(track) = Track::read_from_location(&line, false) {
                 playlist_items.push(track);
                 continue;
             };
@@ -189,7 +194,7 @@ impl Playlist {
         bytes.extend(format!("{}", self.current_track_index).as_bytes());
         bytes.extend("\n".as_bytes());
         for i in &self.tracks {
-            if let Some(f) = i.file() {
+            if let Some(f) = i.location() {
                 bytes.extend(f.as_bytes());
                 bytes.extend("\n".as_bytes());
             }
@@ -291,9 +296,7 @@ impl Playlist {
         if let Some(track) = self.current_track() {
             match track.media_type {
                 Some(MediaType::Music | MediaType::LiveRadio) => {
-                    if let Some(file) = track.file() {
-                        result = Some(file.to_string());
-                    }
+                    result = track.location();
                 }
                 Some(MediaType::Podcast) => {
                     if let Some(local_file) = &track.podcast_localfile {
@@ -321,6 +324,11 @@ impl Playlist {
         self.status = status;
     }
 
+    /// Set the backend playing the tracks, so [`Self::add_playlist`] only adds what it can play.
+    pub fn set_backend(&mut self, backend: Option<BackendKind>) {
+        self.backend = backend;
+    }
+
     #[must_use]
     pub fn is_stopped(&self) -> bool {
         self.status == Status::Stopped
@@ -396,14 +404,43 @@ impl Playlist {
         self.tracks.push(track);
     }
 
+    /// Add the radio station called `name` that streams at `url`.
+    pub fn add_radio(&mut self, url: &str, name: &str) {
+        let mut track = Track::new_radio(url);
+        track.set_title(name);
+        self.tracks.push(track);
+    }
+
+    /// Add the tracks at `vec`, a cue sheet adding all of its tracks instead of the audio files
+    /// it splits.
+    ///
     /// # Errors
     /// Error happens when track cannot be read from local file
     pub fn add_playlist(&mut self, vec: Vec<&str>) -> Result<()> {
+        let split_by_cue: HashSet<String> = vec
+            .iter()
+            .filter(|item| cue::is_cue(item))
+            .filter_map(|item| cue::read(Path::new(item)).ok())
+            .flat_map(|sheet| sheet.tracks.into_iter().map(|track| track.file))
+            .collect();
         for item in vec {
             if item.starts_with("http") {
                 let track = Track::new_radio(item);
                 self.tracks.push(track);
-            } else if !filetype_supported(item) {
+            } else if let Some((sheet, number)) = cue::parse_location(item) {
+                let track = Track::read_from_cue(sheet, number, false)?;
+                self.tracks.push(track);
+            } else if cue::is_cue(item) {
+                let tracks = Track::read_cue_sheet(item, false)?;
+                self.tracks.extend(tracks);
+            } else if split_by_cue.contains(item) {
+                continue;
+            } else if !filetype_supported_by(item, self.backend)
+                && !self
+                    .config
+                    .backend_for(item)
+                    .is_some_and(|backend| filetype_supported_by(item, Some(backend)))
+            {
                 // TODO: add error on fail
                 error!("unsupported filetype: {:#?}", item);
                 continue;
@@ -448,7 +485,7 @@ impl Playlist {
 
     fn find_index_from_file(&self, item: &str) -> Option<usize> {
         for (index, track) in self.tracks.iter().enumerate() {
-            if let Some(file) = track.file() {
+            if let Some(file) = track.location() {
                 if file == item {
                     return Some(index);
                 }
@@ -469,8 +506,10 @@ impl Playlist {
 
     pub fn remove_deleted_items(&mut self) {
         if let Some(current_track_file) = self.get_current_track() {
-            self.tracks
-                .retain(|x| x.file().map_or(false, |p| Path::new(p).exists()));
+            self.tracks.retain(|x| {
+                x.location()
+                    .map_or(false, |p| Path::new(cue::location_file(&p)).exists())
+            });
             match self.find_index_from_file(&current_track_file) {
                 Some(new_index) => self.current_track_index = new_index,
                 None => self.current_track_index = 0,
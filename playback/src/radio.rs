@@ -0,0 +1,280 @@
+//! Resolving the playlists radio stations hand out, like `.pls` and `.m3u`, to the streams in
+//! them before playing.
+
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use termusiclib::playlist::{is_content_hls, is_playlist_url, stream_urls};
+use termusiclib::track::{MediaType, Track};
+
+/// How long connecting to a stream of a playlist may take, before the next one is tried
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The streams the playlists of radio stations were resolved to, by the url of the playlist.
+#[derive(Debug, Default)]
+pub struct RadioResolver {
+    resolved: HashMap<String, String>,
+    /// The streams that failed while playing, by the url of their playlist, which are skipped when
+    /// it is resolved again
+    failed: HashMap<String, HashSet<String>>,
+    /// The url of the playlist being resolved, see [`Self::start_resolving`]
+    resolving: Option<String>,
+}
+
+impl RadioResolver {
+    /// `track` to play right away, with the stream of its playlist if it was resolved before.
+    /// `None` if it has to be resolved first, which takes a while.
+    pub fn resolved(&self, track: &Track) -> Option<Track> {
+        match playlist_url(track) {
+            Some(url) => self
+                .resolved
+                .get(url)
+                .map(|stream| track.with_stream(stream)),
+            None => Some(track.clone()),
+        }
+    }
+
+    /// The url of the playlist of `track` and the streams in it to skip, to [`resolve`] it
+    /// unless it was resolved before or is being resolved already.
+    pub fn start_resolving(&mut self, track: &Track) -> Option<(String, HashSet<String>)> {
+        let url = playlist_url(track)?;
+        if self.resolved.contains_key(url) || self.resolving.as_deref() == Some(url) {
+            return None;
+        }
+        self.resolving = Some(url.to_string());
+        let failed = self.failed.get(url).cloned().unwrap_or_default();
+        Some((url.to_string(), failed))
+    }
+
+    /// The playlist at `url` was resolved to `stream`, or could not be if `None`. The stream is
+    /// remembered until it fails, so it is not fetched again. Returns whether the playlist was
+    /// the one being resolved, which was not taken over by another one since.
+    pub fn resolved_to(&mut self, url: &str, stream: Option<String>) -> bool {
+        match stream {
+            Some(stream) => {
+                self.resolved.insert(url.to_string(), stream);
+            }
+            // start over with all of them the next time
+            None => {
+                self.failed.remove(url);
+            }
+        }
+        if self.resolving.as_deref() == Some(url) {
+            self.resolving = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The stream `track` was resolved to stopped playing, so it is resolved to the next one of
+    /// its playlist from now on. Returns whether there is a stream it was resolved to, to try the
+    /// next one.
+    pub fn stream_failed(&mut self, track: &Track) -> bool {
+        let Some(url) = track.file() else {
+            return false;
+        };
+        match self.resolved.remove(url) {
+            Some(stream) if stream != url => {
+                warn!("radio stream {stream} of {url} failed");
+                self.failed
+                    .entry(url.to_string())
+                    .or_default()
+                    .insert(stream);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The url of the playlist `track` is played from, if it is a radio station that hands out one.
+fn playlist_url(track: &Track) -> Option<&str> {
+    if track.media_type != Some(MediaType::LiveRadio) {
+        return None;
+    }
+    track.file().filter(|url| is_playlist_url(url))
+}
+
+/// The stream to play the radio station with the playlist at `url` from, the first one in it
+/// that connects other than the `failed` ones. `url` itself if it is a stream, and `None` if
+/// none of them can be played.
+pub async fn resolve(url: &str, failed: &HashSet<String>) -> Option<String> {
+    match resolve_playlist(url, failed).await {
+        Ok(Some(stream)) => {
+            info!("radio playlist {url} resolved to {stream}");
+            Some(stream)
+        }
+        Ok(None) => Some(url.to_string()),
+        Err(e) => {
+            error!("error resolving the radio playlist {url}: {e}");
+            None
+        }
+    }
+}
+
+/// The first stream in the playlist at `url` that connects, other than the `failed` ones, or
+/// `None` if it is a stream itself.
+async fn resolve_playlist(url: &str, failed: &HashSet<String>) -> Result<Option<String>> {
+    let client = termusiclib::http::client_builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()?;
+    let base = reqwest::Url::parse(url)?;
+    let response = client.get(base.clone()).send().await?.error_for_status()?;
+    let content = response.text().await?;
+    let streams = stream_urls(&content, &base);
+    if streams.is_empty() {
+        if is_content_hls(&content) {
+            return Ok(None);
+        }
+        bail!("no streams in the playlist");
+    }
+    let streams: Vec<String> = streams
+        .into_iter()
+        .filter(|stream| !failed.contains(stream))
+        .collect();
+    if streams.is_empty() {
+        bail!("all streams in the playlist failed");
+    }
+
+    // the timeout is of the whole request, which for a stream is only the headers
+    for stream in &streams {
+        match client.get(stream).send().await {
+            Ok(response) if response.status().is_success() => return Ok(Some(stream.clone())),
+            Ok(response) => warn!("stream {stream} answered {}", response.status()),
+            Err(e) => warn!("error connecting to stream {stream}: {e}"),
+        }
+    }
+    bail!("none of the {} streams in it connected", streams.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const PLAYLIST: &str = "http://radio.example/station.pls";
+
+    fn stream(name: &str) -> String {
+        format!("http://radio.example/{name}")
+    }
+
+    fn failed(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| stream(name)).collect()
+    }
+
+    /// Serve a M3U playlist of the streams `a`, `b` and `c`, of which `b` is not found, and return
+    /// its url.
+    fn serve_playlist() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let playlist = format!("{base}/a\n{base}/b\n{base}/c\n");
+        std::thread::spawn(move || {
+            for mut connection in listener.incoming().flatten() {
+                let mut request = String::new();
+                BufReader::new(&connection).read_line(&mut request).ok();
+                let (status, body) = match request.split_whitespace().nth(1) {
+                    Some("/station.m3u") => ("200 OK", playlist.as_str()),
+                    Some("/a" | "/c") => ("200 OK", ""),
+                    _ => ("404 Not Found", ""),
+                };
+                write!(
+                    connection,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .ok();
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn falls_back_to_the_next_stream() {
+        let mut radio = RadioResolver::default();
+        let track = Track::new_radio(PLAYLIST);
+        assert!(radio.resolved(&track).is_none());
+
+        let (url, skipped) = radio.start_resolving(&track).unwrap();
+        assert_eq!(url, PLAYLIST);
+        assert!(skipped.is_empty());
+        // it is not resolved twice at the same time
+        assert!(radio.start_resolving(&track).is_none());
+        assert!(radio.resolved_to(PLAYLIST, Some(stream("a"))));
+        assert_eq!(
+            radio.resolved(&track).unwrap().file(),
+            Some(stream("a").as_str())
+        );
+        assert!(radio.start_resolving(&track).is_none());
+
+        // the stream failed, so the next resolve skips it
+        assert!(radio.stream_failed(&track));
+        assert!(radio.resolved(&track).is_none());
+        let (_, skipped) = radio.start_resolving(&track).unwrap();
+        assert_eq!(skipped, failed(&["a"]));
+        assert!(radio.resolved_to(PLAYLIST, Some(stream("b"))));
+        assert!(radio.stream_failed(&track));
+        let (_, skipped) = radio.start_resolving(&track).unwrap();
+        assert_eq!(skipped, failed(&["a", "b"]));
+
+        // none of the others played, so all of them are tried again
+        assert!(radio.resolved_to(PLAYLIST, None));
+        assert!(!radio.stream_failed(&track));
+        let (_, skipped) = radio.start_resolving(&track).unwrap();
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn keeps_a_resolve_taken_over_by_another_station() {
+        let mut radio = RadioResolver::default();
+        let first = Track::new_radio(PLAYLIST);
+        let second = Track::new_radio("http://radio.example/other.m3u");
+        radio.start_resolving(&first).unwrap();
+        radio.start_resolving(&second).unwrap();
+
+        // the first one is not played anymore, but kept for the next time
+        assert!(!radio.resolved_to(PLAYLIST, Some(stream("a"))));
+        assert!(radio.resolved(&first).is_some());
+        assert!(radio.resolved_to("http://radio.example/other.m3u", None));
+    }
+
+    #[test]
+    fn plays_what_is_not_a_radio_playlist_as_it_is() {
+        let mut radio = RadioResolver::default();
+        let track = Track::new_radio("http://radio.example/stream.mp3");
+        assert!(radio.start_resolving(&track).is_none());
+        assert_eq!(
+            radio.resolved(&track).unwrap().file(),
+            Some("http://radio.example/stream.mp3")
+        );
+    }
+
+    #[test]
+    fn resolves_to_the_first_stream_that_connects() {
+        let base = serve_playlist();
+        let url = format!("{base}/station.m3u");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let skipped = |names: &[&str]| -> HashSet<String> {
+            names.iter().map(|name| format!("{base}/{name}")).collect()
+        };
+
+        assert_eq!(
+            runtime.block_on(resolve(&url, &skipped(&[]))),
+            Some(format!("{base}/a"))
+        );
+        // b is not found, so it goes on to c
+        assert_eq!(
+            runtime.block_on(resolve(&url, &skipped(&["a"]))),
+            Some(format!("{base}/c"))
+        );
+        assert_eq!(runtime.block_on(resolve(&url, &skipped(&["a", "c"]))), None);
+        assert_eq!(
+            runtime.block_on(resolve(&url, &skipped(&["a", "b", "c"]))),
+            None
+        );
+    }
+}
@@ -83,6 +83,12 @@ pub trait Sample: CpalSample {
 
     /// Returns the value corresponding to the absence of sound.
     fn zero_value() -> Self;
+
+    /// Converts the sample to a `f32` in the range `-1.0..=1.0`.
+    fn to_f32(self) -> f32;
+
+    /// Builds a sample from a `f32` in the range `-1.0..=1.0`, clamping values outside of it.
+    fn from_f32(value: f32) -> Self;
 }
 
 #[allow(
@@ -114,6 +120,16 @@ impl Sample for u16 {
     fn zero_value() -> Self {
         32768
     }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        (f32::from(self) - 32768.0) / 32768.0
+    }
+
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        (value.clamp(-1.0, 1.0) * 32767.0 + 32768.0) as Self
+    }
 }
 
 #[allow(
@@ -142,6 +158,16 @@ impl Sample for i16 {
     fn zero_value() -> Self {
         0
     }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        f32::from(self) / 32768.0
+    }
+
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        (value.clamp(-1.0, 1.0) * 32767.0) as Self
+    }
 }
 
 #[allow(clippy::cast_precision_loss)]
@@ -165,4 +191,14 @@ impl Sample for f32 {
     fn zero_value() -> Self {
         0.0
     }
+
+    #[inline]
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        value
+    }
 }
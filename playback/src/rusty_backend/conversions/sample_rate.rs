@@ -1,7 +1,28 @@
 use super::super::{ChannelCount, SampleRate};
 use super::Sample;
 
+use std::collections::VecDeque;
+use std::f64::consts::PI;
 use std::mem;
+use termusiclib::config::ResampleQuality;
+
+/// Number of entries in the kernel table between two zero crossings of the sinc.
+const SINC_OVERSAMPLING: usize = 256;
+
+/// Number of consumed input frames after which the history is trimmed.
+const SINC_TRIM_FRAMES: usize = 1024;
+
+/// Maximum number of weights stored to avoid recomputing them for every output frame.
+const SINC_MAX_POLYPHASE_LEN: usize = 1 << 17;
+
+#[inline]
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
 
 /// Iterator that converts from a certain sample rate to another.
 #[derive(Clone, Debug)]
@@ -51,18 +72,7 @@ where
         assert!(to >= 1);
 
         // finding greatest common divisor
-        let gcd = {
-            #[inline]
-            fn gcd(a: u32, b: u32) -> u32 {
-                if b == 0 {
-                    a
-                } else {
-                    gcd(b, a % b)
-                }
-            }
-
-            gcd(from, to)
-        };
+        let gcd = gcd(from, to);
 
         let (first_samples, next_samples) = if from == to {
             // if `from` == `to` == 1, then we just pass through
@@ -234,3 +244,560 @@ where
         }
     }
 }
+
+/// Builds one side of a Blackman windowed sinc, sampled `SINC_OVERSAMPLING` times between two
+/// zero crossings. Two trailing zeros let the lookup interpolate past the last entry.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn build_sinc_kernel(zero_crossings: usize) -> Vec<f32> {
+    let len = zero_crossings * SINC_OVERSAMPLING;
+    let mut kernel = Vec::with_capacity(len + 2);
+    for i in 0..=len {
+        let x = i as f64 / SINC_OVERSAMPLING as f64;
+        let sinc = if i == 0 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        };
+        let u = i as f64 / len as f64;
+        let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+        kernel.push((sinc * window) as f32);
+    }
+    kernel.push(0.0);
+    kernel
+}
+
+/// Looks up the kernel at `position`, expressed in table entries.
+#[inline]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn sinc_kernel_at(kernel: &[f32], position: f32) -> f32 {
+    let index = position as usize;
+    if index + 1 >= kernel.len() {
+        return 0.0;
+    }
+    let fract = position - index as f32;
+    kernel[index] + (kernel[index + 1] - kernel[index]) * fract
+}
+
+/// State of the windowed-sinc converter.
+///
+/// It is kept apart from the input iterator so that it can be handed over from one frame of a
+/// source to the next one, letting the kernel see a continuous signal instead of restarting at
+/// every frame boundary.
+#[derive(Clone, Debug)]
+pub struct SincState {
+    /// Input sample rate, as given to `new`.
+    from: u32,
+    /// Output sample rate, as given to `new`.
+    to: u32,
+    /// Number of channels in the stream
+    channels: ChannelCount,
+    /// Quality the kernel was built for.
+    quality: ResampleQuality,
+    /// `from` divided by the greatest common divisor of `from` and `to`.
+    step_from: u32,
+    /// `to` divided by the greatest common divisor of `from` and `to`.
+    step_to: u32,
+    /// One side of the windowed sinc, see `build_sinc_kernel`.
+    kernel: Vec<f32>,
+    /// Number of table entries per input frame, i.e. the cutoff times `SINC_OVERSAMPLING`.
+    kernel_step: f32,
+    /// Number of input frames used on each side of an output frame.
+    half_taps: usize,
+    /// Normalized weights of every phase, when there are few enough of them to be stored.
+    polyphase: Option<Vec<f32>>,
+    /// Weights of the current phase, when they are not taken from `polyphase`.
+    weights: Vec<f32>,
+    /// Interleaved input frames, starting `half_taps - 1` frames before `position`.
+    history: Vec<f32>,
+    /// Index in `history` of the input frame right before the next output frame.
+    position: usize,
+    /// Offset of the next output frame after `position`, in `1 / step_to` frames.
+    phase: u32,
+    /// Samples of an input frame that has not been fully read yet.
+    partial: Vec<f32>,
+    /// Number of silent frames pushed at the end of `history` to render the last input frames.
+    padding: usize,
+    /// The buffer containing the samples waiting to be output.
+    output_buffer: VecDeque<f32>,
+}
+
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+impl SincState {
+    /// Builds the state for a conversion, or `None` if `quality` does not call for a sinc
+    /// kernel or if there is nothing to convert.
+    #[must_use]
+    pub fn new(
+        from: u32,
+        to: u32,
+        channels: ChannelCount,
+        quality: ResampleQuality,
+    ) -> Option<Self> {
+        let (zero_crossings, rolloff) = match quality {
+            ResampleQuality::Linear => return None,
+            ResampleQuality::Low => (8, 0.85),
+            ResampleQuality::Medium => (16, 0.9),
+            ResampleQuality::High => (32, 0.95),
+        };
+        if from == to || from == 0 || to == 0 || channels == 0 {
+            return None;
+        }
+
+        let gcd = gcd(from, to);
+        // when downsampling, the cutoff has to move below the output nyquist frequency
+        let cutoff = (to as f32 / from as f32).min(1.0) * rolloff;
+        let half_taps = (zero_crossings as f32 / cutoff).ceil() as usize;
+        let history = vec![0.0; (half_taps - 1) * usize::from(channels)];
+
+        let mut state = Self {
+            from,
+            to,
+            channels,
+            quality,
+            step_from: from / gcd,
+            step_to: to / gcd,
+            kernel: build_sinc_kernel(zero_crossings),
+            kernel_step: cutoff * SINC_OVERSAMPLING as f32,
+            half_taps,
+            polyphase: None,
+            weights: vec![0.0; 2 * half_taps],
+            history,
+            position: half_taps - 1,
+            phase: 0,
+            partial: Vec::with_capacity(usize::from(channels)),
+            padding: 0,
+            output_buffer: VecDeque::with_capacity(usize::from(channels)),
+        };
+
+        if state.step_to as usize * 2 * half_taps <= SINC_MAX_POLYPHASE_LEN {
+            let mut polyphase = Vec::with_capacity(state.step_to as usize * 2 * half_taps);
+            for phase in 0..state.step_to {
+                state.compute_weights(phase);
+                polyphase.extend_from_slice(&state.weights);
+            }
+            state.polyphase = Some(polyphase);
+        }
+
+        Some(state)
+    }
+
+    /// Returns `true` if this state was built for the given conversion.
+    #[must_use]
+    pub fn matches(
+        &self,
+        from: u32,
+        to: u32,
+        channels: ChannelCount,
+        quality: ResampleQuality,
+    ) -> bool {
+        self.from == from && self.to == to && self.channels == channels && self.quality == quality
+    }
+
+    fn push_sample(&mut self, sample: f32) {
+        self.partial.push(sample);
+        if self.partial.len() == usize::from(self.channels) {
+            self.history.append(&mut self.partial);
+        }
+    }
+
+    /// Returns `true` once enough frames are buffered after `position` to render a frame.
+    fn is_ready(&self) -> bool {
+        self.history.len() / usize::from(self.channels) > self.position + self.half_taps
+    }
+
+    /// Renders the output frames up to the last input frame, padding `history` with silence for
+    /// the frames the kernel reaches past it. An input frame that was not fully read is dropped.
+    fn flush(&mut self) {
+        let channels = usize::from(self.channels);
+        self.partial.clear();
+        while self.position + self.padding < self.history.len() / channels {
+            while !self.is_ready() {
+                self.history.extend(std::iter::repeat(0.0).take(channels));
+                self.padding += 1;
+            }
+            self.render();
+        }
+    }
+
+    /// Fills `weights` for an output frame `phase / step_to` frames after `position`.
+    fn compute_weights(&mut self, phase: u32) {
+        let offset = phase as f32 / self.step_to as f32;
+        let mut total = 0.0;
+        for (tap, weight) in self.weights.iter_mut().enumerate() {
+            // distance between the input frame of this tap and the output frame
+            let distance = (tap as f32 + 1.0 - self.half_taps as f32 - offset).abs();
+            *weight = sinc_kernel_at(&self.kernel, distance * self.kernel_step);
+            total += *weight;
+        }
+
+        // normalizing keeps the gain at exactly one whatever the phase
+        if total.abs() > f32::EPSILON {
+            self.weights.iter_mut().for_each(|weight| *weight /= total);
+        }
+    }
+
+    fn render(&mut self) {
+        let channels = usize::from(self.channels);
+        let taps = 2 * self.half_taps;
+
+        if self.polyphase.is_none() {
+            self.compute_weights(self.phase);
+        }
+        let weights = self
+            .polyphase
+            .as_ref()
+            .map_or(&self.weights[..], |polyphase| {
+                let start = self.phase as usize * taps;
+                &polyphase[start..start + taps]
+            });
+
+        let first = self.position + 1 - self.half_taps;
+        let frames = &self.history[first * channels..(first + taps) * channels];
+        for channel in 0..channels {
+            let sample = frames
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .zip(weights)
+                .map(|(sample, weight)| sample * weight)
+                .sum::<f32>();
+            self.output_buffer.push_back(sample);
+        }
+
+        self.phase += self.step_from;
+        self.position += (self.phase / self.step_to) as usize;
+        self.phase %= self.step_to;
+
+        let consumed = self.position + 1 - self.half_taps;
+        if consumed >= SINC_TRIM_FRAMES {
+            self.history.drain(..consumed * channels);
+            self.position -= consumed;
+        }
+    }
+}
+
+/// Iterator that converts from a certain sample rate to another with a windowed-sinc kernel.
+///
+/// Output frames are only produced once enough input is available after them, so the state
+/// still holds the last few input frames when the input runs dry. With `flush_tail` those are
+/// rendered against silence before the iterator ends, otherwise they are left in the state to be
+/// picked up again when it is handed to a converter for the next frame.
+#[derive(Clone, Debug)]
+#[allow(clippy::module_name_repetitions)]
+pub struct SincSampleRateConverter<I> {
+    input: I,
+    state: SincState,
+    flush_tail: bool,
+}
+
+impl<I> SincSampleRateConverter<I> {
+    /// Destroys this iterator and returns the underlying iterator with the resampling state.
+    #[inline]
+    pub fn into_parts(self) -> (I, SincState) {
+        (self.input, self.state)
+    }
+}
+
+impl<I> Iterator for SincSampleRateConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            if let Some(sample) = self.state.output_buffer.pop_front() {
+                return Some(Sample::from_f32(sample));
+            }
+
+            while !self.state.is_ready() {
+                match self.input.next() {
+                    Some(sample) => self.state.push_sample(sample.to_f32()),
+                    None if self.flush_tail => {
+                        self.state.flush();
+                        return self.state.output_buffer.pop_front().map(Sample::from_f32);
+                    }
+                    None => return None,
+                }
+            }
+            self.state.render();
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.state.output_buffer.len(), None)
+    }
+}
+
+/// Sample rate converter chosen according to a `ResampleQuality`.
+#[derive(Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub enum ResamplingConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    /// Linear interpolation, see `SampleRateConverter`.
+    Linear(SampleRateConverter<I>),
+    /// Windowed sinc, see `SincSampleRateConverter`.
+    Sinc(SincSampleRateConverter<I>),
+}
+
+impl<I> ResamplingConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample,
+{
+    /// Builds the converter, reusing `state` if it was created for the same conversion.
+    ///
+    /// `flush_tail` tells if `input` is the end of the stream, see `SincSampleRateConverter`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if `from` or `to` are equal to 0.
+    ///
+    pub fn new(
+        input: I,
+        from: SampleRate,
+        to: SampleRate,
+        num_channels: ChannelCount,
+        quality: ResampleQuality,
+        state: Option<SincState>,
+        flush_tail: bool,
+    ) -> Self {
+        let state = state
+            .filter(|state| state.matches(from.0, to.0, num_channels, quality))
+            .or_else(|| SincState::new(from.0, to.0, num_channels, quality));
+
+        match state {
+            Some(state) => Self::Sinc(SincSampleRateConverter {
+                input,
+                state,
+                flush_tail,
+            }),
+            None => Self::Linear(SampleRateConverter::new(input, from, to, num_channels)),
+        }
+    }
+
+    /// Destroys this iterator and returns the underlying iterator.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.into_parts().0
+    }
+
+    /// Destroys this iterator and returns the underlying iterator, along with the sinc state if
+    /// any so that it can be carried over to the next frame.
+    #[inline]
+    pub fn into_parts(self) -> (I, Option<SincState>) {
+        match self {
+            Self::Linear(converter) => (converter.into_inner(), None),
+            Self::Sinc(converter) => {
+                let (input, state) = converter.into_parts();
+                (input, Some(state))
+            }
+        }
+    }
+}
+
+impl<I> Iterator for ResamplingConverter<I>
+where
+    I: Iterator,
+    I::Item: Sample + Clone,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        match self {
+            Self::Linear(converter) => converter.next(),
+            Self::Sinc(converter) => converter.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Linear(converter) => converter.size_hint(),
+            Self::Sinc(converter) => converter.size_hint(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+mod tests {
+    use super::{ResamplingConverter, SampleRateConverter, SincState};
+    use cpal::SampleRate;
+    use std::f64::consts::PI;
+    use std::time::Instant;
+    use termusiclib::config::ResampleQuality;
+
+    const FROM: u32 = 44100;
+    const TO: u32 = 48000;
+
+    fn sine(freq: f32, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * PI * f64::from(freq) * i as f64 / f64::from(rate)).sin() as f32)
+            .collect()
+    }
+
+    /// Root mean square distance to the ideal resampled sine, ignoring both edges.
+    fn rms_error(output: &[f32], freq: f32) -> f32 {
+        let expected = sine(freq, TO, output.len());
+        let skip = 512;
+        let range = skip..output.len() - skip;
+        let sum: f32 = range
+            .clone()
+            .map(|i| (output[i] - expected[i]).powi(2))
+            .sum();
+        (sum / range.len() as f32).sqrt()
+    }
+
+    fn resample(input: &[f32], quality: ResampleQuality) -> Vec<f32> {
+        ResamplingConverter::new(
+            input.iter().copied(),
+            SampleRate(FROM),
+            SampleRate(TO),
+            1,
+            quality,
+            None,
+            true,
+        )
+        .collect()
+    }
+
+    #[test]
+    fn test_sinc_is_more_accurate_than_linear() {
+        let input = sine(10_000.0, FROM, 8192);
+
+        let linear: Vec<f32> =
+            SampleRateConverter::new(input.iter().copied(), SampleRate(FROM), SampleRate(TO), 1)
+                .collect();
+        let linear_error = rms_error(&linear, 10_000.0);
+
+        for quality in [
+            ResampleQuality::Low,
+            ResampleQuality::Medium,
+            ResampleQuality::High,
+        ] {
+            let sinc = resample(&input, quality);
+            let sinc_error = rms_error(&sinc, 10_000.0);
+            assert!(
+                sinc_error * 5.0 < linear_error,
+                "{quality}: sinc error {sinc_error} linear error {linear_error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sinc_keeps_the_length() {
+        let input = sine(1_000.0, FROM, 8192);
+        // one output frame for every `FROM / TO` input frame, up to the last input frame
+        let expected = (input.len() * TO as usize + FROM as usize - 1) / FROM as usize;
+
+        for quality in [
+            ResampleQuality::Low,
+            ResampleQuality::Medium,
+            ResampleQuality::High,
+        ] {
+            let output = resample(&input, quality);
+            assert_eq!(output.len(), expected, "{quality}");
+            // the tail is the sine going on, not silence
+            let tail = &output[output.len() - 32..];
+            assert!(tail.iter().any(|s| s.abs() > 0.5), "{quality}: {tail:?}");
+        }
+    }
+
+    #[test]
+    fn test_sinc_state_carries_over_frames() {
+        let input = sine(1_000.0, FROM, 8192);
+        let whole = resample(&input, ResampleQuality::Medium);
+
+        let mut chunked = Vec::new();
+        let mut state = None;
+        let chunks = input.chunks(300);
+        let last = chunks.len() - 1;
+        for (index, chunk) in chunks.enumerate() {
+            let mut converter = ResamplingConverter::new(
+                chunk.iter().copied(),
+                SampleRate(FROM),
+                SampleRate(TO),
+                1,
+                ResampleQuality::Medium,
+                state.take(),
+                index == last,
+            );
+            chunked.extend(converter.by_ref());
+            state = converter.into_parts().1;
+        }
+
+        assert_eq!(whole, chunked);
+    }
+
+    #[test]
+    fn test_sinc_keeps_channels_apart() {
+        let left = sine(1_000.0, FROM, 4096);
+        let input: Vec<f32> = left.iter().flat_map(|s| [*s, 0.0]).collect();
+        let output: Vec<f32> = ResamplingConverter::new(
+            input.into_iter(),
+            SampleRate(FROM),
+            SampleRate(TO),
+            2,
+            ResampleQuality::High,
+            None,
+            true,
+        )
+        .collect();
+
+        assert_eq!(output.len() % 2, 0);
+        assert!(output
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .all(|s| s.abs() < f32::EPSILON));
+        assert!(output.iter().step_by(2).any(|s| s.abs() > 0.5));
+    }
+
+    #[test]
+    fn test_linear_quality_has_no_sinc_state() {
+        assert!(SincState::new(FROM, TO, 2, ResampleQuality::Linear).is_none());
+        assert!(SincState::new(FROM, FROM, 2, ResampleQuality::High).is_none());
+    }
+
+    /// Rough throughput comparison to run by hand, with
+    /// `cargo test bench_resamplers -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_resamplers() {
+        let input = sine(1_000.0, FROM, FROM as usize * 10);
+
+        let start = Instant::now();
+        let count =
+            SampleRateConverter::new(input.iter().copied(), SampleRate(FROM), SampleRate(TO), 1)
+                .count();
+        eprintln!("linear: {count} samples in {:?}", start.elapsed());
+
+        for quality in [
+            ResampleQuality::Low,
+            ResampleQuality::Medium,
+            ResampleQuality::High,
+        ] {
+            let start = Instant::now();
+            let count = resample(&input, quality).len();
+            eprintln!("{quality}: {count} samples in {:?}", start.elapsed());
+        }
+    }
+}
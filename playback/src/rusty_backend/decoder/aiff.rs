@@ -0,0 +1,469 @@
+//! AIFF and AIFF-C demuxer, symphonia only reads the RIFF based WAV.
+//!
+//! The samples are handed to symphonia's PCM decoder.
+
+use std::io::{Seek, SeekFrom};
+
+use symphonia::core::audio::Channels;
+use symphonia::core::codecs::{
+    CodecParameters, CodecType, CODEC_TYPE_PCM_ALAW, CODEC_TYPE_PCM_F32BE, CODEC_TYPE_PCM_F64BE,
+    CODEC_TYPE_PCM_MULAW, CODEC_TYPE_PCM_S16BE, CODEC_TYPE_PCM_S16LE, CODEC_TYPE_PCM_S24BE,
+    CODEC_TYPE_PCM_S32BE, CODEC_TYPE_PCM_S8,
+};
+use symphonia::core::errors::{
+    decode_error, end_of_stream_error, seek_error, unsupported_error, Result, SeekErrorKind,
+};
+use symphonia::core::formats::{
+    Cue, FormatOptions, FormatReader, Packet, SeekMode, SeekTo, SeekedTo, Track,
+};
+use symphonia::core::io::{MediaSource, MediaSourceStream, ReadBytes};
+use symphonia::core::meta::{Metadata, MetadataLog};
+use symphonia::core::probe::{Descriptor, Instantiate, QueryDescriptor};
+use symphonia::core::support_format;
+use symphonia::core::units::TimeBase;
+
+/// The most frames put in one packet.
+const AIFF_MAX_FRAMES_PER_PACKET: u64 = 1152;
+
+/// What the `COMM` chunk says about the samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CommonChunk {
+    channels: u16,
+    n_frames: u32,
+    /// Bits used by a sample, the samples take up whole bytes
+    sample_size: u16,
+    sample_rate: u32,
+    codec: CodecType,
+}
+
+impl CommonChunk {
+    /// Float and companded samples have an implicit size.
+    fn is_integer(&self) -> bool {
+        ![
+            CODEC_TYPE_PCM_F32BE,
+            CODEC_TYPE_PCM_F64BE,
+            CODEC_TYPE_PCM_ALAW,
+            CODEC_TYPE_PCM_MULAW,
+        ]
+        .contains(&self.codec)
+    }
+
+    fn frame_len(&self) -> u64 {
+        u64::from(self.channels) * u64::from(self.sample_bytes())
+    }
+
+    fn sample_bytes(&self) -> u16 {
+        (self.sample_size + 7) / 8
+    }
+}
+
+/// Reads the `FORM` container of AIFF and AIFF-C files.
+pub struct AiffReader {
+    reader: MediaSourceStream,
+    tracks: Vec<Track>,
+    cues: Vec<Cue>,
+    metadata: MetadataLog,
+    frame_len: u64,
+    data_start_pos: u64,
+    data_end_pos: u64,
+}
+
+impl QueryDescriptor for AiffReader {
+    fn query() -> &'static [Descriptor] {
+        &[support_format!(
+            "aiff",
+            "Audio Interchange File Format",
+            &["aiff", "aif", "aifc"],
+            &["audio/aiff", "audio/x-aiff"],
+            &[b"FORM"]
+        )]
+    }
+
+    fn score(_context: &[u8]) -> u8 {
+        255
+    }
+}
+
+impl FormatReader for AiffReader {
+    fn try_new(mut source: MediaSourceStream, _options: &FormatOptions) -> Result<Self> {
+        if source.read_quad_bytes()? != *b"FORM" {
+            return unsupported_error("aiff: missing form marker");
+        }
+        let form_len = source.read_be_u32()?;
+        let is_aifc = match &source.read_quad_bytes()? {
+            b"AIFF" => false,
+            b"AIFC" => true,
+            _ => return unsupported_error("aiff: form is not aiff"),
+        };
+        // the form type was already read
+        let form_end = source.pos() - 4 + u64::from(form_len);
+
+        let mut common = None;
+        // (start, end) of the samples in the "SSND" chunk
+        let mut sound = None;
+
+        while common.is_none() || sound.is_none() {
+            if source.pos() + 8 > form_end {
+                break;
+            }
+            let id = source.read_quad_bytes()?;
+            let len = u64::from(source.read_be_u32()?);
+            // chunks are padded to an even length
+            let next_pos = source.pos() + len + len % 2;
+
+            match &id {
+                b"COMM" => {
+                    common = Some(read_common_chunk(&mut source, len, is_aifc)?);
+                    source.ignore_bytes(next_pos - source.pos())?;
+                }
+                b"SSND" => {
+                    let offset = u64::from(source.read_be_u32()?);
+                    let _block_size = source.read_be_u32()?;
+                    let start = source.pos() + offset;
+                    sound = Some((start, source.pos() - 8 + len));
+                    if common.is_some() {
+                        break;
+                    }
+                    // the samples come before the format, which is only readable when seekable
+                    if !source.is_seekable() {
+                        return unsupported_error("aiff: sound data before the common chunk");
+                    }
+                    source.seek(SeekFrom::Start(next_pos))?;
+                }
+                _ => source.ignore_bytes(len + len % 2)?,
+            }
+        }
+
+        let (Some(common), Some((data_start_pos, data_end_pos))) = (common, sound) else {
+            return unsupported_error("aiff: missing common or sound data chunk");
+        };
+        if source.pos() != data_start_pos {
+            if source.is_seekable() {
+                source.seek(SeekFrom::Start(data_start_pos))?;
+            } else {
+                source.ignore_bytes(data_start_pos - source.pos())?;
+            }
+        }
+
+        let frame_len = common.frame_len();
+        if frame_len == 0 {
+            return decode_error("aiff: frame length is 0");
+        }
+        let channels = channels_from_count(common.channels)?;
+        let mut codec_params = CodecParameters::new();
+        codec_params
+            .for_codec(common.codec)
+            .with_sample_rate(common.sample_rate)
+            .with_time_base(TimeBase::new(1, common.sample_rate))
+            .with_channels(channels)
+            .with_max_frames_per_packet(AIFF_MAX_FRAMES_PER_PACKET)
+            .with_n_frames(u64::from(common.n_frames));
+        if common.is_integer() {
+            // the samples are stored in whole bytes, left aligned
+            let coded_bits = u32::from(common.sample_bytes()) * 8;
+            codec_params
+                .with_bits_per_sample(coded_bits)
+                .with_bits_per_coded_sample(coded_bits);
+        }
+
+        Ok(Self {
+            reader: source,
+            tracks: vec![Track::new(0, codec_params)],
+            cues: Vec::new(),
+            metadata: MetadataLog::default(),
+            frame_len,
+            data_start_pos,
+            data_end_pos: data_end_pos.max(data_start_pos),
+        })
+    }
+
+    fn next_packet(&mut self) -> Result<Packet> {
+        let pos = self.reader.pos();
+        let frames_left = self.data_end_pos.saturating_sub(pos) / self.frame_len;
+        if frames_left == 0 {
+            return end_of_stream_error();
+        }
+
+        let frames = frames_left.min(AIFF_MAX_FRAMES_PER_PACKET);
+        #[allow(clippy::cast_possible_truncation)]
+        let data = self
+            .reader
+            .read_boxed_slice_exact((frames * self.frame_len) as usize)?;
+        let ts = (pos - self.data_start_pos) / self.frame_len;
+
+        Ok(Packet::new_from_boxed_slice(0, ts, frames, data))
+    }
+
+    fn metadata(&mut self) -> Metadata<'_> {
+        self.metadata.metadata()
+    }
+
+    fn cues(&self) -> &[Cue] {
+        &self.cues
+    }
+
+    fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    fn seek(&mut self, _mode: SeekMode, to: SeekTo) -> Result<SeekedTo> {
+        let params = &self.tracks[0].codec_params;
+        let required_ts = match to {
+            SeekTo::TimeStamp { ts, .. } => ts,
+            SeekTo::Time { time, .. } => match params.sample_rate {
+                Some(rate) => TimeBase::new(1, rate).calc_timestamp(time),
+                None => return seek_error(SeekErrorKind::Unseekable),
+            },
+        };
+        if params
+            .n_frames
+            .map_or(false, |n_frames| required_ts > n_frames)
+        {
+            return seek_error(SeekErrorKind::OutOfRange);
+        }
+
+        // start on a packet boundary, so packets have the same timestamps with or without seeking
+        let actual_ts = required_ts - required_ts % AIFF_MAX_FRAMES_PER_PACKET;
+        let seek_pos = self.data_start_pos + actual_ts * self.frame_len;
+
+        if self.reader.is_seekable() {
+            self.reader.seek(SeekFrom::Start(seek_pos))?;
+        } else if seek_pos >= self.reader.pos() {
+            self.reader.ignore_bytes(seek_pos - self.reader.pos())?;
+        } else {
+            return seek_error(SeekErrorKind::ForwardOnly);
+        }
+
+        Ok(SeekedTo {
+            track_id: 0,
+            actual_ts,
+            required_ts,
+        })
+    }
+
+    fn into_inner(self: Box<Self>) -> MediaSourceStream {
+        self.reader
+    }
+}
+
+fn read_common_chunk(
+    source: &mut MediaSourceStream,
+    len: u64,
+    is_aifc: bool,
+) -> Result<CommonChunk> {
+    if len < 18 {
+        return decode_error("aiff: common chunk too short");
+    }
+    let channels = source.read_be_u16()?;
+    let n_frames = source.read_be_u32()?;
+    let sample_size = source.read_be_u16()?;
+    let mut rate = [0u8; 10];
+    source.read_buf_exact(&mut rate)?;
+    let Some(sample_rate) = extended_to_u32(rate).filter(|rate| *rate > 0) else {
+        return decode_error("aiff: invalid sample rate");
+    };
+
+    // plain AIFF is always big endian signed integers
+    let compression = if is_aifc && len >= 22 {
+        source.read_quad_bytes()?
+    } else {
+        *b"NONE"
+    };
+
+    let codec = match (&compression, (sample_size + 7) / 8) {
+        (b"NONE" | b"twos", 1) => CODEC_TYPE_PCM_S8,
+        (b"NONE" | b"twos", 2) => CODEC_TYPE_PCM_S16BE,
+        (b"NONE" | b"twos", 3) | (b"in24", _) => CODEC_TYPE_PCM_S24BE,
+        (b"NONE" | b"twos", 4) | (b"in32", _) => CODEC_TYPE_PCM_S32BE,
+        (b"sowt", 2) => CODEC_TYPE_PCM_S16LE,
+        (b"fl32" | b"FL32", _) => CODEC_TYPE_PCM_F32BE,
+        (b"fl64" | b"FL64", _) => CODEC_TYPE_PCM_F64BE,
+        (b"alaw" | b"ALAW", _) => CODEC_TYPE_PCM_ALAW,
+        (b"ulaw" | b"ULAW", _) => CODEC_TYPE_PCM_MULAW,
+        _ => return unsupported_error("aiff: unsupported compression type"),
+    };
+    // the sample size of the compressed types is what they decode to, not what is stored
+    let sample_size = match codec {
+        CODEC_TYPE_PCM_S24BE => 24,
+        CODEC_TYPE_PCM_S32BE | CODEC_TYPE_PCM_F32BE => 32,
+        CODEC_TYPE_PCM_F64BE => 64,
+        CODEC_TYPE_PCM_ALAW | CODEC_TYPE_PCM_MULAW => 8,
+        _ => sample_size,
+    };
+
+    Ok(CommonChunk {
+        channels,
+        n_frames,
+        sample_size,
+        sample_rate,
+        codec,
+    })
+}
+
+/// Convert the 80 bit IEEE 754 extended precision sample rate, `None` if it does not fit.
+fn extended_to_u32(bytes: [u8; 10]) -> Option<u32> {
+    let sign_exponent = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let mantissa = u64::from_be_bytes(bytes[2..].try_into().ok()?);
+    if sign_exponent & 0x8000 != 0 {
+        return None;
+    }
+    if mantissa == 0 {
+        return Some(0);
+    }
+
+    // the mantissa has its integer bit at the top, so the value is mantissa * 2^(exponent - 63)
+    let shift = i32::from(sign_exponent) - 16383 - 63;
+    if shift >= 0 {
+        return None;
+    }
+    let value = mantissa.checked_shr(shift.unsigned_abs()).unwrap_or(0);
+    u32::try_from(value).ok()
+}
+
+fn channels_from_count(count: u16) -> Result<Channels> {
+    let channels = match count {
+        1 => Channels::FRONT_LEFT,
+        2 => Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+        // AIFF order: left, right, center
+        3 => Channels::FRONT_LEFT | Channels::FRONT_RIGHT | Channels::FRONT_CENTRE,
+        4 => {
+            Channels::FRONT_LEFT
+                | Channels::FRONT_RIGHT
+                | Channels::REAR_LEFT
+                | Channels::REAR_RIGHT
+        }
+        6 => {
+            Channels::FRONT_LEFT
+                | Channels::FRONT_RIGHT
+                | Channels::FRONT_CENTRE
+                | Channels::LFE1
+                | Channels::REAR_LEFT
+                | Channels::REAR_RIGHT
+        }
+        _ => return unsupported_error("aiff: unsupported channel count"),
+    };
+    Ok(channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusty_backend::decoder::get_codecs;
+    use std::io::Cursor;
+    use symphonia::core::audio::SampleBuffer;
+    use symphonia::core::codecs::DecoderOptions;
+    use symphonia::core::io::MediaSourceStreamOptions;
+    use symphonia::core::units::Time;
+
+    /// 44100Hz as an 80 bit extended float.
+    const RATE_44100: [u8; 10] = [0x40, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0];
+
+    /// A stereo 16 bit AIFF whose left channel counts up from 0 and right channel down from -1,
+    /// with an extra chunk between the common and sound chunks.
+    fn aiff(frames: u16) -> Vec<u8> {
+        let mut comm = Vec::new();
+        comm.extend_from_slice(&2u16.to_be_bytes());
+        comm.extend_from_slice(&u32::from(frames).to_be_bytes());
+        comm.extend_from_slice(&16u16.to_be_bytes());
+        comm.extend_from_slice(&RATE_44100);
+
+        let mut ssnd = vec![0; 8];
+        for i in 0..frames {
+            let i = i16::try_from(i).unwrap();
+            ssnd.extend_from_slice(&i.to_be_bytes());
+            ssnd.extend_from_slice(&(-1 - i).to_be_bytes());
+        }
+
+        let mut chunks = Vec::new();
+        for (id, data) in [(b"COMM", comm), (b"NAME", b"odd".to_vec()), (b"SSND", ssnd)] {
+            chunks.extend_from_slice(id);
+            chunks.extend_from_slice(&u32::try_from(data.len()).unwrap().to_be_bytes());
+            chunks.extend_from_slice(&data);
+            if data.len() % 2 == 1 {
+                chunks.push(0);
+            }
+        }
+
+        let mut file = b"FORM".to_vec();
+        file.extend_from_slice(&u32::try_from(chunks.len() + 4).unwrap().to_be_bytes());
+        file.extend_from_slice(b"AIFF");
+        file.extend_from_slice(&chunks);
+        file
+    }
+
+    fn reader(bytes: Vec<u8>) -> AiffReader {
+        let mss = MediaSourceStream::new(
+            Box::new(Cursor::new(bytes)),
+            MediaSourceStreamOptions::default(),
+        );
+        AiffReader::try_new(mss, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn extended_sample_rates() {
+        assert_eq!(extended_to_u32(RATE_44100), Some(44100));
+        assert_eq!(
+            extended_to_u32([0x40, 0x0e, 0xbb, 0x80, 0, 0, 0, 0, 0, 0]),
+            Some(48000)
+        );
+        assert_eq!(extended_to_u32([0; 10]), Some(0));
+        assert_eq!(
+            extended_to_u32([0xc0, 0x0e, 0xac, 0x44, 0, 0, 0, 0, 0, 0]),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_and_decodes_samples() {
+        let mut reader = reader(aiff(1200));
+        let params = reader.tracks()[0].codec_params.clone();
+        assert_eq!(params.sample_rate, Some(44100));
+        assert_eq!(params.n_frames, Some(1200));
+        assert_eq!(params.channels.map(Channels::count), Some(2));
+
+        let mut decoder = get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .unwrap();
+        let mut samples = Vec::new();
+        while let Ok(packet) = reader.next_packet() {
+            let buf_ref = decoder.decode(&packet).unwrap();
+            let mut buf = SampleBuffer::<i16>::new(buf_ref.capacity() as u64, *buf_ref.spec());
+            buf.copy_interleaved_ref(buf_ref);
+            samples.extend_from_slice(buf.samples());
+        }
+
+        assert_eq!(samples.len(), 2400);
+        assert_eq!(&samples[..4], &[0, -1, 1, -2]);
+        assert_eq!(&samples[2398..], &[1199, -1200]);
+    }
+
+    #[test]
+    fn seeks_to_packet_boundaries() {
+        let mut reader = reader(aiff(3000));
+        let seeked = reader
+            .seek(
+                SeekMode::Coarse,
+                SeekTo::Time {
+                    time: Time::new(0, 0.05),
+                    track_id: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(seeked.required_ts, 2205);
+        assert_eq!(seeked.actual_ts, 1152);
+
+        let packet = reader.next_packet().unwrap();
+        assert_eq!(packet.ts(), 1152);
+        assert_eq!(&packet.buf()[..4], &[0x04, 0x80, 0xfb, 0x7f]);
+
+        assert!(reader
+            .seek(
+                SeekMode::Coarse,
+                SeekTo::TimeStamp {
+                    ts: 3001,
+                    track_id: 0
+                }
+            )
+            .is_err());
+    }
+}
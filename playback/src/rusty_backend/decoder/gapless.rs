@@ -0,0 +1,412 @@
+//! Encoder delay and padding for the formats symphonia does not trim itself.
+//!
+//! Symphonia trims MP3 files with a LAME / Xing header and Ogg files, but AAC in MP4 (and MP3
+//! files tagged by iTunes) only carry the delay and padding in an `iTunSMPB` tag.
+
+use symphonia::core::meta::{MetadataRevision, StandardTagKey};
+use symphonia::core::units::TimeBase;
+
+/// Where the audio of a track starts and how long it is, in frames of the decoded output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GaplessInfo {
+    /// Frames added by the encoder before the audio
+    pub delay: u64,
+    /// Frames of audio, without the delay and the padding at the end
+    pub frames: u64,
+}
+
+impl GaplessInfo {
+    /// Parse the value of an `iTunSMPB` tag, like
+    /// `" 00000000 00000840 000001CA 00000000003F31F6 ..."`.
+    ///
+    /// All fields are hex, the second is the delay, the third the padding and the fourth the
+    /// frame count of the audio.
+    #[must_use]
+    pub fn from_itunsmpb(value: &str) -> Option<Self> {
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        if fields.len() < 4 || fields[..3].iter().any(|v| v.len() != 8) {
+            return None;
+        }
+        let mut numbers = fields[..4].iter().map(|v| u64::from_str_radix(v, 16).ok());
+        let (_reserved, delay, _padding, frames) = (
+            numbers.next()??,
+            numbers.next()??,
+            numbers.next()??,
+            numbers.next()??,
+        );
+        if frames == 0 {
+            return None;
+        }
+
+        Some(Self { delay, frames })
+    }
+
+    /// Find an `iTunSMPB` tag, either a MP4 freeform tag or an ID3 comment.
+    ///
+    /// ID3 comments lose their description, so any comment that parses is taken.
+    #[must_use]
+    pub fn from_metadata(revision: &MetadataRevision) -> Option<Self> {
+        revision
+            .tags()
+            .iter()
+            .filter(|tag| {
+                tag.key.ends_with("iTunSMPB")
+                    || tag.key.starts_with("COMM")
+                    || tag.std_key == Some(StandardTagKey::Comment)
+            })
+            .find_map(|tag| Self::from_itunsmpb(&tag.value.to_string()))
+    }
+
+    /// Frames to cut from the start and the end of a decoded packet of `len` frames, which starts
+    /// `start` frames into the stream.
+    #[must_use]
+    pub fn trim(&self, start: u64, len: u64) -> (usize, usize) {
+        let trim_start = self.delay.saturating_sub(start).min(len);
+        let trim_end = (start + len)
+            .saturating_sub(self.delay + self.frames)
+            .min(len - trim_start);
+        (to_usize(trim_start), to_usize(trim_end))
+    }
+}
+
+/// Frames from the start of the stream to the timestamp `ts` of a packet.
+#[must_use]
+pub fn ts_to_frames(ts: u64, time_base: Option<TimeBase>, sample_rate: u32) -> u64 {
+    match time_base {
+        Some(tb) if tb.denom != sample_rate || tb.numer != 1 => {
+            let frames = u128::from(ts) * u128::from(tb.numer) * u128::from(sample_rate)
+                / u128::from(tb.denom);
+            u64::try_from(frames).unwrap_or(u64::MAX)
+        }
+        _ => ts,
+    }
+}
+
+#[inline]
+fn to_usize(value: u64) -> usize {
+    usize::try_from(value).unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Symphonia;
+    use super::*;
+    use std::io::Cursor;
+    use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+
+    #[test]
+    fn parses_itunsmpb() {
+        let info = GaplessInfo::from_itunsmpb(
+            " 00000000 00000840 000001CA 00000000003F31F6 00000000 00000000 00000000 00000000",
+        )
+        .unwrap();
+        assert_eq!(
+            info,
+            GaplessInfo {
+                delay: 0x840,
+                frames: 0x003F_31F6
+            }
+        );
+        assert_eq!(GaplessInfo::from_itunsmpb("a comment"), None);
+        assert_eq!(GaplessInfo::from_itunsmpb(" 00000000 00000840"), None);
+        assert_eq!(
+            GaplessInfo::from_itunsmpb(" 00000000 00000840 000001CA 0000000000000000"),
+            None
+        );
+    }
+
+    #[test]
+    fn ts_in_other_time_bases() {
+        assert_eq!(
+            ts_to_frames(1024, Some(TimeBase::new(1, 44100)), 44100),
+            1024
+        );
+        assert_eq!(ts_to_frames(1024, None, 44100), 1024);
+        // milliseconds, like Matroska
+        assert_eq!(ts_to_frames(20, Some(TimeBase::new(1, 1000)), 48000), 960);
+    }
+
+    /// Cut `signal` into tracks of the given lengths and "encode" each like an AAC encoder does:
+    /// `delay` frames of junk before, padding after up to a whole packet, in packets of
+    /// `packet_len` frames.
+    fn encode_sequence(
+        signal: &[i32],
+        track_lens: &[usize],
+        delay: usize,
+        packet_len: usize,
+    ) -> Vec<(GaplessInfo, Vec<Vec<i32>>)> {
+        let mut offset = 0;
+        track_lens
+            .iter()
+            .map(|&len| {
+                let mut encoded = vec![-1; delay];
+                encoded.extend_from_slice(&signal[offset..offset + len]);
+                offset += len;
+                while encoded.len() % packet_len != 0 {
+                    encoded.push(-2);
+                }
+                let padding = encoded.len() - delay - len;
+                let tag = format!(" 00000000 {delay:08X} {padding:08X} {len:016X}");
+                let info = GaplessInfo::from_itunsmpb(&tag).unwrap();
+                (
+                    info,
+                    encoded.chunks(packet_len).map(<[i32]>::to_vec).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generated_sequences_are_sample_exact() {
+        let signal: Vec<i32> = (0..200_000).collect();
+        let cases: &[(&[usize], usize, usize)] = &[
+            // AAC: 2112 frames of delay, packets of 1024
+            (&[44_100, 1, 1023, 1024, 50_000], 2112, 1024),
+            // a delay longer than the track
+            (&[10, 3000], 2112, 1024),
+            // HE-AAC, MP3 as tagged by iTunes
+            (&[30_000, 30_001], 5186, 2048),
+            (&[12_345, 6789], 1105, 1152),
+            (&[1000, 1000], 0, 1024),
+        ];
+
+        for &(track_lens, delay, packet_len) in cases {
+            let total: usize = track_lens.iter().sum();
+            let mut output = Vec::new();
+            for (info, packets) in encode_sequence(&signal, track_lens, delay, packet_len) {
+                let mut start = 0;
+                for packet in packets {
+                    let (trim_start, trim_end) = info.trim(start, packet.len() as u64);
+                    start += packet.len() as u64;
+                    output.extend_from_slice(&packet[trim_start..packet.len() - trim_end]);
+                }
+            }
+            // nothing inserted, nothing missing, nothing out of order at the track boundaries
+            assert_eq!(output, signal[..total], "{track_lens:?} delay {delay}");
+        }
+    }
+
+    /// A MPEG-1 Layer III frame header, 44.1kHz mono at 128kbps, without CRC.
+    const MP3_HEADER: [u8; 4] = [0xff, 0xfb, 0x90, 0xc0];
+    /// Length of a frame with [`MP3_HEADER`].
+    const MP3_FRAME_LEN: usize = 417;
+    /// Length of the mono side info after the header.
+    const MP3_SIDE_INFO_LEN: usize = 17;
+
+    /// A MP3 file of `frames` silent frames, with a Info header written by ffmpeg ("Lavf")
+    /// holding the encoder delay and padding, which symphonia trims itself.
+    fn lame_mp3(frames: usize, delay: u32, padding: u32) -> Vec<u8> {
+        let mut info = MP3_HEADER.to_vec();
+        info.resize(4 + MP3_SIDE_INFO_LEN, 0);
+        info.extend_from_slice(b"Info");
+        // only the frame count is present
+        info.extend_from_slice(&1u32.to_be_bytes());
+        info.extend_from_slice(&u32::try_from(frames).unwrap().to_be_bytes());
+        // encoder, revision, lowpass, peak, radio and audiophile gain, flags, bitrate
+        info.extend_from_slice(b"Lavf58.76");
+        info.extend_from_slice(&[0; 1 + 1 + 4 + 2 + 2 + 1 + 1]);
+        let trim = (delay << 12) | padding;
+        info.extend_from_slice(&trim.to_be_bytes()[1..]);
+        info.resize(MP3_FRAME_LEN, 0);
+
+        let mut file = info;
+        file.extend(silent_mp3_frames(frames));
+        file
+    }
+
+    /// A MP3 file of `frames` silent frames, with an ID3 comment like iTunes writes.
+    fn itunes_mp3(frames: usize, delay: u32, padding: u32, len: u64) -> Vec<u8> {
+        let value = format!(" 00000000 {delay:08X} {padding:08X} {len:016X}");
+        let mut comm = vec![0];
+        comm.extend_from_slice(b"engiTunSMPB\0");
+        comm.extend_from_slice(value.as_bytes());
+
+        let mut frame = b"COMM".to_vec();
+        frame.extend_from_slice(&u32::try_from(comm.len()).unwrap().to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend(comm);
+
+        // ID3v2.3, the size is syncsafe but small enough to not need it
+        let mut file = b"ID3\x03\x00\x00\x00\x00\x00".to_vec();
+        file.push(u8::try_from(frame.len()).unwrap());
+        file.extend(frame);
+        file.extend(silent_mp3_frames(frames));
+        file
+    }
+
+    fn silent_mp3_frames(frames: usize) -> Vec<u8> {
+        let mut frame = MP3_HEADER.to_vec();
+        frame.resize(MP3_FRAME_LEN, 0);
+        frame.repeat(frames)
+    }
+
+    /// A raw AAC-LC packet of 1024 frames of mono silence: a channel element without any
+    /// scale factor bands, and the end element.
+    const AAC_SILENT_PACKET: [u8; 4] = [0x00, 0x00, 0x00, 0x07];
+    /// The AAC-LC decoder config for 44.1kHz mono.
+    const AAC_CONFIG: [u8; 2] = [0x12, 0x08];
+
+    fn mp4_box(kind: &[u8], content: &[u8]) -> Vec<u8> {
+        let mut data = u32::try_from(content.len() + 8)
+            .unwrap()
+            .to_be_bytes()
+            .to_vec();
+        data.extend_from_slice(kind);
+        data.extend_from_slice(content);
+        data
+    }
+
+    /// A box with a version and flags of 0 before `content`.
+    fn full_box(kind: &[u8], content: &[u8]) -> Vec<u8> {
+        mp4_box(kind, &[&[0; 4], content].concat())
+    }
+
+    fn u32s(fields: &[u32]) -> Vec<u8> {
+        fields
+            .iter()
+            .flat_map(|field| field.to_be_bytes())
+            .collect()
+    }
+
+    /// The unity matrix of the movie and track headers.
+    fn unity_matrix() -> Vec<u8> {
+        u32s(&[0x1_0000, 0, 0, 0, 0x1_0000, 0, 0, 0, 0x4000_0000])
+    }
+
+    /// A track of `packets` silent AAC packets of 1024 frames, one after the other at `offset`
+    /// in the file.
+    fn aac_track(packets: u32, offset: u32) -> Vec<u8> {
+        let mut esds = vec![0x03, 25, 0, 1, 0];
+        esds.extend_from_slice(&[0x04, 17, 0x40, 0x15, 0, 0, 0]);
+        esds.extend_from_slice(&u32s(&[128_000, 128_000]));
+        esds.extend_from_slice(&[0x05, 2]);
+        esds.extend_from_slice(&AAC_CONFIG);
+        esds.extend_from_slice(&[0x06, 1, 0x02]);
+        // data reference 1, mono, 16 bit, 44.1kHz
+        let mut mp4a = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        mp4a.extend_from_slice(&u32s(&[0, 0, 0x0001_0010, 0, 44100 << 16]));
+        mp4a.extend(full_box(b"esds", &esds));
+
+        let packet_len = u32::try_from(AAC_SILENT_PACKET.len()).unwrap();
+        let stbl = [
+            full_box(b"stsd", &[u32s(&[1]), mp4_box(b"mp4a", &mp4a)].concat()),
+            full_box(b"stts", &u32s(&[1, packets, 1024])),
+            full_box(b"stsc", &u32s(&[1, 1, packets, 1])),
+            full_box(b"stsz", &u32s(&[packet_len, packets])),
+            full_box(b"stco", &u32s(&[1, offset])),
+        ]
+        .concat();
+        // the packets are in this file
+        let dref = full_box(
+            b"dref",
+            &[u32s(&[1]), mp4_box(b"url ", &[0, 0, 0, 1])].concat(),
+        );
+        let minf = [
+            full_box(b"smhd", &[0; 4]),
+            mp4_box(b"dinf", &dref),
+            mp4_box(b"stbl", &stbl),
+        ]
+        .concat();
+
+        let duration = packets * 1024;
+        // the language is "und"
+        let media_header = [u32s(&[0, 0, 44100, duration]), vec![0x55, 0xc4, 0, 0]].concat();
+        let mdia = [
+            full_box(b"mdhd", &media_header),
+            full_box(b"hdlr", &[&u32s(&[0])[..], b"soun", &[0; 13]].concat()),
+            mp4_box(b"minf", &minf),
+        ]
+        .concat();
+        let track_header = [
+            u32s(&[0, 0, 1, 0, duration, 0, 0, 0, 0x0100_0000]),
+            unity_matrix(),
+            u32s(&[0, 0]),
+        ]
+        .concat();
+        mp4_box(
+            b"trak",
+            &[full_box(b"tkhd", &track_header), mp4_box(b"mdia", &mdia)].concat(),
+        )
+    }
+
+    /// A M4A file of `packets` silent AAC packets, with a `iTunSMPB` tag like iTunes writes.
+    fn itunes_m4a(packets: u32, delay: u32, padding: u32, len: u64) -> Vec<u8> {
+        let value = format!(" 00000000 {delay:08X} {padding:08X} {len:016X}");
+        let freeform = [
+            full_box(b"mean", b"com.apple.iTunes"),
+            full_box(b"name", b"iTunSMPB"),
+            mp4_box(b"data", &[&u32s(&[1, 0]), value.as_bytes()].concat()),
+        ]
+        .concat();
+        let meta = [
+            full_box(b"hdlr", &[&u32s(&[0])[..], b"mdir", &[0; 13]].concat()),
+            mp4_box(b"ilst", &mp4_box(b"----", &freeform)),
+        ]
+        .concat();
+
+        let ftyp = mp4_box(b"ftyp", &[&b"M4A "[..], &u32s(&[0]), b"M4A isom"].concat());
+        let mdat = mp4_box(b"mdat", &AAC_SILENT_PACKET.repeat(packets as usize));
+        let offset = u32::try_from(ftyp.len() + 8).unwrap();
+        let movie_header = [
+            u32s(&[0, 0, 44100, packets * 1024, 0x1_0000]),
+            vec![1, 0],
+            vec![0; 10],
+            unity_matrix(),
+            vec![0; 24],
+            u32s(&[2]),
+        ]
+        .concat();
+        let moov = mp4_box(
+            b"moov",
+            &[
+                full_box(b"mvhd", &movie_header),
+                aac_track(packets, offset),
+                mp4_box(b"udta", &full_box(b"meta", &meta)),
+            ]
+            .concat(),
+        );
+        [ftyp, mdat, moov].concat()
+    }
+
+    fn decoded_len(file: Vec<u8>) -> usize {
+        let mss = MediaSourceStream::new(
+            Box::new(Cursor::new(file)),
+            MediaSourceStreamOptions::default(),
+        );
+        Symphonia::new(mss, true).unwrap().count()
+    }
+
+    #[test]
+    fn mp3_sequences_decode_to_the_encoded_length() {
+        for &(frames, delay, padding) in &[(20, 576, 1000), (8, 576, 1152 + 600), (3, 576, 529)] {
+            let expected = frames * 1152 - delay as usize - padding as usize;
+            assert_eq!(
+                decoded_len(lame_mp3(frames, delay, padding)),
+                expected,
+                "LAME header {frames} {delay} {padding}"
+            );
+        }
+
+        for &(frames, delay, padding) in &[(20u32, 2112, 400), (3, 3000, 200), (4, 0, 0)] {
+            let len = u64::from(frames * 1152 - delay - padding);
+            assert_eq!(
+                decoded_len(itunes_mp3(frames as usize, delay, padding, len)) as u64,
+                len,
+                "iTunSMPB {frames} {delay} {padding}"
+            );
+        }
+    }
+
+    #[test]
+    fn aac_sequences_decode_to_the_encoded_length() {
+        // the delay spans several packets, or ends on a packet boundary
+        for &(packets, delay, padding) in &[(10, 2112, 1000), (6, 2048, 1024), (4, 0, 0)] {
+            let len = u64::from(packets * 1024 - delay - padding);
+            assert_eq!(
+                decoded_len(itunes_m4a(packets, delay, padding, len)) as u64,
+                len,
+                "iTunSMPB {packets} {delay} {padding}"
+            );
+        }
+    }
+}
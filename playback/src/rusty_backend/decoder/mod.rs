@@ -1,21 +1,57 @@
+mod aiff;
 pub mod buffered_source;
+mod gapless;
+mod opus;
 
+use self::aiff::AiffReader;
+use self::gapless::{ts_to_frames, GaplessInfo};
+use self::opus::OpusDecoder;
 use super::Source;
+use lazy_static::lazy_static;
 use std::{fmt, time::Duration};
 use symphonia::{
     core::{
-        audio::{AudioBufferRef, SampleBuffer, SignalSpec},
-        codecs::{self, CodecParameters, CODEC_TYPE_NULL},
+        audio::{AudioBufferRef, SampleBuffer, Signal, SignalSpec},
+        codecs::{self, CodecParameters, CodecRegistry, CODEC_TYPE_NULL},
         errors::Error,
         formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track},
         io::MediaSourceStream,
         meta::MetadataOptions,
-        probe::Hint,
+        probe::{Hint, Probe},
         units::{Time, TimeBase},
     },
-    default::get_probe,
+    default::{register_enabled_codecs, register_enabled_formats},
 };
 
+lazy_static! {
+    /// Symphonia's codecs with the Opus decoder added.
+    static ref CODECS: CodecRegistry = {
+        let mut registry = CodecRegistry::new();
+        register_enabled_codecs(&mut registry);
+        registry.register_all::<OpusDecoder>();
+        registry
+    };
+    /// Symphonia's formats with the AIFF demuxer added.
+    static ref PROBE: Probe = {
+        let mut probe = Probe::default();
+        register_enabled_formats(&mut probe);
+        probe.register_all::<AiffReader>();
+        probe
+    };
+}
+
+/// The codecs the rusty backend can decode.
+#[must_use]
+pub fn get_codecs() -> &'static CodecRegistry {
+    &CODECS
+}
+
+/// The formats the rusty backend can demux.
+#[must_use]
+pub fn get_probe() -> &'static Probe {
+    &PROBE
+}
+
 fn is_codec_null(track: &Track) -> bool {
     track.codec_params.codec == CODEC_TYPE_NULL
 }
@@ -30,6 +66,8 @@ pub struct Symphonia {
     elapsed: Duration,
     track_id: u32,
     time_base: Option<TimeBase>,
+    /// Delay and padding symphonia does not trim, from an `iTunSMPB` tag
+    gapless: Option<GaplessInfo>,
 }
 
 impl Symphonia {
@@ -88,17 +126,40 @@ impl Symphonia {
             track.id, track.codec_params.codec
         );
 
-        let mut decoder = symphonia::default::get_codecs().make(
+        let mut decoder = get_codecs().make(
             &track.codec_params,
             &codecs::DecoderOptions { verify: true },
         )?;
 
-        let duration = Self::get_duration(&track.codec_params);
+        let mut duration = Self::get_duration(&track.codec_params);
         let track_id = track.id;
         let time_base = track.codec_params.time_base;
+        let sample_rate = track.codec_params.sample_rate;
+
+        // the reader trims itself when it knows the delay, like for MP3 with a LAME header
+        let gapless = if gapless && track.codec_params.delay.is_none() {
+            probed
+                .format
+                .metadata()
+                .current()
+                .and_then(GaplessInfo::from_metadata)
+                .or_else(|| {
+                    probed
+                        .metadata
+                        .get()
+                        .and_then(|m| m.current().and_then(GaplessInfo::from_metadata))
+                })
+        } else {
+            None
+        };
+        if let (Some(info), Some(rate)) = (gapless, sample_rate) {
+            info!("Trimming {} frames of encoder delay", info.delay);
+            let time = TimeBase::new(1, rate).calc_time(info.frames);
+            duration = Some(Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac));
+        }
 
         // let mut decode_errors: usize = 0;
-        let decode_result = loop {
+        let (spec, buffer) = loop {
             let packet = probed.format.next_packet()?;
 
             // Skip all packets that are not the selected track
@@ -107,15 +168,25 @@ impl Symphonia {
             }
 
             match decoder.decode(&packet) {
-                Ok(result) => break result,
+                Ok(result) => {
+                    let spec = *result.spec();
+                    let buffer = Self::get_trimmed_buffer(
+                        result,
+                        spec,
+                        gapless,
+                        ts_to_frames(packet.ts(), time_base, spec.rate),
+                    );
+                    // the delay may span several packets
+                    if !buffer.samples().is_empty() {
+                        break (spec, buffer);
+                    }
+                }
                 Err(Error::DecodeError(err)) => {
                     info!("Non-fatal Decoder Error: {}", err);
                 }
                 Err(e) => return Err(e),
             }
         };
-        let spec = *decode_result.spec();
-        let buffer = Self::get_buffer(decode_result, spec);
 
         Ok(Some(Self {
             decoder,
@@ -127,6 +198,7 @@ impl Symphonia {
             elapsed: Duration::from_secs(0),
             track_id,
             time_base,
+            gapless,
         }))
     }
 
@@ -146,6 +218,31 @@ impl Symphonia {
         buffer.copy_interleaved_ref(decoded);
         buffer
     }
+
+    /// Like [`Self::get_buffer`], without the frames outside of the audio of `gapless`.
+    ///
+    /// `start` is the frame of the stream the decoded packet starts at.
+    fn get_trimmed_buffer(
+        decoded: AudioBufferRef<'_>,
+        spec: SignalSpec,
+        gapless: Option<GaplessInfo>,
+        start: u64,
+    ) -> SampleBuffer<i16> {
+        let Some(info) = gapless else {
+            return Self::get_buffer(decoded, spec);
+        };
+        let (trim_start, trim_end) = info.trim(start, decoded.frames() as u64);
+        if trim_start == 0 && trim_end == 0 {
+            return Self::get_buffer(decoded, spec);
+        }
+
+        let mut trimmed = decoded.make_equivalent::<i16>();
+        decoded.convert(&mut trimmed);
+        trimmed.trim(trim_start, trim_end);
+        let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_typed(&trimmed);
+        buffer
+    }
 }
 
 impl Source for Symphonia {
@@ -176,7 +273,6 @@ impl Source for Symphonia {
     }
 
     #[inline]
-    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
     fn seek(&mut self, time: Duration) -> Option<Duration> {
         let nanos_per_sec = 1_000_000_000.0;
         match self.format.seek(
@@ -190,12 +286,20 @@ impl Source for Symphonia {
             },
         ) {
             Ok(seeked_to) => {
-                let base = TimeBase::new(1, self.sample_rate());
+                // decoders keep state from the previous packets, like the Opus overlap
+                self.decoder.reset();
+
+                // the track's own time base, which is not the sample rate for Matroska
+                let base = self
+                    .time_base
+                    .unwrap_or_else(|| TimeBase::new(1, self.sample_rate()));
                 let time = base.calc_time(seeked_to.actual_ts);
+                self.elapsed =
+                    Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac);
+                // the rest of the packet before the seek is not where it landed
+                self.current_frame_offset = self.buffer.len();
 
-                Some(Duration::from_millis(
-                    time.seconds * 1000 + ((time.frac * 60. * 1000.).round() as u64),
-                ))
+                Some(self.elapsed)
             }
             Err(_) => None,
         }
@@ -207,9 +311,10 @@ impl Iterator for Symphonia {
 
     #[inline]
     fn next(&mut self) -> Option<i16> {
-        if self.current_frame_offset == self.buffer.len() {
+        // packets trimmed away entirely leave an empty buffer, which is not the end of the track
+        while self.current_frame_offset == self.buffer.len() {
             // let mut decode_errors: usize = 0;
-            let decoded = loop {
+            let (decoded, ts) = loop {
                 let packet = self.format.next_packet().ok()?;
 
                 // Skip all packets that are not the selected track
@@ -225,7 +330,7 @@ impl Iterator for Symphonia {
                             self.elapsed = Duration::from_secs(time.seconds)
                                 + Duration::from_secs_f64(time.frac);
                         }
-                        break decoded;
+                        break (decoded, ts);
                     }
                     Err(Error::DecodeError(err)) => {
                         info!("Non-fatal Decoder Error: {}", err);
@@ -234,14 +339,11 @@ impl Iterator for Symphonia {
                 }
             };
             self.spec = *decoded.spec();
-            self.buffer = Self::get_buffer(decoded, self.spec);
+            let start = ts_to_frames(ts, self.time_base, self.spec.rate);
+            self.buffer = Self::get_trimmed_buffer(decoded, self.spec, self.gapless, start);
             self.current_frame_offset = 0;
         }
 
-        if self.buffer.samples().is_empty() {
-            return None;
-        }
-
         let sample = *self.buffer.samples().get(self.current_frame_offset)?;
         self.current_frame_offset += 1;
 
@@ -0,0 +1,214 @@
+//! Opus decoder on top of libopus, symphonia demuxes Opus from Ogg and Matroska but cannot decode
+//! it.
+
+use std::sync::{Mutex, PoisonError};
+
+use symphonia::core::audio::{
+    AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal, SignalSpec,
+};
+use symphonia::core::codecs::{
+    CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
+};
+use symphonia::core::errors::{decode_error, unsupported_error, Result};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+/// Opus is always decoded at 48kHz.
+const OPUS_SAMPLE_RATE: u32 = 48_000;
+/// Frames in the longest Opus packet, 120ms.
+const OPUS_MAX_FRAMES: usize = 5760;
+
+/// Decodes mono and stereo Opus.
+pub struct OpusDecoder {
+    // libopus decoders can be sent but not shared, symphonia decoders have to be both
+    decoder: Mutex<::opus::Decoder>,
+    params: CodecParameters,
+    buf: AudioBuffer<f32>,
+    /// Interleaved output of libopus
+    pcm: Vec<f32>,
+}
+
+impl Decoder for OpusDecoder {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+        // Ogg only sets the channels, Matroska only the layout, both have the Opus header
+        let channel_count = params
+            .extra_data
+            .as_deref()
+            .and_then(opus_head_channels)
+            .or_else(|| params.channels.map(Channels::count))
+            .or_else(|| params.channel_layout.map(|l| l.into_channels().count()))
+            .unwrap_or(2);
+        let (opus_channels, channels) = match channel_count {
+            1 => (::opus::Channels::Mono, Channels::FRONT_LEFT),
+            2 => (
+                ::opus::Channels::Stereo,
+                Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+            ),
+            _ => return unsupported_error("opus: only mono and stereo are supported"),
+        };
+
+        let decoder = match ::opus::Decoder::new(OPUS_SAMPLE_RATE, opus_channels) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("error creating opus decoder: {e}");
+                return unsupported_error("opus: decoder could not be created");
+            }
+        };
+
+        let mut params = params.clone();
+        params
+            .with_sample_rate(OPUS_SAMPLE_RATE)
+            .with_channels(channels)
+            .with_max_frames_per_packet(OPUS_MAX_FRAMES as u64);
+
+        Ok(Self {
+            decoder: Mutex::new(decoder),
+            params,
+            buf: AudioBuffer::new(
+                OPUS_MAX_FRAMES as u64,
+                SignalSpec::new(OPUS_SAMPLE_RATE, channels),
+            ),
+            pcm: vec![0.0; OPUS_MAX_FRAMES * channel_count],
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        let decoder = self
+            .decoder
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = decoder.reset_state() {
+            warn!("error resetting opus decoder: {e}");
+        }
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+        self.buf.clear();
+
+        let decoder = self
+            .decoder
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let frames = match decoder.decode_float(packet.buf(), &mut self.pcm, false) {
+            Ok(v) => v,
+            Err(e) => {
+                debug!("error decoding opus packet: {e}");
+                return decode_error("opus: invalid packet");
+            }
+        };
+
+        self.buf.render_reserved(Some(frames));
+        {
+            let mut planes = self.buf.planes_mut();
+            let channels = planes.planes().len();
+            for (channel, plane) in planes.planes().iter_mut().enumerate() {
+                for (frame, sample) in plane.iter_mut().enumerate() {
+                    *sample = self.pcm[frame * channels + channel];
+                }
+            }
+        }
+
+        // the pre-skip at the start and the padding at the end, when playing gapless
+        self.buf
+            .trim(packet.trim_start() as usize, packet.trim_end() as usize);
+        Ok(self.buf.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buf.as_audio_buffer_ref()
+    }
+}
+
+/// Channel count from an `OpusHead` identification header.
+fn opus_head_channels(head: &[u8]) -> Option<usize> {
+    if head.len() < 19 || !head.starts_with(b"OpusHead") {
+        return None;
+    }
+    Some(usize::from(head[9]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use symphonia::core::audio::SampleBuffer;
+
+    fn opus_params(channels: Channels) -> CodecParameters {
+        let mut params = CodecParameters::new();
+        params.for_codec(CODEC_TYPE_OPUS).with_channels(channels);
+        params
+    }
+
+    #[test]
+    fn decodes_what_libopus_encodes() {
+        let mut encoder = ::opus::Encoder::new(
+            OPUS_SAMPLE_RATE,
+            ::opus::Channels::Stereo,
+            ::opus::Application::Audio,
+        )
+        .unwrap();
+        let mut decoder = OpusDecoder::try_new(
+            &opus_params(Channels::FRONT_LEFT | Channels::FRONT_RIGHT),
+            &DecoderOptions::default(),
+        )
+        .unwrap();
+
+        // 20ms frames of a 1kHz sine on both channels
+        let mut output = Vec::new();
+        for frame in 0..10u32 {
+            let input: Vec<f32> = (0..960)
+                .flat_map(|i| {
+                    let t = f64::from(frame * 960 + i) / f64::from(OPUS_SAMPLE_RATE);
+                    #[allow(clippy::cast_possible_truncation)]
+                    let sample = ((t * 1000.0 * std::f64::consts::TAU).sin() * 0.5) as f32;
+                    [sample, sample]
+                })
+                .collect();
+            let mut data = vec![0; 4000];
+            let len = encoder.encode_float(&input, &mut data).unwrap();
+            data.truncate(len);
+
+            let packet = Packet::new_from_slice(0, u64::from(frame) * 960, 960, &data);
+            let buf = decoder.decode(&packet).unwrap();
+            assert_eq!(buf.frames(), 960);
+            assert_eq!(buf.spec().rate, OPUS_SAMPLE_RATE);
+            let mut samples = SampleBuffer::<f32>::new(buf.capacity() as u64, *buf.spec());
+            samples.copy_interleaved_ref(buf);
+            output.extend_from_slice(samples.samples());
+        }
+
+        // past the encoder delay there is a signal, the same on both channels
+        let tail = &output[output.len() / 2..];
+        assert!(tail.iter().any(|s| s.abs() > 0.25));
+        assert!(tail.chunks(2).all(|lr| (lr[0] - lr[1]).abs() < 0.05));
+    }
+
+    #[test]
+    fn channels_from_opus_head() {
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 1, 0x38, 0x01, 0x80, 0xbb, 0, 0, 0, 0, 0]);
+        assert_eq!(opus_head_channels(&head), Some(1));
+        assert_eq!(opus_head_channels(b"OpusTags"), None);
+
+        let mut params = opus_params(Channels::FRONT_LEFT | Channels::FRONT_RIGHT);
+        params.with_extra_data(head.into_boxed_slice());
+        let decoder = OpusDecoder::try_new(&params, &DecoderOptions::default()).unwrap();
+        assert_eq!(decoder.codec_params().channels, Some(Channels::FRONT_LEFT));
+        assert!(OpusDecoder::try_new(
+            &opus_params(Channels::FRONT_LEFT | Channels::FRONT_RIGHT | Channels::FRONT_CENTRE),
+            &DecoderOptions::default()
+        )
+        .is_err());
+    }
+}
@@ -1,11 +1,14 @@
 //! Mixer that plays multiple sounds at the same time.
 
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use super::pcm_tap::{PcmChunk, PcmTap};
 use super::source::{Source, UniformSourceIterator};
 use super::Sample;
+use termusiclib::config::ResampleQuality;
 
 /// Builds a new mixer.
 ///
@@ -25,6 +28,9 @@ where
         pending_sources: Mutex::new(Vec::new()),
         channels,
         sample_rate,
+        resample_quality: Mutex::new(ResampleQuality::Medium),
+        has_pending_taps: AtomicBool::new(false),
+        pending_taps: Mutex::new(Vec::new()),
     });
 
     let output = DynamicMixer {
@@ -33,6 +39,7 @@ where
         sample_count: 0,
         still_pending: vec![],
         still_current: vec![],
+        taps: Vec::new(),
     };
 
     (input, output)
@@ -45,6 +52,9 @@ pub struct DynamicMixerController<S> {
     pending_sources: Mutex<Vec<Box<dyn Source<Item = S> + Send>>>,
     channels: u16,
     sample_rate: u32,
+    resample_quality: Mutex<ResampleQuality>,
+    has_pending_taps: AtomicBool,
+    pending_taps: Mutex<Vec<SyncSender<PcmChunk>>>,
 }
 
 impl<S> DynamicMixerController<S>
@@ -57,13 +67,29 @@ where
     where
         T: Source<Item = S> + Send + 'static,
     {
-        let uniform_source = UniformSourceIterator::new(source, self.channels, self.sample_rate);
+        let resample_quality = *self.resample_quality.lock().unwrap();
+        let uniform_source =
+            UniformSourceIterator::new(source, self.channels, self.sample_rate, resample_quality);
         self.pending_sources
             .lock()
             .unwrap()
             .push(Box::new(uniform_source) as Box<_>);
         self.has_pending.store(true, Ordering::SeqCst); // TODO: can we relax this ordering?
     }
+
+    /// Sets the sample rate conversion quality used for the sources added from now on.
+    #[inline]
+    pub fn set_resample_quality(&self, quality: ResampleQuality) {
+        *self.resample_quality.lock().unwrap() = quality;
+    }
+
+    /// Sends a copy of everything the mixer plays to `tx` from the next frame on, until its
+    /// receiver is dropped.
+    #[inline]
+    pub fn add_tap(&self, tx: SyncSender<PcmChunk>) {
+        self.pending_taps.lock().unwrap().push(tx);
+        self.has_pending_taps.store(true, Ordering::SeqCst);
+    }
 }
 
 /// The output of the mixer. Implements `Source`.
@@ -82,6 +108,9 @@ pub struct DynamicMixer<S> {
 
     // A temporary vec used in sum_current_sources.
     still_current: Vec<Box<dyn Source<Item = S> + Send>>,
+
+    // Receive a copy of the output.
+    taps: Vec<PcmTap>,
 }
 
 impl<S> Source for DynamicMixer<S>
@@ -130,10 +159,22 @@ where
             self.start_pending_sources();
         }
 
+        // only start the taps on a frame boundary, so they do not mix up the channels
+        if self.input.has_pending_taps.load(Ordering::SeqCst)
+            && self.sample_count % self.input.channels as usize == 0
+        {
+            self.start_pending_taps();
+        }
+
         self.sample_count += 1;
 
         let sum = self.sum_current_sources();
 
+        if !self.taps.is_empty() {
+            let sample = sum.to_f32();
+            self.taps.retain_mut(|tap| tap.push(sample));
+        }
+
         if self.current_sources.is_empty() {
             None
         } else {
@@ -173,6 +214,15 @@ where
         self.input.has_pending.store(has_pending, Ordering::SeqCst); // TODO: relax ordering?
     }
 
+    fn start_pending_taps(&mut self) {
+        let mut pending = self.input.pending_taps.lock().unwrap();
+        for tx in pending.drain(..) {
+            self.taps
+                .push(PcmTap::new(tx, self.input.channels, self.input.sample_rate));
+        }
+        self.input.has_pending_taps.store(false, Ordering::SeqCst);
+    }
+
     fn sum_current_sources(&mut self) -> S {
         let mut sum = S::zero_value();
 
@@ -0,0 +1,286 @@
+//! Outputs that do not need a sound card, used to run the player headless.
+
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::source::Source;
+
+/// Number of channels the headless outputs play with.
+pub const HEADLESS_CHANNELS: u16 = 2;
+/// Sample rate the headless outputs play with.
+pub const HEADLESS_SAMPLE_RATE: u32 = 44100;
+
+/// Length of the header written by [`WavWriter`], the samples start after it.
+const WAV_HEADER_LEN: usize = 58;
+/// `WAVE_FORMAT_IEEE_FLOAT`
+const WAV_FORMAT_FLOAT: u16 = 3;
+
+/// Pulls samples out of a source on its own thread, in place of a sound card.
+///
+/// The samples are written to a [`WavWriter`] if there is one, otherwise they are discarded.
+/// The source is consumed `speed` times faster than real time.
+/// Dropping this stops the thread and finalizes the WAV file.
+pub struct HeadlessOutput {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HeadlessOutput {
+    /// Start consuming `source` on a new thread.
+    ///
+    /// # Panics
+    ///
+    /// If the thread cannot be spawned.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn spawn<S, W>(mut source: S, mut writer: Option<WavWriter<W>>, speed: u16) -> Self
+    where
+        S: Source<Item = f32> + Send + 'static,
+        W: Write + Seek + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_local = stop.clone();
+        let channels = usize::from(source.channels());
+        let sample_rate = source.sample_rate();
+        let speed = f64::from(speed.max(1));
+
+        let thread = std::thread::Builder::new()
+            .name("headless audio output".into())
+            .spawn(move || {
+                // 10ms worth of frames at a time, like a sound card callback would ask for
+                let frames_per_chunk = (sample_rate / 100).max(1) as usize;
+                let mut chunk = vec![0f32; frames_per_chunk * channels];
+                let start = Instant::now();
+                let mut frames_done: u64 = 0;
+
+                while !stop_local.load(Ordering::SeqCst) {
+                    for sample in &mut chunk {
+                        *sample = source.next().unwrap_or(0.0);
+                    }
+
+                    if let Some(Err(e)) = writer.as_mut().map(|w| w.write_samples(&chunk)) {
+                        error!("error writing wav file: {e}");
+                        writer = None;
+                    }
+
+                    frames_done += frames_per_chunk as u64;
+                    let played = Duration::from_secs_f64(
+                        frames_done as f64 / f64::from(sample_rate) / speed,
+                    );
+                    if let Some(wait) = played.checked_sub(start.elapsed()) {
+                        std::thread::sleep(wait);
+                    }
+                }
+
+                if let Some(mut writer) = writer {
+                    if let Err(e) = writer.finalize() {
+                        error!("error finalizing wav file: {e}");
+                    }
+                }
+            })
+            .expect("failed to spawn thread");
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for HeadlessOutput {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Writes 32-bit float samples to a WAV file.
+///
+/// The sizes in the header are kept up to date about once per second of audio, so the file can
+/// be read while it is still being written.
+pub struct WavWriter<W: Write + Seek> {
+    inner: W,
+    channels: u16,
+    sample_rate: u32,
+    data_len: u64,
+    unsynced_len: u64,
+}
+
+impl WavWriter<BufWriter<std::fs::File>> {
+    /// Create the file at `path`, replacing it if it exists.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be created or written.
+    pub fn create<P: AsRef<Path>>(path: P, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Self::new(BufWriter::new(file), channels, sample_rate)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write the header to `inner` and return a writer for the samples.
+    ///
+    /// # Errors
+    ///
+    /// If writing the header fails.
+    pub fn new(inner: W, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let mut writer = Self {
+            inner,
+            channels,
+            sample_rate,
+            data_len: 0,
+            unsynced_len: 0,
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let block_align = self.channels * 4;
+        let w = &mut self.inner;
+        w.write_all(b"RIFF")?;
+        w.write_all(&0u32.to_le_bytes())?;
+        w.write_all(b"WAVE")?;
+
+        w.write_all(b"fmt ")?;
+        w.write_all(&18u32.to_le_bytes())?;
+        w.write_all(&WAV_FORMAT_FLOAT.to_le_bytes())?;
+        w.write_all(&self.channels.to_le_bytes())?;
+        w.write_all(&self.sample_rate.to_le_bytes())?;
+        w.write_all(&(self.sample_rate * u32::from(block_align)).to_le_bytes())?;
+        w.write_all(&block_align.to_le_bytes())?;
+        w.write_all(&32u16.to_le_bytes())?;
+        w.write_all(&0u16.to_le_bytes())?;
+
+        // non-PCM formats need a "fact" chunk with the number of frames
+        w.write_all(b"fact")?;
+        w.write_all(&4u32.to_le_bytes())?;
+        w.write_all(&0u32.to_le_bytes())?;
+
+        w.write_all(b"data")?;
+        w.write_all(&0u32.to_le_bytes())?;
+        Ok(())
+    }
+
+    /// Append interleaved samples.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the underlying writer fails.
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        for sample in samples {
+            self.inner.write_all(&sample.to_le_bytes())?;
+        }
+        let len = samples.len() as u64 * 4;
+        self.data_len += len;
+        self.unsynced_len += len;
+
+        if self.unsynced_len >= u64::from(self.sample_rate) * u64::from(self.channels) * 4 {
+            self.sync_header()?;
+        }
+        Ok(())
+    }
+
+    /// Write the final sizes to the header and flush.
+    ///
+    /// # Errors
+    ///
+    /// If writing to the underlying writer fails.
+    pub fn finalize(&mut self) -> io::Result<()> {
+        self.sync_header()?;
+        self.inner.flush()
+    }
+
+    fn sync_header(&mut self) -> io::Result<()> {
+        // sizes are capped at what the 32 bit fields can hold
+        let data_len = u32::try_from(self.data_len).unwrap_or(u32::MAX);
+        let riff_len = u32::try_from(self.data_len + WAV_HEADER_LEN as u64 - 8).unwrap_or(u32::MAX);
+        let frames =
+            u32::try_from(self.data_len / (u64::from(self.channels) * 4)).unwrap_or(u32::MAX);
+
+        self.inner.seek(SeekFrom::Start(4))?;
+        self.inner.write_all(&riff_len.to_le_bytes())?;
+        self.inner.seek(SeekFrom::Start(46))?;
+        self.inner.write_all(&frames.to_le_bytes())?;
+        self.inner.seek(SeekFrom::Start(54))?;
+        self.inner.write_all(&data_len.to_le_bytes())?;
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+        self.unsynced_len = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rusty_backend::buffer::SamplesBuffer;
+    use std::io::Cursor;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn samples_from(bytes: &[u8]) -> Vec<f32> {
+        bytes[WAV_HEADER_LEN..]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn wav_header_has_final_sizes() {
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), 2, 48000).unwrap();
+        writer.write_samples(&[0.25, -0.25, 0.5, -0.5]).unwrap();
+        writer.finalize().unwrap();
+        let bytes = writer.inner.into_inner();
+
+        assert_eq!(bytes.len(), WAV_HEADER_LEN + 16);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4), u32::try_from(bytes.len() - 8).unwrap());
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), WAV_FORMAT_FLOAT);
+        assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 2);
+        assert_eq!(u32_at(&bytes, 24), 48000);
+        assert_eq!(&bytes[38..42], b"fact");
+        assert_eq!(u32_at(&bytes, 46), 2);
+        assert_eq!(&bytes[50..54], b"data");
+        assert_eq!(u32_at(&bytes, 54), 16);
+        assert_eq!(samples_from(&bytes), vec![0.25, -0.25, 0.5, -0.5]);
+    }
+
+    #[test]
+    fn headless_output_writes_source_then_silence() {
+        let path =
+            std::env::temp_dir().join(format!("termusic-headless-test-{}.wav", std::process::id()));
+        let source = SamplesBuffer::new(2, 44100, vec![0.5f32; 8820]);
+        let writer = WavWriter::create(&path, 2, 44100).unwrap();
+
+        let output = HeadlessOutput::spawn(source, Some(writer), 100);
+        // 100ms of audio at 100 times real time, with plenty of room for a slow machine
+        std::thread::sleep(Duration::from_millis(50));
+        drop(output);
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let samples = samples_from(&bytes);
+
+        assert_eq!(u32_at(&bytes, 54) as usize, samples.len() * 4);
+        assert!(
+            samples.len() > 8820,
+            "only {} samples written",
+            samples.len()
+        );
+        assert!(samples[..8820]
+            .iter()
+            .all(|s| (s - 0.5).abs() < f32::EPSILON));
+        assert!(samples[8820..].iter().all(|s| s.abs() < f32::EPSILON));
+    }
+}
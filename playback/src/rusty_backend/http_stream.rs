@@ -0,0 +1,539 @@
+//! Icecast compatible HTTP stream of what is playing, for players on other devices.
+//!
+//! Every client gets the stream from the moment it connects, there is no burst of past audio.
+//! Clients asking for `Icy-MetaData: 1` get the title of the current track in band.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+
+use super::pcm_tap::{converted_pcm, PcmChunk};
+use super::pipe::encode_samples;
+use termusiclib::config::{HttpStreamFormat, PipeSampleFormat, ResampleQuality, Settings};
+
+/// Channel count of the stream.
+const STREAM_CHANNELS: u16 = 2;
+/// Sample rate of the stream.
+const STREAM_SAMPLE_RATE: u32 = 44100;
+/// Chunks of 10ms the mixer may be ahead of the stream before chunks get dropped.
+const STREAM_QUEUE_CHUNKS: usize = 50;
+/// Chunks of 100ms a client may fall behind before it gets disconnected.
+const CLIENT_QUEUE_CHUNKS: usize = 50;
+/// Audio bytes between two metadata blocks.
+const ICY_METAINT: usize = 16000;
+/// Longest request head accepted from a client.
+const REQUEST_HEAD_LIMIT: usize = 8192;
+
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<Vec<u8>>>>>>;
+
+/// Counts the clients served, to turn away the ones beyond `player_http_stream_max_clients`.
+struct ClientSlots {
+    max: usize,
+    served: AtomicUsize,
+}
+
+/// A client being served, given back to the [`ClientSlots`] when dropped.
+struct ClientSlot(Arc<ClientSlots>);
+
+impl ClientSlot {
+    /// `None` if `max` clients are served already.
+    fn take(slots: &Arc<ClientSlots>) -> Option<Self> {
+        slots
+            .served
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |served| {
+                (served < slots.max).then_some(served + 1)
+            })
+            .ok()?;
+        Some(Self(slots.clone()))
+    }
+}
+
+impl Drop for ClientSlot {
+    fn drop(&mut self) {
+        self.0.served.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// How the stream is sent to the clients.
+#[derive(Clone, Copy, Debug)]
+enum Encoding {
+    /// 16 bit PCM after a WAV header
+    Wav,
+    /// Encoded by an `ffmpeg` process per client
+    Ffmpeg(HttpStreamFormat),
+}
+
+impl Encoding {
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Wav | Self::Ffmpeg(HttpStreamFormat::Wav) => "audio/wav",
+            Self::Ffmpeg(HttpStreamFormat::Flac) => "audio/flac",
+            Self::Ffmpeg(HttpStreamFormat::Mp3) => "audio/mpeg",
+        }
+    }
+}
+
+/// The HTTP stream server, running until the player exits.
+pub struct HttpStream {
+    title: Arc<Mutex<String>>,
+}
+
+impl HttpStream {
+    /// Listen on `player_http_stream_address` and `player_http_stream_port` and return the sender
+    /// for the taps feeding the stream. At most `player_http_stream_max_clients` are served at the
+    /// same time.
+    ///
+    /// # Errors
+    ///
+    /// If the address cannot be listened on.
+    pub fn start(config: &Settings) -> Result<(Self, SyncSender<PcmChunk>)> {
+        let (address, port) = (
+            config.player_http_stream_address.as_str(),
+            config.player_http_stream_port,
+        );
+        let listener = TcpListener::bind((address, port))
+            .with_context(|| format!("listening on {address} port {port}"))?;
+        let encoding = find_encoding(config.player_http_stream_format);
+        let slots = Arc::new(ClientSlots {
+            max: config.player_http_stream_max_clients,
+            served: AtomicUsize::new(0),
+        });
+        let title = Arc::new(Mutex::new(String::new()));
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::sync_channel(STREAM_QUEUE_CHUNKS);
+
+        let clients_local = clients.clone();
+        let resample_quality = config.player_resample_quality;
+        std::thread::Builder::new()
+            .name("http stream encoder".into())
+            .spawn(move || broadcast_pcm(rx, &clients_local, resample_quality))?;
+
+        let title_local = title.clone();
+        std::thread::Builder::new()
+            .name("http stream listener".into())
+            .spawn(move || {
+                for socket in listener.incoming() {
+                    let socket = match socket {
+                        Ok(v) => v,
+                        Err(e) => {
+                            warn!("http stream connection failed: {e}");
+                            continue;
+                        }
+                    };
+                    let title = title_local.clone();
+                    let clients = clients.clone();
+                    let slots = slots.clone();
+                    let spawned = std::thread::Builder::new()
+                        .name("http stream client".into())
+                        .spawn(move || {
+                            let peer = socket
+                                .peer_addr()
+                                .map(|a| a.to_string())
+                                .unwrap_or_default();
+                            info!("http stream client {peer} connected");
+                            if let Err(e) = serve_client(socket, encoding, title, &clients, &slots)
+                            {
+                                info!("http stream client {peer} disconnected: {e}");
+                            }
+                        });
+                    if let Err(e) = spawned {
+                        error!("error starting http stream client: {e}");
+                    }
+                }
+            })?;
+
+        info!(
+            "http stream listening on {address} port {port} as {}",
+            encoding.content_type()
+        );
+        Ok((Self { title }, tx))
+    }
+
+    /// Set the title sent to the clients asking for metadata.
+    pub fn set_title(&self, title: String) {
+        *self.title.lock() = title;
+    }
+}
+
+/// Use `ffmpeg` for the compressed formats if it can be found, wav otherwise.
+fn find_encoding(format: HttpStreamFormat) -> Encoding {
+    if format == HttpStreamFormat::Wav {
+        return Encoding::Wav;
+    }
+
+    let found = Command::new("ffmpeg")
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success());
+    if found {
+        Encoding::Ffmpeg(format)
+    } else {
+        warn!("ffmpeg not found, the http stream is sent as wav instead of {format}");
+        Encoding::Wav
+    }
+}
+
+/// Convert the mixer output to the stream format and hand it to every client.
+fn broadcast_pcm(rx: Receiver<PcmChunk>, clients: &Clients, resample_quality: ResampleQuality) {
+    let Some(mut converted) =
+        converted_pcm(rx, STREAM_CHANNELS, STREAM_SAMPLE_RATE, resample_quality)
+    else {
+        return;
+    };
+    // 100ms at a time
+    let chunk_len = STREAM_SAMPLE_RATE as usize / 10 * usize::from(STREAM_CHANNELS);
+    let mut samples = Vec::with_capacity(chunk_len);
+
+    loop {
+        samples.clear();
+        samples.extend(converted.by_ref().take(chunk_len));
+        if samples.is_empty() {
+            break;
+        }
+        let mut bytes = Vec::with_capacity(samples.len() * 2);
+        encode_samples(&samples, PipeSampleFormat::S16Le, &mut bytes);
+        let bytes = Arc::new(bytes);
+
+        clients
+            .lock()
+            .retain(|client| match client.try_send(bytes.clone()) {
+                Ok(()) => true,
+                // dropping the sender ends the client once it has sent what is queued
+                Err(TrySendError::Full(_)) => {
+                    warn!("http stream client cannot keep up, disconnecting it");
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+fn serve_client(
+    mut socket: TcpStream,
+    encoding: Encoding,
+    title: Arc<Mutex<String>>,
+    clients: &Clients,
+    slots: &Arc<ClientSlots>,
+) -> io::Result<()> {
+    socket.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let request = read_request_head(&mut socket)?;
+    if !request.starts_with("GET ") {
+        socket.write_all(b"HTTP/1.0 405 Method Not Allowed\r\nConnection: close\r\n\r\n")?;
+        return Ok(());
+    }
+    let Some(_slot) = ClientSlot::take(slots) else {
+        warn!(
+            "http stream serves {} clients already, turning one away",
+            slots.max
+        );
+        socket.write_all(b"HTTP/1.0 503 Service Unavailable\r\nConnection: close\r\n\r\n")?;
+        return Ok(());
+    };
+    let metaint = wants_metadata(&request).then_some(ICY_METAINT);
+
+    let mut head = format!(
+        "HTTP/1.0 200 OK\r\n\
+         Content-Type: {}\r\n\
+         Cache-Control: no-cache\r\n\
+         Connection: close\r\n\
+         icy-name: termusic\r\n",
+        encoding.content_type()
+    );
+    if let Some(metaint) = metaint {
+        head = format!("{head}icy-metaint: {metaint}\r\n");
+    }
+    head.push_str("\r\n");
+    socket.write_all(head.as_bytes())?;
+
+    let (tx, rx) = mpsc::sync_channel(CLIENT_QUEUE_CHUNKS);
+    clients.lock().push(tx);
+    let mut out = IcyWriter::new(socket, metaint, title);
+
+    match encoding {
+        Encoding::Wav => {
+            out.write_all(&wav_stream_header())?;
+            for chunk in rx {
+                out.write_all(&chunk)?;
+            }
+            Ok(())
+        }
+        Encoding::Ffmpeg(format) => {
+            let child = Command::new("ffmpeg")
+                .args(["-hide_banner", "-loglevel", "error", "-f", "s16le"])
+                .args(["-ar", &STREAM_SAMPLE_RATE.to_string()])
+                .args(["-ac", &STREAM_CHANNELS.to_string()])
+                .args(["-i", "pipe:0", "-f", &format.to_string(), "pipe:1"])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()?;
+            let child = Arc::new(Mutex::new(child));
+            let mut stdin = child.lock().stdin.take().expect("stdin is piped");
+            let mut stdout = child.lock().stdout.take().expect("stdout is piped");
+
+            // once the client is gone ffmpeg is killed, which ends the loop below
+            let child_local = child.clone();
+            let pump = std::thread::spawn(move || {
+                let result = io::copy(&mut stdout, &mut out);
+                kill(&mut child_local.lock());
+                result
+            });
+            for chunk in rx {
+                if stdin.write_all(&chunk).is_err() {
+                    break;
+                }
+            }
+            drop(stdin);
+            kill(&mut child.lock());
+            match pump.join() {
+                Ok(result) => result.map(|_| ()),
+                Err(_) => Ok(()),
+            }
+        }
+    }
+}
+
+/// Stop `ffmpeg`, if it has not already.
+fn kill(child: &mut Child) {
+    child.kill().ok();
+    child.wait().ok();
+}
+
+/// Read the request up to the empty line ending its headers.
+fn read_request_head(socket: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut byte = [0u8];
+    while !head.ends_with(b"\r\n\r\n") && !head.ends_with(b"\n\n") {
+        if head.len() >= REQUEST_HEAD_LIMIT {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request head too long",
+            ));
+        }
+        if socket.read(&mut byte)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        head.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Whether the request has `Icy-MetaData: 1`.
+fn wants_metadata(request: &str) -> bool {
+    request.lines().any(|line| {
+        line.split_once(':').map_or(false, |(name, value)| {
+            name.trim().eq_ignore_ascii_case("icy-metadata") && value.trim() == "1"
+        })
+    })
+}
+
+/// Header of a 16 bit PCM WAV file of unknown length.
+fn wav_stream_header() -> Vec<u8> {
+    let block_align = STREAM_CHANNELS * 2;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    // the sizes are not known, players take the largest ones as "until the end"
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&STREAM_CHANNELS.to_le_bytes());
+    header.extend_from_slice(&STREAM_SAMPLE_RATE.to_le_bytes());
+    header.extend_from_slice(&(STREAM_SAMPLE_RATE * u32::from(block_align)).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&(u32::MAX - 36).to_le_bytes());
+    header
+}
+
+/// A metadata block as inserted every `icy-metaint` bytes, with the title as `StreamTitle`.
+fn icy_metadata_block(title: &str) -> Vec<u8> {
+    // the length byte counts blocks of 16 bytes
+    const MAX_LEN: usize = 255 * 16;
+    const FRAME_LEN: usize = "StreamTitle='';".len();
+
+    let mut end = title.len().min(MAX_LEN - FRAME_LEN);
+    while !title.is_char_boundary(end) {
+        end -= 1;
+    }
+    let text = format!("StreamTitle='{}';", &title[..end]);
+    let blocks = (text.len() + 15) / 16;
+
+    let mut block = Vec::with_capacity(1 + blocks * 16);
+    block.push(u8::try_from(blocks).unwrap_or(u8::MAX));
+    block.extend_from_slice(text.as_bytes());
+    block.resize(1 + blocks * 16, 0);
+    block
+}
+
+/// Inserts the ICY metadata blocks between the audio bytes, if the client asked for them.
+struct IcyWriter<W: Write> {
+    inner: W,
+    metaint: Option<usize>,
+    until_metadata: usize,
+    title: Arc<Mutex<String>>,
+    sent_title: Option<String>,
+}
+
+impl<W: Write> IcyWriter<W> {
+    fn new(inner: W, metaint: Option<usize>, title: Arc<Mutex<String>>) -> Self {
+        Self {
+            inner,
+            metaint,
+            until_metadata: metaint.unwrap_or_default(),
+            title,
+            sent_title: None,
+        }
+    }
+
+    fn write_metadata(&mut self) -> io::Result<()> {
+        let title = self.title.lock().clone();
+        // an empty block keeps what the client shows
+        if self.sent_title.as_ref() == Some(&title) {
+            return self.inner.write_all(&[0]);
+        }
+        self.inner.write_all(&icy_metadata_block(&title))?;
+        self.sent_title = Some(title);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for IcyWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(metaint) = self.metaint else {
+            return self.inner.write(buf);
+        };
+        if self.until_metadata == 0 {
+            self.write_metadata()?;
+            self.until_metadata = metaint;
+        }
+        let len = buf.len().min(self.until_metadata);
+        let written = self.inner.write(&buf[..len])?;
+        self.until_metadata -= written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_block_is_padded_and_limited() {
+        let block = icy_metadata_block("a - b");
+        assert_eq!(block[0], 2);
+        assert_eq!(block.len(), 33);
+        assert_eq!(&block[1..21], b"StreamTitle='a - b';");
+        assert!(block[21..].iter().all(|b| *b == 0));
+
+        let block = icy_metadata_block(&"ä".repeat(5000));
+        assert_eq!(block[0], 255);
+        assert_eq!(block.len(), 1 + 255 * 16);
+        assert!(std::str::from_utf8(&block[1..]).is_ok());
+    }
+
+    #[test]
+    fn icy_writer_inserts_metadata_every_metaint_bytes() {
+        let title = Arc::new(Mutex::new("x".to_string()));
+        let mut writer = IcyWriter::new(Vec::new(), Some(4), title.clone());
+        writer.write_all(&[1; 6]).unwrap();
+        writer.write_all(&[2; 2]).unwrap();
+        writer.write_all(&[3; 5]).unwrap();
+        *title.lock() = "y".to_string();
+        writer.write_all(&[4; 4]).unwrap();
+
+        let x = icy_metadata_block("x");
+        let y = icy_metadata_block("y");
+        let mut expected = vec![1; 4];
+        expected.extend_from_slice(&x);
+        expected.extend_from_slice(&[1, 1, 2, 2]);
+        // unchanged title, empty block
+        expected.push(0);
+        expected.extend_from_slice(&[3; 4]);
+        expected.push(0);
+        expected.extend_from_slice(&[3, 4, 4, 4]);
+        expected.extend_from_slice(&y);
+        expected.push(4);
+        assert_eq!(writer.inner, expected);
+
+        let mut writer = IcyWriter::new(Vec::new(), None, title);
+        writer.write_all(&vec![5; 20000]).unwrap();
+        assert_eq!(writer.inner.len(), 20000);
+    }
+
+    #[test]
+    fn metadata_header_is_case_insensitive() {
+        assert!(wants_metadata("GET / HTTP/1.1\r\nicy-metadata: 1\r\n\r\n"));
+        assert!(wants_metadata("GET / HTTP/1.1\r\nIcy-MetaData:1\r\n\r\n"));
+        assert!(!wants_metadata("GET / HTTP/1.1\r\nIcy-MetaData: 0\r\n\r\n"));
+        assert!(!wants_metadata("GET / HTTP/1.1\r\n\r\n"));
+    }
+
+    /// Connect a client to `serve_client` and return the status line it gets.
+    fn connect(
+        listener: &TcpListener,
+        clients: &Clients,
+        slots: &Arc<ClientSlots>,
+    ) -> (TcpStream, String) {
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (socket, _) = listener.accept().unwrap();
+        let (clients, slots) = (clients.clone(), slots.clone());
+        std::thread::spawn(move || {
+            let title = Arc::new(Mutex::new(String::new()));
+            serve_client(socket, Encoding::Wav, title, &clients, &slots).ok();
+        });
+        client.write_all(b"GET / HTTP/1.0\r\n\r\n").unwrap();
+        let mut status = Vec::new();
+        let mut byte = [0u8];
+        while !status.ends_with(b"\r\n") {
+            client.read_exact(&mut byte).unwrap();
+            status.push(byte[0]);
+        }
+        (client, String::from_utf8(status).unwrap())
+    }
+
+    #[test]
+    fn turns_away_clients_beyond_the_limit() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let slots = Arc::new(ClientSlots {
+            max: 2,
+            served: AtomicUsize::new(0),
+        });
+
+        let (_first, status) = connect(&listener, &clients, &slots);
+        assert_eq!(status, "HTTP/1.0 200 OK\r\n");
+        let (_second, status) = connect(&listener, &clients, &slots);
+        assert_eq!(status, "HTTP/1.0 200 OK\r\n");
+        let (_third, status) = connect(&listener, &clients, &slots);
+        assert_eq!(status, "HTTP/1.0 503 Service Unavailable\r\n");
+
+        // ending the served ones makes room again
+        let wait_for = |done: &dyn Fn() -> bool| {
+            let start = std::time::Instant::now();
+            while !done() {
+                assert!(start.elapsed() < Duration::from_secs(5));
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        };
+        wait_for(&|| clients.lock().len() == 2);
+        clients.lock().clear();
+        wait_for(&|| slots.served.load(Ordering::SeqCst) == 0);
+        let (_fourth, status) = connect(&listener, &clients, &slots);
+        assert_eq!(status, "HTTP/1.0 200 OK\r\n");
+    }
+}
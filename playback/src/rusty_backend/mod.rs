@@ -1,8 +1,13 @@
 #![cfg_attr(test, deny(missing_docs))]
 
 mod conversions;
+mod headless;
+mod http_stream;
+mod pcm_tap;
+mod pipe;
 #[allow(unused)]
 mod sink;
+mod spectrum_tap;
 mod stream;
 
 pub mod buffer;
@@ -18,10 +23,15 @@ pub use decoder::Symphonia;
 pub use sink::Sink;
 // use source::SeekableRequest;
 pub use source::Source;
-pub use stream::OutputStream;
+pub use stream::{output_device_names, OutputStream};
 
 use self::decoder::buffered_source::BufferedSource;
+use self::http_stream::HttpStream;
+use self::pcm_tap::PcmChunk;
+use self::pipe::{spawn_pipe_output, PipeConfig};
+use self::spectrum_tap::spawn_spectrum_output;
 
+use super::spectrum::SpectrumSender;
 use super::{PlayerCmd, PlayerProgress, PlayerTrait};
 use anyhow::Result;
 use std::path::Path;
@@ -29,15 +39,20 @@ use std::sync::atomic::{AtomicU16, Ordering};
 use std::sync::mpsc::RecvTimeoutError;
 // use std::sync::atomic::{AtomicUsize, Ordering};
 use parking_lot::Mutex;
-use std::io::Read;
-use std::str::FromStr;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::fs::File;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
 use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::Duration;
-use std::{fs::File, io::Cursor};
 use symphonia::core::io::{MediaSource, MediaSourceStream, MediaSourceStreamOptions};
+use termusic_stream::cache::StreamCache;
+use termusic_stream::recorder::Recorder;
+use termusic_stream::source::StreamState;
+use termusic_stream::timeshift::{Timeshift, TimeshiftProgress};
 use termusic_stream::StreamDownload;
-use termusiclib::config::Settings;
+use termusiclib::config::{AudioSink, ResampleQuality, Settings};
+use termusiclib::playlist::cue::CueSection;
+use termusiclib::podcast::sanitize_file_name;
 use termusiclib::track::{MediaType, Track};
 
 static VOLUME_STEP: u16 = 5;
@@ -47,23 +62,31 @@ pub type ArcTotalDuration = Arc<Mutex<TotalDuration>>;
 
 // #[allow(clippy::module_name_repetitions)]
 #[allow(unused)]
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub enum PlayerInternalCmd {
+    AbLoop(Option<(Duration, Duration)>),
     MessageOnEnd,
     Play(Box<Track>, bool),
     // PlayLocal(Box<File>, bool),
     // PlayPod(Box<dyn MediaSource>, bool, Duration),
     Progress(Duration),
-    QueueNext(String, bool),
+    QueueNext(Box<Track>, bool),
+    /// A streamed next track, buffered by the prefetch with the given id, see `prefetch_next`
+    QueuePrefetched(u64, Box<Track>, bool, StreamDownload),
     Resume,
     SeekAbsolute(Duration),
     SeekRelative(i64),
+    /// Catch up with the live edge of the radio playing
+    GoLive,
     Skip,
     Speed(i32),
     Stop,
     TogglePause,
     Volume(u16),
     Eos,
+    OutputDevice(String),
+    /// End the player thread, closing the output device
+    Quit,
 }
 pub struct RustyBackend {
     volume: Arc<AtomicU16>,
@@ -73,7 +96,18 @@ pub struct RustyBackend {
     pub position: Arc<Mutex<Duration>>,
     pub total_duration: ArcTotalDuration,
     pub radio_title: Arc<Mutex<String>>,
-    pub radio_downloaded: Arc<Mutex<u64>>,
+    /// Where the radio playing is behind its live edge
+    pub timeshift: Timeshift,
+    /// Records the radio playing, when turned on
+    pub recorder: Recorder,
+    /// How the connection of the stream playing is doing
+    pub stream_state: Arc<Mutex<StreamState>>,
+    /// Playing without a sound card, see [`AudioSink`]
+    headless: bool,
+    http_stream: Option<HttpStream>,
+    spectrum_tx: SpectrumSender,
+    /// The player loop, joined on drop so the output, like a WAV file, is closed by then
+    player_thread: Option<JoinHandle<()>>,
     // cmd_tx_outside: crate::PlayerCmdSender,
 }
 
@@ -93,6 +127,15 @@ impl RustyBackend {
         let volume_local = volume.clone();
         let speed = config.player_speed;
         let gapless = config.player_gapless;
+        let resample_quality = config.player_resample_quality;
+        let output_device = config.player_output_device.clone();
+        let prefetch_bytes = config.player_prefetch_kb * 1024;
+        let cache = crate::stream_cache(config);
+        let fade = Duration::from_millis(config.player_fade_ms);
+        let audio_sink = config.audio_sink_from_cli.clone();
+        let pipe = PipeConfig::from_settings(config);
+        let headless =
+            audio_sink != AudioSink::Device || pipe.as_ref().map_or(false, |p| p.exclusive);
         let position = Arc::new(Mutex::new(Duration::default()));
         let total_duration = Arc::new(Mutex::new(None));
         let total_duration_local = total_duration.clone();
@@ -100,10 +143,42 @@ impl RustyBackend {
         let pcmd_tx_local = cmd_tx;
         let radio_title = Arc::new(Mutex::new(String::new()));
         let radio_title_local = radio_title.clone();
-        let radio_downloaded = Arc::new(Mutex::new(100_u64));
-        let radio_downloaded_local = radio_downloaded.clone();
+        let timeshift = Timeshift::new(Duration::from_secs(
+            config.player_radio_timeshift_minutes * 60,
+        ));
+        let timeshift_local = timeshift.clone();
+        let recorder = Recorder::new(
+            termusiclib::utils::get_radio_record_path(config),
+            config.player_radio_record_skip_partial,
+            sanitize_file_name,
+        );
+        let recorder_local = recorder.clone();
+        let stream_state = Arc::new(Mutex::new(StreamState::default()));
+        let stream_state_local = stream_state.clone();
 
-        std::thread::Builder::new()
+        // outputs getting a copy of everything that is played
+        let mut pcm_taps = Vec::new();
+        if let Some(pipe) = &pipe {
+            pcm_taps.push(spawn_pipe_output(pipe));
+        }
+        let http_stream = if config.player_http_stream_enable {
+            match HttpStream::start(config) {
+                Ok((http_stream, tx)) => {
+                    pcm_taps.push(tx);
+                    Some(http_stream)
+                }
+                Err(e) => {
+                    error!("error starting http stream: {e:#}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let (spectrum_tx, _) = tokio::sync::broadcast::channel(4);
+        pcm_taps.push(spawn_spectrum_output(spectrum_tx.clone()));
+
+        let player_thread = std::thread::Builder::new()
             .name("playback player loop".into())
             .spawn(move || {
                 player_thread(
@@ -112,10 +187,20 @@ impl RustyBackend {
                     picmd_tx_local,
                     picmd_rx,
                     radio_title_local,
-                    radio_downloaded_local,
+                    timeshift_local,
+                    recorder_local,
+                    stream_state_local,
                     position_local,
                     volume_local,
                     speed,
+                    resample_quality,
+                    prefetch_bytes,
+                    cache,
+                    fade,
+                    &output_device,
+                    &audio_sink,
+                    pipe.as_ref(),
+                    &pcm_taps,
                 );
             })
             .expect("failed to spawn thread");
@@ -128,40 +213,23 @@ impl RustyBackend {
             command_tx: picmd_tx,
             position,
             radio_title,
-            radio_downloaded,
+            timeshift,
+            recorder,
+            stream_state,
+            headless,
+            http_stream,
+            spectrum_tx,
+            player_thread: Some(player_thread),
             // cmd_tx_outside: cmd_tx,
         }
     }
 
-    #[allow(clippy::needless_pass_by_value)]
     fn command(&self, cmd: PlayerInternalCmd) {
-        if let Err(e) = self.command_tx.send(cmd.clone()) {
-            error!("error in {cmd:?}: {e}");
+        if let Err(e) = self.command_tx.send(cmd) {
+            error!("error in {:?}: {e}", e.0);
         }
     }
 
-    fn cache_complete(url: &str) -> Result<Cursor<Vec<u8>>> {
-        let agent = reqwest::blocking::ClientBuilder::new()
-            .build()
-            .expect("build client error.");
-        let mut res = agent.get(url).send()?;
-        let mut len = 99;
-
-        if let Some(length) = res.headers().get(reqwest::header::CONTENT_LENGTH) {
-            let length = u64::from_str(length.to_str().map_err(|e| {
-                std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
-            })?)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
-            info!("Got content length {length}");
-            len = length;
-        } else {
-            warn!("Content length header missing");
-        }
-        let mut bytes: Vec<u8> = Vec::with_capacity(len as usize);
-        res.read_to_end(&mut bytes)?;
-        Ok(Cursor::new(bytes))
-    }
-
     #[allow(clippy::unused_async)]
     pub async fn enqueue(&mut self, item: &Track) {
         self.command(PlayerInternalCmd::Play(
@@ -198,8 +266,11 @@ impl RustyBackend {
         // }
     }
 
-    pub fn enqueue_next(&mut self, item: &str) {
-        self.command(PlayerInternalCmd::QueueNext(item.to_string(), self.gapless));
+    pub fn enqueue_next(&mut self, item: &Track) {
+        self.command(PlayerInternalCmd::QueueNext(
+            Box::new(item.clone()),
+            self.gapless,
+        ));
     }
 
     async fn play(&mut self, current_item: &Track) {
@@ -220,6 +291,15 @@ impl RustyBackend {
     }
 }
 
+impl Drop for RustyBackend {
+    fn drop(&mut self) {
+        self.command(PlayerInternalCmd::Quit);
+        if let Some(player_thread) = self.player_thread.take() {
+            player_thread.join().ok();
+        }
+    }
+}
+
 #[async_trait]
 impl PlayerTrait for RustyBackend {
     async fn add_and_play(&mut self, current_track: &Track) {
@@ -324,26 +404,98 @@ impl PlayerTrait for RustyBackend {
         self.skip_one();
     }
 
-    fn enqueue_next(&mut self, file: &str) {
-        self.enqueue_next(file);
+    fn enqueue_next(&mut self, track: &Track) {
+        self.enqueue_next(track);
+    }
+
+    fn output_devices(&self) -> Vec<String> {
+        if self.headless {
+            return Vec::new();
+        }
+        output_device_names()
+    }
+
+    fn set_output_device(&mut self, name: &str) {
+        self.command(PlayerInternalCmd::OutputDevice(name.to_string()));
+    }
+
+    fn set_now_playing(&mut self, track: &Track) {
+        if let Some(http_stream) = &self.http_stream {
+            let title = match (track.artist(), track.title()) {
+                (Some(artist), Some(title)) => format!("{artist} - {title}"),
+                (None, Some(title)) => title.to_string(),
+                _ => track.name().unwrap_or_default().to_string(),
+            };
+            http_stream.set_title(title);
+        }
+    }
+
+    fn spectrum_sender(&self) -> Option<SpectrumSender> {
+        Some(self.spectrum_tx.clone())
+    }
+
+    fn stream_state(&self) -> StreamState {
+        *self.stream_state.lock()
+    }
+
+    fn set_ab_loop(&mut self, range: Option<(Duration, Duration)>) -> bool {
+        self.command(PlayerInternalCmd::AbLoop(range));
+        true
+    }
+
+    fn radio_timeshift(&self) -> Option<TimeshiftProgress> {
+        self.timeshift.progress()
+    }
+
+    fn radio_go_live(&mut self) {
+        self.command(PlayerInternalCmd::GoLive);
+    }
+
+    fn toggle_radio_record(&mut self) -> bool {
+        self.recorder.toggle()
+    }
+
+    fn radio_recording(&self) -> bool {
+        self.recorder.is_recording()
     }
 }
 
 /// Append the `media_source` to the `sink`, while allowing different functions to run with `func`
-fn append_to_sink_inner<F: FnOnce(&Symphonia)>(
+///
+/// Only the part of it in `section` is played, for a track of a cue sheet.
+fn append_to_sink_inner<F: FnOnce(Option<Duration>)>(
     media_source: Box<dyn MediaSource>,
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    section: Option<&CueSection>,
     func: F,
 ) {
     let mss = MediaSourceStream::new(media_source, MediaSourceStreamOptions::default());
-    match Symphonia::new(mss, gapless) {
-        Ok(decoder) => {
-            func(&decoder);
-            sink.append(decoder);
+    let decoder = match Symphonia::new(mss, gapless) {
+        Ok(decoder) => decoder,
+        Err(e) => {
+            error!("error decoding '{trace}' is: {e:?}");
+            return;
+        }
+    };
+    let Some(section) = section else {
+        func(decoder.total_duration());
+        sink.append(decoder);
+        return;
+    };
+    let source = decoder.offset(section.start);
+    match section.end {
+        // the next track starts at the end, so stop there for it to be played
+        Some(end) => {
+            let source = source.take_duration(end.saturating_sub(section.start));
+            func(source.total_duration());
+            sink.append(source);
+        }
+        None => {
+            func(source.total_duration());
+            sink.append(source);
         }
-        Err(e) => error!("error decoding '{trace}' is: {e:?}"),
     }
 }
 
@@ -353,13 +505,11 @@ fn append_to_sink(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    section: Option<&CueSection>,
     total_duration_local: &ArcTotalDuration,
 ) {
-    append_to_sink_inner(media_source, trace, sink, gapless, |decoder| {
-        std::mem::swap(
-            &mut *total_duration_local.lock(),
-            &mut decoder.total_duration(),
-        );
+    append_to_sink_inner(media_source, trace, sink, gapless, section, |duration| {
+        *total_duration_local.lock() = duration;
     });
 }
 
@@ -371,7 +521,7 @@ fn append_to_sink_no_duration(
     gapless: bool,
     total_duration_local: &ArcTotalDuration,
 ) {
-    append_to_sink_inner(media_source, trace, sink, gapless, |_| {
+    append_to_sink_inner(media_source, trace, sink, gapless, None, |_| {
         // remove old stale duration
         total_duration_local.lock().take();
     });
@@ -385,16 +535,112 @@ fn append_to_sink_queue(
     trace: &str,
     sink: &Sink,
     gapless: bool,
+    section: Option<&CueSection>,
     // total_duration_local: &ArcTotalDuration,
     next_duration_opt: &mut Option<Duration>,
 ) {
-    append_to_sink_inner(media_source, trace, sink, gapless, |decoder| {
-        std::mem::swap(next_duration_opt, &mut decoder.total_duration());
+    append_to_sink_inner(media_source, trace, sink, gapless, section, |duration| {
+        *next_duration_opt = duration;
         // rely on EOS message to set next duration
         sink.message_on_end();
     });
 }
 
+/// How long a streamed next track may take to buffer, before it is queued anyway
+const PREFETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The url of `track` when it is streamed rather than played from a local file
+fn stream_url(track: &Track) -> Option<reqwest::Url> {
+    let file = track.file()?;
+    let streamed = match track.media_type {
+        Some(MediaType::LiveRadio) => true,
+        Some(MediaType::Podcast) => local_file(track) == Some(file),
+        Some(MediaType::Music) | None => !Path::new(file).exists(),
+    };
+    if !streamed {
+        return None;
+    }
+    file.parse::<reqwest::Url>()
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// The file `track` is played from, the download of a podcast episode if there is one
+fn local_file(track: &Track) -> Option<&str> {
+    if let Some(local_file) = &track.podcast_localfile {
+        if Path::new(local_file).exists() {
+            return Some(local_file);
+        }
+    }
+    track.file()
+}
+
+/// A client for a stream, with the HTTP settings the config has now.
+fn stream_client() -> reqwest::Client {
+    termusiclib::http::client_builder()
+        .build()
+        .unwrap_or_else(|e| {
+            error!("error building the http client, using the defaults: {e}");
+            reqwest::Client::new()
+        })
+}
+
+/// Open the stream of the next track and download its start in the background, then hand it
+/// back with [`PlayerInternalCmd::QueuePrefetched`] to be queued, so it plays without a gap.
+#[allow(clippy::too_many_arguments)]
+fn prefetch_next(
+    url: reqwest::Url,
+    track: Box<Track>,
+    gapless: bool,
+    id: u64,
+    prefetch_bytes: u64,
+    cache: Option<StreamCache>,
+    radio_title: Arc<Mutex<String>>,
+    timeshift: Timeshift,
+    recorder: Recorder,
+    stream_state: Arc<Mutex<StreamState>>,
+    picmd_tx: Sender<PlayerInternalCmd>,
+) {
+    let is_radio = track.media_type == Some(MediaType::LiveRadio);
+    let spawned = std::thread::Builder::new()
+        .name("rusty prefetch next".into())
+        .spawn(move || {
+            let reader = match StreamDownload::new_http(
+                url,
+                stream_client(),
+                is_radio,
+                radio_title,
+                timeshift,
+                recorder,
+                stream_state,
+                prefetch_bytes,
+                cache.as_ref(),
+            ) {
+                Ok(reader) => reader,
+                Err(e) => {
+                    error!("error prefetching the next track: {e}");
+                    return;
+                }
+            };
+            if !reader.wait_for_prefetch(PREFETCH_TIMEOUT) {
+                warn!("prefetching the next track is slow, queueing it anyway");
+            }
+            if reader.failed() {
+                // it is opened again when it is played, not queued to stall playback
+                error!("error prefetching the next track, its stream did not start");
+                return;
+            }
+            picmd_tx
+                .send(PlayerInternalCmd::QueuePrefetched(
+                    id, track, gapless, reader,
+                ))
+                .ok();
+        });
+    if let Err(e) = spawned {
+        error!("error spawning the prefetch thread: {e}");
+    }
+}
+
 /// Player thread loop
 #[allow(
     clippy::cast_precision_loss,
@@ -410,19 +656,43 @@ fn player_thread(
     picmd_tx: Sender<PlayerInternalCmd>,
     picmd_rx: Receiver<PlayerInternalCmd>,
     radio_title: Arc<Mutex<String>>,
-    radio_downloaded: Arc<Mutex<u64>>,
+    timeshift: Timeshift,
+    recorder: Recorder,
+    stream_state: Arc<Mutex<StreamState>>,
     position: Arc<Mutex<Duration>>,
     volume_inside: Arc<AtomicU16>,
     mut speed_inside: i32,
+    resample_quality: ResampleQuality,
+    prefetch_bytes: u64,
+    cache: Option<StreamCache>,
+    fade: Duration,
+    output_device: &str,
+    audio_sink: &AudioSink,
+    pipe: Option<&PipeConfig>,
+    pcm_taps: &[SyncSender<PcmChunk>],
 ) {
     let mut is_radio = false;
+    // the track currently playing, to start it again when switching output device
+    let mut current_track: Option<(Box<Track>, bool)> = None;
+    // the track queued after it, which becomes the current one at its Eos
+    let mut next_track: Option<(Box<Track>, bool)> = None;
 
     // option to store enqueued's duration
     // note that the current implementation is only meant to have 1 enqueued next after the current playing song
     let mut next_duration_opt = None;
-    let (_stream, handle) = OutputStream::try_default().unwrap();
+    // whether the enqueued one is a radio station, which has no duration
+    let mut next_is_radio = false;
+    // id of the latest prefetch of a streamed next track, the result of an older one is dropped
+    let mut prefetch_id: u64 = 0;
+    let (mut _stream, mut handle) =
+        OutputStream::try_from_sink(audio_sink, output_device, pipe).unwrap();
+    handle.set_resample_quality(resample_quality).unwrap();
+    for tx in pcm_taps {
+        handle.add_tap(tx.clone()).unwrap();
+    }
     let mut sink = Sink::try_new(&handle, picmd_tx.clone(), pcmd_tx.clone()).unwrap();
     sink.set_speed(speed_inside as f32 / 10.0);
+    sink.set_fade(fade);
     sink.set_volume(f32::from(volume_inside.load(Ordering::SeqCst)) / 100.0);
     loop {
         let cmd = match picmd_rx.recv_timeout(Duration::from_micros(100)) {
@@ -431,6 +701,19 @@ fn player_thread(
             Err(_) => continue,
         };
 
+        if let PlayerInternalCmd::Play(track, gapless) = &cmd {
+            current_track = Some((track.clone(), *gapless));
+            next_track = None;
+            // a loop on the previous track does not fit this one
+            sink.set_ab_loop(None);
+            *stream_state.lock() = StreamState::default();
+        }
+        if matches!(cmd, PlayerInternalCmd::Play(..) | PlayerInternalCmd::Stop) {
+            // a next track still buffering, and a recording, are of what played before
+            prefetch_id += 1;
+            recorder.stop();
+        }
+
         match cmd {
             // PlayerInternalCmd::PlayPod(stream, gapless, duration) => {
             //     append_to_sink(
@@ -462,6 +745,7 @@ fn player_thread(
                                 file_path,
                                 &sink,
                                 gapless,
+                                track.cue(),
                                 &total_duration,
                             ),
                             Err(e) => error!("error open file: {e}"),
@@ -481,9 +765,14 @@ fn player_thread(
 
                         match StreamDownload::new_http(
                             url,
+                            stream_client(),
                             false,
                             radio_title.clone(),
-                            radio_downloaded.clone(),
+                            timeshift.clone(),
+                            recorder.clone(),
+                            stream_state.clone(),
+                            prefetch_bytes,
+                            cache.as_ref(),
                         ) {
                             Ok(reader) => {
                                 append_to_sink(
@@ -491,6 +780,7 @@ fn player_thread(
                                     url_str,
                                     &sink,
                                     gapless,
+                                    None,
                                     &total_duration,
                                 );
                             }
@@ -515,9 +805,14 @@ fn player_thread(
 
                         match StreamDownload::new_http(
                             url,
+                            stream_client(),
                             true,
                             radio_title.clone(),
-                            radio_downloaded.clone(),
+                            timeshift.clone(),
+                            recorder.clone(),
+                            stream_state.clone(),
+                            prefetch_bytes,
+                            cache.as_ref(),
                         ) {
                             Ok(reader) => {
                                 append_to_sink_no_duration(
@@ -540,36 +835,93 @@ fn player_thread(
             PlayerInternalCmd::TogglePause => {
                 sink.toggle_playback();
             }
-            PlayerInternalCmd::QueueNext(url, gapless) => {
-                match File::open(Path::new(&url)) {
+            PlayerInternalCmd::AbLoop(range) => {
+                sink.set_ab_loop(range);
+            }
+            PlayerInternalCmd::QueueNext(track, gapless) => {
+                prefetch_id += 1;
+                if let Some(url) = stream_url(&track) {
+                    prefetch_next(
+                        url,
+                        track,
+                        gapless,
+                        prefetch_id,
+                        prefetch_bytes,
+                        cache.clone(),
+                        radio_title.clone(),
+                        timeshift.clone(),
+                        recorder.clone(),
+                        stream_state.clone(),
+                        picmd_tx.clone(),
+                    );
+                    continue;
+                }
+                let Some(file_path) = local_file(&track) else {
+                    continue;
+                };
+                match File::open(Path::new(file_path)) {
                     Ok(file) => {
                         append_to_sink_queue(
                             Box::new(BufferedSource::new_default_size(file)),
-                            &url,
+                            file_path,
                             &sink,
                             gapless,
+                            track.cue(),
                             &mut next_duration_opt,
                         );
-                    }
-
-                    Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {
-                        if let Ok(cursor) = RustyBackend::cache_complete(&url) {
-                            // TODO: replace "trace" param once knowing what to set for trace
-                            append_to_sink_queue(
-                                Box::new(cursor),
-                                // maybe there is a better trace point?
-                                "QueueNext Error cache_complete",
-                                &sink,
-                                gapless,
-                                &mut next_duration_opt,
-                            );
-                        }
+                        next_is_radio = false;
+                        next_track = Some((track, gapless));
                     }
                     Err(e) => {
-                        error!("error is now: {e:?}");
+                        error!("error open file: {e}");
                     }
                 }
             }
+            PlayerInternalCmd::QueuePrefetched(id, track, gapless, reader) => {
+                if id != prefetch_id {
+                    debug!("dropping the stale prefetch of {:?}", track.file());
+                    continue;
+                }
+                let trace = track.file().unwrap_or("prefetched stream");
+                let queued_radio = track.media_type == Some(MediaType::LiveRadio);
+                if sink.empty() {
+                    // the current track ended before the next one was buffered, so start it now,
+                    // the Eos of the ended one already moved the playlist on
+                    is_radio = queued_radio;
+                    next_duration_opt = None;
+                    if queued_radio {
+                        append_to_sink_no_duration(
+                            Box::new(reader),
+                            trace,
+                            &sink,
+                            gapless,
+                            &total_duration,
+                        );
+                    } else {
+                        append_to_sink(
+                            Box::new(reader),
+                            trace,
+                            &sink,
+                            gapless,
+                            None,
+                            &total_duration,
+                        );
+                    }
+                    sink.message_on_end();
+                    current_track = Some((track, gapless));
+                } else {
+                    append_to_sink_queue(
+                        Box::new(reader),
+                        trace,
+                        &sink,
+                        gapless,
+                        None,
+                        &mut next_duration_opt,
+                    );
+                    next_is_radio = queued_radio;
+                    next_track = Some((track, gapless));
+                }
+            }
             PlayerInternalCmd::Resume => {
                 sink.play();
             }
@@ -578,8 +930,11 @@ fn player_thread(
                 sink.set_speed(speed_inside as f32 / 10.0);
             }
             PlayerInternalCmd::Stop => {
+                current_track = None;
+                next_track = None;
                 sink = Sink::try_new(&handle, picmd_tx.clone(), pcmd_tx.clone()).unwrap();
                 sink.set_speed(speed_inside as f32 / 10.0);
+                sink.set_fade(fade);
                 sink.set_volume(f32::from(volume_inside.load(Ordering::SeqCst)) / 100.0);
             }
             PlayerInternalCmd::Volume(volume) => {
@@ -587,6 +942,8 @@ fn player_thread(
                 volume_inside.store(volume, Ordering::SeqCst);
             }
             PlayerInternalCmd::Skip => {
+                // the loop is for the track skipped away from
+                sink.set_ab_loop(None);
                 sink.skip_one();
                 if sink.is_paused() {
                     sink.play();
@@ -619,11 +976,18 @@ fn player_thread(
                 sink.message_on_end();
             }
 
+            PlayerInternalCmd::SeekRelative(offset) if is_radio => {
+                if !timeshift.seek(offset) {
+                    info!("not seeking in the radio, its bitrate is not known yet");
+                }
+            }
+            PlayerInternalCmd::GoLive => {
+                if is_radio && !timeshift.go_live() {
+                    info!("not going live, the bitrate of the radio is not known yet");
+                }
+            }
             PlayerInternalCmd::SeekRelative(offset) => {
                 let paused = sink.is_paused();
-                if paused {
-                    sink.set_volume(0.0);
-                }
                 if offset.is_positive() {
                     let new_pos = sink.elapsed().as_secs() + offset as u64;
                     if let Some(d) = *total_duration.lock() {
@@ -638,19 +1002,77 @@ fn player_thread(
                         .saturating_sub(offset.unsigned_abs());
                     sink.seek(Duration::from_secs(new_pos));
                 }
+                // seeking resumes, the sound stays faded out until it is paused again
                 if paused {
-                    std::thread::sleep(std::time::Duration::from_millis(50));
                     sink.pause();
-                    sink.set_volume(f32::from(volume_inside.load(Ordering::SeqCst)) / 100.0);
                 }
             }
 
             PlayerInternalCmd::Eos => {
                 // replace the current total_duration with the next one
                 // this is only present when QueueNext was used; which is only used if gapless is enabled
-                if next_duration_opt.is_some() {
+                if next_is_radio {
+                    is_radio = true;
+                    next_is_radio = false;
+                    total_duration.lock().take();
+                } else if next_duration_opt.is_some() {
+                    is_radio = false;
                     *total_duration.lock() = next_duration_opt;
                 }
+                if let Some(track) = next_track.take() {
+                    current_track = Some(track);
+                }
+            }
+
+            PlayerInternalCmd::Quit => {
+                sink.stop();
+                break;
+            }
+
+            PlayerInternalCmd::OutputDevice(name) => {
+                if *audio_sink != AudioSink::Device || pipe.map_or(false, |p| p.exclusive) {
+                    warn!("not switching output device to \"{name}\", playing without one");
+                    continue;
+                }
+                let (new_stream, new_handle) =
+                    match OutputStream::try_from_sink(audio_sink, &name, pipe) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("error opening output device \"{name}\": {e}");
+                            continue;
+                        }
+                    };
+                if let Err(e) = new_handle.set_resample_quality(resample_quality) {
+                    error!("error setting resample quality: {e}");
+                }
+                for tx in pcm_taps {
+                    if let Err(e) = new_handle.add_tap(tx.clone()) {
+                        error!("error attaching output to the new device: {e}");
+                    }
+                }
+
+                let elapsed = sink.elapsed();
+                let paused = sink.is_paused();
+                let ab_loop = sink.ab_loop();
+                sink.stop();
+                // replacing the stream drops the old one, which closes the previous device
+                (_stream, handle) = (new_stream, new_handle);
+                sink = Sink::try_new(&handle, picmd_tx.clone(), pcmd_tx.clone()).unwrap();
+                sink.set_speed(speed_inside as f32 / 10.0);
+                sink.set_fade(fade);
+                sink.set_volume(f32::from(volume_inside.load(Ordering::SeqCst)) / 100.0);
+                next_duration_opt = None;
+                next_track = None;
+
+                // the queue went away with the old stream, so start the current track again
+                if let Some((track, gapless)) = current_track.clone() {
+                    picmd_tx.send(PlayerInternalCmd::Play(track, gapless)).ok();
+                    picmd_tx.send(PlayerInternalCmd::SeekAbsolute(elapsed)).ok();
+                    picmd_tx.send(PlayerInternalCmd::AbLoop(ab_loop)).ok();
+                    if paused {
+                        picmd_tx.send(PlayerInternalCmd::TogglePause).ok();
+                    }
+                }
             }
         }
     }
@@ -0,0 +1,199 @@
+//! Copies of the mixer output for the outputs that are not the sound card.
+
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+use std::time::Duration;
+
+use super::source::{Source, UniformSourceIterator};
+use termusiclib::config::ResampleQuality;
+
+/// A piece of the mixer output, with the format it was played in.
+#[derive(Debug)]
+pub struct PcmChunk {
+    /// Channel count of `samples`
+    pub channels: u16,
+    /// Sample rate of `samples`
+    pub sample_rate: u32,
+    /// Interleaved samples, always whole frames
+    pub samples: Vec<f32>,
+}
+
+/// Collects the samples played by the mixer and sends them in chunks of 10ms.
+///
+/// This never blocks, chunks are dropped when the receiver falls behind.
+pub struct PcmTap {
+    tx: SyncSender<PcmChunk>,
+    buf: Vec<f32>,
+    chunk_len: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl PcmTap {
+    /// Tap for a mixer playing with the given channel count and rate.
+    pub fn new(tx: SyncSender<PcmChunk>, channels: u16, sample_rate: u32) -> Self {
+        let chunk_len = (sample_rate / 100).max(1) as usize * usize::from(channels);
+        Self {
+            tx,
+            buf: Vec::with_capacity(chunk_len),
+            chunk_len,
+            channels,
+            sample_rate,
+        }
+    }
+
+    /// Add the next interleaved sample, returns `false` once the receiver is gone.
+    #[inline]
+    pub fn push(&mut self, sample: f32) -> bool {
+        self.buf.push(sample);
+        if self.buf.len() < self.chunk_len {
+            return true;
+        }
+
+        let chunk = PcmChunk {
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            samples: std::mem::replace(&mut self.buf, Vec::with_capacity(self.chunk_len)),
+        };
+        match self.tx.try_send(chunk) {
+            Ok(()) => true,
+            Err(TrySendError::Full(chunk)) => {
+                self.buf = chunk.samples;
+                self.buf.clear();
+                true
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+/// Turn the chunks of a [`PcmTap`] into samples with the given channel count and rate.
+///
+/// This blocks waiting for chunks and ends once every tap sending to `rx` is gone.
+/// `None` if that happens before the first chunk.
+pub fn converted_pcm(
+    rx: Receiver<PcmChunk>,
+    channels: u16,
+    sample_rate: u32,
+    resample_quality: ResampleQuality,
+) -> Option<impl Iterator<Item = f32>> {
+    let first = rx.recv().ok()?;
+    let source = ChunkSource {
+        channels: first.channels,
+        sample_rate: first.sample_rate,
+        chunk: first.samples.into_iter(),
+        rx,
+    };
+
+    Some(UniformSourceIterator::<_, f32>::new(
+        source,
+        channels,
+        sample_rate,
+        resample_quality,
+    ))
+}
+
+/// The chunks of a [`PcmTap`] as a source, each chunk is a frame so the format can change.
+struct ChunkSource {
+    rx: Receiver<PcmChunk>,
+    chunk: std::vec::IntoIter<f32>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Iterator for ChunkSource {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.chunk.next()?;
+        // fetch the next chunk right away, so its format is known when this frame ends
+        if self.chunk.len() == 0 {
+            if let Ok(chunk) = self.rx.recv() {
+                self.channels = chunk.channels;
+                self.sample_rate = chunk.sample_rate;
+                self.chunk = chunk.samples.into_iter();
+            }
+        }
+        Some(sample)
+    }
+}
+
+impl Source for ChunkSource {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.chunk.len())
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    #[inline]
+    fn elapsed(&mut self) -> Duration {
+        Duration::from_secs(0)
+    }
+
+    fn seek(&mut self, _time: Duration) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn tap_drops_chunks_when_full_and_keeps_frames_whole() {
+        let (tx, rx) = mpsc::sync_channel(1);
+        // 100Hz stereo makes chunks of one frame
+        let mut tap = PcmTap::new(tx, 2, 100);
+        for i in 0..6u8 {
+            assert!(tap.push(f32::from(i)));
+        }
+
+        let chunk = rx.try_recv().unwrap();
+        assert_eq!((chunk.channels, chunk.sample_rate), (2, 100));
+        assert_eq!(chunk.samples, vec![0.0, 1.0]);
+        assert!(rx.try_recv().is_err());
+        assert!(tap.buf.is_empty());
+
+        drop(rx);
+        tap.push(0.0);
+        assert!(!tap.push(0.0));
+    }
+
+    #[test]
+    fn converted_pcm_follows_format_changes() {
+        let (tx, rx) = mpsc::sync_channel(4);
+        tx.send(PcmChunk {
+            channels: 2,
+            sample_rate: 100,
+            samples: vec![0.25, 0.25, 0.5, 0.5],
+        })
+        .unwrap();
+        tx.send(PcmChunk {
+            channels: 1,
+            sample_rate: 100,
+            samples: vec![0.75],
+        })
+        .unwrap();
+        drop(tx);
+
+        let samples: Vec<f32> = converted_pcm(rx, 1, 100, ResampleQuality::Linear)
+            .unwrap()
+            .collect();
+        assert_eq!(samples, vec![0.25, 0.5, 0.75]);
+    }
+}
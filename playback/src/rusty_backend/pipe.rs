@@ -0,0 +1,227 @@
+//! Raw PCM output to a FIFO or stdout, for example for a snapcast server to read.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::time::{Duration, Instant};
+
+use super::pcm_tap::{converted_pcm, PcmChunk};
+use termusiclib::config::{PipeSampleFormat, ResampleQuality, Settings};
+
+/// Chunks of 10ms the mixer may be ahead of the pipe thread before chunks get dropped.
+const PIPE_QUEUE_CHUNKS: usize = 50;
+/// Encoded bytes kept for a slow reader before chunks get dropped.
+const PIPE_PENDING_LIMIT: usize = 1 << 18;
+/// How often to try opening the FIFO again while there is no reader.
+const PIPE_REOPEN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to write to the pipe, from the `player_pipe_*` settings.
+#[derive(Clone, Debug)]
+pub struct PipeConfig {
+    /// Path of the FIFO, "-" for stdout
+    pub path: String,
+    /// Sample format written
+    pub format: PipeSampleFormat,
+    /// Channel count written
+    pub channels: u16,
+    /// Sample rate written
+    pub sample_rate: u32,
+    /// Write only to the pipe, without an audio output device
+    pub exclusive: bool,
+    /// Quality of the conversion from the output device rate
+    pub resample_quality: ResampleQuality,
+}
+
+impl PipeConfig {
+    /// `None` if no pipe is configured.
+    #[must_use]
+    pub fn from_settings(config: &Settings) -> Option<Self> {
+        if config.player_pipe_path.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            path: config.player_pipe_path.clone(),
+            format: config.player_pipe_format,
+            channels: config.player_pipe_channels.max(1),
+            sample_rate: config.player_pipe_sample_rate.max(1),
+            exclusive: config.player_pipe_only,
+            resample_quality: config.player_resample_quality,
+        })
+    }
+}
+
+/// Start the thread writing to the pipe and return the sender for the taps feeding it.
+///
+/// The thread converts the samples to the configured format and ends once every tap is gone.
+pub fn spawn_pipe_output(config: &PipeConfig) -> SyncSender<PcmChunk> {
+    let (tx, rx) = mpsc::sync_channel(PIPE_QUEUE_CHUNKS);
+    let config = config.clone();
+
+    if let Err(e) = std::thread::Builder::new()
+        .name("pipe audio output".into())
+        .spawn(move || pipe_thread(&config, rx))
+    {
+        error!("error starting pipe output: {e}");
+    }
+
+    tx
+}
+
+fn pipe_thread(config: &PipeConfig, rx: Receiver<PcmChunk>) {
+    let Some(mut converted) = converted_pcm(
+        rx,
+        config.channels,
+        config.sample_rate,
+        config.resample_quality,
+    ) else {
+        return;
+    };
+    let chunk_len = (config.sample_rate / 100).max(1) as usize * usize::from(config.channels);
+    let mut samples = Vec::with_capacity(chunk_len);
+    let mut writer = PipeWriter::new(&config.path);
+
+    loop {
+        samples.clear();
+        samples.extend(converted.by_ref().take(chunk_len));
+        // the taps are gone together with the player
+        if samples.is_empty() {
+            break;
+        }
+        writer.write(&samples, config.format);
+    }
+}
+
+/// Writes encoded samples to the FIFO or stdout, (re)opening the FIFO when a reader shows up.
+struct PipeWriter {
+    path: String,
+    out: Option<Box<dyn Write + Send>>,
+    pending: Vec<u8>,
+    last_open: Option<Instant>,
+    open_error_reported: bool,
+}
+
+impl PipeWriter {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            out: None,
+            pending: Vec::new(),
+            last_open: None,
+            open_error_reported: false,
+        }
+    }
+
+    fn write(&mut self, samples: &[f32], format: PipeSampleFormat) {
+        if self.out.is_none() {
+            self.open();
+        }
+        // no reader, the samples are discarded to keep up with the player
+        let Some(out) = &mut self.out else {
+            return;
+        };
+
+        // a reader that cannot keep up loses whole chunks, never part of a frame
+        if self.pending.len() < PIPE_PENDING_LIMIT {
+            encode_samples(samples, format, &mut self.pending);
+        }
+
+        while !self.pending.is_empty() {
+            match out.write(&self.pending) {
+                Ok(0) => break,
+                Ok(n) => {
+                    self.pending.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    info!("pipe output \"{}\" closed: {e}", self.path);
+                    self.out = None;
+                    self.pending.clear();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn open(&mut self) {
+        if self
+            .last_open
+            .map_or(false, |last| last.elapsed() < PIPE_REOPEN_INTERVAL)
+        {
+            return;
+        }
+        self.last_open = Some(Instant::now());
+
+        if self.path == "-" {
+            self.out = Some(Box::new(io::stdout()));
+            return;
+        }
+
+        match open_fifo(&self.path) {
+            Ok(file) => {
+                info!("pipe output \"{}\" opened", self.path);
+                self.out = Some(Box::new(file));
+                self.open_error_reported = false;
+            }
+            Err(e) => {
+                // a FIFO without a reader fails to open until one shows up, do not flood the log
+                if !self.open_error_reported {
+                    warn!("pipe output \"{}\" not opened: {e}", self.path);
+                    self.open_error_reported = true;
+                }
+            }
+        }
+    }
+}
+
+/// Open the FIFO for writing without waiting for a reader.
+#[cfg(unix)]
+fn open_fifo(path: &str) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new()
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(path)
+}
+
+/// Open the pipe for writing.
+#[cfg(not(unix))]
+fn open_fifo(path: &str) -> io::Result<File> {
+    OpenOptions::new().write(true).open(path)
+}
+
+/// Append `samples` to `out` in the given format, clamped to the range of the format.
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode_samples(samples: &[f32], format: PipeSampleFormat, out: &mut Vec<u8>) {
+    for sample in samples {
+        let sample = sample.clamp(-1.0, 1.0);
+        match format {
+            PipeSampleFormat::S16Le => {
+                out.extend_from_slice(&((sample * f32::from(i16::MAX)) as i16).to_le_bytes());
+            }
+            PipeSampleFormat::S32Le => {
+                let value = f64::from(sample) * f64::from(i32::MAX);
+                out.extend_from_slice(&(value as i32).to_le_bytes());
+            }
+            PipeSampleFormat::F32Le => out.extend_from_slice(&sample.to_le_bytes()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_clamps_and_scales() {
+        let mut out = Vec::new();
+        encode_samples(&[0.0, 1.0, -2.0], PipeSampleFormat::S16Le, &mut out);
+        assert_eq!(out, [0, 0, 0xff, 0x7f, 0x01, 0x80]);
+
+        out.clear();
+        encode_samples(&[0.5], PipeSampleFormat::F32Le, &mut out);
+        assert_eq!(out, 0.5f32.to_le_bytes());
+    }
+}
@@ -32,6 +32,8 @@ struct Controls {
     stopped: AtomicBool,
     speed: Mutex<f32>,
     to_clear: Mutex<u32>,
+    ab_loop: Mutex<Option<(Duration, Duration)>>,
+    fade: Mutex<Duration>,
 }
 
 impl Sink {
@@ -66,6 +68,8 @@ impl Sink {
                 seek: Mutex::new(None),
                 speed: Mutex::new(1.0),
                 to_clear: Mutex::new(0),
+                ab_loop: Mutex::new(None),
+                fade: Mutex::new(Duration::ZERO),
             }),
             sound_count: Arc::new(AtomicUsize::new(0)),
             detached: false,
@@ -100,9 +104,11 @@ impl Sink {
         let tx = self.message_tx.clone();
         let elapsed = self.elapsed.clone();
         let source = source
+            .ab_loop()
             .speed(1.0)
             .pausable(false)
             .amplify(1.0)
+            .fade(*self.controls.fade.lock())
             .skippable()
             .stoppable()
             .periodic_access(Duration::from_millis(500), move |src| {
@@ -110,27 +116,44 @@ impl Sink {
             })
             .periodic_access(Duration::from_millis(5), move |src| {
                 let src = src.inner_mut();
+                let fade_duration = *controls.fade.lock();
                 if controls.stopped.load(Ordering::SeqCst) {
-                    src.stop();
+                    let fade = src.inner_mut().inner_mut();
+                    fade.set_duration(fade_duration);
+                    fade.fade_out();
+                    if fade.is_silent() {
+                        src.stop();
+                    }
                 } else {
-                    if let Some(seek_time) = controls.seek.lock().take() {
-                        src.seek(seek_time);
+                    let paused = controls.pause.load(Ordering::SeqCst);
+                    let skipping = *controls.to_clear.lock() > 0;
+                    let seeking = controls.seek.lock().is_some();
+                    // pausing, skipping and seeking wait for the sound to fade out
+                    let fade = src.inner_mut().inner_mut();
+                    fade.set_duration(fade_duration);
+                    if paused || skipping || seeking {
+                        fade.fade_out();
+                    } else {
+                        fade.fade_in();
                     }
-                    *elapsed.write() = src.elapsed();
-                    {
+                    let silent = fade.is_silent();
+                    if silent {
+                        if let Some(seek_time) = controls.seek.lock().take() {
+                            src.seek(seek_time);
+                        }
                         let mut to_clear = controls.to_clear.lock();
                         if *to_clear > 0 {
                             src.inner_mut().skip();
                             *to_clear -= 1;
                         }
                     }
-                    let amp = src.inner_mut().inner_mut();
+                    *elapsed.write() = src.elapsed();
+                    let amp = src.inner_mut().inner_mut().inner_mut();
                     amp.set_factor(*controls.volume.lock());
-                    amp.inner_mut()
-                        .set_paused(controls.pause.load(Ordering::SeqCst));
-                    amp.inner_mut()
-                        .inner_mut()
-                        .set_factor(*controls.speed.lock());
+                    amp.inner_mut().set_paused(paused && silent);
+                    let speed = amp.inner_mut().inner_mut();
+                    speed.set_factor(*controls.speed.lock());
+                    speed.inner_mut().set_range(*controls.ab_loop.lock());
                     start_played.store(true, Ordering::SeqCst);
                 }
             })
@@ -202,11 +225,27 @@ impl Sink {
     }
 
     pub fn seek(&self, seek_time: Duration) {
+        // asked for first, so the sound stays faded out from resuming until the seek is done
+        *self.controls.seek.lock() = Some(seek_time);
         if self.is_paused() {
             self.play();
         }
-        *self.controls.seek.lock() = Some(seek_time);
     }
+    /// Ramp the volume over `duration` on pause, resume, stop, skip and seek, zero to cut hard.
+    pub fn set_fade(&self, duration: Duration) {
+        *self.controls.fade.lock() = duration;
+    }
+
+    /// Loop the playing source between the two positions, or play on with `None`.
+    pub fn set_ab_loop(&self, range: Option<(Duration, Duration)>) {
+        *self.controls.ab_loop.lock() = range;
+    }
+
+    /// The section the playing source loops, if any.
+    pub fn ab_loop(&self) -> Option<(Duration, Duration)> {
+        *self.controls.ab_loop.lock()
+    }
+
     /// Toggles playback of the sink
     pub fn toggle_playback(&self) {
         if self.is_paused() {
@@ -283,10 +322,15 @@ impl Sink {
         if let Some(sleep_until_end) = self.sleep_until_end.lock().take() {
             let cmd_tx = self.cmd_tx.clone();
             let message_tx = self.message_tx.clone();
+            let controls = self.controls.clone();
             std::thread::Builder::new()
                 .name("rusty message_on_end".into())
                 .spawn(move || {
                     let _drop = sleep_until_end.recv();
+                    // a stopped sink, like that of a replaced backend, did not play to the end
+                    if controls.stopped.load(Ordering::SeqCst) {
+                        return;
+                    }
                     if let Err(e) = cmd_tx.send(PlayerCmd::Eos) {
                         error!("Error in message_on_end: {e}");
                     }
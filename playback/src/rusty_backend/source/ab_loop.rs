@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use super::{Sample, Source};
+
+/// Internal function that builds a `AbLoop` object.
+pub const fn ab_loop<I>(input: I) -> AbLoop<I> {
+    AbLoop {
+        input,
+        range: None,
+        frame: 0,
+        channel: 0,
+        to_skip: 0,
+    }
+}
+
+/// Filter that seeks back to A whenever the frame at B is reached.
+///
+/// The position is counted in frames from the last seek, so the loop is sample-accurate as long
+/// as the inner source reports where a seek actually landed.
+#[derive(Clone, Debug)]
+pub struct AbLoop<I> {
+    input: I,
+    /// A and B in frames
+    range: Option<(u64, u64)>,
+    /// Frame the next sample belongs to
+    frame: u64,
+    /// Channel of the next sample
+    channel: u16,
+    /// Frames to drop after a seek landed before where it should have
+    to_skip: u64,
+}
+
+#[allow(unused)]
+impl<I> AbLoop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Loop between A and B, or play on with `None`.
+    #[inline]
+    pub fn set_range(&mut self, range: Option<(Duration, Duration)>) {
+        let rate = self.input.sample_rate();
+        self.range = range
+            .map(|(a, b)| (to_frames(a, rate), to_frames(b, rate)))
+            .filter(|(a, b)| a < b);
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Take the next sample of the inner source, keeping track of the frame.
+    #[inline]
+    fn next_counted(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        self.channel += 1;
+        if self.channel >= self.input.channels() {
+            self.channel = 0;
+            self.frame += 1;
+        }
+        Some(sample)
+    }
+}
+
+impl<I> Iterator for AbLoop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            if let Some((a, b)) = self.range {
+                if self.frame >= b {
+                    let rate = self.input.sample_rate();
+                    if self.seek(from_frames(a, rate)).is_none() {
+                        // the source cannot go back, play on
+                        self.range = None;
+                    }
+                }
+            }
+            while self.to_skip > 0 {
+                for _ in 0..self.input.channels() {
+                    self.next_counted()?;
+                }
+                self.to_skip -= 1;
+            }
+        }
+        self.next_counted()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for AbLoop<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn elapsed(&mut self) -> Duration {
+        self.input.elapsed()
+    }
+
+    #[inline]
+    fn seek(&mut self, time: Duration) -> Option<Duration> {
+        let landed = self.input.seek(time)?;
+        let rate = self.input.sample_rate();
+        self.frame = to_frames(landed, rate);
+        self.channel = 0;
+        self.to_skip = to_frames(time, rate).saturating_sub(self.frame);
+        Some(time)
+    }
+}
+
+/// The frame closest to `time`, so that a time from [`from_frames`] converts back exactly.
+#[allow(clippy::cast_possible_truncation)]
+pub(super) fn to_frames(time: Duration, sample_rate: u32) -> u64 {
+    ((time.as_nanos() * u128::from(sample_rate) + 500_000_000) / 1_000_000_000) as u64
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub(super) fn from_frames(frames: u64, sample_rate: u32) -> Duration {
+    Duration::from_nanos(
+        (u128::from(frames) * 1_000_000_000 / u128::from(sample_rate.max(1))) as u64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every sample is the number of its frame, seeking lands on the start of a packet.
+    struct Packets {
+        channels: u16,
+        frame: u64,
+        channel: u16,
+        frames: u64,
+        packet_len: u64,
+    }
+
+    impl Iterator for Packets {
+        type Item = f32;
+
+        #[allow(clippy::cast_precision_loss)]
+        fn next(&mut self) -> Option<f32> {
+            if self.frame >= self.frames {
+                return None;
+            }
+            let sample = self.frame as f32;
+            self.channel += 1;
+            if self.channel == self.channels {
+                self.channel = 0;
+                self.frame += 1;
+            }
+            Some(sample)
+        }
+    }
+
+    impl Source for Packets {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            self.channels
+        }
+
+        fn sample_rate(&self) -> u32 {
+            1000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+
+        fn elapsed(&mut self) -> Duration {
+            from_frames(self.frame, 1000)
+        }
+
+        fn seek(&mut self, time: Duration) -> Option<Duration> {
+            let frame = to_frames(time, 1000) / self.packet_len * self.packet_len;
+            self.frame = frame;
+            self.channel = 0;
+            Some(from_frames(frame, 1000))
+        }
+    }
+
+    fn packets(channels: u16, frames: u64) -> Packets {
+        Packets {
+            channels,
+            frame: 0,
+            channel: 0,
+            frames,
+            packet_len: 64,
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn loops_sample_accurate() {
+        for channels in [1, 2] {
+            let mut source = ab_loop(packets(channels, 10_000));
+            source.set_range(Some((
+                Duration::from_millis(1234),
+                Duration::from_millis(1300),
+            )));
+            let samples: Vec<f32> = source
+                .by_ref()
+                .take((1300 + 66 * 3) * usize::from(channels))
+                .collect();
+            let frames: Vec<u64> = samples
+                .chunks(usize::from(channels))
+                .map(|frame| frame[0] as u64)
+                .collect();
+
+            let mut expected: Vec<u64> = (0..1300).collect();
+            for _ in 0..3 {
+                expected.extend(1234..1300);
+            }
+            assert_eq!(frames, expected, "{channels} channels");
+
+            // playing on from where it is
+            source.set_range(None);
+            assert_eq!(source.next(), Some(1300.0));
+        }
+    }
+
+    #[test]
+    fn seeking_lands_exactly() {
+        let mut source = ab_loop(packets(2, 10_000));
+        assert_eq!(
+            source.seek(Duration::from_millis(500)),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(source.next(), Some(500.0));
+        assert_eq!(source.next(), Some(500.0));
+        assert_eq!(source.next(), Some(501.0));
+
+        // no loop with B before A
+        source.set_range(Some((
+            Duration::from_millis(600),
+            Duration::from_millis(550),
+        )));
+        assert_eq!(source.nth(2 * 100), Some(601.0));
+    }
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use super::ab_loop::to_frames;
+use super::{Sample, Source};
+
+/// Internal function that builds a `Fade` object.
+pub fn fade<I>(input: I, duration: Duration) -> Fade<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    Fade {
+        input,
+        duration,
+        gain: 1.0,
+        target: 1.0,
+        channel: 0,
+    }
+}
+
+/// Filter that ramps the volume to silence or back to full over a time period, so pausing,
+/// seeking and the like do not cut the sound off hard.
+///
+/// The sound starts at full volume, it only fades once told so with [`Fade::fade_out`] or
+/// [`Fade::fade_in`].
+#[derive(Clone, Debug)]
+pub struct Fade<I> {
+    input: I,
+    /// How long a fade from full volume to silence takes
+    duration: Duration,
+    gain: f32,
+    /// What `gain` moves to, a frame at a time
+    target: f32,
+    /// Channel of the next sample, the gain only changes between frames
+    channel: u16,
+}
+
+#[allow(unused)]
+impl<I> Fade<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Changes how long fades take, from the next one on.
+    #[inline]
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    /// Fade to silence, at once if the duration is zero.
+    #[inline]
+    pub fn fade_out(&mut self) {
+        self.fade_to(0.0);
+    }
+
+    /// Fade back to full volume, at once if the duration is zero.
+    #[inline]
+    pub fn fade_in(&mut self) {
+        self.fade_to(1.0);
+    }
+
+    /// Whether a fade out is done.
+    #[inline]
+    pub fn is_silent(&self) -> bool {
+        self.gain <= 0.0
+    }
+
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    #[inline]
+    fn fade_to(&mut self, target: f32) {
+        self.target = target;
+        if self.duration.is_zero() {
+            self.gain = target;
+        }
+    }
+
+    /// Move the gain a frame's worth towards the target.
+    #[allow(clippy::cast_precision_loss)]
+    fn step(&mut self) {
+        let frames = to_frames(self.duration, self.input.sample_rate()).max(1);
+        let step = 1.0 / frames as f32;
+        self.gain = if self.gain < self.target {
+            (self.gain + step).min(self.target)
+        } else {
+            (self.gain - step).max(self.target)
+        };
+    }
+}
+
+impl<I> Iterator for Fade<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    #[allow(clippy::float_cmp)]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 && self.gain != self.target {
+            self.step();
+        }
+        let sample = self.input.next()?;
+        self.channel += 1;
+        if self.channel >= self.input.channels() {
+            self.channel = 0;
+        }
+
+        if self.gain >= 1.0 {
+            Some(sample)
+        } else {
+            Some(sample.amplify(self.gain))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Fade<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+
+    #[inline]
+    fn elapsed(&mut self) -> Duration {
+        self.input.elapsed()
+    }
+
+    #[inline]
+    fn seek(&mut self, time: Duration) -> Option<Duration> {
+        self.input.seek(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two channels of ones at 1000 Hz.
+    struct Ones;
+
+    impl Iterator for Ones {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            Some(1.0)
+        }
+    }
+
+    impl Source for Ones {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            2
+        }
+
+        fn sample_rate(&self) -> u32 {
+            1000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+
+        fn elapsed(&mut self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn seek(&mut self, _time: Duration) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn ramps_a_frame_at_a_time() {
+        let mut source = fade(Ones, Duration::from_millis(4));
+        assert_eq!(source.next(), Some(1.0));
+        assert_eq!(source.next(), Some(1.0));
+
+        source.fade_out();
+        let samples: Vec<f32> = source.by_ref().take(10).collect();
+        assert_eq!(
+            samples,
+            [0.75, 0.75, 0.5, 0.5, 0.25, 0.25, 0.0, 0.0, 0.0, 0.0]
+        );
+        assert!(source.is_silent());
+
+        source.fade_in();
+        let samples: Vec<f32> = source.take(10).collect();
+        assert_eq!(
+            samples,
+            [0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn no_duration_is_a_cut() {
+        let mut source = fade(Ones, Duration::ZERO);
+        source.fade_out();
+        assert!(source.is_silent());
+        assert_eq!(source.next(), Some(0.0));
+
+        source.fade_in();
+        assert_eq!(source.next(), Some(1.0));
+    }
+}
@@ -4,11 +4,14 @@ use std::time::Duration;
 
 use super::Sample;
 
+pub use self::ab_loop::AbLoop;
 pub use self::amplify::Amplify;
 pub use self::done::Done;
 pub use self::empty::Empty;
+pub use self::fade::Fade;
 pub use self::fadein::FadeIn;
 // pub use self::http::SeekableRequest;
+pub use self::offset::Offset;
 pub use self::pausable::Pausable;
 pub use self::periodic::PeriodicAccess;
 pub use self::samples_converter::SamplesConverter;
@@ -19,11 +22,14 @@ pub use self::take::TakeDuration;
 pub use self::uniform::UniformSourceIterator;
 pub use self::zero::Zero;
 
+mod ab_loop;
 mod amplify;
 mod done;
 mod empty;
+mod fade;
 mod fadein;
 // mod http;
+mod offset;
 mod pausable;
 mod periodic;
 mod samples_converter;
@@ -141,6 +147,24 @@ where
     ///
     /// If the specified duration is longer than the source itself, `skip_duration` will skip to the end of the source.
 
+    /// Plays from `offset` on, as if the source started there.
+    #[inline]
+    fn offset(self, offset: Duration) -> Offset<Self>
+    where
+        Self: Sized,
+    {
+        offset::offset(self, offset)
+    }
+
+    /// Plays a section over and over once given one with [`AbLoop::set_range`].
+    #[inline]
+    fn ab_loop(self) -> AbLoop<Self>
+    where
+        Self: Sized,
+    {
+        ab_loop::ab_loop(self)
+    }
+
     /// Amplifies the sound by the given value.
     #[inline]
     fn amplify(self, value: f32) -> Amplify<Self>
@@ -150,6 +174,15 @@ where
         amplify::amplify(self, value)
     }
 
+    /// Ramps the volume on [`Fade::fade_out`] and [`Fade::fade_in`] over `duration`.
+    #[inline]
+    fn fade(self, duration: Duration) -> Fade<Self>
+    where
+        Self: Sized,
+    {
+        fade::fade(self, duration)
+    }
+
     /// Fades in the sound.
     #[inline]
     fn fade_in(self, duration: Duration) -> FadeIn<Self>
@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use super::ab_loop::{from_frames, to_frames};
+use super::{Sample, Source};
+
+/// Internal function that builds a `Offset` object.
+pub fn offset<I>(input: I, offset: Duration) -> Offset<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    let mut source = Offset {
+        input,
+        start: offset,
+        frame: 0,
+        channel: 0,
+        to_skip: 0,
+    };
+    if !offset.is_zero() && source.seek(Duration::ZERO).is_none() {
+        // the source cannot seek, so drop what comes before the offset
+        source.to_skip = to_frames(offset, source.input.sample_rate());
+    }
+    source
+}
+
+/// Filter that plays the inner source from an offset on, like a track of a cue sheet in the file
+/// of a whole album.
+///
+/// Times, of seeks and of what elapsed, are from the offset on. The position is counted in frames
+/// like in [`AbLoop`](super::AbLoop), so the offset is sample-accurate.
+#[derive(Clone, Debug)]
+pub struct Offset<I> {
+    input: I,
+    /// Where in the inner source playing starts
+    start: Duration,
+    /// Frame of the inner source the next sample belongs to
+    frame: u64,
+    /// Channel of the next sample
+    channel: u16,
+    /// Frames to drop after a seek landed before where it should have
+    to_skip: u64,
+}
+
+#[allow(unused)]
+impl<I> Offset<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    /// Returns a reference to the inner source.
+    #[inline]
+    pub fn inner(&self) -> &I {
+        &self.input
+    }
+
+    /// Returns a mutable reference to the inner source.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut I {
+        &mut self.input
+    }
+
+    /// Returns the inner source.
+    #[inline]
+    pub fn into_inner(self) -> I {
+        self.input
+    }
+
+    /// Take the next sample of the inner source, keeping track of the frame.
+    #[inline]
+    fn next_counted(&mut self) -> Option<I::Item> {
+        let sample = self.input.next()?;
+        self.channel += 1;
+        if self.channel >= self.input.channels() {
+            self.channel = 0;
+            self.frame += 1;
+        }
+        Some(sample)
+    }
+}
+
+impl<I> Iterator for Offset<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<I::Item> {
+        if self.channel == 0 {
+            while self.to_skip > 0 {
+                for _ in 0..self.input.channels() {
+                    self.next_counted()?;
+                }
+                self.to_skip -= 1;
+            }
+        }
+        self.next_counted()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<I> Source for Offset<I>
+where
+    I: Source,
+    I::Item: Sample,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.input
+            .total_duration()
+            .map(|duration| duration.saturating_sub(self.start))
+    }
+
+    #[inline]
+    fn elapsed(&mut self) -> Duration {
+        from_frames(self.frame, self.input.sample_rate()).saturating_sub(self.start)
+    }
+
+    #[inline]
+    fn seek(&mut self, time: Duration) -> Option<Duration> {
+        let target = self.start + time;
+        let landed = self.input.seek(target)?;
+        let rate = self.input.sample_rate();
+        self.frame = to_frames(landed, rate);
+        self.channel = 0;
+        self.to_skip = to_frames(target, rate).saturating_sub(self.frame);
+        Some(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every sample is the number of its frame, seeking lands on the start of a packet.
+    struct Packets {
+        frame: u64,
+        frames: u64,
+        seekable: bool,
+    }
+
+    impl Iterator for Packets {
+        type Item = f32;
+
+        #[allow(clippy::cast_precision_loss)]
+        fn next(&mut self) -> Option<f32> {
+            if self.frame >= self.frames {
+                return None;
+            }
+            self.frame += 1;
+            Some((self.frame - 1) as f32)
+        }
+    }
+
+    impl Source for Packets {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            1
+        }
+
+        fn sample_rate(&self) -> u32 {
+            1000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            Some(from_frames(self.frames, 1000))
+        }
+
+        fn elapsed(&mut self) -> Duration {
+            from_frames(self.frame, 1000)
+        }
+
+        fn seek(&mut self, time: Duration) -> Option<Duration> {
+            if !self.seekable {
+                return None;
+            }
+            self.frame = to_frames(time, 1000) / 64 * 64;
+            Some(from_frames(self.frame, 1000))
+        }
+    }
+
+    fn packets(seekable: bool) -> Packets {
+        Packets {
+            frame: 0,
+            frames: 10_000,
+            seekable,
+        }
+    }
+
+    #[test]
+    fn plays_from_the_offset() {
+        for seekable in [true, false] {
+            let mut source = offset(packets(seekable), Duration::from_millis(1234));
+            assert_eq!(source.next(), Some(1234.0), "seekable: {seekable}");
+            assert_eq!(source.elapsed(), Duration::from_millis(1));
+            assert_eq!(source.total_duration(), Some(Duration::from_millis(8766)));
+        }
+
+        let mut source = offset(packets(true), Duration::from_millis(1234));
+        assert_eq!(
+            source.seek(Duration::from_millis(100)),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(source.next(), Some(1334.0));
+    }
+
+    #[test]
+    fn takes_the_section() {
+        let mut source =
+            offset(packets(true), Duration::from_secs(1)).take_duration(Duration::from_secs(2));
+        assert_eq!(source.by_ref().count(), 2000);
+
+        // a seek gives back what is left after it
+        source.seek(Duration::from_millis(1500));
+        assert_eq!(source.next(), Some(2500.0));
+        assert_eq!(source.count(), 499);
+    }
+}
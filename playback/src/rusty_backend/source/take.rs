@@ -122,7 +122,7 @@ where
             }
         }
 
-        if self.remaining_duration <= self.duration_per_sample {
+        if self.remaining_duration < self.duration_per_sample {
             None
         } else if let Some(sample) = self.input.next() {
             let sample = match &self.filter {
@@ -186,6 +186,9 @@ where
         self.input.elapsed()
     }
     fn seek(&mut self, time: Duration) -> Option<Duration> {
-        self.input.seek(time)
+        let landed = self.input.seek(time)?;
+        // what is left to play counts from where the seek landed
+        self.remaining_duration = self.requested_duration.saturating_sub(landed);
+        Some(landed)
     }
 }
@@ -1,8 +1,11 @@
-use super::super::conversions::{ChannelCountConverter, DataConverter, SampleRateConverter};
+use super::super::conversions::{
+    ChannelCountConverter, DataConverter, ResamplingConverter, SincState,
+};
 use super::{Sample, Source};
 use cpal::FromSample;
 use std::cmp;
 use std::time::Duration;
+use termusiclib::config::ResampleQuality;
 
 /// An iterator that reads from a `Source` and converts the samples to a specific rate and
 /// channels count.
@@ -17,9 +20,10 @@ where
     I::Item: Sample,
     D: Sample,
 {
-    inner: Option<DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D>>,
+    inner: Option<DataConverter<ChannelCountConverter<ResamplingConverter<Take<I>>>, D>>,
     target_channels: u16,
     target_sample_rate: u32,
+    resample_quality: ResampleQuality,
     total_duration: Option<Duration>,
 }
 
@@ -35,14 +39,23 @@ where
         input: I,
         target_channels: u16,
         target_sample_rate: u32,
+        resample_quality: ResampleQuality,
     ) -> UniformSourceIterator<I, D> {
         let total_duration = input.total_duration();
-        let input = UniformSourceIterator::bootstrap(input, target_channels, target_sample_rate);
+        let input = UniformSourceIterator::bootstrap(
+            input,
+            target_channels,
+            target_sample_rate,
+            resample_quality,
+            None,
+            false,
+        );
 
         UniformSourceIterator {
             inner: Some(input),
             target_channels,
             target_sample_rate,
+            resample_quality,
             total_duration,
         }
     }
@@ -52,7 +65,10 @@ where
         input: I,
         target_channels: u16,
         target_sample_rate: u32,
-    ) -> DataConverter<ChannelCountConverter<SampleRateConverter<Take<I>>>, D> {
+        resample_quality: ResampleQuality,
+        sinc_state: Option<SincState>,
+        ended: bool,
+    ) -> DataConverter<ChannelCountConverter<ResamplingConverter<Take<I>>>, D> {
         // Limit the frame length to something reasonable
         let frame_len = input.current_frame_len().map(|x| x.min(32768));
 
@@ -63,11 +79,14 @@ where
             iter: input,
             n: frame_len,
         };
-        let input = SampleRateConverter::new(
+        let input = ResamplingConverter::new(
             input,
             cpal::SampleRate(from_sample_rate),
             cpal::SampleRate(target_sample_rate),
             from_channels,
+            resample_quality,
+            sinc_state,
+            ended,
         );
         let input = ChannelCountConverter::new(input, from_channels, target_channels);
 
@@ -85,24 +104,42 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<D> {
-        if let Some(value) = self.inner.as_mut().unwrap().next() {
-            return Some(value);
-        }
-
-        let input = self
-            .inner
-            .take()
-            .unwrap()
-            .into_inner()
-            .into_inner()
-            .into_inner()
-            .iter;
-
-        let mut input = Self::bootstrap(input, self.target_channels, self.target_sample_rate);
+        loop {
+            if let Some(value) = self.inner.as_mut().unwrap().next() {
+                return Some(value);
+            }
 
-        let value = input.next();
-        self.inner = Some(input);
-        value
+            let (input, sinc_state) = self
+                .inner
+                .take()
+                .unwrap()
+                .into_inner()
+                .into_inner()
+                .into_parts();
+
+            // The sinc converter holds samples back until it has enough lookahead, so a short
+            // frame can be consumed entirely without producing anything. Keep going with the
+            // next frame in that case instead of reporting the end of the source.
+            let ended = input.n != Some(0) || input.iter.current_frame_len() == Some(0);
+            let retry = sinc_state.is_some() && !ended;
+
+            // once the source has ended, the held back samples are flushed by a converter
+            // without any input left
+            let mut input = Self::bootstrap(
+                input.iter,
+                self.target_channels,
+                self.target_sample_rate,
+                self.resample_quality,
+                sinc_state,
+                ended,
+            );
+
+            let value = input.next();
+            self.inner = Some(input);
+            if value.is_some() || !retry {
+                return value;
+            }
+        }
     }
 
     #[inline]
@@ -150,7 +187,15 @@ where
             .into_inner()
             .iter;
         let ret = input.seek(time);
-        let input = Self::bootstrap(input, self.target_channels, self.target_sample_rate);
+        // the signal is not continuous anymore, so the sinc state starts over
+        let input = Self::bootstrap(
+            input,
+            self.target_channels,
+            self.target_sample_rate,
+            self.resample_quality,
+            None,
+            false,
+        );
 
         self.inner = Some(input);
         ret
@@ -0,0 +1,47 @@
+//! Spectrum analysis of the mixer output, for the visualizers of the clients.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+
+use super::pcm_tap::PcmChunk;
+use crate::spectrum::{SpectrumAnalyzer, SpectrumSender};
+
+/// Chunks of 10ms the mixer may be ahead of the analyzer before chunks get dropped.
+const SPECTRUM_QUEUE_CHUNKS: usize = 20;
+/// Chunks of 10ms between two updates, about 30 updates a second.
+const CHUNKS_PER_UPDATE: usize = 3;
+
+/// Start the thread analyzing the mixer output and return the sender for the taps feeding it.
+///
+/// Nothing is computed while no client is subscribed to `spectrum_tx`.
+pub fn spawn_spectrum_output(spectrum_tx: SpectrumSender) -> SyncSender<PcmChunk> {
+    let (tx, rx) = mpsc::sync_channel(SPECTRUM_QUEUE_CHUNKS);
+
+    if let Err(e) = std::thread::Builder::new()
+        .name("spectrum analyzer".into())
+        .spawn(move || spectrum_thread(&spectrum_tx, &rx))
+    {
+        error!("error starting spectrum analyzer: {e}");
+    }
+
+    tx
+}
+
+fn spectrum_thread(spectrum_tx: &SpectrumSender, rx: &Receiver<PcmChunk>) {
+    let mut analyzer = SpectrumAnalyzer::default();
+    let mut chunks = 0;
+
+    // the taps are gone together with the player
+    while let Ok(chunk) = rx.recv() {
+        if spectrum_tx.receiver_count() == 0 {
+            continue;
+        }
+
+        analyzer.push(chunk.channels, chunk.sample_rate, &chunk.samples);
+        chunks += 1;
+        if chunks >= CHUNKS_PER_UPDATE {
+            chunks = 0;
+            // the last receiver may just have gone
+            let _ = spectrum_tx.send(analyzer.take());
+        }
+    }
+}
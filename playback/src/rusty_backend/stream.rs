@@ -1,14 +1,21 @@
 // use std::io::{Read, Seek};
 // use std::marker::Sync;
+use std::fs::File;
+use std::io::BufWriter;
+use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Weak};
 use std::{error, fmt};
 
 use super::decoder;
 use super::dynamic_mixer::{self, DynamicMixerController};
+use super::headless::{HeadlessOutput, WavWriter, HEADLESS_CHANNELS, HEADLESS_SAMPLE_RATE};
+use super::pcm_tap::PcmChunk;
+use super::pipe::PipeConfig;
 // use super::sink::Sink;
 use super::source::Source;
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Sample, SupportedStreamConfig};
+use termusiclib::config::{AudioSink, ResampleQuality};
 
 /// `cpal::Stream` container. Also see the more useful `OutputStreamHandle`.
 ///
@@ -16,7 +23,8 @@ use cpal::{Sample, SupportedStreamConfig};
 #[allow(clippy::module_name_repetitions)]
 pub struct OutputStream {
     mixer: Arc<DynamicMixerController<f32>>,
-    _stream: cpal::Stream,
+    _stream: Option<cpal::Stream>,
+    _headless: Option<HeadlessOutput>,
 }
 
 /// More flexible handle to a `OutputStream` that provides playback.
@@ -47,7 +55,8 @@ impl OutputStream {
         stream.play()?;
         let out = Self {
             mixer,
-            _stream: stream,
+            _stream: Some(stream),
+            _headless: None,
         };
         let handle = OutputStreamHandle {
             mixer: Arc::downgrade(&out.mixer),
@@ -76,6 +85,94 @@ impl OutputStream {
                 .ok_or(original_err)
         })
     }
+
+    /// Return a new stream & handle using the output device called `name`.
+    ///
+    /// If `name` is empty, no device matches it or the device fails to open, this falls back to
+    /// [`OutputStream::try_default`].
+    pub fn try_from_name(name: &str) -> Result<(Self, OutputStreamHandle), StreamError> {
+        if name.is_empty() {
+            return Self::try_default();
+        }
+
+        let device = cpal::default_host()
+            .output_devices()
+            .ok()
+            .and_then(|mut devices| {
+                devices.find(|d| d.name().map_or(false, |device_name| device_name == name))
+            });
+
+        match device {
+            Some(device) => Self::try_from_device(&device).or_else(|e| {
+                warn!("error opening output device \"{name}\": {e}, using default device");
+                Self::try_default()
+            }),
+            None => {
+                warn!("output device \"{name}\" not found, using default device");
+                Self::try_default()
+            }
+        }
+    }
+
+    /// Return a new stream & handle playing to `sink`.
+    ///
+    /// [`AudioSink::Device`] opens the output device called `device_name`, see
+    /// [`OutputStream::try_from_name`], unless `pipe` is exclusive and takes its place. The
+    /// other sinks need no sound card.
+    pub fn try_from_sink(
+        sink: &AudioSink,
+        device_name: &str,
+        pipe: Option<&PipeConfig>,
+    ) -> Result<(Self, OutputStreamHandle), StreamError> {
+        let exclusive_pipe = pipe.filter(|pipe| pipe.exclusive);
+        Ok(match (sink, exclusive_pipe) {
+            // the pipe reader takes the place of the sound card, so play at its format in real time
+            (AudioSink::Device, Some(pipe)) => {
+                Self::headless(pipe.channels, pipe.sample_rate, None, 1)
+            }
+            (AudioSink::Device, None) => Self::try_from_name(device_name)?,
+            (AudioSink::Null { speed }, _) => {
+                Self::headless(HEADLESS_CHANNELS, HEADLESS_SAMPLE_RATE, None, *speed)
+            }
+            (AudioSink::Wav { path, speed }, _) => {
+                let writer = WavWriter::create(path, HEADLESS_CHANNELS, HEADLESS_SAMPLE_RATE)
+                    .map_err(StreamError::WavFile)?;
+                Self::headless(
+                    HEADLESS_CHANNELS,
+                    HEADLESS_SAMPLE_RATE,
+                    Some(writer),
+                    *speed,
+                )
+            }
+        })
+    }
+
+    fn headless(
+        channels: u16,
+        sample_rate: u32,
+        writer: Option<WavWriter<BufWriter<File>>>,
+        speed: u16,
+    ) -> (Self, OutputStreamHandle) {
+        let (mixer, mixer_rx) = dynamic_mixer::mixer::<f32>(channels, sample_rate);
+        let out = Self {
+            mixer,
+            _stream: None,
+            _headless: Some(HeadlessOutput::spawn(mixer_rx, writer, speed)),
+        };
+        let handle = OutputStreamHandle {
+            mixer: Arc::downgrade(&out.mixer),
+        };
+        (out, handle)
+    }
+}
+
+/// Returns the names of all the output devices of the default host.
+pub fn output_device_names() -> Vec<String> {
+    let Ok(devices) = cpal::default_host().output_devices() else {
+        return Vec::new();
+    };
+
+    devices.filter_map(|d| d.name().ok()).collect()
 }
 
 impl OutputStreamHandle {
@@ -89,6 +186,20 @@ impl OutputStreamHandle {
         Ok(())
     }
 
+    /// Sends a copy of everything played to `tx`, until its receiver is dropped.
+    pub fn add_tap(&self, tx: SyncSender<PcmChunk>) -> Result<(), PlayError> {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer.add_tap(tx);
+        Ok(())
+    }
+
+    /// Sets the sample rate conversion quality for the sources played from now on.
+    pub fn set_resample_quality(&self, quality: ResampleQuality) -> Result<(), PlayError> {
+        let mixer = self.mixer.upgrade().ok_or(PlayError::NoDevice)?;
+        mixer.set_resample_quality(quality);
+        Ok(())
+    }
+
     // Plays a sound once. Returns a `Sink` that can be used to control the sound.
     // pub fn play_once<R>(&self, input: R) -> Result<Sink, PlayError>
     // where
@@ -142,6 +253,7 @@ pub enum StreamError {
     BuildStreamError(cpal::BuildStreamError),
     SupportedStreamConfigsError(cpal::SupportedStreamConfigsError),
     NoDevice,
+    WavFile(std::io::Error),
 }
 
 impl From<cpal::DefaultStreamConfigError> for StreamError {
@@ -176,6 +288,7 @@ impl fmt::Display for StreamError {
             Self::DefaultStreamConfigError(e) => e.fmt(f),
             Self::SupportedStreamConfigsError(e) => e.fmt(f),
             Self::NoDevice => write!(f, "NoDevice"),
+            Self::WavFile(e) => write!(f, "error creating wav file: {e}"),
         }
     }
 }
@@ -188,6 +301,7 @@ impl error::Error for StreamError {
             Self::DefaultStreamConfigError(e) => Some(e),
             Self::SupportedStreamConfigsError(e) => Some(e),
             Self::NoDevice => None,
+            Self::WavFile(e) => Some(e),
         }
     }
 }
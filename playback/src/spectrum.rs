@@ -0,0 +1,301 @@
+//! Spectrum and levels of the played audio, for visualizers.
+
+use tokio::sync::broadcast;
+
+/// Frequency bands in [`SpectrumData::bands`].
+pub const SPECTRUM_BANDS: usize = 32;
+/// Samples in [`SpectrumData::waveform`].
+pub const WAVEFORM_LEN: usize = 512;
+/// Samples the FFT runs over, about 46ms at 44.1kHz.
+const FFT_LEN: usize = 2048;
+/// Lower edge of the lowest band, in Hz.
+const LOWEST_FREQUENCY: f32 = 30.0;
+/// Upper edge of the highest band, in Hz, lowered to half the sample rate if needed.
+const HIGHEST_FREQUENCY: f32 = 16_000.0;
+/// Level of a band shown as nothing.
+const FLOOR_DB: f32 = -70.0;
+
+/// Where the spectrum of the played audio is published, about 30 times a second.
+pub type SpectrumSender = broadcast::Sender<SpectrumData>;
+
+/// What a visualizer draws.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpectrumData {
+    /// Magnitude of each band from low to high frequencies, 0.0 is the floor, 1.0 full scale
+    pub bands: Vec<f32>,
+    /// Peak level of each channel since the last update, 0.0 to 1.0
+    pub peak: Vec<f32>,
+    /// RMS level of each channel since the last update, 0.0 to 1.0
+    pub rms: Vec<f32>,
+    /// The latest samples mixed to mono, oldest first, -1.0 to 1.0
+    pub waveform: Vec<f32>,
+}
+
+impl From<SpectrumData> for crate::player::SpectrumReply {
+    fn from(value: SpectrumData) -> Self {
+        Self {
+            bands: value.bands,
+            peak: value.peak,
+            rms: value.rms,
+            waveform: value.waveform,
+        }
+    }
+}
+
+/// Turns interleaved samples into [`SpectrumData`].
+pub struct SpectrumAnalyzer {
+    channels: u16,
+    sample_rate: u32,
+    /// The latest mono samples, a ring buffer starting at `history_pos`
+    history: Vec<f32>,
+    history_pos: usize,
+    peak: Vec<f32>,
+    sum_squares: Vec<f64>,
+    frames: usize,
+    /// Hann window over `FFT_LEN` samples
+    window: Vec<f32>,
+    /// `e^(-2πik/FFT_LEN)` for the first half of `k`
+    twiddles: Vec<(f32, f32)>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl Default for SpectrumAnalyzer {
+    fn default() -> Self {
+        Self::new(2, 44100)
+    }
+}
+
+impl SpectrumAnalyzer {
+    /// Analyzer for audio with the given channel count and rate.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        let window = (0..FFT_LEN)
+            .map(|i| {
+                let phase = std::f64::consts::TAU * i as f64 / FFT_LEN as f64;
+                (0.5 - 0.5 * phase.cos()) as f32
+            })
+            .collect();
+        let twiddles = (0..FFT_LEN / 2)
+            .map(|k| {
+                let angle = -std::f64::consts::TAU * k as f64 / FFT_LEN as f64;
+                (angle.cos() as f32, angle.sin() as f32)
+            })
+            .collect();
+        let channels = channels.max(1);
+
+        Self {
+            channels,
+            sample_rate: sample_rate.max(1),
+            history: vec![0.0; FFT_LEN],
+            history_pos: 0,
+            peak: vec![0.0; usize::from(channels)],
+            sum_squares: vec![0.0; usize::from(channels)],
+            frames: 0,
+            window,
+            twiddles,
+            re: vec![0.0; FFT_LEN],
+            im: vec![0.0; FFT_LEN],
+        }
+    }
+
+    /// Add interleaved samples, starting over when the format changes.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn push(&mut self, channels: u16, sample_rate: u32, samples: &[f32]) {
+        if channels.max(1) != self.channels || sample_rate.max(1) != self.sample_rate {
+            *self = Self::new(channels, sample_rate);
+        }
+
+        let channel_count = usize::from(self.channels);
+        for frame in samples.chunks_exact(channel_count) {
+            let mut mono = 0.0;
+            for (channel, sample) in frame.iter().enumerate() {
+                self.peak[channel] = self.peak[channel].max(sample.abs());
+                self.sum_squares[channel] += f64::from(*sample) * f64::from(*sample);
+                mono += sample;
+            }
+            self.history[self.history_pos] = mono / channel_count as f32;
+            self.history_pos = (self.history_pos + 1) % FFT_LEN;
+            self.frames += 1;
+        }
+    }
+
+    /// The spectrum of the latest samples and the levels since the last call.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn take(&mut self) -> SpectrumData {
+        let ordered = self.history[self.history_pos..]
+            .iter()
+            .chain(&self.history[..self.history_pos]);
+        for (i, sample) in ordered.enumerate() {
+            self.re[i] = *sample;
+            self.im[i] = 0.0;
+        }
+        let waveform = self.re[FFT_LEN - WAVEFORM_LEN..].to_vec();
+        for (sample, window) in self.re.iter_mut().zip(&self.window) {
+            *sample *= window;
+        }
+        fft(&mut self.re, &mut self.im, &self.twiddles);
+
+        // a full scale sine through a Hann window peaks at FFT_LEN / 4
+        let bin_width = self.sample_rate as f32 / FFT_LEN as f32;
+        let highest = HIGHEST_FREQUENCY.min(self.sample_rate as f32 / 2.0);
+        let magnitude = |bin: usize| {
+            let bin = bin.clamp(1, FFT_LEN / 2 - 1);
+            (self.re[bin].hypot(self.im[bin]) * 4.0 / FFT_LEN as f32).min(1.0)
+        };
+        let bands = (0..SPECTRUM_BANDS)
+            .map(|band| {
+                let edge = |band: usize| {
+                    LOWEST_FREQUENCY
+                        * (highest / LOWEST_FREQUENCY).powf(band as f32 / SPECTRUM_BANDS as f32)
+                };
+                let (low, high) = (edge(band), edge(band + 1));
+                let (first, last) = ((low / bin_width).ceil(), (high / bin_width).floor());
+                let amplitude = if last >= first {
+                    (first as usize..=last as usize)
+                        .map(magnitude)
+                        .fold(0.0, f32::max)
+                } else {
+                    // a band narrower than a bin, take the one closest to its center
+                    magnitude(((low * high).sqrt() / bin_width).round() as usize)
+                };
+                let db = 20.0 * amplitude.max(f32::MIN_POSITIVE).log10();
+                ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+            })
+            .collect();
+
+        let frames = self.frames.max(1) as f64;
+        let data = SpectrumData {
+            bands,
+            peak: self.peak.iter().map(|v| v.min(1.0)).collect(),
+            rms: self
+                .sum_squares
+                .iter()
+                .map(|v| ((v / frames).sqrt() as f32).min(1.0))
+                .collect(),
+            waveform,
+        };
+        self.peak.iter_mut().for_each(|v| *v = 0.0);
+        self.sum_squares.iter_mut().for_each(|v| *v = 0.0);
+        self.frames = 0;
+        data
+    }
+}
+
+/// In place radix-2 FFT, the length has to be a power of two matching `twiddles`.
+fn fft(re: &mut [f32], im: &mut [f32], twiddles: &[(f32, f32)]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let step = n / len;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (w_re, w_im) = twiddles[k * step];
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+    fn sine(frequency: f64, amplitude: f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let t = i as f64 / 44100.0;
+                let sample = ((t * frequency * std::f64::consts::TAU).sin() * amplitude) as f32;
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sine_lands_in_its_band() {
+        let mut analyzer = SpectrumAnalyzer::default();
+        analyzer.push(2, 44100, &sine(1000.0, 1.0, FFT_LEN));
+        let data = analyzer.take();
+        assert_eq!(data.bands.len(), SPECTRUM_BANDS);
+
+        let loudest = (0..SPECTRUM_BANDS)
+            .max_by(|a, b| data.bands[*a].total_cmp(&data.bands[*b]))
+            .unwrap();
+        // 30Hz * (16000 / 30) ^ (band / 32) reaches 1kHz in band 17
+        assert_eq!(loudest, 17);
+        assert!(data.bands[loudest] > 0.95, "{:?}", data.bands);
+        assert!(
+            data.bands[2] < 0.2 && data.bands[30] < 0.2,
+            "{:?}",
+            data.bands
+        );
+
+        // -20dB is 20 of the 70dB shown lower
+        analyzer.push(2, 44100, &sine(1000.0, 0.1, FFT_LEN));
+        let quieter = analyzer.take().bands[loudest];
+        assert!((quieter - 50.0 / 70.0).abs() < 0.05, "{quieter}");
+    }
+
+    #[test]
+    fn levels_are_per_update() {
+        let mut analyzer = SpectrumAnalyzer::default();
+        analyzer.push(2, 44100, &[0.5, -0.25, -0.5, 0.25]);
+        let data = analyzer.take();
+        assert_eq!(data.peak, vec![0.5, 0.25]);
+        assert_eq!(data.rms, vec![0.5, 0.25]);
+        assert_eq!(data.waveform.len(), WAVEFORM_LEN);
+        assert_eq!(data.waveform[WAVEFORM_LEN - 2..], [0.125, -0.125]);
+
+        let data = analyzer.take();
+        assert_eq!(data.peak, vec![0.0, 0.0]);
+        assert_eq!(data.rms, vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn format_change_starts_over() {
+        let mut analyzer = SpectrumAnalyzer::default();
+        analyzer.push(2, 44100, &sine(1000.0, 1.0, FFT_LEN));
+        analyzer.push(1, 48000, &vec![0.0; FFT_LEN]);
+        let data = analyzer.take();
+        assert_eq!(data.peak, vec![0.0]);
+        assert!(data.bands.iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn fft_of_an_impulse_is_flat() {
+        let analyzer = SpectrumAnalyzer::default();
+        let mut re = vec![0.0; FFT_LEN];
+        let mut im = vec![0.0; FFT_LEN];
+        re[0] = 1.0;
+        fft(&mut re, &mut im, &analyzer.twiddles);
+        assert!(re.iter().all(|v| (v - 1.0).abs() < 1e-5));
+        assert!(im.iter().all(|v| v.abs() < 1e-5));
+    }
+}
@@ -0,0 +1,243 @@
+//! Play a track through [`GeneralPlayer`] headless into the WAV sink and check what came out.
+#![cfg(feature = "rusty")]
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use termusiclib::config::{AudioSink, Settings};
+use termusicplayback::{BackendSelect, GeneralPlayer, PlayerCmd, PlayerTrait};
+use tokio::sync::mpsc;
+
+const SAMPLE_RATE: u32 = 44100;
+const CHANNELS: u16 = 2;
+/// How many times faster than real time the sink plays, to keep the tests short
+const SPEED: u16 = 10;
+/// What is taken for silence, well above the rounding of 16 bit samples
+const SILENCE: f32 = 0.01;
+
+/// Held by every test, the player reads its config directory from the environment.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Where the tests keep their files, the player its config and database too, away from those of
+/// the user.
+fn base_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("termusic-wav-sink-{}", std::process::id()))
+}
+
+/// A fresh directory for the files of the test called `name`.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = base_dir().join(name);
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write `secs` of a 440 Hz sine at half the full scale to `path`, as 16 bit PCM WAV.
+fn write_fixture(path: &Path, secs: u32) {
+    let frames = SAMPLE_RATE * secs;
+    let data_len = frames * u32::from(CHANNELS) * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&CHANNELS.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * u32::from(CHANNELS) * 2).to_le_bytes());
+    bytes.extend_from_slice(&(CHANNELS * 2).to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for frame in 0..frames {
+        let t = f64::from(frame) / f64::from(SAMPLE_RATE);
+        #[allow(clippy::cast_possible_truncation)]
+        let sample = ((2.0 * std::f64::consts::PI * 440.0 * t).sin() * 16384.0) as i16;
+        for _ in 0..CHANNELS {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+    std::fs::write(path, bytes).unwrap();
+}
+
+/// A player for the tracks at `tracks` writing to the WAV file at `output`, and where it sends
+/// its commands.
+fn player(tracks: &[&Path], output: &Path) -> (GeneralPlayer, mpsc::UnboundedReceiver<PlayerCmd>) {
+    std::env::set_var("XDG_CONFIG_HOME", base_dir());
+    let mut config = Settings::default();
+    config.audio_sink_from_cli = AudioSink::Wav {
+        path: output.to_path_buf(),
+        speed: SPEED,
+    };
+    config.player_fade_ms = 0;
+    config.player_volume = 100;
+    config.player_use_mpris = false;
+    config.player_use_discord = false;
+
+    let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
+    let mut player = GeneralPlayer::new_backend(BackendSelect::Rusty, &config, cmd_tx).unwrap();
+    player.playlist.clear();
+    player
+        .playlist
+        .add_playlist(tracks.iter().map(|track| track.to_str().unwrap()).collect())
+        .unwrap();
+    (player, cmd_rx)
+}
+
+/// Wait for the end of the track the player plays.
+fn wait_for_eos(cmd_rx: &mut mpsc::UnboundedReceiver<PlayerCmd>) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while Instant::now() < deadline {
+        match cmd_rx.try_recv() {
+            Ok(PlayerCmd::Eos) => return,
+            Ok(_) => {}
+            Err(mpsc::error::TryRecvError::Empty) => std::thread::sleep(Duration::from_millis(10)),
+            Err(mpsc::error::TryRecvError::Disconnected) => break,
+        }
+    }
+    panic!("the track did not end");
+}
+
+/// Stop `player` and read the samples it wrote to `output`.
+fn finish(player: GeneralPlayer, output: &Path) -> Vec<f32> {
+    // the sink has written the final sizes to the header once the player is dropped
+    drop(player);
+    let bytes = std::fs::read(output).unwrap();
+    assert_eq!(&bytes[0..4], b"RIFF");
+    let data_len = u32::from_le_bytes(bytes[54..58].try_into().unwrap()) as usize;
+    bytes[58..58 + data_len]
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+/// How long the sound in `samples` lasts, from the first sample not silent to the last.
+#[allow(clippy::cast_precision_loss)]
+fn sounding(samples: &[f32]) -> Duration {
+    let first = samples.iter().position(|s| s.abs() > SILENCE);
+    let last = samples.iter().rposition(|s| s.abs() > SILENCE);
+    match (first, last) {
+        (Some(first), Some(last)) => Duration::from_secs_f64(
+            (last - first) as f64 / f64::from(CHANNELS) / f64::from(SAMPLE_RATE),
+        ),
+        _ => Duration::ZERO,
+    }
+}
+
+/// The longest run of silence in `samples` between the first sample not silent and the last.
+#[allow(clippy::cast_precision_loss)]
+fn longest_gap(samples: &[f32]) -> Duration {
+    let first = samples.iter().position(|s| s.abs() > SILENCE).unwrap_or(0);
+    let last = samples.iter().rposition(|s| s.abs() > SILENCE).unwrap_or(0);
+    let mut longest = 0;
+    let mut run = 0;
+    for sample in samples.get(first..last).unwrap_or_default() {
+        if sample.abs() > SILENCE {
+            run = 0;
+        } else {
+            run += 1;
+            longest = longest.max(run);
+        }
+    }
+    Duration::from_secs_f64(longest as f64 / f64::from(CHANNELS) / f64::from(SAMPLE_RATE))
+}
+
+fn assert_about(duration: Duration, expected: Duration) {
+    let off = if duration > expected {
+        duration - expected
+    } else {
+        expected - duration
+    };
+    assert!(
+        off < Duration::from_millis(150),
+        "played {duration:?}, expected {expected:?}"
+    );
+}
+
+#[test]
+fn plays_whole_track() {
+    let _serial = serial();
+    let dir = test_dir("whole");
+    let track = dir.join("fixture.wav");
+    let output = dir.join("output.wav");
+    write_fixture(&track, 3);
+
+    let (mut player, mut cmd_rx) = player(&[&track], &output);
+    player.start_play();
+    wait_for_eos(&mut cmd_rx);
+    let samples = finish(player, &output);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_about(sounding(&samples), Duration::from_secs(3));
+    // the sine comes out at the amplitude it went in with
+    let peak = samples.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+    assert!((peak - 0.5).abs() < 0.02, "peak is {peak}");
+}
+
+#[test]
+fn plays_from_seek_position() {
+    let _serial = serial();
+    let dir = test_dir("seek");
+    let track = dir.join("fixture.wav");
+    let output = dir.join("output.wav");
+    write_fixture(&track, 4);
+
+    let (mut player, mut cmd_rx) = player(&[&track], &output);
+    player.start_play();
+    player.seek_to(Duration::from_secs(3));
+    wait_for_eos(&mut cmd_rx);
+    let samples = finish(player, &output);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_about(sounding(&samples), Duration::from_secs(1));
+}
+
+#[test]
+fn plays_next_track_gapless() {
+    let _serial = serial();
+    let dir = test_dir("gapless");
+    let first = dir.join("first.wav");
+    let second = dir.join("second.wav");
+    let output = dir.join("output.wav");
+    write_fixture(&first, 4);
+    write_fixture(&second, 4);
+
+    let (mut player, mut cmd_rx) = player(&[&first, &second], &output);
+    player.start_play();
+    // what the server does with the commands of the player, up to the end of the second track
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut ended = 0;
+    while ended < 2 {
+        assert!(Instant::now() < deadline, "the tracks did not end");
+        match cmd_rx.try_recv() {
+            Ok(PlayerCmd::AboutToFinish) if !player.playlist.has_next_track() && ended == 0 => {
+                player.enqueue_next_from_playlist();
+            }
+            Ok(PlayerCmd::Eos) => {
+                ended += 1;
+                if ended == 1 {
+                    assert!(
+                        player.playlist.has_next_track(),
+                        "the second track was not queued"
+                    );
+                    player.playlist.clear_current_track();
+                    player.start_play();
+                }
+            }
+            Ok(_) => {}
+            Err(mpsc::error::TryRecvError::Empty) => std::thread::sleep(Duration::from_millis(1)),
+            Err(mpsc::error::TryRecvError::Disconnected) => panic!("the player quit"),
+        }
+    }
+    let samples = finish(player, &output);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_about(sounding(&samples), Duration::from_secs(8));
+    // only the zero crossings of the sine, nothing between the tracks
+    let gap = longest_gap(&samples);
+    assert!(gap < Duration::from_millis(1), "silent for {gap:?}");
+}
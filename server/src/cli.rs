@@ -23,7 +23,9 @@ use std::path::PathBuf;
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
+use anyhow::{bail, Result};
 use clap::{builder::ArgPredicate, Parser, Subcommand, ValueEnum};
+use termusiclib::config::AudioSink;
 use termusicplayback::BackendSelect;
 
 #[derive(Parser, Debug)]
@@ -49,10 +51,35 @@ pub struct Args {
     pub max_depth: Option<usize>,
     #[arg(short, long, default_value_t = Backend::Default)]
     pub backend: Backend,
+    /// Where the rusty backend sends its audio, "null" and "wav" need no sound card.
+    #[arg(long, value_enum, default_value_t = AudioSinkKind::Device)]
+    pub audio_sink: AudioSinkKind,
+    /// File(FILE) the "wav" audio sink writes to.
+    #[arg(long, value_name = "FILE", required_if_eq("audio_sink", "wav"))]
+    pub audio_sink_file: Option<PathBuf>,
+    /// How many times faster than real time the "null" and "wav" audio sinks play, default is 1.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..))]
+    pub audio_sink_speed: u16,
     #[clap(flatten)]
     pub log_options: LogOptions,
 }
 
+impl Args {
+    /// The [`AudioSink`] of `audio_sink`, with its file and speed.
+    pub fn audio_sink(&self) -> Result<AudioSink> {
+        let speed = self.audio_sink_speed;
+        Ok(match (self.audio_sink, &self.audio_sink_file) {
+            (AudioSinkKind::Device, _) => AudioSink::Device,
+            (AudioSinkKind::Null, _) => AudioSink::Null { speed },
+            (AudioSinkKind::Wav, Some(path)) => AudioSink::Wav {
+                path: path.clone(),
+                speed,
+            },
+            (AudioSinkKind::Wav, None) => bail!("audio sink \"wav\" needs a file to write to"),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum Backend {
     #[cfg(feature = "mpv")]
@@ -104,6 +131,14 @@ impl std::fmt::Display for Backend {
     }
 }
 
+/// The kinds of [`AudioSink`], which `audio_sink_file` and `audio_sink_speed` go with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AudioSinkKind {
+    Device,
+    Null,
+    Wav,
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Action {
     /// Import feeds from opml file.
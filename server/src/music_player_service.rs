@@ -1,16 +1,23 @@
 use anyhow::Result;
 use parking_lot::Mutex;
 use std::sync::Arc;
+use termusiclib::config::BackendKind;
 use termusicplayback::player::music_player_server::MusicPlayer;
 use termusicplayback::player::{
-    CycleLoopReply, CycleLoopRequest, EmptyReply, GetProgressRequest, GetProgressResponse,
-    PlaySelectedRequest, PlayerTime, ReloadConfigRequest, ReloadPlaylistRequest,
-    SeekBackwardRequest, SeekForwardRequest, SkipNextRequest, SkipNextResponse,
-    SkipPreviousRequest, SpeedDownRequest, SpeedReply, SpeedUpRequest, ToggleGaplessReply,
-    ToggleGaplessRequest, TogglePauseRequest, TogglePauseResponse, VolumeDownRequest, VolumeReply,
-    VolumeUpRequest,
+    AbLoopClearRequest, AbLoopSetARequest, AbLoopSetBRequest, AudiobookJumpRequest,
+    ChapterNextRequest, ChapterPreviousRequest, CycleLoopReply, CycleLoopRequest, EmptyReply,
+    GetOutputDevicesRequest, GetProgressRequest, GetProgressResponse, OutputDevicesReply,
+    PlaySelectedRequest, PlayerTime, RadioGoLiveRequest, RadioRecordReply, RadioRecordRequest,
+    ReloadConfigRequest, ReloadPlaylistRequest, SeekBackwardRequest, SeekForwardRequest,
+    SetOutputDeviceRequest, SkipNextRequest, SkipNextResponse, SkipPreviousRequest, SpectrumReply,
+    SpeedDownRequest, SpeedReply, SpeedUpRequest, SubscribeSpectrumRequest, SwitchBackendRequest,
+    ToggleGaplessReply, ToggleGaplessRequest, TogglePauseRequest, TogglePauseResponse,
+    VolumeDownRequest, VolumeReply, VolumeUpRequest,
 };
-use termusicplayback::{PlayerCmd, PlayerCmdSender};
+use termusicplayback::spectrum::SpectrumSender;
+use termusicplayback::{Backend, PlayerCmd, PlayerCmdSender};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
 
 use crate::PlayerStats;
@@ -19,6 +26,8 @@ use crate::PlayerStats;
 pub struct MusicPlayerService {
     cmd_tx: PlayerCmdSender,
     pub(crate) player_stats: Arc<Mutex<PlayerStats>>,
+    /// Set once the player is created, if its backend analyzes the audio
+    pub(crate) spectrum: Arc<Mutex<Option<SpectrumSender>>>,
 }
 
 impl MusicPlayerService {
@@ -28,6 +37,7 @@ impl MusicPlayerService {
         Self {
             cmd_tx,
             player_stats,
+            spectrum: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -42,6 +52,8 @@ impl MusicPlayerService {
 
 #[tonic::async_trait]
 impl MusicPlayer for MusicPlayerService {
+    type SubscribeSpectrumStream = ReceiverStream<Result<SpectrumReply, Status>>;
+
     async fn cycle_loop(
         &self,
         _request: Request<CycleLoopRequest>,
@@ -60,6 +72,7 @@ impl MusicPlayer for MusicPlayerService {
         if r.current_track_updated {
             r.current_track_updated = false;
         }
+        r.chapters = None;
 
         Ok(Response::new(reply))
     }
@@ -220,4 +233,155 @@ impl MusicPlayer for MusicPlayerService {
 
         Ok(Response::new(reply))
     }
+
+    async fn get_output_devices(
+        &self,
+        _request: Request<GetOutputDevicesRequest>,
+    ) -> Result<Response<OutputDevicesReply>, Status> {
+        self.command(&PlayerCmd::GetOutputDevices);
+        // This is to let the player refresh the device list within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+        let reply = r.as_output_devices_reply();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn set_output_device(
+        &self,
+        request: Request<SetOutputDeviceRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let reply = EmptyReply {};
+        self.command(&PlayerCmd::SetOutputDevice(request.into_inner().name));
+
+        Ok(Response::new(reply))
+    }
+
+    async fn subscribe_spectrum(
+        &self,
+        _request: Request<SubscribeSpectrumRequest>,
+    ) -> Result<Response<Self::SubscribeSpectrumStream>, Status> {
+        let Some(spectrum) = self.spectrum.lock().clone() else {
+            return Err(Status::unavailable(
+                "the backend in use does not provide spectrum data",
+            ));
+        };
+        let mut spectrum_rx = spectrum.subscribe();
+        let (tx, rx) = tokio::sync::mpsc::channel(2);
+
+        // ends once the client is gone, which stops the analyzer when it was the last one
+        tokio::spawn(async move {
+            loop {
+                match spectrum_rx.recv().await {
+                    Ok(data) => {
+                        if tx.send(Ok(data.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    // a slow client skips updates
+                    Err(RecvError::Lagged(_)) => {}
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn ab_loop_set_a(
+        &self,
+        _request: Request<AbLoopSetARequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::AbLoopSetA);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn ab_loop_set_b(
+        &self,
+        _request: Request<AbLoopSetBRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::AbLoopSetB);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn ab_loop_clear(
+        &self,
+        _request: Request<AbLoopClearRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::AbLoopClear);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn chapter_next(
+        &self,
+        _request: Request<ChapterNextRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::ChapterNext);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn chapter_previous(
+        &self,
+        _request: Request<ChapterPreviousRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::ChapterPrevious);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn audiobook_jump(
+        &self,
+        request: Request<AudiobookJumpRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let request = request.into_inner();
+        self.command(&PlayerCmd::AudiobookJump(
+            request.file,
+            request.position.map(Into::into).unwrap_or_default(),
+        ));
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn switch_backend(
+        &self,
+        request: Request<SwitchBackendRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let backend = request.into_inner().backend;
+        let kind = if backend.is_empty() {
+            None
+        } else {
+            let kind = backend
+                .parse::<BackendKind>()
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            if !Backend::compiled().contains(&kind) {
+                return Err(Status::unavailable(format!(
+                    "backend \"{kind}\" is not compiled in"
+                )));
+            }
+            Some(kind)
+        };
+        self.command(&PlayerCmd::SwitchBackend(kind));
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn radio_go_live(
+        &self,
+        _request: Request<RadioGoLiveRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        self.command(&PlayerCmd::RadioGoLive);
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn radio_record(
+        &self,
+        _request: Request<RadioRecordRequest>,
+    ) -> Result<Response<RadioRecordReply>, Status> {
+        self.command(&PlayerCmd::RadioRecord);
+        // This is to let the player start or stop recording within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+        let reply = RadioRecordReply {
+            recording: r.radio_recording,
+        };
+
+        Ok(Response::new(reply))
+    }
 }
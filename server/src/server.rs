@@ -8,12 +8,14 @@ use std::time::Duration;
 use anyhow::{Context, Result};
 use clap::Parser;
 use music_player_service::MusicPlayerService;
+use termusiclib::chapters::Chapter;
 use termusiclib::config::Settings;
 use termusiclib::track::MediaType;
 use termusicplayback::player::music_player_server::MusicPlayerServer;
-use termusicplayback::player::{GetProgressResponse, PlayerTime};
+use termusicplayback::player::{GetProgressResponse, OutputDevicesReply, PlayerTime};
 use termusicplayback::{
-    Backend, GeneralPlayer, PlayerCmd, PlayerCmdSender, PlayerProgress, PlayerTrait, Status,
+    AbLoop, Backend, GeneralPlayer, PlayerCmd, PlayerCmdSender, PlayerProgress, PlayerTrait,
+    Status, StreamState,
 };
 use tonic::transport::server::TcpIncoming;
 use tonic::transport::Server;
@@ -34,6 +36,18 @@ struct PlayerStats {
     pub gapless: bool,
     pub current_track_updated: bool,
     pub radio_title: String,
+    pub output_devices: Vec<String>,
+    pub output_device: String,
+    pub ab_loop: AbLoop,
+    /// Chapters of the current track fetched since the last progress response
+    pub chapters: Option<Vec<Chapter>>,
+    pub backend: String,
+    /// Empty while the stream playing is connected
+    pub stream_state: String,
+    /// How far the radio playing is behind its live edge, when the backend can go back in it
+    pub radio_behind_live: Option<Duration>,
+    /// Whether the radio playing is being recorded
+    pub radio_recording: bool,
 }
 
 impl PlayerStats {
@@ -50,6 +64,14 @@ impl PlayerStats {
             gapless: true,
             current_track_updated: false,
             radio_title: String::new(),
+            output_devices: Vec::new(),
+            output_device: String::new(),
+            ab_loop: AbLoop::default(),
+            chapters: None,
+            backend: String::new(),
+            stream_state: String::new(),
+            radio_behind_live: None,
+            radio_recording: false,
         }
     }
 
@@ -63,12 +85,33 @@ impl PlayerStats {
             gapless: self.gapless,
             current_track_updated: self.current_track_updated,
             radio_title: self.radio_title.clone(),
+            ab_loop_a: self.ab_loop.a.map(Into::into),
+            ab_loop_b: self.ab_loop.b.map(Into::into),
+            chapters_updated: self.chapters.is_some(),
+            chapters: self
+                .chapters
+                .iter()
+                .flatten()
+                .cloned()
+                .map(Into::into)
+                .collect(),
+            backend: self.backend.clone(),
+            stream_state: self.stream_state.clone(),
+            radio_behind_live: self.radio_behind_live.map(Into::into),
+            radio_recording: self.radio_recording,
         }
     }
 
     pub fn as_playertime(&self) -> PlayerTime {
         self.progress.into()
     }
+
+    pub fn as_output_devices_reply(&self) -> OutputDevicesReply {
+        OutputDevicesReply {
+            devices: self.output_devices.clone(),
+            current: self.output_device.clone(),
+        }
+    }
 }
 
 fn main() -> Result<()> {
@@ -92,6 +135,7 @@ async fn actual_main() -> Result<()> {
     let music_player_service: MusicPlayerService = MusicPlayerService::new(cmd_tx.clone());
     let mut config = get_config(&args)?;
     let playerstats = music_player_service.player_stats.clone();
+    let spectrum = music_player_service.spectrum.clone();
 
     let cmd_tx_ctrlc = cmd_tx.clone();
     let cmd_tx_ticker = cmd_tx.clone();
@@ -116,6 +160,14 @@ async fn actual_main() -> Result<()> {
 
     let player_handle = tokio::task::spawn_blocking(move || -> Result<()> {
         let mut player = GeneralPlayer::new_backend(args.backend.into(), &config, cmd_tx.clone())?;
+        let mut backend = player.backend_kind();
+        {
+            let mut p_tick = playerstats.lock();
+            p_tick.output_devices = player.output_devices();
+            p_tick.output_device = config.player_output_device.clone();
+            p_tick.backend = backend.to_string();
+        }
+        *spectrum.lock() = player.spectrum_sender();
         // move "cmd_rx" and change to be mutable
         let mut cmd_rx = cmd_rx;
         while let Some(cmd) = cmd_rx.blocking_recv() {
@@ -130,6 +182,46 @@ async fn actual_main() -> Result<()> {
                         player.enqueue_next_from_playlist();
                     }
                 }
+                PlayerCmd::AudiobookJump(file, position) => {
+                    player.audiobook_jump(file, position);
+                }
+                PlayerCmd::AudiobookOpened(book) => {
+                    player.audiobook_opened(book);
+                }
+                PlayerCmd::RadioResolved(url, stream) => {
+                    player.radio_resolved(&url, stream);
+                }
+                PlayerCmd::AbLoopSetA => {
+                    player.ab_loop_set_a();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.ab_loop = player.ab_loop;
+                }
+                PlayerCmd::AbLoopSetB => {
+                    player.ab_loop_set_b();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.ab_loop = player.ab_loop;
+                }
+                PlayerCmd::AbLoopClear => {
+                    player.ab_loop_clear();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.ab_loop = player.ab_loop;
+                }
+                PlayerCmd::ChapterNext => {
+                    player.chapter_next();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.progress = player.get_progress();
+                }
+                PlayerCmd::ChapterPrevious => {
+                    player.chapter_previous();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.progress = player.get_progress();
+                }
+                PlayerCmd::ChaptersFetched(file, chapters) => {
+                    if player.chapters_fetched(&file, chapters.clone()) {
+                        let mut p_tick = playerstats.lock();
+                        p_tick.chapters = Some(chapters);
+                    }
+                }
                 PlayerCmd::Quit => {
                     info!("PlayerCmd::Quit received");
                     player.player_save_last_position();
@@ -154,6 +246,10 @@ async fn actual_main() -> Result<()> {
                         "current track index: {:?}",
                         player.playlist.get_current_track_index()
                     );
+                    if player.radio_stream_failed() {
+                        info!("radio stream failed, playing the next stream of the station");
+                        player.playlist.proceed_false();
+                    }
                     player.playlist.clear_current_track();
                     player.start_play();
                     debug!(
@@ -162,12 +258,38 @@ async fn actual_main() -> Result<()> {
                     );
                 }
                 PlayerCmd::GetProgress | PlayerCmd::ProcessID => {}
+                PlayerCmd::GetOutputDevices => {
+                    let output_devices = player.output_devices();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.output_devices = output_devices;
+                }
+                PlayerCmd::SetOutputDevice(name) => {
+                    info!("switch output device to \"{name}\"");
+                    player.set_output_device(&name);
+                    config.player_output_device = name.clone();
+                    let mut p_tick = playerstats.lock();
+                    p_tick.output_device = name;
+                }
                 PlayerCmd::PlaySelected => {
                     info!("play selected");
                     player.player_save_last_position();
                     player.playlist.proceed_false();
                     player.next();
                 }
+                PlayerCmd::RadioGoLive => {
+                    player.radio_go_live();
+                }
+                PlayerCmd::RadioRecord => {
+                    let is_radio = player
+                        .playlist
+                        .current_track()
+                        .is_some_and(|track| track.media_type == Some(MediaType::LiveRadio));
+                    // only a radio is recorded, but a recording can always be stopped
+                    if is_radio || player.radio_recording() {
+                        player.toggle_radio_record();
+                    }
+                    playerstats.lock().radio_recording = player.radio_recording();
+                }
                 PlayerCmd::SkipPrevious => {
                     info!("skip to previous track");
                     player.player_save_last_position();
@@ -175,6 +297,9 @@ async fn actual_main() -> Result<()> {
                 }
                 PlayerCmd::ReloadConfig => {
                     config.load()?;
+                    if let Err(e) = termusiclib::http::configure(&config) {
+                        error!("error applying the http settings: {e:#}");
+                    }
                     info!("config reloaded");
                     player.config = config.clone();
                 }
@@ -211,12 +336,25 @@ async fn actual_main() -> Result<()> {
                     let mut p_tick = playerstats.lock();
                     p_tick.speed = config.player_speed;
                 }
+                PlayerCmd::SwitchBackend(kind) => {
+                    let kind = kind.unwrap_or_else(|| player.next_backend());
+                    if let Err(e) = player.switch_backend(kind) {
+                        error!("error switching backend: {e}");
+                    }
+                }
                 PlayerCmd::Tick => {
                     // info!("tick received");
                     if config.player_use_mpris {
                         player.update_mpris();
                     }
                     let mut p_tick = playerstats.lock();
+                    // switched on request or for the extension of a track
+                    if player.backend_kind() != backend {
+                        backend = player.backend_kind();
+                        p_tick.backend = backend.to_string();
+                        p_tick.output_devices = player.output_devices();
+                        *spectrum.lock() = player.spectrum_sender();
+                    }
                     p_tick.status = player.playlist.status().as_u32();
                     // branch to auto-start playing if status is "stopped"(not paused) and playlist is not empty anymore
                     if player.playlist.status() == Status::Stopped {
@@ -232,14 +370,24 @@ async fn actual_main() -> Result<()> {
                         player.start_play();
                         continue;
                     }
+                    player.ab_loop_check();
+                    player.audiobook_tick();
+                    p_tick.ab_loop = player.ab_loop;
                     let pprogress = player.get_progress();
                     p_tick.progress = pprogress;
+                    p_tick.stream_state = match player.stream_state() {
+                        StreamState::Connected => String::new(),
+                        state => state.to_string(),
+                    };
                     if player.current_track_updated {
                         p_tick.current_track_index =
                             player.playlist.get_current_track_index() as u32;
                         p_tick.current_track_updated = player.current_track_updated;
                         player.current_track_updated = false;
                     }
+                    p_tick.radio_behind_live = None;
+                    // a recording stops with the radio
+                    p_tick.radio_recording = player.radio_recording();
                     if let Some(track) = player.playlist.current_track() {
                         if let Some(MediaType::LiveRadio) = &track.media_type {
                             // TODO: consider changing "radio_title" and "media_title" to be consistent
@@ -251,13 +399,6 @@ async fn actual_main() -> Result<()> {
                                 #[cfg(feature = "rusty")]
                                 Backend::Rusty(ref mut backend) => {
                                     p_tick.radio_title = backend.radio_title.lock().clone();
-                                    p_tick.progress.total_duration = Some(Duration::from_secs(
-                                        ((*backend.radio_downloaded.lock() as f32 * 44100.0
-                                            / 1000000.0
-                                            / 1024.0)
-                                            * (backend.speed() as f32 / 10.0))
-                                            as u64,
-                                    ));
                                 }
                                 #[cfg(feature = "gst")]
                                 Backend::GStreamer(ref mut backend) => {
@@ -269,6 +410,13 @@ async fn actual_main() -> Result<()> {
                                     // error!("radio title: {}", p_tick.radio_title);
                                 }
                             }
+                            player.radio_history_record(&p_tick.radio_title);
+                            // the window of the radio that can be gone back in, up to its live edge
+                            if let Some(timeshift) = player.radio_timeshift() {
+                                p_tick.progress.position = timeshift.position;
+                                p_tick.progress.total_duration = Some(timeshift.buffered);
+                                p_tick.radio_behind_live = Some(timeshift.behind_live());
+                            }
                         }
                     }
                 }
@@ -344,6 +492,7 @@ fn ticker_thread(cmd_tx: PlayerCmdSender) -> Result<()> {
 fn get_config(args: &cli::Args) -> Result<Settings> {
     let mut config = Settings::default();
     config.load()?;
+    termusiclib::http::configure(&config)?;
 
     config.disable_album_art_from_cli = args.disable_cover;
     config.disable_discord_rpc_from_cli = args.disable_discord;
@@ -357,6 +506,8 @@ fn get_config(args: &cli::Args) -> Result<Settings> {
         None => MAX_DEPTH,
     };
 
+    config.audio_sink_from_cli = args.audio_sink()?;
+
     Ok(config)
 }
 
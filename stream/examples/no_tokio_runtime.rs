@@ -1,6 +1,12 @@
 use parking_lot::Mutex;
 use std::sync::Arc;
-use termusic_stream::StreamDownload;
+use std::time::Duration;
+use termusic_stream::{
+    recorder::Recorder,
+    source::{StreamState, DEFAULT_PREFETCH_BYTES},
+    timeshift::Timeshift,
+    StreamDownload,
+};
 use tracing_subscriber::EnvFilter;
 
 fn main() {
@@ -18,9 +24,14 @@ fn main() {
         "https://dl.espressif.com/dl/audio/ff-16b-2c-44100hz.flac"
             .parse()
             .unwrap(),
+        reqwest::Client::new(),
         true,
         Arc::new(Mutex::new(String::new())),
-        Arc::new(Mutex::new(0_u64)),
+        Timeshift::new(Duration::from_secs(600)),
+        Recorder::new(std::env::temp_dir(), true, str::to_string),
+        Arc::new(Mutex::new(StreamState::default())),
+        DEFAULT_PREFETCH_BYTES,
+        None,
     )
     .unwrap();
 
@@ -0,0 +1,304 @@
+//! Streams kept on disk, so playing or seeking in them again does not download them again.
+//!
+//! An entry is named after the md5 of its url: `<md5>.data` has the bytes downloaded so far at
+//! their place in the stream, and `<md5>.meta` says which bytes those are. The least recently used
+//! entries are removed once the cache grows over its size limit.
+
+use rangemap::RangeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+const DATA_EXTENSION: &str = "data";
+const META_EXTENSION: &str = "meta";
+
+#[derive(Debug, Clone)]
+pub struct StreamCache {
+    dir: PathBuf,
+    /// Bytes the entries may take up together
+    max_bytes: u64,
+}
+
+impl StreamCache {
+    pub fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self { dir, max_bytes }
+    }
+
+    /// Open the entry of `url`, a new one if it was not cached before.
+    ///
+    /// Makes room for it by removing other entries, so the cache stays within its size limit.
+    pub fn open(&self, url: &str) -> io::Result<CacheEntry> {
+        fs::create_dir_all(&self.dir)?;
+        let key = key(url);
+        if let Err(e) = self.evict(&key) {
+            warn!("Error removing old entries of the stream cache: {e}");
+        }
+
+        let data_path = self.path(&key, DATA_EXTENSION);
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&data_path)?;
+        let mut entry = CacheEntry {
+            data_path,
+            meta_path: self.path(&key, META_EXTENSION),
+            meta: Meta {
+                url: url.to_string(),
+                ..Meta::default()
+            },
+        };
+        match Meta::read(&entry.meta_path) {
+            // the same md5 for another url is not likely, but not impossible
+            Ok(meta) if meta.url == url => entry.meta = meta,
+            _ => File::create(&entry.data_path).map(|_| ())?,
+        }
+        // what the meta data has beyond the end of the data was not written after all
+        let data_length = fs::metadata(&entry.data_path)?.len();
+        entry.meta.downloaded.remove(data_length..u64::MAX);
+        // saving it marks it as used
+        entry.save()?;
+        Ok(entry)
+    }
+
+    /// The file with the data of `url`, when all of it is cached.
+    pub fn complete_file(&self, url: &str) -> Option<PathBuf> {
+        let key = key(url);
+        let meta = Meta::read(&self.path(&key, META_EXTENSION)).ok()?;
+        (meta.url == url && meta.is_complete()).then(|| self.path(&key, DATA_EXTENSION))
+    }
+
+    /// Move the data of `url`, all of which is cached, to `path`, taking it out of the cache.
+    ///
+    /// Returns `false` if not all of it is cached.
+    pub fn take_complete(&self, url: &str, path: &Path) -> io::Result<bool> {
+        let Some(data_path) = self.complete_file(url) else {
+            return Ok(false);
+        };
+        // renaming does not work across file systems
+        if fs::rename(&data_path, path).is_err() {
+            fs::copy(&data_path, path)?;
+            fs::remove_file(&data_path)?;
+        }
+        fs::remove_file(self.path(&key(url), META_EXTENSION))?;
+        info!("Moved cached stream {url} to {}", path.display());
+        Ok(true)
+    }
+
+    /// Remove the least recently used entries until the others fit in the size limit, except the
+    /// entry of `keep`.
+    fn evict(&self, keep: &str) -> io::Result<()> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(&self.dir)? {
+            let path = dir_entry?.path();
+            if path.extension() != Some(META_EXTENSION.as_ref()) {
+                continue;
+            }
+            let Some(key) = path
+                .file_stem()
+                .map(|key| key.to_string_lossy().to_string())
+            else {
+                continue;
+            };
+            let used = fs::metadata(&path)?
+                .modified()
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            let size = Meta::read(&path).map_or(0, |meta| meta.size());
+            entries.push((used, size, key));
+        }
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort();
+        for (_, size, key) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if key == keep {
+                continue;
+            }
+            info!("Removing {key} from the stream cache");
+            fs::remove_file(self.path(&key, META_EXTENSION))?;
+            if let Err(e) = fs::remove_file(self.path(&key, DATA_EXTENSION)) {
+                warn!("Error removing cached stream {key}: {e}");
+            }
+            total -= size;
+        }
+        Ok(())
+    }
+
+    fn path(&self, key: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{extension}"))
+    }
+}
+
+/// A stream in the cache, opened with [`StreamCache::open`].
+#[derive(Debug)]
+pub struct CacheEntry {
+    data_path: PathBuf,
+    meta_path: PathBuf,
+    meta: Meta,
+}
+
+impl CacheEntry {
+    /// The file the bytes of the stream are written to and read from.
+    pub fn data_path(&self) -> &Path {
+        &self.data_path
+    }
+
+    /// The ranges of bytes of the stream that are cached.
+    pub fn downloaded(&self) -> &RangeSet<u64> {
+        &self.meta.downloaded
+    }
+
+    /// The length of the stream, when it was known.
+    pub const fn content_length(&self) -> Option<u64> {
+        self.meta.content_length
+    }
+
+    /// Whether all of the stream is cached.
+    pub fn is_complete(&self) -> bool {
+        self.meta.is_complete()
+    }
+
+    /// Remember the length of the stream and what of it is written to [`Self::data_path`].
+    pub fn update(
+        &mut self,
+        content_length: Option<u64>,
+        downloaded: &RangeSet<u64>,
+    ) -> io::Result<()> {
+        self.meta.content_length = content_length;
+        self.meta.downloaded.clone_from(downloaded);
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        fs::write(&self.meta_path, self.meta.to_string())
+    }
+}
+
+/// What the `.meta` file of an entry says.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Meta {
+    url: String,
+    content_length: Option<u64>,
+    downloaded: RangeSet<u64>,
+}
+
+impl Meta {
+    fn read(path: &Path) -> io::Result<Self> {
+        let mut meta = Self::default();
+        for line in fs::read_to_string(path)?.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key {
+                "url" => meta.url = value.to_string(),
+                "length" => meta.content_length = value.parse().ok(),
+                "range" => {
+                    if let Some((Ok(start), Ok(end))) = value
+                        .split_once('-')
+                        .map(|(start, end)| (start.parse(), end.parse()))
+                    {
+                        if start < end {
+                            meta.downloaded.insert(start..end);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(meta)
+    }
+
+    fn is_complete(&self) -> bool {
+        self.content_length
+            .is_some_and(|length| self.downloaded.gaps(&(0..length)).next().is_none())
+    }
+
+    /// Bytes cached.
+    fn size(&self) -> u64 {
+        self.downloaded
+            .iter()
+            .map(|range| range.end - range.start)
+            .sum()
+    }
+}
+
+impl std::fmt::Display for Meta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "url={}", self.url)?;
+        if let Some(length) = self.content_length {
+            writeln!(f, "length={length}")?;
+        }
+        for range in self.downloaded.iter() {
+            writeln!(f, "range={}-{}", range.start, range.end)?;
+        }
+        Ok(())
+    }
+}
+
+fn key(url: &str) -> String {
+    format!("{:x}", md5::compute(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_what_was_downloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = StreamCache::new(dir.path().to_path_buf(), 1000);
+        let url = "https://example.com/episode.mp3";
+
+        let mut entry = cache.open(url).unwrap();
+        assert!(entry.downloaded().is_empty());
+        fs::write(entry.data_path(), [1; 100]).unwrap();
+        let mut downloaded = RangeSet::new();
+        downloaded.insert(0..40);
+        downloaded.insert(60..100);
+        entry.update(Some(100), &downloaded).unwrap();
+        assert_eq!(cache.complete_file(url), None);
+
+        let mut entry = cache.open(url).unwrap();
+        assert_eq!(entry.downloaded(), &downloaded);
+        assert_eq!(entry.content_length(), Some(100));
+        downloaded.insert(40..60);
+        entry.update(Some(100), &downloaded).unwrap();
+        assert_eq!(cache.complete_file(url).as_deref(), Some(entry.data_path()));
+
+        let path = dir.path().join("episode.mp3");
+        assert!(cache.take_complete(url, &path).unwrap());
+        assert_eq!(fs::read(&path).unwrap(), [1; 100]);
+        assert!(cache.open(url).unwrap().downloaded().is_empty());
+    }
+
+    #[test]
+    fn removes_the_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = StreamCache::new(dir.path().to_path_buf(), 250);
+        for (index, url) in ["a", "b", "c"].into_iter().enumerate() {
+            let mut entry = cache.open(url).unwrap();
+            fs::write(entry.data_path(), [0; 100]).unwrap();
+            let mut downloaded = RangeSet::new();
+            downloaded.insert(0..100);
+            entry.update(Some(100), &downloaded).unwrap();
+            // file times are not precise enough to tell entries apart made right after another
+            let used = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(index as u64 + 1);
+            File::options()
+                .write(true)
+                .open(cache.path(&key(url), META_EXTENSION))
+                .unwrap()
+                .set_modified(used)
+                .unwrap();
+        }
+
+        // a new entry makes room for itself
+        cache.open("d").unwrap();
+        assert_eq!(cache.complete_file("a"), None);
+        assert!(cache.complete_file("b").is_some());
+        assert!(cache.complete_file("c").is_some());
+    }
+}
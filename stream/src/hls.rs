@@ -0,0 +1,580 @@
+//! HTTP Live Streaming: a playlist of short segments, which a live stream keeps adding to. The
+//! audio of the segments is handed out as one stream, the transport stream container taken off.
+
+use crate::mpegts::{self, Demuxer};
+use crate::source::{set_radio_title, SourceStream};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::Stream;
+use parking_lot::Mutex;
+use reqwest::{Client, Url};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{
+    io,
+    pin::Pin,
+    task::{self, Poll},
+};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Segments downloaded ahead of what was read
+const SEGMENTS_AHEAD: usize = 3;
+/// How far from the end of a live playlist playing starts, in segments
+const LIVE_START_SEGMENTS: usize = 3;
+/// Failed reloads of a live playlist in a row before giving up
+const MAX_RELOAD_FAILURES: u32 = 5;
+/// Shortest wait before reloading a live playlist, whatever its target duration says
+const MIN_RELOAD_WAIT: Duration = Duration::from_millis(500);
+
+/// Whether `url` points to a HLS playlist, by its extension.
+pub fn is_hls_url(url: &Url) -> bool {
+    url.path().to_lowercase().ends_with(".m3u8")
+}
+
+pub struct HlsStream {
+    segments: mpsc::Receiver<io::Result<Bytes>>,
+    /// What it was created with, to start over when reconnecting
+    url: Url,
+    client: Client,
+    radio_title: Option<Arc<Mutex<String>>>,
+}
+
+impl Stream for HlsStream {
+    type Item = io::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Option<Self::Item>> {
+        self.segments.poll_recv(cx)
+    }
+}
+
+#[async_trait]
+impl SourceStream for HlsStream {
+    type Url = Url;
+    type Error = io::Error;
+
+    async fn create(
+        url: Self::Url,
+        client: Client,
+        is_radio: bool,
+        radio_title: Arc<Mutex<String>>,
+    ) -> io::Result<Self> {
+        let radio_title = is_radio.then_some(radio_title);
+        let (media_url, playlist) = open_media_playlist(&client, url.clone()).await?;
+        if playlist.encrypted {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "encrypted HLS streams are not supported",
+            ));
+        }
+        if playlist.fragmented_mp4 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "HLS streams of fragmented MP4 are not supported",
+            ));
+        }
+        info!(
+            "Playing HLS playlist {media_url}, live: {}",
+            !playlist.end_list
+        );
+        let (tx, segments) = mpsc::channel(SEGMENTS_AHEAD);
+        tokio::spawn(download_segments(
+            client.clone(),
+            media_url,
+            playlist,
+            radio_title.clone(),
+            tx,
+        ));
+        Ok(Self {
+            segments,
+            url,
+            client,
+            radio_title,
+        })
+    }
+
+    async fn content_length(&self) -> Option<u64> {
+        None
+    }
+
+    async fn seek_range(&mut self, _start: u64, _end: Option<u64>) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "HLS streams cannot seek",
+        ))
+    }
+
+    /// Start over from the live end of the playlist, the segments keep their own place in it.
+    async fn reconnect(&mut self, _position: u64) -> io::Result<()> {
+        let radio_title = self.radio_title.clone();
+        let is_radio = radio_title.is_some();
+        *self = Self::create(
+            self.url.clone(),
+            self.client.clone(),
+            is_radio,
+            radio_title.unwrap_or_default(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Fetch the playlist at `url`, and if it lists variants, the playlist of the one chosen for audio.
+async fn open_media_playlist(client: &Client, url: Url) -> io::Result<(Url, MediaPlaylist)> {
+    match Playlist::decode(&fetch_text(client, &url).await?) {
+        Playlist::Media(playlist) => Ok((url, playlist)),
+        Playlist::Master(master) => {
+            let variant = master
+                .audio_uri()
+                .ok_or_else(|| invalid_data("HLS master playlist without variants"))?;
+            let url = url.join(variant).map_err(invalid_data)?;
+            info!("Chose HLS variant {url}");
+            match Playlist::decode(&fetch_text(client, &url).await?) {
+                Playlist::Media(playlist) => Ok((url, playlist)),
+                Playlist::Master(_) => Err(invalid_data("HLS variant is a master playlist")),
+            }
+        }
+    }
+}
+
+/// Send the audio of the segments of `playlist` down `tx`, reloading it until it ends, or until
+/// nothing reads from `tx` anymore.
+async fn download_segments(
+    client: Client,
+    url: Url,
+    mut playlist: MediaPlaylist,
+    radio_title: Option<Arc<Mutex<String>>>,
+    tx: mpsc::Sender<io::Result<Bytes>>,
+) {
+    let mut demuxer = Demuxer::default();
+    let mut next_sequence = if playlist.end_list {
+        playlist.media_sequence
+    } else {
+        let start = playlist.segments.len().saturating_sub(LIVE_START_SEGMENTS);
+        playlist.media_sequence + start as u64
+    };
+    let mut failures = 0;
+    loop {
+        if playlist.media_sequence > next_sequence {
+            warn!(
+                "HLS segments {next_sequence} to {} dropped out of the playlist before they were fetched",
+                playlist.media_sequence - 1
+            );
+            next_sequence = playlist.media_sequence;
+        }
+        let first_new =
+            usize::try_from(next_sequence - playlist.media_sequence).unwrap_or(usize::MAX);
+        let new_segments = playlist.segments.get(first_new..).unwrap_or_default();
+        for segment in new_segments {
+            let audio = match fetch_segment(&client, &url, &segment.uri, &mut demuxer).await {
+                Ok(audio) => audio,
+                Err(e) => {
+                    warn!("Error fetching HLS segment {}: {e}", segment.uri);
+                    next_sequence += 1;
+                    continue;
+                }
+            };
+            if let (Some(radio_title), Some(title)) = (&radio_title, &segment.title) {
+                set_radio_title(radio_title, title);
+            }
+            if tx.send(Ok(audio)).await.is_err() {
+                return;
+            }
+            next_sequence += 1;
+        }
+        if playlist.end_list {
+            info!("HLS playlist ended");
+            return;
+        }
+
+        tokio::time::sleep(reload_wait(&playlist, !new_segments.is_empty())).await;
+        match fetch_text(&client, &url)
+            .await
+            .map(|text| Playlist::decode(&text))
+        {
+            Ok(Playlist::Media(reloaded)) => {
+                failures = 0;
+                playlist = reloaded;
+            }
+            Ok(Playlist::Master(_)) => {
+                tx.send(Err(invalid_data(
+                    "HLS playlist turned into a master playlist",
+                )))
+                .await
+                .ok();
+                return;
+            }
+            Err(e) => {
+                failures += 1;
+                warn!("Error reloading HLS playlist ({failures}/{MAX_RELOAD_FAILURES}): {e}");
+                if failures >= MAX_RELOAD_FAILURES {
+                    tx.send(Err(e)).await.ok();
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// How long to wait before reloading the live `playlist`, after it had new segments or not.
+fn reload_wait(playlist: &MediaPlaylist, changed: bool) -> Duration {
+    // a playlist that did not change is asked for again sooner
+    let wait = if changed {
+        playlist.target_duration
+    } else {
+        playlist.target_duration / 2
+    };
+    wait.max(MIN_RELOAD_WAIT)
+}
+
+async fn fetch_segment(
+    client: &Client,
+    playlist_url: &Url,
+    uri: &str,
+    demuxer: &mut Demuxer,
+) -> io::Result<Bytes> {
+    let url = playlist_url.join(uri).map_err(invalid_data)?;
+    let data = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(other)?
+        .bytes()
+        .await
+        .map_err(other)?;
+    if mpegts::is_transport_stream(&data) {
+        return Ok(demuxer.demux(&data).into());
+    }
+    // packed audio, with its timestamp in an id3 tag in front
+    let audio = skip_id3(&data);
+    Ok(data.slice_ref(audio))
+}
+
+async fn fetch_text(client: &Client, url: &Url) -> io::Result<String> {
+    client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(other)?
+        .text()
+        .await
+        .map_err(other)
+}
+
+/// `data` without the ID3v2 tags at its start.
+fn skip_id3(mut data: &[u8]) -> &[u8] {
+    while data.len() >= 10 && data.starts_with(b"ID3") {
+        // the size is 4 bytes of 7 bits each, and does not count the header and footer
+        let size = data[6..10]
+            .iter()
+            .fold(0, |size, byte| size << 7 | usize::from(byte & 0x7F));
+        let footer = if data[5] & 0x10 == 0 { 0 } else { 10 };
+        data = data.get(10 + size + footer..).unwrap_or_default();
+    }
+    data
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn other(error: reqwest::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+#[derive(Debug, PartialEq)]
+enum Playlist {
+    /// Lists variants of the stream, each with a media playlist of its own
+    Master(MasterPlaylist),
+    Media(MediaPlaylist),
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct MasterPlaylist {
+    variants: Vec<Variant>,
+    /// Audio to go with the variants, from `EXT-X-MEDIA`
+    audio_renditions: Vec<Rendition>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Variant {
+    uri: String,
+    bandwidth: u64,
+    codecs: Option<String>,
+    /// Only video has a resolution
+    has_resolution: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Rendition {
+    uri: String,
+    default: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct MediaPlaylist {
+    target_duration: Duration,
+    /// Number of the first segment
+    media_sequence: u64,
+    segments: Vec<Segment>,
+    /// No segments are added anymore, which is not the case for live streams
+    end_list: bool,
+    encrypted: bool,
+    /// Segments are fragments of a MP4, whose header is in `EXT-X-MAP`
+    fragmented_mp4: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct Segment {
+    uri: String,
+    /// What `EXTINF` says after the duration, the song on some radios
+    title: Option<String>,
+}
+
+impl Playlist {
+    fn decode(content: &str) -> Self {
+        let mut master = MasterPlaylist::default();
+        let mut media = MediaPlaylist {
+            target_duration: Duration::from_secs(10),
+            media_sequence: 0,
+            segments: Vec::new(),
+            end_list: false,
+            encrypted: false,
+            fragmented_mp4: false,
+        };
+        let mut is_master = false;
+        // the tag the next uri line belongs to
+        let mut stream_inf: Option<Vec<(&str, &str)>> = None;
+        let mut title: Option<String> = None;
+
+        for line in content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let Some(tag) = line.strip_prefix('#') else {
+                if let Some(attributes) = stream_inf.take() {
+                    master.variants.push(Variant::new(line, &attributes));
+                } else {
+                    media.segments.push(Segment {
+                        uri: line.to_string(),
+                        title: title.take(),
+                    });
+                }
+                continue;
+            };
+            let (name, value) = tag.split_once(':').unwrap_or((tag, ""));
+            match name {
+                "EXT-X-STREAM-INF" => {
+                    is_master = true;
+                    stream_inf = Some(attributes(value));
+                }
+                "EXT-X-MEDIA" => {
+                    let attributes = attributes(value);
+                    if let (Some("AUDIO"), Some(uri)) = (
+                        attribute(&attributes, "TYPE"),
+                        attribute(&attributes, "URI"),
+                    ) {
+                        master.audio_renditions.push(Rendition {
+                            uri: uri.to_string(),
+                            default: attribute(&attributes, "DEFAULT") == Some("YES"),
+                        });
+                    }
+                }
+                "EXT-X-TARGETDURATION" => {
+                    if let Ok(seconds) = value.trim().parse() {
+                        media.target_duration = Duration::from_secs(seconds);
+                    }
+                }
+                "EXT-X-MEDIA-SEQUENCE" => {
+                    media.media_sequence = value.trim().parse().unwrap_or_default();
+                }
+                "EXTINF" => {
+                    title = value
+                        .split_once(',')
+                        .map(|(_, title)| title.trim())
+                        .filter(|title| !title.is_empty())
+                        .map(str::to_string);
+                }
+                "EXT-X-ENDLIST" => media.end_list = true,
+                "EXT-X-KEY" => {
+                    media.encrypted = attribute(&attributes(value), "METHOD") != Some("NONE");
+                }
+                "EXT-X-MAP" => media.fragmented_mp4 = true,
+                _ => {}
+            }
+        }
+
+        if is_master {
+            Self::Master(master)
+        } else {
+            Self::Media(media)
+        }
+    }
+}
+
+impl Variant {
+    fn new(uri: &str, attributes: &[(&str, &str)]) -> Self {
+        Self {
+            uri: uri.to_string(),
+            bandwidth: attribute(attributes, "BANDWIDTH")
+                .and_then(|bandwidth| bandwidth.parse().ok())
+                .unwrap_or_default(),
+            codecs: attribute(attributes, "CODECS").map(str::to_string),
+            has_resolution: attribute(attributes, "RESOLUTION").is_some(),
+        }
+    }
+
+    fn is_audio_only(&self) -> bool {
+        const AUDIO_CODECS: [&str; 4] = ["mp4a", "mp3", "flac", "opus"];
+        !self.has_resolution
+            && self.codecs.as_deref().is_some_and(|codecs| {
+                codecs.split(',').all(|codec| {
+                    AUDIO_CODECS
+                        .iter()
+                        .any(|audio| codec.trim().starts_with(audio))
+                })
+            })
+    }
+}
+
+impl MasterPlaylist {
+    /// The playlist to get the audio from: the best variant without video, or else the audio
+    /// rendition of the variants, or else the smallest variant, to take its audio out.
+    fn audio_uri(&self) -> Option<&str> {
+        let audio_only = self
+            .variants
+            .iter()
+            .filter(|variant| variant.is_audio_only())
+            .max_by_key(|variant| variant.bandwidth);
+        let rendition = self
+            .audio_renditions
+            .iter()
+            .find(|rendition| rendition.default)
+            .or_else(|| self.audio_renditions.first());
+        let smallest = self.variants.iter().min_by_key(|variant| variant.bandwidth);
+
+        audio_only
+            .map(|variant| variant.uri.as_str())
+            .or(rendition.map(|rendition| rendition.uri.as_str()))
+            .or(smallest.map(|variant| variant.uri.as_str()))
+    }
+}
+
+/// The `KEY=VALUE` pairs of an attribute list, with the quotes taken off values.
+fn attributes(list: &str) -> Vec<(&str, &str)> {
+    let mut attributes = Vec::new();
+    let mut rest = list.trim();
+    while let Some((key, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            // a quoted value may have commas
+            Some(quoted) => {
+                let (value, next) = quoted.split_once('"').unwrap_or((quoted, ""));
+                (value, next.trim_start_matches(','))
+            }
+            None => value.split_once(',').unwrap_or((value, "")),
+        };
+        attributes.push((key.trim(), value));
+        rest = next.trim_start();
+    }
+    attributes
+}
+
+fn attribute<'a>(attributes: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, value)| *value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chooses_the_audio_variant() {
+        let content = r#"#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=1280000,RESOLUTION=640x360,CODECS="avc1.42e00a,mp4a.40.2"
+video/low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS="mp4a.40.5"
+audio/64.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=128000,CODECS="mp4a.40.2"
+audio/128.m3u8
+"#;
+        let Playlist::Master(master) = Playlist::decode(content) else {
+            panic!("not a master playlist");
+        };
+        assert_eq!(master.variants.len(), 3);
+        assert_eq!(master.audio_uri(), Some("audio/128.m3u8"));
+
+        let content = r#"#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID="aac",NAME="English",DEFAULT=YES,URI="audio/en.m3u8"
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,CODECS="avc1.4d401f,mp4a.40.2",AUDIO="aac"
+video/high.m3u8
+"#;
+        let Playlist::Master(master) = Playlist::decode(content) else {
+            panic!("not a master playlist");
+        };
+        assert_eq!(master.audio_uri(), Some("audio/en.m3u8"));
+    }
+
+    #[test]
+    fn decodes_a_live_playlist() {
+        let content = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-TARGETDURATION:6
+#EXT-X-MEDIA-SEQUENCE:2680
+
+#EXTINF:5.975,Artist - Song
+https://example.com/segment2680.aac
+#EXTINF:5.975,
+segment2681.ts
+";
+        assert_eq!(
+            Playlist::decode(content),
+            Playlist::Media(MediaPlaylist {
+                target_duration: Duration::from_secs(6),
+                media_sequence: 2680,
+                segments: vec![
+                    Segment {
+                        uri: "https://example.com/segment2680.aac".to_string(),
+                        title: Some("Artist - Song".to_string()),
+                    },
+                    Segment {
+                        uri: "segment2681.ts".to_string(),
+                        title: None,
+                    },
+                ],
+                end_list: false,
+                encrypted: false,
+                fragmented_mp4: false,
+            })
+        );
+    }
+
+    #[test]
+    fn waits_before_reloading() {
+        let Playlist::Media(mut playlist) = Playlist::decode("#EXTM3U\n#EXT-X-TARGETDURATION:6\n")
+        else {
+            panic!("not a media playlist");
+        };
+        assert_eq!(reload_wait(&playlist, true), Duration::from_secs(6));
+        assert_eq!(reload_wait(&playlist, false), Duration::from_secs(3));
+
+        // a target duration of 0 is not taken as reloading right away
+        playlist.target_duration = Duration::ZERO;
+        assert_eq!(reload_wait(&playlist, true), MIN_RELOAD_WAIT);
+        assert_eq!(reload_wait(&playlist, false), MIN_RELOAD_WAIT);
+    }
+
+    #[test]
+    fn skips_id3_tags() {
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x01\x01".to_vec();
+        data.extend([0; 129]);
+        data.extend(b"\xFF\xF1audio");
+        assert_eq!(skip_id3(&data), b"\xFF\xF1audio");
+        assert_eq!(skip_id3(b"\xFF\xF1audio"), b"\xFF\xF1audio");
+    }
+}
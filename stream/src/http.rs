@@ -1,9 +1,11 @@
-use crate::source::SourceStream;
+use crate::icy::IcyDemuxer;
+use crate::ogg::OggComments;
+use crate::source::{set_radio_title, SourceStream};
 use async_trait::async_trait;
 use bytes::Bytes;
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use parking_lot::Mutex;
-use reqwest::Client;
+use reqwest::{header, Client, RequestBuilder, Response, StatusCode};
 use std::sync::Arc;
 use std::{
     io,
@@ -13,14 +15,17 @@ use std::{
 };
 use tracing::{info, warn};
 
-const STONG_TITLE_ERROR: &str = "Error Please Try Again";
-const CHUNKS_BEFORE_START: u8 = 20;
+type BytesStream = Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Unpin + Send + Sync>;
 
 pub struct HttpStream {
-    stream: Box<dyn Stream<Item = Result<Bytes, reqwest::Error>> + Unpin + Send + Sync>,
+    stream: BytesStream,
     client: Client,
     content_length: Option<u64>,
     url: reqwest::Url,
+    /// Where the titles of a radio go, `None` for anything else
+    radio_title: Option<Arc<Mutex<String>>>,
+    /// The bitrate in kbit/s a radio tells in its `icy-br` header
+    bitrate: Option<u32>,
 }
 
 impl Stream for HttpStream {
@@ -38,13 +43,13 @@ impl SourceStream for HttpStream {
 
     async fn create(
         url: Self::Url,
+        client: Client,
         is_radio: bool,
         radio_title: Arc<Mutex<String>>,
     ) -> io::Result<Self> {
-        let client = Client::new();
+        let radio_title = is_radio.then_some(radio_title);
         info!("Requesting content length");
-        let response = client
-            .get(url.as_str())
+        let response = request(&client, &url, radio_title.is_some())
             .send()
             .await
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
@@ -61,121 +66,21 @@ impl SourceStream for HttpStream {
         } else {
             warn!("Content length header missing");
         }
-        let stream = response.bytes_stream();
-        let mut count_down = CHUNKS_BEFORE_START;
-        let url_inside = url.clone();
-        if is_radio {
-            let mut should_restart = true;
-            let client_inside = Client::new();
-            tokio::spawn(async move {
-                loop {
-                    let mut response = match client_inside
-                        .get(url_inside.as_str())
-                        .header("icy-metadata", "1")
-                        .send()
-                        .await
-                    {
-                        Ok(t) => t,
-                        Err(_) => {
-                            *radio_title.lock() = STONG_TITLE_ERROR.to_string();
-                            continue;
-                        }
-                    };
-                    if let Some(header_value) = response.headers().get("content-type") {
-                        if header_value.to_str().unwrap_or_default() != "audio/mpeg" {
-                            *radio_title.lock() = STONG_TITLE_ERROR.to_string();
-                            continue;
-                        }
-                    } else {
-                        *radio_title.lock() = STONG_TITLE_ERROR.to_string();
-                        continue;
-                    }
-                    let meta_interval: usize =
-                        if let Some(header_value) = response.headers().get("icy-metaint") {
-                            header_value
-                                .to_str()
-                                .unwrap_or_default()
-                                .parse()
-                                .unwrap_or_default()
-                        } else {
-                            0
-                        };
-                    let mut counter = meta_interval;
-                    let mut awaiting_metadata_size = false;
-                    let mut metadata_size: u8 = 0;
-                    let mut awaiting_metadata = false;
-                    let mut metadata: Vec<u8> = Vec::new();
-                    let mut title_string = String::new();
-                    while let Some(chunk) = response.chunk().await.expect("Couldn't get next chunk")
-                    {
-                        for byte in &chunk {
-                            if meta_interval != 0 {
-                                if awaiting_metadata_size {
-                                    awaiting_metadata_size = false;
-                                    metadata_size = *byte * 16;
-                                    if metadata_size == 0 {
-                                        counter = meta_interval;
-                                    } else {
-                                        awaiting_metadata = true;
-                                    }
-                                } else if awaiting_metadata {
-                                    metadata.push(*byte);
-                                    metadata_size = metadata_size.saturating_sub(1);
-                                    if metadata_size == 0 {
-                                        awaiting_metadata = false;
-                                        let metadata_string =
-                                            std::str::from_utf8(&metadata).unwrap_or("");
-                                        if !metadata_string.is_empty() {
-                                            const STREAM_TITLE_KEYWORD: &str = "StreamTitle='";
-                                            if let Some(index) =
-                                                metadata_string.find(STREAM_TITLE_KEYWORD)
-                                            {
-                                                let left_index = index + 13;
-                                                let stream_title_substring =
-                                                    &metadata_string[left_index..];
-                                                if let Some(right_index) =
-                                                    stream_title_substring.find('\'')
-                                                {
-                                                    let trimmed_song_title =
-                                                        &stream_title_substring[..right_index];
-                                                    title_string += " ";
-                                                    title_string += trimmed_song_title;
-                                                    *radio_title.lock() =
-                                                        format!("Current playing: {title_string}");
-                                                }
-                                            }
-                                        }
-                                        metadata.clear();
-                                        counter = meta_interval;
-                                    }
-                                } else {
-                                    // file.write_all(&[*byte]).expect("Couldn't write to file");
-                                    counter = counter.saturating_sub(1);
-                                    if counter == 0 {
-                                        awaiting_metadata_size = true;
-                                    }
-                                }
-                            } else {
-                                // file.write_all(&[*byte]).expect("Couldn't write to file");
-                            }
-                        }
-                        if should_restart {
-                            if count_down == 0 {
-                                should_restart = false;
-                                title_string = String::new();
-                            } else {
-                                count_down -= 1;
-                            }
-                        }
-                    }
-                }
-            });
-        }
+        // "128", or "128,128" from some servers
+        let bitrate = response
+            .headers()
+            .get("icy-br")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next()?.trim().parse().ok())
+            .filter(|bitrate| *bitrate > 0);
+        let stream = body_stream(response, radio_title.clone());
         Ok(Self {
-            stream: Box::new(stream),
+            stream,
             client,
             content_length,
             url,
+            radio_title,
+            bitrate,
         })
     }
 
@@ -185,9 +90,7 @@ impl SourceStream for HttpStream {
 
     async fn seek_range(&mut self, start: u64, end: Option<u64>) -> io::Result<()> {
         info!("Seeking: {start}-{end:?}");
-        let response = self
-            .client
-            .get(self.url.as_str())
+        let response = request(&self.client, &self.url, self.radio_title.is_some())
             .header(
                 "Range",
                 format!(
@@ -204,8 +107,157 @@ impl SourceStream for HttpStream {
                 .map(|_| ())
                 .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()));
         }
-        self.stream = Box::new(response.bytes_stream());
+        // a server that ignores the range sends all of it, which must not be written at `start`
+        if served_from(&response) != Some(start) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "server did not serve the range from byte {start}, status {}",
+                    response.status()
+                ),
+            ));
+        }
+        self.stream = body_stream(response, self.radio_title.clone());
         info!("Done seeking");
         Ok(())
     }
+
+    fn bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    fn is_live(&self) -> bool {
+        self.radio_title.is_some()
+    }
+
+    async fn reconnect(&mut self, position: u64) -> io::Result<()> {
+        if self.radio_title.is_none() {
+            return self.seek_range(position, None).await;
+        }
+        // a radio goes on from what it plays now
+        let response = request(&self.client, &self.url, true)
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+        self.stream = body_stream(response, self.radio_title.clone());
+        Ok(())
+    }
+}
+
+/// The byte the body of `response` starts at, `None` when it does not tell.
+fn served_from(response: &Response) -> Option<u64> {
+    match response.status() {
+        StatusCode::OK => Some(0),
+        StatusCode::PARTIAL_CONTENT => response
+            .headers()
+            .get(header::CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(content_range_start),
+        _ => None,
+    }
+}
+
+/// The first byte of a `Content-Range` like `bytes 100-199/1000`.
+fn content_range_start(content_range: &str) -> Option<u64> {
+    let range = content_range.trim().strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// A request for `url`, which asks a radio for ICY metadata.
+fn request(client: &Client, url: &reqwest::Url, is_radio: bool) -> RequestBuilder {
+    let request = client.get(url.as_str());
+    if is_radio {
+        request.header("icy-metadata", "1")
+    } else {
+        request
+    }
+}
+
+/// The body of `response`, with the titles of a radio taken out of it into `radio_title`.
+fn body_stream(response: Response, radio_title: Option<Arc<Mutex<String>>>) -> BytesStream {
+    let Some(radio_title) = radio_title else {
+        return Box::new(response.bytes_stream());
+    };
+    let mut metadata = RadioMetadata::new(&response, radio_title);
+    Box::new(
+        response
+            .bytes_stream()
+            .map(move |chunk| chunk.map(|chunk| metadata.push(chunk))),
+    )
+}
+
+/// Reads the titles of a radio: ICY metadata between the audio, which is taken out, and the
+/// comment headers of Ogg streams.
+struct RadioMetadata {
+    icy: Option<IcyDemuxer>,
+    ogg: Option<OggComments>,
+    /// Whether the start of the audio was looked at for an Ogg stream the headers did not tell of
+    sniffed: bool,
+    radio_title: Arc<Mutex<String>>,
+}
+
+impl RadioMetadata {
+    fn new(response: &Response, radio_title: Arc<Mutex<String>>) -> Self {
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let icy = header("icy-metaint")
+            .and_then(|interval| interval.trim().parse().ok())
+            .filter(|interval| *interval > 0)
+            .map(IcyDemuxer::new);
+        let content_type = header("content-type").unwrap_or_default().to_lowercase();
+        let is_ogg = content_type.contains("ogg") || content_type.contains("opus");
+        info!(
+            "Radio metadata: icy {}, ogg {is_ogg}, content type {content_type}",
+            icy.is_some()
+        );
+        Self {
+            icy,
+            ogg: is_ogg.then(OggComments::default),
+            sniffed: is_ogg,
+            radio_title,
+        }
+    }
+
+    /// The audio of `chunk`, after reading the titles in it.
+    fn push(&mut self, chunk: Bytes) -> Bytes {
+        let audio = match &mut self.icy {
+            Some(icy) => {
+                let mut audio = Vec::with_capacity(chunk.len());
+                if let Some(title) = icy.push(&chunk, &mut audio) {
+                    set_radio_title(&self.radio_title, &title);
+                }
+                Bytes::from(audio)
+            }
+            None => chunk,
+        };
+        if !self.sniffed && !audio.is_empty() {
+            self.sniffed = true;
+            if OggComments::is_ogg(&audio) {
+                self.ogg = Some(OggComments::default());
+            }
+        }
+        if let Some(title) = self.ogg.as_mut().and_then(|ogg| ogg.push(&audio)) {
+            set_radio_title(&self.radio_title, &title);
+        }
+        audio
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_content_range_start() {
+        assert_eq!(content_range_start("bytes 100-199/1000"), Some(100));
+        assert_eq!(content_range_start("bytes 0-99/*"), Some(0));
+        assert_eq!(content_range_start("bytes */1000"), None);
+        assert_eq!(content_range_start("items 1-2/3"), None);
+    }
 }
@@ -0,0 +1,114 @@
+//! Shoutcast/Icecast metadata: with `icy-metadata: 1` asked for, a radio puts a block of metadata,
+//! like the title of the song playing, between its audio every `icy-metaint` bytes.
+
+/// Takes the metadata out of the stream of a radio, leaving the audio.
+#[derive(Debug)]
+pub struct IcyDemuxer {
+    /// Bytes of audio between metadata blocks
+    interval: usize,
+    /// Bytes of audio until the next metadata block
+    audio_left: usize,
+    /// Length of the metadata block being read, once its length byte is
+    metadata_length: Option<usize>,
+    metadata: Vec<u8>,
+}
+
+impl IcyDemuxer {
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval,
+            audio_left: interval,
+            metadata_length: None,
+            metadata: Vec::new(),
+        }
+    }
+
+    /// Add the audio of `chunk` to `audio`, and return the title of the last metadata block that
+    /// was done in `chunk`, if it had one.
+    pub fn push(&mut self, mut chunk: &[u8], audio: &mut Vec<u8>) -> Option<String> {
+        let mut title = None;
+        while !chunk.is_empty() {
+            match self.metadata_length {
+                None if self.audio_left > 0 => {
+                    let length = self.audio_left.min(chunk.len());
+                    audio.extend_from_slice(&chunk[..length]);
+                    self.audio_left -= length;
+                    chunk = &chunk[length..];
+                }
+                // the length byte counts in 16 byte blocks
+                None => {
+                    self.metadata_length = Some(usize::from(chunk[0]) * 16);
+                    chunk = &chunk[1..];
+                }
+                Some(length) => {
+                    let length = (length - self.metadata.len()).min(chunk.len());
+                    self.metadata.extend_from_slice(&chunk[..length]);
+                    chunk = &chunk[length..];
+                }
+            }
+
+            if self.metadata_length == Some(self.metadata.len()) {
+                if let Some(block_title) = stream_title(&self.metadata) {
+                    title = Some(block_title);
+                }
+                self.metadata.clear();
+                self.metadata_length = None;
+                self.audio_left = self.interval;
+            }
+        }
+        title
+    }
+}
+
+/// The `StreamTitle` of a metadata block like `StreamTitle='Artist - Song';StreamUrl='';`, which
+/// is padded with zeros.
+fn stream_title(metadata: &[u8]) -> Option<String> {
+    const KEY: &str = "StreamTitle='";
+
+    let metadata = match std::str::from_utf8(metadata) {
+        Ok(metadata) => metadata.to_string(),
+        // not every radio uses utf-8, the others are taken for latin-1
+        Err(_) => metadata.iter().map(|b| char::from(*b)).collect(),
+    };
+    let metadata = metadata.trim_end_matches('\0');
+    let start = metadata.find(KEY)? + KEY.len();
+    let rest = &metadata[start..];
+    // titles can have quotes of their own
+    let end = rest.find("';").or_else(|| rest.rfind('\''))?;
+    let title = rest[..end].trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(metadata: &str) -> Vec<u8> {
+        let mut block = metadata.as_bytes().to_vec();
+        block.resize((metadata.len() + 15) / 16 * 16, 0);
+        let mut data = vec![u8::try_from(block.len() / 16).unwrap()];
+        data.extend(block);
+        data
+    }
+
+    #[test]
+    fn takes_the_metadata_out() {
+        let mut data = b"aaaa".to_vec();
+        data.extend(block("StreamTitle='Artist - It's a Song';StreamUrl='';"));
+        data.extend(b"bbbb");
+        data.push(0);
+        data.extend(b"cc");
+
+        // in any chunks
+        for size in [1, 3, data.len()] {
+            let mut demuxer = IcyDemuxer::new(4);
+            let mut audio = Vec::new();
+            let mut titles = Vec::new();
+            for chunk in data.chunks(size) {
+                titles.extend(demuxer.push(chunk, &mut audio));
+            }
+            assert_eq!(audio, b"aaaabbbbcc", "chunk size {size}");
+            assert_eq!(titles, ["Artist - It's a Song"], "chunk size {size}");
+        }
+    }
+}
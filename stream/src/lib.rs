@@ -1,6 +1,10 @@
+use cache::{CacheEntry, StreamCache};
 use parking_lot::Mutex;
-use source::{Source, SourceHandle, SourceStream};
+use recorder::Recorder;
+use source::{Source, SourceHandle, SourceStream, StreamState};
+use std::fs::{File, OpenOptions};
 use std::sync::Arc;
+use std::time::Duration;
 use std::{
     io::{self, BufReader, Read, Seek, SeekFrom},
     thread,
@@ -8,45 +12,125 @@ use std::{
 use symphonia::core::io::MediaSource;
 use tap::{Tap, TapFallible};
 use tempfile::NamedTempFile;
-use tracing::{debug, error};
+use timeshift::Timeshift;
+use tracing::{debug, error, warn};
 
+pub mod cache;
+pub mod hls;
 pub mod http;
+mod icy;
+mod mpegts;
+mod ogg;
+pub mod recorder;
 pub mod source;
+pub mod timeshift;
 
 #[derive(Debug)]
 pub struct StreamDownload {
-    output_reader: BufReader<NamedTempFile>,
+    output_reader: BufReader<File>,
+    /// The file downloaded to, when the stream is not cached, which is removed with it
+    _tempfile: Option<NamedTempFile>,
     handle: SourceHandle,
     pub radio_title: Arc<Mutex<String>>,
+    /// Where playing a radio is behind its live edge, `None` for anything else
+    timeshift: Option<Timeshift>,
 }
 
 impl StreamDownload {
+    /// Stream `url` over HTTP with `client`, as HLS when it points to a `.m3u8` playlist.
+    ///
+    /// With a `cache`, what was streamed of `url` before is read from it rather than downloaded
+    /// again. Radios and HLS streams are not cached, as they are not the same the next time.
+    ///
+    /// A radio is played through `timeshift` and recorded with `recorder`, which start over for
+    /// it.
+    #[allow(clippy::too_many_arguments)]
     pub fn new_http(
         url: reqwest::Url,
+        client: reqwest::Client,
         is_radio: bool,
         radio_title: Arc<Mutex<String>>,
-        radio_downloaded: Arc<Mutex<u64>>,
+        timeshift: Timeshift,
+        recorder: Recorder,
+        stream_state: Arc<Mutex<StreamState>>,
+        prefetch_bytes: u64,
+        cache: Option<&StreamCache>,
     ) -> io::Result<Self> {
-        Self::new::<http::HttpStream>(url, is_radio, radio_title, radio_downloaded)
+        if hls::is_hls_url(&url) {
+            return Self::new::<hls::HlsStream>(
+                url,
+                client,
+                is_radio,
+                radio_title,
+                timeshift,
+                recorder,
+                stream_state,
+                prefetch_bytes,
+                None,
+            );
+        }
+        let cache = cache.filter(|_| !is_radio).and_then(|cache| {
+            cache
+                .open(url.as_str())
+                .map_err(|e| warn!("Error opening the stream cache: {e}"))
+                .ok()
+        });
+        Self::new::<http::HttpStream>(
+            url,
+            client,
+            is_radio,
+            radio_title,
+            timeshift,
+            recorder,
+            stream_state,
+            prefetch_bytes,
+            cache,
+        )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn new<S: SourceStream>(
         url: S::Url,
+        client: reqwest::Client,
         is_radio: bool,
         radio_title: Arc<Mutex<String>>,
-        radio_downloaded: Arc<Mutex<u64>>,
+        timeshift: Timeshift,
+        recorder: Recorder,
+        stream_state: Arc<Mutex<StreamState>>,
+        prefetch_bytes: u64,
+        cache: Option<CacheEntry>,
     ) -> io::Result<Self> {
-        let tempfile = tempfile::Builder::new().tempfile()?;
-        let source = Source::new(tempfile.reopen()?);
+        let (source, output_reader, tempfile) = open_source(prefetch_bytes, cache)?;
         let handle = source.source_handle();
+        if source.is_cached() {
+            debug!("Streaming from the cache");
+            source.serve_cached();
+            return Ok(Self {
+                output_reader,
+                _tempfile: tempfile,
+                handle,
+                radio_title,
+                timeshift: None,
+            });
+        }
+        let timeshift = is_radio.then(|| {
+            timeshift.restart();
+            timeshift
+        });
+        let recorder = is_radio.then(|| {
+            recorder.restart(radio_title.clone());
+            recorder
+        });
         let radio_title_inside = radio_title.clone();
-        let radio_downloaded_inside1 = radio_downloaded.clone();
+        let timeshift_inside = timeshift.clone();
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
-                let stream = S::create(url, is_radio, radio_title_inside)
+                let stream = S::create(url, client, is_radio, radio_title_inside)
                     .await
                     .tap_err(|e| error!("Error creating stream: {e}"))?;
-                source.download(stream, radio_downloaded_inside1).await?;
+                source
+                    .download(stream, timeshift_inside, recorder, stream_state)
+                    .await?;
                 Ok::<_, io::Error>(())
             });
         } else {
@@ -56,35 +140,47 @@ impl StreamDownload {
                     .build()
                     .tap_err(|e| error!("Error creating tokio runtime: {e}"))?;
                 rt.block_on(async move {
-                    let stream = S::create(url, is_radio, radio_title_inside)
+                    let stream = S::create(url, client, is_radio, radio_title_inside)
                         .await
                         .tap_err(|e| error!("Error creating stream {e}"))?;
-                    source.download(stream, radio_downloaded).await?;
+                    source
+                        .download(stream, timeshift_inside, recorder, stream_state)
+                        .await?;
                     Ok::<_, io::Error>(())
                 })?;
                 Ok::<_, io::Error>(())
             });
         };
         Ok(Self {
-            output_reader: BufReader::new(tempfile),
+            output_reader,
+            _tempfile: tempfile,
             handle,
             radio_title,
+            timeshift,
         })
     }
 
     pub fn from_stream<S: SourceStream>(
         stream: S,
         radio_title: Arc<Mutex<String>>,
-        radio_downloaded: Arc<Mutex<u64>>,
+        timeshift: Option<Timeshift>,
+        recorder: Option<Recorder>,
+        stream_state: Arc<Mutex<StreamState>>,
+        prefetch_bytes: u64,
     ) -> Result<Self, io::Error> {
-        let tempfile = tempfile::Builder::new().tempfile()?;
-        let source = Source::new(tempfile.reopen()?);
+        let (source, output_reader, tempfile) = open_source(prefetch_bytes, None)?;
         let handle = source.source_handle();
-        let radio_downloaded_inside1 = radio_downloaded.clone();
+        if let Some(timeshift) = &timeshift {
+            timeshift.restart();
+        }
+        if let Some(recorder) = &recorder {
+            recorder.restart(radio_title.clone());
+        }
+        let timeshift_inside = timeshift.clone();
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 source
-                    .download(stream, radio_downloaded_inside1)
+                    .download(stream, timeshift_inside, recorder, stream_state)
                     .await
                     .tap_err(|e| error!("Error downloading stream: {e}"))?;
                 Ok::<_, io::Error>(())
@@ -97,7 +193,7 @@ impl StreamDownload {
                     .tap_err(|e| error!("Error creating tokio runtime: {e}"))?;
                 rt.block_on(async move {
                     source
-                        .download(stream, radio_downloaded)
+                        .download(stream, timeshift_inside, recorder, stream_state)
                         .await
                         .tap_err(|e| error!("Error downloading stream: {e}"))?;
                     Ok::<_, io::Error>(())
@@ -106,15 +202,62 @@ impl StreamDownload {
             });
         };
         Ok(Self {
-            output_reader: BufReader::new(tempfile),
+            output_reader,
+            _tempfile: tempfile,
             handle,
             radio_title,
+            timeshift,
         })
     }
+
+    /// Block until the start of the stream is downloaded, so playing it does not stall.
+    /// Returns `false` when that took longer than `timeout`.
+    pub fn wait_for_prefetch(&self, timeout: Duration) -> bool {
+        self.handle.wait_for_prefetch(timeout)
+    }
+
+    /// Whether the stream could not be opened or its download stopped before the prefetch was
+    /// done, so reading it gives nothing.
+    pub fn failed(&self) -> bool {
+        self.handle.failed()
+    }
+}
+
+/// The source to download to, writing to the file of `cache` or else to a temporary one, and the
+/// reader of that file.
+fn open_source(
+    prefetch_bytes: u64,
+    cache: Option<CacheEntry>,
+) -> io::Result<(Source, BufReader<File>, Option<NamedTempFile>)> {
+    if let Some(cache) = cache {
+        let writer = OpenOptions::new().write(true).open(cache.data_path())?;
+        let reader = File::open(cache.data_path())?;
+        let source = Source::new(writer, prefetch_bytes).with_cache(cache);
+        return Ok((source, BufReader::new(reader), None));
+    }
+    let tempfile = tempfile::Builder::new().tempfile()?;
+    let source = Source::new(tempfile.reopen()?, prefetch_bytes);
+    Ok((source, BufReader::new(tempfile.reopen()?), Some(tempfile)))
 }
 
 impl Read for StreamDownload {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(timeshift) = self.timeshift.clone() else {
+            return self.read_downloaded(buf);
+        };
+        if let Some(position) = timeshift.take_jump() {
+            debug!("Timeshift to byte {position}");
+            self.output_reader.seek(SeekFrom::Start(position))?;
+        }
+        let length = self.read_downloaded(buf)?;
+        timeshift.read(self.output_reader.stream_position()?);
+        Ok(length)
+    }
+}
+
+impl StreamDownload {
+    /// Read from the download, waiting for what is not downloaded yet.
+    fn read_downloaded(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         debug!("Read request buf len: {}", buf.len());
         let stream_position = self.output_reader.stream_position()?;
         let requested_position = stream_position + buf.len() as u64;
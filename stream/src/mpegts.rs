@@ -0,0 +1,199 @@
+//! Pull the audio out of MPEG transport streams, the container of most HLS segments, as the raw
+//! stream of ADTS AAC or MPEG audio frames the decoder can read on its own.
+
+const PACKET_SIZE: usize = 188;
+const SYNC_BYTE: u8 = 0x47;
+/// The program association table, which leads to the program map table
+const PAT_PID: u16 = 0;
+
+/// Stream types in a program map table that carry audio the decoder understands: MPEG-1 and
+/// MPEG-2 audio, and AAC in ADTS frames
+const AUDIO_STREAM_TYPES: [u8; 3] = [0x03, 0x04, 0x0F];
+
+/// Whether `data` starts like a transport stream.
+pub fn is_transport_stream(data: &[u8]) -> bool {
+    data.len() >= PACKET_SIZE
+        && data[0] == SYNC_BYTE
+        && (data.len() == PACKET_SIZE || data[PACKET_SIZE] == SYNC_BYTE)
+}
+
+/// Demuxes the first audio stream of the first program, a segment at a time.
+///
+/// The tables are kept between segments, as not every segment has to repeat them.
+#[derive(Debug, Default)]
+pub struct Demuxer {
+    pmt_pid: Option<u16>,
+    audio_pid: Option<u16>,
+    /// The PES packet being put together from transport packets
+    pes: Vec<u8>,
+}
+
+impl Demuxer {
+    /// The audio in the transport packets of `data`.
+    ///
+    /// A PES packet does not span segments, so the last one is done at the end of `data`.
+    pub fn demux(&mut self, data: &[u8]) -> Vec<u8> {
+        let mut audio = Vec::with_capacity(data.len());
+        for packet in data.chunks_exact(PACKET_SIZE) {
+            if packet[0] != SYNC_BYTE {
+                continue;
+            }
+            let unit_start = packet[1] & 0x40 != 0;
+            let pid = u16::from(packet[1] & 0x1F) << 8 | u16::from(packet[2]);
+            let adaptation = (packet[3] >> 4) & 0x3;
+            let mut start = 4;
+            if adaptation & 0x2 != 0 {
+                start += 1 + usize::from(packet[4]);
+            }
+            if adaptation & 0x1 == 0 || start >= PACKET_SIZE {
+                continue;
+            }
+            let payload = &packet[start..];
+
+            if pid == PAT_PID {
+                if unit_start {
+                    self.pmt_pid = psi_section(payload).and_then(pmt_pid).or(self.pmt_pid);
+                }
+            } else if Some(pid) == self.pmt_pid {
+                if unit_start {
+                    self.audio_pid = psi_section(payload).and_then(audio_pid).or(self.audio_pid);
+                }
+            } else if Some(pid) == self.audio_pid {
+                if unit_start {
+                    self.flush(&mut audio);
+                    self.pes.extend_from_slice(payload);
+                } else if !self.pes.is_empty() {
+                    // continuations of a packet whose start was missed are dropped
+                    self.pes.extend_from_slice(payload);
+                }
+            }
+        }
+        self.flush(&mut audio);
+        audio
+    }
+
+    fn flush(&mut self, audio: &mut Vec<u8>) {
+        if let Some(payload) = pes_payload(&self.pes) {
+            audio.extend_from_slice(payload);
+        }
+        self.pes.clear();
+    }
+}
+
+/// The table section of a PSI payload, which starts with a pointer to it.
+fn psi_section(payload: &[u8]) -> Option<&[u8]> {
+    let pointer = usize::from(*payload.first()?);
+    let section = payload.get(1 + pointer..)?;
+    let length = usize::from(u16::from(section.get(1)? & 0x0F) << 8 | u16::from(*section.get(2)?));
+    // the length counts from after itself, and includes the crc at the end
+    section.get(..(3 + length).checked_sub(4)?)
+}
+
+/// The PID of the map table of the first program in a program association table.
+fn pmt_pid(section: &[u8]) -> Option<u16> {
+    section.get(8..)?.chunks_exact(4).find_map(|program| {
+        let number = u16::from(program[0]) << 8 | u16::from(program[1]);
+        // program 0 points to the network information table
+        (number != 0).then_some(u16::from(program[2] & 0x1F) << 8 | u16::from(program[3]))
+    })
+}
+
+/// The PID of the first audio stream in a program map table.
+fn audio_pid(section: &[u8]) -> Option<u16> {
+    let info_length =
+        usize::from(u16::from(section.get(10)? & 0x0F) << 8 | u16::from(*section.get(11)?));
+    let mut streams = section.get(12 + info_length..)?;
+    while streams.len() >= 5 {
+        let stream_type = streams[0];
+        let pid = u16::from(streams[1] & 0x1F) << 8 | u16::from(streams[2]);
+        let es_info_length = usize::from(u16::from(streams[3] & 0x0F) << 8 | u16::from(streams[4]));
+        if AUDIO_STREAM_TYPES.contains(&stream_type) {
+            return Some(pid);
+        }
+        streams = streams.get(5 + es_info_length..)?;
+    }
+    None
+}
+
+/// The elementary stream data of a PES packet, after its header.
+fn pes_payload(pes: &[u8]) -> Option<&[u8]> {
+    if !pes.starts_with(&[0, 0, 1]) {
+        return None;
+    }
+    // audio streams always have the optional header
+    let header_length = usize::from(*pes.get(8)?);
+    pes.get(9 + header_length..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A transport packet of `pid`, with the payload padded by an adaptation field.
+    fn packet(pid: u16, unit_start: bool, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![
+            SYNC_BYTE,
+            u8::from(unit_start) << 6 | (pid >> 8) as u8,
+            pid as u8,
+            0x30,
+        ];
+        let stuffing = PACKET_SIZE - 4 - 1 - payload.len();
+        packet.push(stuffing as u8);
+        if stuffing > 0 {
+            packet.push(0);
+            packet.resize(packet.len() + stuffing - 1, 0xFF);
+        }
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// A PSI section with `body` after the section header, and a crc that is not checked.
+    fn section(table_id: u8, body: &[u8]) -> Vec<u8> {
+        let length = 5 + body.len() + 4;
+        let mut section = vec![0, table_id, 0xB0 | (length >> 8) as u8, length as u8];
+        section.extend_from_slice(&[0, 1, 0xC1, 0, 0]);
+        section.extend_from_slice(body);
+        section.extend_from_slice(&[0; 4]);
+        section
+    }
+
+    #[test]
+    fn demuxes_the_audio() {
+        let pat = section(0x00, &[0, 1, 0xE1, 0x00]);
+        // a video stream before the audio one, which has a descriptor
+        let pmt = section(
+            0x02,
+            &[
+                0xE1, 0x01, 0xF0, 0, 0x1B, 0xE1, 0x01, 0xF0, 0, 0x0F, 0xE1, 0x02, 0xF0, 2, 9, 9,
+            ],
+        );
+        let pes_header = [0, 0, 1, 0xC0, 0, 0, 0x80, 0x80, 5, 1, 2, 3, 4, 5];
+
+        let mut data = packet(PAT_PID, true, &pat);
+        data.extend(packet(0x100, true, &pmt));
+        data.extend(packet(
+            0x101,
+            true,
+            &[0, 0, 1, 0xE0, 0, 0, 0x80, 0, 0, 7, 7],
+        ));
+        data.extend(packet(
+            0x102,
+            true,
+            &[pes_header.as_slice(), b"ab"].concat(),
+        ));
+        data.extend(packet(0x102, false, b"cd"));
+        data.extend(packet(
+            0x102,
+            true,
+            &[pes_header.as_slice(), b"ef"].concat(),
+        ));
+
+        assert!(is_transport_stream(&data));
+        let mut demuxer = Demuxer::default();
+        assert_eq!(demuxer.demux(&data), b"abcdef");
+
+        // the tables are remembered for the next segment
+        let next = packet(0x102, true, &[pes_header.as_slice(), b"gh"].concat());
+        assert_eq!(demuxer.demux(&next), b"gh");
+    }
+}
@@ -0,0 +1,219 @@
+//! Titles in Ogg streams: a radio streaming Vorbis or Opus starts a new logical stream for every
+//! song, whose comment header has the title of it.
+
+/// Comment headers are read up to this size, beyond that it is mostly cover art
+const MAX_COMMENT_BYTES: usize = 1024 * 1024;
+
+/// Reads the titles out of the comment headers of the Ogg pages pushed to it.
+#[derive(Debug, Default)]
+pub struct OggComments {
+    /// Bytes of a page not yet complete
+    buffer: Vec<u8>,
+    /// The comment header being put together, when the packet being read is one
+    packet: Option<Vec<u8>>,
+}
+
+impl OggComments {
+    /// Whether `data` starts like an Ogg stream.
+    pub fn is_ogg(data: &[u8]) -> bool {
+        data.starts_with(b"OggS")
+    }
+
+    /// Read the pages done with `chunk`, and return the title of the last comment header in them.
+    pub fn push(&mut self, chunk: &[u8]) -> Option<String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut title = None;
+        loop {
+            // drop what is not a page, like after joining a stream in the middle of one
+            let Some(start) = self
+                .buffer
+                .windows(4)
+                .position(|capture| capture == b"OggS")
+            else {
+                let keep = self.buffer.len().min(3);
+                self.buffer.drain(..self.buffer.len() - keep);
+                return title;
+            };
+            self.buffer.drain(..start);
+
+            let Some(segments) = self.buffer.get(26).map(|count| usize::from(*count)) else {
+                return title;
+            };
+            let Some(lacing) = self.buffer.get(27..27 + segments) else {
+                return title;
+            };
+            let lacing = lacing.to_vec();
+            let page_length = 27 + segments + lacing.iter().map(|l| usize::from(*l)).sum::<usize>();
+            if self.buffer.len() < page_length {
+                return title;
+            }
+            let page: Vec<u8> = self.buffer.drain(..page_length).collect();
+            if let Some(page_title) = self.read_page(&page, &lacing) {
+                title = Some(page_title);
+            }
+        }
+    }
+
+    fn read_page(&mut self, page: &[u8], lacing: &[u8]) -> Option<String> {
+        let continued = page[5] & 0x01 != 0;
+        if !continued {
+            // the end of the packet was lost
+            self.packet = None;
+        }
+        let mut title = None;
+        let mut offset = 27 + lacing.len();
+        let mut packet_start = !continued;
+        for length in lacing.iter().map(|l| usize::from(*l)) {
+            let segment = &page[offset..offset + length];
+            offset += length;
+            if packet_start {
+                self.packet = is_comment_header(segment).then(Vec::new);
+            }
+            if let Some(packet) = &mut self.packet {
+                if packet.len() + segment.len() <= MAX_COMMENT_BYTES {
+                    packet.extend_from_slice(segment);
+                }
+            }
+            // a segment shorter than 255 bytes ends a packet
+            packet_start = length < 255;
+            if packet_start {
+                if let Some(packet) = self.packet.take() {
+                    title = comments_title(&packet).or(title);
+                }
+            }
+        }
+        title
+    }
+}
+
+fn is_comment_header(packet: &[u8]) -> bool {
+    packet.starts_with(b"\x03vorbis") || packet.starts_with(b"OpusTags")
+}
+
+/// `ARTIST - TITLE` of a Vorbis or Opus comment header, or just the title.
+fn comments_title(packet: &[u8]) -> Option<String> {
+    let magic = if packet.starts_with(b"OpusTags") {
+        8
+    } else {
+        7
+    };
+    let mut rest = packet.get(magic..)?;
+    let vendor_length = take_length(&mut rest)?;
+    take(&mut rest, vendor_length)?;
+
+    let mut title = None;
+    let mut artist = None;
+    for _ in 0..take_length(&mut rest)? {
+        let length = take_length(&mut rest)?;
+        // comments after `MAX_COMMENT_BYTES` are cut off
+        let Some(comment) = take(&mut rest, length) else {
+            break;
+        };
+        let comment = String::from_utf8_lossy(comment);
+        let Some((key, value)) = comment.split_once('=') else {
+            continue;
+        };
+        if key.eq_ignore_ascii_case("TITLE") {
+            title = Some(value.to_string());
+        } else if key.eq_ignore_ascii_case("ARTIST") {
+            artist = Some(value.to_string());
+        }
+    }
+
+    match (artist, title) {
+        (Some(artist), Some(title)) => Some(format!("{artist} - {title}")),
+        (None, Some(title)) => Some(title),
+        _ => None,
+    }
+}
+
+/// Take `length` bytes off the front of `data`.
+fn take<'a>(data: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+    if data.len() < length {
+        return None;
+    }
+    let (taken, rest) = data.split_at(length);
+    *data = rest;
+    Some(taken)
+}
+
+/// Take a little endian `u32` length off the front of `data`.
+fn take_length(data: &mut &[u8]) -> Option<usize> {
+    let bytes = take(data, 4)?.try_into().ok()?;
+    usize::try_from(u32::from_le_bytes(bytes)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ogg pages of `packets`, with at most `max_segments` segments a page.
+    fn pages(packets: &[Vec<u8>], max_segments: usize) -> Vec<u8> {
+        let mut segments: Vec<&[u8]> = Vec::new();
+        for packet in packets {
+            segments.extend(packet.chunks(255));
+            if packet.len() % 255 == 0 {
+                segments.push(&[]);
+            }
+        }
+        let mut data = Vec::new();
+        let mut continued = false;
+        for page in segments.chunks(max_segments) {
+            data.extend(b"OggS\0");
+            data.push(u8::from(continued));
+            data.extend([0; 20]);
+            data.push(u8::try_from(page.len()).unwrap());
+            data.extend(
+                page.iter()
+                    .map(|segment| u8::try_from(segment.len()).unwrap()),
+            );
+            for segment in page {
+                data.extend(*segment);
+            }
+            continued = page.last().is_some_and(|segment| segment.len() == 255);
+        }
+        data
+    }
+
+    fn comment_header(comments: &[&str]) -> Vec<u8> {
+        let mut packet = b"OpusTags".to_vec();
+        packet.extend(6u32.to_le_bytes());
+        packet.extend(b"vendor");
+        packet.extend(u32::try_from(comments.len()).unwrap().to_le_bytes());
+        for comment in comments {
+            packet.extend(u32::try_from(comment.len()).unwrap().to_le_bytes());
+            packet.extend(comment.as_bytes());
+        }
+        packet
+    }
+
+    #[test]
+    fn reads_the_titles() {
+        let long = format!("DESCRIPTION={}", "x".repeat(1000));
+        let data = pages(
+            &[
+                b"OpusHead".to_vec(),
+                comment_header(&["title=Song", long.as_str(), "ARTIST=Artist"]),
+                vec![1; 600],
+                comment_header(&["TITLE=Next Song"]),
+                vec![2; 100],
+            ],
+            3,
+        );
+        assert!(OggComments::is_ogg(&data));
+
+        for size in [1, 100] {
+            let mut comments = OggComments::default();
+            let titles: Vec<String> = data
+                .chunks(size)
+                .filter_map(|chunk| comments.push(chunk))
+                .collect();
+            assert_eq!(titles, ["Artist - Song", "Next Song"], "chunk size {size}");
+        }
+        // only the last title of a chunk is of interest
+        assert_eq!(
+            OggComments::default().push(&data).as_deref(),
+            Some("Next Song")
+        );
+    }
+}
@@ -0,0 +1,513 @@
+//! Recording a radio to files, a new one for every song.
+//!
+//! What is recorded is the audio of the stream as it is downloaded for playing, so recording
+//! does not connect again and goes on while playing is paused or gone back in. A new file is
+//! started when the title of the radio changes, named after it, at the first frame of the new song
+//! for MP3 and AAC and at the start of its logical stream for Ogg. MP3 and AAC files get an ID3 tag
+//! with the artist and title read from it; Ogg streams carry their own comments.
+
+use crate::source::strip_radio_title;
+use id3::TagLike;
+use parking_lot::Mutex;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Records the radio playing, when turned on.
+#[derive(Clone)]
+pub struct Recorder(Arc<Mutex<State>>);
+
+struct State {
+    /// Where the recordings go
+    dir: PathBuf,
+    /// Leave out the song playing when recording starts, and the one playing when it stops, as
+    /// only a part of them is recorded
+    skip_partial: bool,
+    /// Makes a title safe to use as a file name
+    sanitize: fn(&str) -> String,
+    recording: bool,
+    /// The title of the radio, as its stream sets it
+    radio_title: Arc<Mutex<String>>,
+    /// The title of the song the last audio was of, `None` before any audio came
+    title: Option<String>,
+    /// The song being written
+    file: Option<Recording>,
+    /// Of the files, found from the first audio recorded
+    extension: Option<&'static str>,
+    /// The audio last pushed, not written yet as the next song may start in it: the page that
+    /// starts an Ogg stream comes before the comments with its title
+    held: Vec<u8>,
+}
+
+struct Recording {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn new(dir: PathBuf, skip_partial: bool, sanitize: fn(&str) -> String) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            dir,
+            skip_partial,
+            sanitize,
+            recording: false,
+            radio_title: Arc::default(),
+            title: None,
+            file: None,
+            extension: None,
+            held: Vec::new(),
+        })))
+    }
+
+    /// Stop recording the radio played before, to record the one whose title goes to
+    /// `radio_title` next.
+    pub(crate) fn restart(&self, radio_title: Arc<Mutex<String>>) {
+        self.stop();
+        let mut state = self.0.lock();
+        state.radio_title = radio_title;
+        state.title = None;
+        state.extension = None;
+    }
+
+    /// Whether the radio is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.0.lock().recording
+    }
+
+    /// Start recording the radio playing, or stop it. Returns whether it is recording now.
+    pub fn toggle(&self) -> bool {
+        if self.is_recording() {
+            self.stop();
+            false
+        } else {
+            info!("Recording the radio to {}", self.0.lock().dir.display());
+            self.0.lock().recording = true;
+            true
+        }
+    }
+
+    /// Stop recording, removing the song being written if it is partial and those are skipped.
+    pub fn stop(&self) {
+        let mut state = self.0.lock();
+        if !state.recording {
+            return;
+        }
+        state.recording = false;
+        let skip = state.skip_partial;
+        let held = mem::take(&mut state.held);
+        if let Some(mut recording) = state.file.take() {
+            if let Err(e) = recording.writer.write_all(&held) {
+                error!("Error writing {}: {e}", recording.path.display());
+            }
+            recording.finish(skip);
+        }
+        info!("Stopped recording the radio");
+    }
+
+    /// Record `audio`, the next bytes of the radio.
+    pub(crate) fn push(&self, audio: &[u8]) {
+        let mut state = self.0.lock();
+        let title = strip_radio_title(&state.radio_title.lock()).to_string();
+        let changed = state.title.as_ref().is_some_and(|last| *last != title);
+        state.title = Some(title);
+        if !state.recording {
+            return;
+        }
+        if let Err(e) = state.write(audio, changed) {
+            error!("Error recording the radio: {e}");
+            state.file = None;
+            state.held.clear();
+            state.recording = false;
+        }
+    }
+}
+
+impl State {
+    /// Write the audio held back and hold back `audio` instead, or if the song `changed` in
+    /// `audio`, write up to where the new song starts and go on in a new file.
+    fn write(&mut self, audio: &[u8], changed: bool) -> io::Result<()> {
+        let held = self.held.len();
+        let mut data = mem::take(&mut self.held);
+        data.extend_from_slice(audio);
+        let extension = *self.extension.get_or_insert_with(|| extension(&data));
+        let start = if changed {
+            song_start(&data, held, extension)
+        } else {
+            held
+        };
+        self.held = data.split_off(held.max(start));
+        let (old, new) = data.split_at(start);
+
+        if let Some(recording) = &mut self.file {
+            recording.writer.write_all(old)?;
+        }
+        if changed {
+            if let Some(recording) = self.file.take() {
+                recording.finish(false);
+            }
+        }
+        if self.file.is_none() {
+            // recording started in the middle of the song
+            if self.skip_partial && !changed {
+                return Ok(());
+            }
+            self.file = Some(self.create(extension)?);
+        }
+        if let Some(recording) = &mut self.file {
+            recording.writer.write_all(new)?;
+        }
+        Ok(())
+    }
+
+    /// A new file for the song of [`Self::title`].
+    fn create(&self, extension: &str) -> io::Result<Recording> {
+        let title = self.title.clone().unwrap_or_default();
+        let name = match (self.sanitize)(&title) {
+            name if name.trim().is_empty() => "radio".to_string(),
+            name => name,
+        };
+        fs::create_dir_all(&self.dir)?;
+        let path = free_path(&self.dir, &name, extension);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        if matches!(extension, "mp3" | "aac") {
+            let (artist, title) = split_title(&title);
+            let mut tag = id3::Tag::new();
+            if let Some(artist) = artist {
+                tag.set_artist(artist);
+            }
+            tag.set_title(title);
+            tag.write_to(&mut writer, id3::Version::Id3v24)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+        info!("Recording {}", path.display());
+        Ok(Recording { path, writer })
+    }
+}
+
+impl Recording {
+    /// Close the file, or remove it when it is `partial`.
+    fn finish(mut self, partial: bool) {
+        if partial {
+            info!("Removing the partial recording {}", self.path.display());
+            drop(self.writer);
+            if let Err(e) = fs::remove_file(&self.path) {
+                warn!("Error removing {}: {e}", self.path.display());
+            }
+            return;
+        }
+        if let Err(e) = self.writer.flush() {
+            error!("Error writing {}: {e}", self.path.display());
+        }
+        info!("Recorded {}", self.path.display());
+    }
+}
+
+/// `name.extension` in `dir`, or `name (2).extension` and so on if that is taken.
+fn free_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{name}.{extension}"));
+    let mut number = 2;
+    while path.exists() {
+        path = dir.join(format!("{name} ({number}).{extension}"));
+        number += 1;
+    }
+    path
+}
+
+/// The artist and title of a radio title like `Artist - Title`.
+fn split_title(title: &str) -> (Option<&str>, &str) {
+    match title.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() && !title.trim().is_empty() => {
+            (Some(artist.trim()), title.trim())
+        }
+        _ => (None, title.trim()),
+    }
+}
+
+/// The extension of the files of a stream, from some of its audio.
+///
+/// The audio may start anywhere in a frame, so MPEG and ADTS frame headers are counted rather
+/// than looked for at the start.
+fn extension(audio: &[u8]) -> &'static str {
+    let contains = |needle: &[u8]| audio.windows(needle.len()).any(|window| window == needle);
+    if contains(b"OggS") {
+        return if contains(b"OpusHead") { "opus" } else { "ogg" };
+    }
+    if contains(b"fLaC") {
+        return "flac";
+    }
+    let (mut mpeg, mut adts) = (0, 0);
+    for pair in audio.windows(2) {
+        if pair[0] != 0xFF {
+            continue;
+        }
+        // ADTS has the layer bits MPEG audio has reserved
+        if pair[1] & 0xF6 == 0xF0 {
+            adts += 1;
+        } else if pair[1] & 0xE0 == 0xE0 && pair[1] & 0x06 != 0 {
+            mpeg += 1;
+        }
+    }
+    if adts > mpeg {
+        "aac"
+    } else {
+        "mp3"
+    }
+}
+
+/// Where a new song starts in `data`, the title having changed in what comes from `from` on: at
+/// the last page that begins an Ogg stream, or else at the first frame from `from` on. `from` if
+/// there is none.
+fn song_start(data: &[u8], from: usize, extension: &str) -> usize {
+    let start = match extension {
+        "ogg" | "opus" => {
+            let page = |header: &[u8]| header.starts_with(b"OggS\0");
+            data.windows(6)
+                .rposition(|header| page(header) && header[5] & 0x02 != 0)
+                .or_else(|| data[from..].windows(6).position(page).map(|at| from + at))
+        }
+        "mp3" => first_frame(data, from, mpeg_frame_len),
+        "aac" => first_frame(data, from, adts_frame_len),
+        _ => None,
+    };
+    start.unwrap_or(from)
+}
+
+/// The first frame in `data` from `from` on, whose length `frame_len` reads from its header.
+/// Frames are taken only when another one follows them, if there is room for it.
+fn first_frame(data: &[u8], from: usize, frame_len: fn(&[u8]) -> Option<usize>) -> Option<usize> {
+    (from..data.len()).find(|&start| {
+        frame_len(&data[start..]).is_some_and(|len| {
+            data.get(start + len..)
+                .filter(|next| next.len() >= 7)
+                .map_or(true, |next| frame_len(next).is_some())
+        })
+    })
+}
+
+/// The length of the MPEG audio frame `header` starts, `None` if it is no frame header.
+fn mpeg_frame_len(header: &[u8]) -> Option<usize> {
+    const BITRATES: [[u16; 14]; 5] = [
+        // version 1, layers I, II and III
+        [
+            32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+        ],
+        [
+            32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+        ],
+        [
+            32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+        ],
+        // versions 2 and 2.5, layer I, then layers II and III
+        [
+            32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+        ],
+        [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    ];
+    const SAMPLE_RATES: [u32; 3] = [44100, 48000, 32000];
+
+    let [0xFF, second, third, ..] = *header else {
+        return None;
+    };
+    // version: 0 is 2.5, 1 is reserved, 2 is 2 and 3 is 1, layer: 0 is reserved, 3 is I
+    let (version, layer) = ((second >> 3) & 0x03, (second >> 1) & 0x03);
+    let (bitrate, rate, padding) = (third >> 4, (third >> 2) & 0x03, u32::from((third >> 1) & 1));
+    if second & 0xE0 != 0xE0 || version == 1 || layer == 0 || bitrate == 0 || bitrate == 15 {
+        return None;
+    }
+    let layer = 3 - usize::from(layer);
+    let table = match (version, layer) {
+        (3, _) => layer,
+        (_, 0) => 3,
+        _ => 4,
+    };
+    let bitrate = u32::from(BITRATES[table][usize::from(bitrate) - 1]) * 1000;
+    let rate = SAMPLE_RATES.get(usize::from(rate))? >> (3 - version.max(1));
+    let len = match layer {
+        0 => (12 * bitrate / rate + padding) * 4,
+        2 if version != 3 => 72 * bitrate / rate + padding,
+        _ => 144 * bitrate / rate + padding,
+    };
+    usize::try_from(len).ok()
+}
+
+/// The length of the ADTS frame `header` starts, `None` if it is no frame header.
+fn adts_frame_len(header: &[u8]) -> Option<usize> {
+    let [0xFF, second, third, fourth, fifth, sixth, ..] = *header else {
+        return None;
+    };
+    if second & 0xF6 != 0xF0 || (third >> 2) & 0x0F > 12 {
+        return None;
+    }
+    let len =
+        (usize::from(fourth & 0x03) << 11) | (usize::from(fifth) << 3) | usize::from(sixth >> 5);
+    (len >= 7).then_some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(name: &str) -> String {
+        name.replace('/', "")
+    }
+
+    fn set_title(recorder: &Recorder, title: &str) {
+        *recorder.0.lock().radio_title.lock() = format!("Current playing: {title}");
+    }
+
+    /// An MP3 frame of 128 kbit/s at 44.1 kHz, filled with `fill`.
+    fn mp3_frame(fill: u8) -> Vec<u8> {
+        let mut frame = vec![fill; 417];
+        frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        frame
+    }
+
+    /// The audio of a recorded MP3, after its ID3 tag.
+    fn mp3_audio(path: &Path) -> Vec<u8> {
+        let file = fs::read(path).unwrap();
+        let size = file[6..10]
+            .iter()
+            .fold(0, |size, byte| (size << 7) | usize::from(*byte));
+        file[10 + size..].to_vec()
+    }
+
+    /// An Ogg page with no segments, starting a logical stream if `bos`.
+    fn ogg_page(bos: bool, serial: u8) -> Vec<u8> {
+        let mut page = b"OggS\0".to_vec();
+        page.push(if bos { 0x02 } else { 0 });
+        page.extend_from_slice(&[0; 8]);
+        page.extend_from_slice(&[serial, 0, 0, 0]);
+        page.extend_from_slice(&[0; 9]);
+        page
+    }
+
+    fn files(dir: &Path) -> Vec<String> {
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn splits_songs_and_skips_partial_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::new(dir.path().to_path_buf(), true, sanitize);
+        recorder.restart(Arc::default());
+        set_title(&recorder, "Artist - First");
+        recorder.push(&mp3_frame(0));
+        assert!(recorder.toggle());
+        recorder.push(&mp3_frame(1));
+        set_title(&recorder, "Artist - Second");
+        recorder.push(&mp3_frame(2));
+        set_title(&recorder, "Third");
+        recorder.push(&mp3_frame(3));
+        assert!(!recorder.toggle());
+        recorder.push(&mp3_frame(4));
+
+        assert_eq!(files(dir.path()), ["Artist - Second.mp3"]);
+        let path = dir.path().join("Artist - Second.mp3");
+        let tag = id3::Tag::read_from_path(&path).unwrap();
+        assert_eq!(tag.artist(), Some("Artist"));
+        assert_eq!(tag.title(), Some("Second"));
+        assert!(fs::read(&path).unwrap().ends_with(&mp3_frame(2)));
+    }
+
+    #[test]
+    fn splits_mp3_at_a_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::new(dir.path().to_path_buf(), false, sanitize);
+        recorder.restart(Arc::default());
+        set_title(&recorder, "First");
+        assert!(recorder.toggle());
+        let audio = [mp3_frame(1), mp3_frame(1), mp3_frame(2), mp3_frame(2)].concat();
+        recorder.push(&audio[..600]);
+        // the new song starts at the first frame after where the chunks were cut
+        set_title(&recorder, "Second");
+        recorder.push(&audio[600..]);
+        recorder.stop();
+
+        assert_eq!(
+            mp3_audio(&dir.path().join("First.mp3")),
+            [mp3_frame(1), mp3_frame(1)].concat()
+        );
+        assert_eq!(
+            mp3_audio(&dir.path().join("Second.mp3")),
+            [mp3_frame(2), mp3_frame(2)].concat()
+        );
+    }
+
+    #[test]
+    fn splits_ogg_at_the_new_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::new(dir.path().to_path_buf(), false, sanitize);
+        recorder.restart(Arc::default());
+        set_title(&recorder, "First");
+        assert!(recorder.toggle());
+        recorder.push(&[ogg_page(true, 1), ogg_page(false, 1)].concat());
+        recorder.push(&[ogg_page(false, 1), ogg_page(true, 2)].concat());
+        // the title is in the comments, after the page starting the stream
+        set_title(&recorder, "Second");
+        recorder.push(&[ogg_page(false, 2), ogg_page(false, 2)].concat());
+        recorder.stop();
+
+        assert_eq!(
+            fs::read(dir.path().join("First.ogg")).unwrap(),
+            [ogg_page(true, 1), ogg_page(false, 1), ogg_page(false, 1)].concat()
+        );
+        assert_eq!(
+            fs::read(dir.path().join("Second.ogg")).unwrap(),
+            [ogg_page(true, 2), ogg_page(false, 2), ogg_page(false, 2)].concat()
+        );
+    }
+
+    #[test]
+    fn keeps_partial_songs() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::new(dir.path().to_path_buf(), false, sanitize);
+        recorder.restart(Arc::default());
+        set_title(&recorder, "AC/DC - Song");
+        assert!(recorder.toggle());
+        recorder.push(b"OggS");
+        set_title(&recorder, "AC/DC - Song");
+        recorder.push(b"OggS");
+        recorder.stop();
+        set_title(&recorder, "AC/DC - Song");
+        assert!(recorder.toggle());
+        recorder.push(b"OggS");
+        recorder.restart(Arc::default());
+        assert!(!recorder.is_recording());
+
+        assert_eq!(
+            files(dir.path()),
+            ["ACDC - Song (2).ogg", "ACDC - Song.ogg"]
+        );
+        assert_eq!(
+            fs::read(dir.path().join("ACDC - Song.ogg")).unwrap(),
+            b"OggSOggS"
+        );
+    }
+
+    #[test]
+    fn tells_the_format() {
+        assert_eq!(extension(&[0, 0xFF, 0xF1, 0x50, 0xFF, 0xF1]), "aac");
+        assert_eq!(extension(&[0, 0xFF, 0xFB, 0x90, 0xFF, 0xFB]), "mp3");
+        assert_eq!(extension(b"..OggS\0\x02....OpusHead"), "opus");
+        assert_eq!(mpeg_frame_len(&[0xFF, 0xFB, 0x90, 0x00]), Some(417));
+        assert_eq!(mpeg_frame_len(&[0xFF, 0xFB, 0x92, 0x00]), Some(418));
+        assert_eq!(mpeg_frame_len(&[0xFF, 0xF3, 0x90, 0x00]), Some(261));
+        assert_eq!(mpeg_frame_len(&[0xFF, 0xFB, 0xF0, 0x00]), None);
+        assert_eq!(mpeg_frame_len(&[0xFF, 0xF1, 0x50, 0x80]), None);
+        assert_eq!(
+            adts_frame_len(&[0xFF, 0xF1, 0x50, 0x80, 0x2E, 0x7F]),
+            Some(371)
+        );
+        assert_eq!(adts_frame_len(&[0xFF, 0xFB, 0x90, 0x00, 0x00, 0x00]), None);
+        assert_eq!(split_title("Artist - Title"), (Some("Artist"), "Title"));
+        assert_eq!(split_title(" - Title"), (None, "- Title"));
+    }
+}
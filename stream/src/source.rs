@@ -8,13 +8,17 @@ use std::{
     fs::File,
     io::{self, BufWriter, Seek, SeekFrom, Write},
     sync::{
-        atomic::{AtomicI64, Ordering},
+        atomic::{AtomicBool, AtomicI64, Ordering},
         Arc,
     },
+    time::Duration,
 };
-use tap::TapFallible;
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
+
+use crate::cache::CacheEntry;
+use crate::recorder::Recorder;
+use crate::timeshift::Timeshift;
 
 #[async_trait]
 pub trait SourceStream:
@@ -23,13 +27,70 @@ pub trait SourceStream:
     type Url: Send;
     type Error: Error + Send;
 
+    /// Open the stream of `url`, requesting it with `client`.
     async fn create(
         url: Self::Url,
+        client: reqwest::Client,
         is_radio: bool,
         radio_title: Arc<Mutex<String>>,
     ) -> io::Result<Self>;
     async fn content_length(&self) -> Option<u64>;
+
+    /// The bitrate of the stream in kbit/s, when it tells.
+    fn bitrate(&self) -> Option<u32> {
+        None
+    }
     async fn seek_range(&mut self, start: u64, end: Option<u64>) -> io::Result<()>;
+
+    /// Whether the stream goes on for as long as it is listened to, like a radio, so it ending
+    /// means the connection dropped.
+    fn is_live(&self) -> bool {
+        false
+    }
+
+    /// Connect again after the connection dropped, with `position` the first byte not
+    /// downloaded yet.
+    async fn reconnect(&mut self, position: u64) -> io::Result<()> {
+        self.seek_range(position, None).await
+    }
+}
+
+/// What the title of a radio is put after, for showing it
+const RADIO_TITLE_PREFIX: &str = "Current playing: ";
+
+/// Show `title` as what the radio plays.
+pub(crate) fn set_radio_title(radio_title: &Mutex<String>, title: &str) {
+    *radio_title.lock() = format!("{RADIO_TITLE_PREFIX}{title}");
+}
+
+/// The title of the song a radio plays, from what [`set_radio_title`] made of it.
+pub fn strip_radio_title(radio_title: &str) -> &str {
+    radio_title
+        .strip_prefix(RADIO_TITLE_PREFIX)
+        .unwrap_or(radio_title)
+}
+
+/// How the connection of a stream is doing, to tell the user about it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StreamState {
+    #[default]
+    Connected,
+    /// The connection dropped, and attempt `attempt` to get it back is under way
+    Reconnecting { attempt: u32 },
+    /// The connection dropped and could not be made again
+    Failed,
+}
+
+impl std::fmt::Display for StreamState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connected => write!(f, "connected"),
+            Self::Reconnecting { attempt } => {
+                write!(f, "reconnecting, attempt {attempt} of {MAX_RECONNECTS}")
+            }
+            Self::Failed => write!(f, "connection lost"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -39,11 +100,13 @@ pub struct SourceHandle {
     position_reached: Arc<(Mutex<Waiter>, Condvar)>,
     content_length_retrieved: Arc<(Mutex<bool>, Condvar)>,
     content_length: Arc<AtomicI64>,
+    prefetched: Arc<(Mutex<bool>, Condvar)>,
+    failed: Arc<AtomicBool>,
     seek_tx: mpsc::Sender<u64>,
 }
 
 impl SourceHandle {
-    pub fn downloaded(&self) -> RwLockReadGuard<rangemap::RangeSet<u64>> {
+    pub fn downloaded(&self) -> RwLockReadGuard<'_, rangemap::RangeSet<u64>> {
         self.downloaded.read()
     }
 
@@ -84,6 +147,23 @@ impl SourceHandle {
             None
         }
     }
+
+    /// Wait until the prefetch is downloaded, or the whole stream if it is shorter than that.
+    /// Returns `false` when it did not happen within `timeout`.
+    pub fn wait_for_prefetch(&self, timeout: Duration) -> bool {
+        let (mutex, cvar) = &*self.prefetched;
+        let mut done = mutex.lock();
+        if !*done {
+            cvar.wait_while_for(&mut done, |done| !*done, timeout);
+        }
+        *done
+    }
+
+    /// Whether the download stopped before the prefetch was done, as the stream could not be
+    /// opened or written, so there is nothing to play.
+    pub fn failed(&self) -> bool {
+        self.failed.load(Ordering::SeqCst)
+    }
 }
 
 #[derive(Default, Debug)]
@@ -99,14 +179,31 @@ pub struct Source {
     position_reached: Arc<(Mutex<Waiter>, Condvar)>,
     content_length_retrieved: Arc<(Mutex<bool>, Condvar)>,
     content_length: Arc<AtomicI64>,
+    prefetched: Arc<(Mutex<bool>, Condvar)>,
+    failed: Arc<AtomicBool>,
+    prefetch_bytes: u64,
     seek_tx: mpsc::Sender<u64>,
     seek_rx: mpsc::Receiver<u64>,
+    /// Attempts to reconnect since bytes last came in
+    reconnects: u32,
+    /// Where the stream is kept on disk, when it is cached
+    cache: Option<CacheEntry>,
+    /// Bytes downloaded since the cache entry was last saved
+    unsaved_bytes: u64,
 }
 
-const PREFETCH_BYTES: u64 = 1024 * 256;
+/// Bytes downloaded before a stream is handed out for reading, when not configured otherwise
+pub const DEFAULT_PREFETCH_BYTES: u64 = 1024 * 256;
+/// Attempts to reconnect a dropped stream before giving up
+pub const MAX_RECONNECTS: u32 = 8;
+/// The wait before the first attempt to reconnect, which doubles with every attempt after it
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// Bytes downloaded between saves of the cache entry, so little is lost when playback stops
+const CACHE_SAVE_BYTES: u64 = 1024 * 1024;
 
 impl Source {
-    pub fn new(tempfile: File) -> Self {
+    pub fn new(tempfile: File, prefetch_bytes: u64) -> Self {
         let (seek_tx, seek_rx) = mpsc::channel(32);
         Self {
             writer: BufWriter::new(tempfile),
@@ -117,66 +214,134 @@ impl Source {
             seek_tx,
             seek_rx,
             content_length: Default::default(),
+            prefetched: Default::default(),
+            failed: Default::default(),
+            prefetch_bytes,
+            reconnects: 0,
+            cache: None,
+            unsaved_bytes: 0,
         }
     }
 
+    /// Write the stream to `cache`, whose file the source was made with, downloading only what
+    /// is not in it yet.
+    pub fn with_cache(mut self, cache: CacheEntry) -> Self {
+        *self.downloaded.write() = cache.downloaded().clone();
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Whether all of the stream is cached, so it does not have to be downloaded.
+    pub fn is_cached(&self) -> bool {
+        self.cache.as_ref().is_some_and(CacheEntry::is_complete)
+    }
+
+    /// Hand out the stream from the cache, when [`Self::is_cached`].
+    pub fn serve_cached(self) {
+        let content_length = self.cache.as_ref().and_then(CacheEntry::content_length);
+        self.set_content_length(content_length);
+        self.notify_prefetched();
+        self.notify_stream_done();
+    }
+
     pub async fn download<S: SourceStream>(
         mut self,
         mut stream: S,
-        radio_downloaded: Arc<Mutex<u64>>,
+        timeshift: Option<Timeshift>,
+        recorder: Option<Recorder>,
+        stream_state: Arc<Mutex<StreamState>>,
     ) -> io::Result<()> {
         info!("Starting file download");
         let content_length = stream.content_length().await;
-        if let Some(content_length) = content_length {
-            self.content_length
-                .swap(content_length as i64, Ordering::SeqCst);
-        } else {
-            self.content_length.swap(-1, Ordering::SeqCst);
-        }
-        {
-            let (mutex, cvar) = &*self.content_length_retrieved;
-            *mutex.lock() = true;
-            cvar.notify_all();
+        self.set_content_length(content_length);
+        if let (Some(timeshift), Some(bitrate)) = (&timeshift, stream.bitrate()) {
+            info!("Stream bitrate is {bitrate} kbit/s");
+            timeshift.set_bitrate(bitrate);
         }
-        loop {
-            if let Some(Ok(bytes)) = stream
-                .next()
-                .await
-                .map(|b| b.tap_err(|e| error!("Error reading stream: {e}")))
-            {
-                self.writer.write_all(&bytes)?;
-                let stream_position = self.writer.stream_position()?;
-                trace!("Prefetch: {}/{} bytes", stream_position, PREFETCH_BYTES);
-                if stream_position >= PREFETCH_BYTES {
-                    self.downloaded.write().insert(0..stream_position);
-                    break;
+        let start = match self.resume_position(content_length) {
+            Some(0) => 0,
+            Some(start) => {
+                info!("Resuming cached stream at byte {start}");
+                // the stream opened from the start is left as it was when resuming fails
+                match stream.seek_range(start, None).await {
+                    Ok(()) => {
+                        self.writer.seek(SeekFrom::Start(start))?;
+                        start
+                    }
+                    Err(e) => {
+                        warn!("Error resuming cached stream, downloading it again: {e}");
+                        0
+                    }
                 }
-            } else {
-                info!("File shorter than prefetch length");
-                self.writer.flush()?;
-                self.downloaded
-                    .write()
-                    .insert(0..self.writer.stream_position()?);
-                let (mutex, cvar) = &*self.position_reached;
-                (mutex.lock()).stream_done = true;
-                cvar.notify_all();
+            }
+            None => {
+                info!("Stream is cached");
+                self.notify_prefetched();
+                self.notify_stream_done();
                 return Ok(());
             }
+        };
+        // the start of the stream is in the cache
+        let mut prefetching = start < self.prefetch_bytes;
+        while prefetching {
+            match stream.next().await {
+                Some(Ok(bytes)) => {
+                    self.reconnects = 0;
+                    self.writer.write_all(&bytes)?;
+                    if let Some(recorder) = &recorder {
+                        recorder.push(&bytes);
+                    }
+                    let stream_position = self.writer.stream_position()?;
+                    trace!(
+                        "Prefetch: {}/{} bytes",
+                        stream_position,
+                        self.prefetch_bytes
+                    );
+                    if stream_position >= self.prefetch_bytes {
+                        self.downloaded.write().insert(start..stream_position);
+                        if let Some(timeshift) = &timeshift {
+                            timeshift.downloaded(stream_position);
+                        }
+                        prefetching = false;
+                    }
+                }
+                dropped => {
+                    if self.is_dropped(&stream, dropped)
+                        && self.reconnect(&mut stream, &stream_state).await?
+                    {
+                        continue;
+                    }
+                    info!("File shorter than prefetch length");
+                    self.writer.flush()?;
+                    let end = self.writer.stream_position()?;
+                    if start < end {
+                        self.downloaded.write().insert(start..end);
+                    }
+                    self.notify_stream_done();
+                    self.notify_prefetched();
+                    return Ok(());
+                }
+            }
         }
         info!("Prefetch complete");
+        self.notify_prefetched();
         loop {
             tokio::select! {
                 bytes = stream.next() => {
-                    if let Some(Ok(bytes)) =
-                        bytes.map(|b| b.tap_err(|e| error!("Error reading from stream: {e}"))) {
+                    if let Some(Ok(bytes)) = bytes {
+                        self.reconnects = 0;
                         let position = self.writer.stream_position()?;
-                        *radio_downloaded.lock() = position;
                         self.writer.write_all(&bytes)?;
+                        if let Some(recorder) = &recorder {
+                            recorder.push(&bytes);
+                        }
                         let new_position = self.writer.stream_position()?;
-                        // *radio_downloaded.lock() = new_position;
-                    // error!("downloaded: {new_position}");
                         // trace!("Received response chunk. position={}", new_position);
                         self.downloaded.write().insert(position .. new_position);
+                        if let Some(timeshift) = &timeshift {
+                            timeshift.downloaded(new_position);
+                        }
+                        self.save_cache_every(new_position - position, content_length)?;
                         let requested = self.requested_position.load(Ordering::SeqCst);
                         if requested > -1 {
                             debug!("downloader: requested {requested} current {}", new_position);
@@ -188,6 +353,13 @@ impl Source {
                             (mutex.lock()).position_reached = true;
                             cvar.notify_all();
                         }
+                    } else if self.is_dropped(&stream, bytes) {
+                        if self.reconnect(&mut stream, &stream_state).await? {
+                            continue;
+                        }
+                        self.writer.flush()?;
+                        self.notify_stream_done();
+                        return Ok(());
                     } else {
                         info!("Stream finished downloading");
                         if let Some(content_length) = content_length {
@@ -205,9 +377,7 @@ impl Source {
                             }
                         }
                         self.writer.flush()?;
-                        let (mutex, cvar) = &*self.position_reached;
-                        (mutex.lock()).stream_done = true;
-                        cvar.notify_all();
+                        self.notify_stream_done();
                         return Ok(());
                     }
                 },
@@ -233,6 +403,123 @@ impl Source {
         }
     }
 
+    fn set_content_length(&self, content_length: Option<u64>) {
+        self.content_length.swap(
+            content_length.map_or(-1, |length| length as i64),
+            Ordering::SeqCst,
+        );
+        let (mutex, cvar) = &*self.content_length_retrieved;
+        *mutex.lock() = true;
+        cvar.notify_all();
+    }
+
+    /// The first byte of the stream not in the cache, or `None` when all of it is.
+    ///
+    /// What is cached is dropped when the stream is not of the length it was, as then it changed.
+    fn resume_position(&mut self, content_length: Option<u64>) -> Option<u64> {
+        let Some(cache) = &self.cache else {
+            return Some(0);
+        };
+        let Some(length) = content_length.filter(|length| Some(*length) == cache.content_length())
+        else {
+            self.downloaded.write().clear();
+            return Some(0);
+        };
+        let gap = self.downloaded.read().gaps(&(0..length)).next();
+        gap.map(|gap| gap.start)
+    }
+
+    /// Save what is downloaded to the cache entry, once [`CACHE_SAVE_BYTES`] came in since the
+    /// last time.
+    fn save_cache_every(&mut self, new_bytes: u64, content_length: Option<u64>) -> io::Result<()> {
+        self.unsaved_bytes += new_bytes;
+        if self.cache.is_none() || self.unsaved_bytes < CACHE_SAVE_BYTES {
+            return Ok(());
+        }
+        self.unsaved_bytes = 0;
+        self.save_cache(content_length)
+    }
+
+    fn save_cache(&mut self, content_length: Option<u64>) -> io::Result<()> {
+        if let Some(cache) = &mut self.cache {
+            // what is saved as downloaded has to be in the file
+            self.writer.flush()?;
+            cache.update(content_length, &self.downloaded.read())?;
+        }
+        Ok(())
+    }
+
+    /// Whether the stream gave `next` because the connection dropped, rather than because it is
+    /// done.
+    fn is_dropped<S: SourceStream>(
+        &self,
+        stream: &S,
+        next: Option<Result<Bytes, S::Error>>,
+    ) -> bool {
+        match next {
+            Some(Err(e)) => {
+                error!("Error reading stream: {e}");
+                true
+            }
+            Some(Ok(_)) => false,
+            None => stream.is_live(),
+        }
+    }
+
+    /// Connect `stream` again where the download stopped, waiting twice as long after every
+    /// failed attempt. Returns `false` when it could not be done in [`MAX_RECONNECTS`] attempts.
+    async fn reconnect<S: SourceStream>(
+        &mut self,
+        stream: &mut S,
+        stream_state: &Mutex<StreamState>,
+    ) -> io::Result<bool> {
+        self.writer.flush()?;
+        // the end of what was downloaded last
+        let position = self.writer.stream_position()?;
+        let position = self
+            .downloaded
+            .read()
+            .get(&position.saturating_sub(1))
+            .map_or(position, |range| range.end);
+
+        while self.reconnects < MAX_RECONNECTS {
+            self.reconnects += 1;
+            let attempt = self.reconnects;
+            *stream_state.lock() = StreamState::Reconnecting { attempt };
+            let delay = INITIAL_RECONNECT_DELAY
+                .saturating_mul(2u32.saturating_pow(attempt - 1))
+                .min(MAX_RECONNECT_DELAY);
+            warn!(
+                "Stream dropped, reconnecting at byte {position} in {delay:?}, attempt {attempt}"
+            );
+            tokio::time::sleep(delay).await;
+            match stream.reconnect(position).await {
+                Ok(()) => {
+                    info!("Stream reconnected");
+                    self.writer.seek(SeekFrom::Start(position))?;
+                    *stream_state.lock() = StreamState::Connected;
+                    return Ok(true);
+                }
+                Err(e) => warn!("Error reconnecting stream: {e}"),
+            }
+        }
+        error!("Giving up reconnecting the stream");
+        *stream_state.lock() = StreamState::Failed;
+        Ok(false)
+    }
+
+    fn notify_stream_done(&self) {
+        let (mutex, cvar) = &*self.position_reached;
+        (mutex.lock()).stream_done = true;
+        cvar.notify_all();
+    }
+
+    fn notify_prefetched(&self) {
+        let (mutex, cvar) = &*self.prefetched;
+        *mutex.lock() = true;
+        cvar.notify_all();
+    }
+
     pub fn source_handle(&self) -> SourceHandle {
         SourceHandle {
             downloaded: self.downloaded.clone(),
@@ -241,6 +528,188 @@ impl Source {
             seek_tx: self.seek_tx.clone(),
             content_length_retrieved: self.content_length_retrieved.clone(),
             content_length: self.content_length.clone(),
+            prefetched: self.prefetched.clone(),
+            failed: self.failed.clone(),
+        }
+    }
+}
+
+impl Drop for Source {
+    fn drop(&mut self) {
+        // the download is done, or stopped with playback
+        let content_length = self.content_length.load(Ordering::SeqCst);
+        let content_length = u64::try_from(content_length).ok();
+        if let Err(e) = self.save_cache(content_length) {
+            warn!("Error saving the stream cache: {e}");
+        }
+
+        // release whoever waits on a download that ended early, as it could not be opened or
+        // an error stopped it
+        if !*self.prefetched.0.lock() {
+            self.failed.store(true, Ordering::SeqCst);
+            self.notify_prefetched();
+        }
+        if !*self.content_length_retrieved.0.lock() {
+            self.set_content_length(None);
+        }
+        self.notify_stream_done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::task::{Context, Poll};
+    use std::io::Read;
+    use std::pin::Pin;
+    use tokio::time::Instant;
+
+    /// A stream of `data` whose connection drops once at each of `drops`, and that fails to
+    /// reconnect `failing_reconnects` times after every drop.
+    struct DroppingStream {
+        data: Vec<u8>,
+        position: usize,
+        drops: Vec<usize>,
+        failing_reconnects: u32,
+        failed_reconnects: u32,
+        /// The position and time of every attempt to reconnect
+        attempts: Arc<Mutex<Vec<(u64, Instant)>>>,
+    }
+
+    impl DroppingStream {
+        const CHUNK: usize = 50;
+
+        fn new(data: Vec<u8>, drops: Vec<usize>, failing_reconnects: u32) -> Self {
+            Self {
+                data,
+                position: 0,
+                drops,
+                failing_reconnects,
+                failed_reconnects: 0,
+                attempts: Arc::default(),
+            }
+        }
+    }
+
+    impl Stream for DroppingStream {
+        type Item = io::Result<Bytes>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let position = self.position;
+            if self.drops.first() == Some(&position) {
+                self.drops.remove(0);
+                return Poll::Ready(Some(Err(io::ErrorKind::ConnectionReset.into())));
+            }
+            let end = (position + Self::CHUNK)
+                .min(self.data.len())
+                .min(self.drops.first().copied().unwrap_or(usize::MAX));
+            if position == end {
+                return Poll::Ready(None);
+            }
+            self.position = end;
+            Poll::Ready(Some(Ok(Bytes::copy_from_slice(&self.data[position..end]))))
+        }
+    }
+
+    #[async_trait]
+    impl SourceStream for DroppingStream {
+        type Url = ();
+        type Error = io::Error;
+
+        async fn create(
+            _url: Self::Url,
+            _client: reqwest::Client,
+            _is_radio: bool,
+            _radio_title: Arc<Mutex<String>>,
+        ) -> io::Result<Self> {
+            Err(io::ErrorKind::Unsupported.into())
+        }
+
+        async fn content_length(&self) -> Option<u64> {
+            Some(self.data.len() as u64)
         }
+
+        async fn seek_range(&mut self, start: u64, _end: Option<u64>) -> io::Result<()> {
+            self.position = start as usize;
+            Ok(())
+        }
+
+        async fn reconnect(&mut self, position: u64) -> io::Result<()> {
+            self.attempts.lock().push((position, Instant::now()));
+            if self.failed_reconnects < self.failing_reconnects {
+                self.failed_reconnects += 1;
+                return Err(io::ErrorKind::ConnectionRefused.into());
+            }
+            self.failed_reconnects = 0;
+            self.seek_range(position, None).await
+        }
+    }
+
+    fn test_data() -> Vec<u8> {
+        (0..1000u32).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn reconnects_where_the_stream_dropped() {
+        let data = test_data();
+        // one drop while prefetching and one after it
+        let stream = DroppingStream::new(data.clone(), vec![60, 700], 2);
+        let attempts = stream.attempts.clone();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let source = Source::new(file.reopen().unwrap(), 100);
+        let stream_state = Arc::new(Mutex::new(StreamState::Reconnecting { attempt: 1 }));
+
+        let start = Instant::now();
+        source
+            .download(stream, None, None, stream_state.clone())
+            .await
+            .unwrap();
+
+        let attempts: Vec<_> = attempts
+            .lock()
+            .iter()
+            .map(|(position, at)| (*position, *at - start))
+            .collect();
+        let ms = Duration::from_millis;
+        assert_eq!(
+            attempts,
+            [
+                // every attempt waits twice as long as the one before
+                (60, ms(500)),
+                (60, ms(1500)),
+                (60, ms(3500)),
+                // and it starts over once bytes came in again
+                (700, ms(4000)),
+                (700, ms(5000)),
+                (700, ms(7000)),
+            ]
+        );
+        assert_eq!(*stream_state.lock(), StreamState::Connected);
+        let mut downloaded = Vec::new();
+        File::open(file.path())
+            .unwrap()
+            .read_to_end(&mut downloaded)
+            .unwrap();
+        assert_eq!(downloaded, data);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_reconnecting() {
+        let stream = DroppingStream::new(test_data(), vec![300], u32::MAX);
+        let attempts = stream.attempts.clone();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let source = Source::new(file.reopen().unwrap(), 100);
+        let handle = source.source_handle();
+        let stream_state = Arc::new(Mutex::new(StreamState::Connected));
+
+        source
+            .download(stream, None, None, stream_state.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.lock().len(), MAX_RECONNECTS as usize);
+        assert!(attempts.lock().iter().all(|(position, _)| *position == 300));
+        assert_eq!(*stream_state.lock(), StreamState::Failed);
+        assert_eq!(handle.downloaded().iter().collect::<Vec<_>>(), [&(0..300)]);
     }
 }
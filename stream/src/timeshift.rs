@@ -0,0 +1,251 @@
+//! Playing a radio behind its live edge: pausing it and playing on from where it was paused,
+//! going back within a window of what was downloaded, and catching up with the live edge again.
+//!
+//! A radio has no seek table, so positions are bytes of the download, turned into time with the
+//! bitrate the stream tells in its `icy-br` header, or else with how fast it comes in. Jumping
+//! to a byte lands in the middle of a frame, which the decoder skips to sync on the next one.
+
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long the download of a radio is timed before its rate is taken as its bitrate
+const RATE_MEASURE_TIME: Duration = Duration::from_secs(5);
+/// How far behind the download playing live is, until it is known from the first read
+const DEFAULT_LIVE_LAG: Duration = Duration::from_secs(2);
+
+/// The position in the radio playing, shared between its download, its reader and the player.
+#[derive(Debug, Clone)]
+pub struct Timeshift(Arc<Mutex<State>>);
+
+/// Where playing a radio is in what can be gone back to of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeshiftProgress {
+    /// From the start of the window to where playing is
+    pub position: Duration,
+    /// From the start of the window to the live edge
+    pub buffered: Duration,
+}
+
+impl TimeshiftProgress {
+    /// How far playing is behind the live edge.
+    pub fn behind_live(&self) -> Duration {
+        self.buffered.saturating_sub(self.position)
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    /// How far back from the live edge can be gone
+    window: Duration,
+    /// Bytes of audio per second, once known
+    byte_rate: Option<u64>,
+    /// Whether the stream told its bitrate, so it is not measured
+    rate_told: bool,
+    /// When timing the download started and the bytes downloaded then
+    measure_start: Option<(Instant, u64)>,
+    /// Bytes downloaded, the live edge
+    downloaded: u64,
+    /// Bytes read for playing
+    read: u64,
+    /// Bytes the read is behind the download when playing live, taken on the first read
+    live_lag: Option<u64>,
+    /// The byte reading goes on from next, after a seek
+    jump: Option<u64>,
+}
+
+impl Timeshift {
+    pub fn new(window: Duration) -> Self {
+        Self(Arc::new(Mutex::new(State::new(window))))
+    }
+
+    /// Forget the radio played before, when another starts.
+    pub(crate) fn restart(&self) {
+        let mut state = self.0.lock();
+        *state = State::new(state.window);
+    }
+
+    /// Set the bitrate in kbit/s the stream told, rather than measuring it.
+    pub(crate) fn set_bitrate(&self, kbps: u32) {
+        let mut state = self.0.lock();
+        state.byte_rate = Some(u64::from(kbps) * 1000 / 8);
+        state.rate_told = true;
+    }
+
+    /// Bytes up to `position` are downloaded.
+    pub(crate) fn downloaded(&self, position: u64) {
+        self.downloaded_at(position, Instant::now());
+    }
+
+    fn downloaded_at(&self, position: u64, now: Instant) {
+        let mut state = self.0.lock();
+        state.downloaded = position;
+        if state.rate_told {
+            return;
+        }
+        match state.measure_start {
+            None => state.measure_start = Some((now, position)),
+            Some((start, start_position)) => {
+                let elapsed = now.duration_since(start);
+                if elapsed >= RATE_MEASURE_TIME {
+                    let bytes = position.saturating_sub(start_position);
+                    state.byte_rate = Some((bytes as f64 / elapsed.as_secs_f64()) as u64)
+                        .filter(|rate| *rate > 0);
+                }
+            }
+        }
+    }
+
+    /// Bytes up to `position` are read for playing.
+    pub(crate) fn read(&self, position: u64) {
+        let mut state = self.0.lock();
+        state.read = position;
+        if state.live_lag.is_none() {
+            state.live_lag = Some(state.downloaded.saturating_sub(position));
+        }
+    }
+
+    /// The byte reading has to go on from, after a seek or when what was paused at fell out of
+    /// the window.
+    pub(crate) fn take_jump(&self) -> Option<u64> {
+        let mut state = self.0.lock();
+        if let Some(jump) = state.jump.take() {
+            return Some(jump);
+        }
+        let start = state.window_start()?;
+        (state.read < start).then_some(start)
+    }
+
+    /// Go `offset` seconds back or forward, no further back than the window and no further
+    /// forward than the live edge. Returns `false` while the bitrate is not known yet.
+    pub fn seek(&self, offset: i64) -> bool {
+        let mut state = self.0.lock();
+        let (Some(rate), Some(start), Some(live)) =
+            (state.byte_rate, state.window_start(), state.live_edge())
+        else {
+            return false;
+        };
+        let from = state.jump.unwrap_or(state.read);
+        let bytes = offset.unsigned_abs() * rate;
+        let target = if offset.is_negative() {
+            from.saturating_sub(bytes)
+        } else {
+            from.saturating_add(bytes)
+        };
+        state.jump = Some(target.clamp(start, live.max(start)));
+        true
+    }
+
+    /// Catch up with the live edge. Returns `false` while the bitrate is not known yet.
+    pub fn go_live(&self) -> bool {
+        let mut state = self.0.lock();
+        let Some(live) = state.live_edge() else {
+            return false;
+        };
+        state.jump = Some(live);
+        true
+    }
+
+    /// Where playing is between the start of the window and the live edge, `None` while the
+    /// bitrate is not known yet.
+    pub fn progress(&self) -> Option<TimeshiftProgress> {
+        let state = self.0.lock();
+        let rate = state.byte_rate?;
+        let start = state.window_start()?;
+        let live = state.live_edge()?.max(start);
+        let read = state.jump.unwrap_or(state.read).clamp(start, live);
+        let duration = |bytes: u64| Duration::from_secs_f64(bytes as f64 / rate as f64);
+        Some(TimeshiftProgress {
+            position: duration(read - start),
+            buffered: duration(live - start),
+        })
+    }
+}
+
+impl State {
+    const fn new(window: Duration) -> Self {
+        Self {
+            window,
+            byte_rate: None,
+            rate_told: false,
+            measure_start: None,
+            downloaded: 0,
+            read: 0,
+            live_lag: None,
+            jump: None,
+        }
+    }
+
+    /// The byte playing live is at, as far behind the download as it was when playing started.
+    fn live_edge(&self) -> Option<u64> {
+        let lag = match self.live_lag {
+            Some(lag) => lag,
+            None => self.byte_rate? * DEFAULT_LIVE_LAG.as_secs(),
+        };
+        Some(self.downloaded.saturating_sub(lag))
+    }
+
+    /// The first byte that can be gone back to.
+    fn window_start(&self) -> Option<u64> {
+        let window = self.byte_rate? * self.window.as_secs();
+        Some(self.live_edge()?.saturating_sub(window))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 128 kbit/s
+    const RATE: u64 = 16000;
+
+    #[test]
+    fn seeks_within_the_window() {
+        let timeshift = Timeshift::new(Duration::from_secs(60));
+        timeshift.set_bitrate(128);
+        timeshift.downloaded(RATE * 10);
+        // playing starts 10 seconds behind the download
+        timeshift.read(0);
+        timeshift.downloaded(RATE * 100);
+        timeshift.read(RATE * 85);
+
+        let progress = timeshift.progress().unwrap();
+        assert_eq!(progress.buffered, Duration::from_secs(60));
+        assert_eq!(progress.behind_live(), Duration::from_secs(5));
+        assert_eq!(timeshift.take_jump(), None);
+
+        assert!(timeshift.seek(-30));
+        assert!(timeshift.seek(-10));
+        assert_eq!(timeshift.take_jump(), Some(RATE * 45));
+        timeshift.read(RATE * 45);
+        assert!(timeshift.seek(-60));
+        assert_eq!(timeshift.take_jump(), Some(RATE * 30));
+        assert!(timeshift.seek(600));
+        assert_eq!(timeshift.take_jump(), Some(RATE * 90));
+
+        assert!(timeshift.go_live());
+        assert_eq!(timeshift.take_jump(), Some(RATE * 90));
+    }
+
+    #[test]
+    fn plays_on_from_the_window_after_a_long_pause() {
+        let timeshift = Timeshift::new(Duration::from_secs(60));
+        let start = Instant::now();
+        timeshift.downloaded_at(0, start);
+        timeshift.read(0);
+        assert!(timeshift.progress().is_none());
+        assert!(!timeshift.seek(-10));
+
+        // the rate is measured
+        timeshift.downloaded_at(RATE * 10, start + Duration::from_secs(10));
+        assert_eq!(
+            timeshift.progress().unwrap().buffered,
+            Duration::from_secs(10)
+        );
+        timeshift.downloaded_at(RATE * 200, start + Duration::from_secs(200));
+        assert_eq!(timeshift.take_jump(), Some(RATE * 140));
+
+        timeshift.restart();
+        assert!(timeshift.progress().is_none());
+    }
+}
@@ -39,7 +39,7 @@ use std::path::Path;
 use std::process;
 
 use sysinfo::System;
-use termusiclib::{config, podcast, utils};
+use termusiclib::{config, http, podcast, utils};
 use ui::UI;
 #[macro_use]
 extern crate log;
@@ -142,6 +142,7 @@ async fn actual_main() -> Result<()> {
 fn get_config(args: &cli::Args) -> Result<Settings> {
     let mut config = Settings::default();
     config.load()?;
+    http::configure(&config)?;
 
     config.disable_album_art_from_cli = args.disable_cover;
     config.disable_discord_rpc_from_cli = args.disable_discord;
@@ -0,0 +1,427 @@
+//! Audiobooks in progress with their bookmarks, and naming a new bookmark.
+
+use crate::ui::model::Model;
+use std::path::Path;
+use termusiclib::audiobook::{Audiobook, BookProgress, Bookmark};
+use termusiclib::config::{Keys, Settings};
+use termusiclib::track::Track;
+use termusiclib::types::{AudiobookMsg, Id, Msg};
+use termusicplayback::PlayerCmd;
+use tui_realm_stdlib::{Input, Table};
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{Alignment, BorderType, Borders, Color, InputType, TableBuilder, TextSpan};
+use tuirealm::{Component, Event, MockComponent, State, StateValue};
+
+/// A row of [`AudiobookTablePopup`], every book is followed by its bookmarks.
+pub enum AudiobookRow {
+    Book(BookProgress),
+    Bookmark(Bookmark),
+}
+
+#[derive(MockComponent)]
+pub struct AudiobookTablePopup {
+    component: Table,
+    keys: Keys,
+}
+
+impl AudiobookTablePopup {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Magenta),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::Magenta),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(
+                        " Audiobooks: Enter to resume or jump to a bookmark, {} to delete ",
+                        config.keys.library_delete
+                    ),
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Progress", "Book / Bookmark", "Position"])
+                .column_spacing(2)
+                .widths(&[10, 55, 35])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from("Loading..."))
+                        .add_col(TextSpan::from(""))
+                        .build(),
+                ),
+            keys: config.keys.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for AudiobookTablePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Audiobook(AudiobookMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_quit.key_event() => {
+                return Some(Msg::Audiobook(AudiobookMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.library_delete.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Audiobook(AudiobookMsg::TablePopupDelete(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Audiobook(AudiobookMsg::TablePopupCloseOk(index)));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct AudiobookBookmarkInputPopup {
+    component: Input,
+}
+
+impl AudiobookBookmarkInputPopup {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Input::default()
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Magenta),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::Magenta),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .title(" Bookmark name: ", Alignment::Left),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for AudiobookBookmarkInputPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Audiobook(AudiobookMsg::BookmarkInputCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => Some(
+                Msg::Audiobook(AudiobookMsg::BookmarkInputCloseOk(input_string)),
+            ),
+
+            _ => Some(Msg::None),
+        }
+    }
+}
+
+impl Model {
+    pub fn mount_audiobook_table(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::AudiobookTablePopup,
+                Box::new(AudiobookTablePopup::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::AudiobookTablePopup).is_ok());
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+        self.audiobook_table_sync();
+    }
+
+    pub fn umount_audiobook_table(&mut self) {
+        if self.app.mounted(&Id::AudiobookTablePopup) {
+            assert!(self.app.umount(&Id::AudiobookTablePopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+    }
+
+    /// Read the books and bookmarks from the database and show them.
+    pub fn audiobook_table_sync(&mut self) {
+        let books = match self.db.get_audiobooks() {
+            Ok(books) => books,
+            Err(e) => {
+                self.mount_error_popup(format!("error loading audiobooks: {e}"));
+                Vec::new()
+            }
+        };
+        self.audiobook_rows.clear();
+        for book in books {
+            let bookmarks = self
+                .db
+                .get_audiobook_bookmarks(&book.book)
+                .unwrap_or_default();
+            self.audiobook_rows.push(AudiobookRow::Book(book));
+            self.audiobook_rows
+                .extend(bookmarks.into_iter().map(AudiobookRow::Bookmark));
+        }
+
+        let mut table: TableBuilder = TableBuilder::default();
+        for (idx, row) in self.audiobook_rows.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            match row {
+                AudiobookRow::Book(book) => table
+                    .add_col(TextSpan::new(format!("{:>4.0}%", book.percent())))
+                    .add_col(TextSpan::new(&book.title).bold())
+                    .add_col(TextSpan::new(format!(
+                        "{} / {}",
+                        Track::duration_formatted_short(&book.elapsed),
+                        Track::duration_formatted_short(&book.duration)
+                    ))),
+                AudiobookRow::Bookmark(bookmark) => table
+                    .add_col(TextSpan::from(""))
+                    .add_col(TextSpan::new(format!("  {}", bookmark.name)))
+                    .add_col(TextSpan::new(format!(
+                        "{} @ {}",
+                        file_name(&bookmark.file),
+                        Track::duration_formatted_short(&bookmark.position)
+                    ))),
+            };
+        }
+        if self.audiobook_rows.is_empty() {
+            table
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from("no audiobook started yet"))
+                .add_col(TextSpan::from(""));
+        }
+        let table = table.build();
+
+        self.app
+            .attr(
+                &Id::AudiobookTablePopup,
+                tuirealm::Attribute::Content,
+                tuirealm::AttrValue::Table(table),
+            )
+            .ok();
+    }
+
+    /// Play the book of the row at `index` where it was left, or from its bookmark.
+    pub fn audiobook_jump(&mut self, index: usize) {
+        let (book, file, position) = match self.audiobook_rows.get(index) {
+            Some(AudiobookRow::Book(book)) => (&book.book, &book.file, book.position),
+            Some(AudiobookRow::Bookmark(bookmark)) => {
+                (&bookmark.book, &bookmark.file, bookmark.position)
+            }
+            None => return,
+        };
+        let (book, file) = (book.clone(), file.clone());
+
+        if self.playlist_find_file(&file).is_none() {
+            let audiobook = Audiobook::open(Path::new(&book));
+            let files: Vec<&str> = audiobook.files.iter().map(|(f, _)| f.as_str()).collect();
+            if let Err(e) = self.playlist.add_playlist(files) {
+                self.mount_error_popup(format!("error adding audiobook: {e}"));
+                return;
+            }
+            if let Err(e) = self.player_sync_playlist() {
+                self.mount_error_popup(format!("sync playlist error: {e}"));
+            }
+            self.playlist_sync();
+        }
+        let Some(playlist_index) = self.playlist_find_file(&file) else {
+            self.mount_error_popup(format!("{file} is not found"));
+            return;
+        };
+
+        self.umount_audiobook_table();
+        let playing = self
+            .current_song
+            .as_ref()
+            .and_then(Track::file)
+            .is_some_and(|current| current == file);
+        self.command(&PlayerCmd::AudiobookJump(file, position));
+        if !playing {
+            self.playlist_play_selected(playlist_index);
+        }
+    }
+
+    /// Forget the book or the bookmark at `index`.
+    pub fn audiobook_delete(&mut self, index: usize) {
+        let result = match self.audiobook_rows.get(index) {
+            Some(AudiobookRow::Book(book)) => self.db.delete_audiobook(&book.book),
+            Some(AudiobookRow::Bookmark(bookmark)) => {
+                self.db.delete_audiobook_bookmark(bookmark.id)
+            }
+            None => return,
+        };
+        if let Err(e) = result {
+            self.mount_error_popup(format!("error deleting: {e}"));
+        }
+        self.audiobook_table_sync();
+    }
+
+    pub fn mount_audiobook_bookmark_input(&mut self) {
+        if self.audiobook_current_book().is_none() {
+            self.mount_error_popup("The current track is not part of an audiobook.");
+            return;
+        }
+        assert!(self
+            .app
+            .remount(
+                Id::AudiobookBookmarkInputPopup,
+                Box::new(AudiobookBookmarkInputPopup::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::AudiobookBookmarkInputPopup).is_ok());
+    }
+
+    pub fn umount_audiobook_bookmark_input(&mut self) {
+        if self.app.mounted(&Id::AudiobookBookmarkInputPopup) {
+            assert!(self.app.umount(&Id::AudiobookBookmarkInputPopup).is_ok());
+        }
+    }
+
+    /// Bookmark the current position, named after it if `name` is empty.
+    pub fn audiobook_add_bookmark(&mut self, name: &str) {
+        let Some((book, file)) = self.audiobook_current_book() else {
+            return;
+        };
+        let name = if name.trim().is_empty() {
+            format!(
+                "{} @ {}",
+                file_name(&file),
+                Track::duration_formatted_short(&self.time_pos)
+            )
+        } else {
+            name.trim().to_string()
+        };
+        let bookmark = Bookmark::new(book, name, file, self.time_pos);
+        if let Err(e) = self.db.add_audiobook_bookmark(&bookmark) {
+            self.mount_error_popup(format!("error adding bookmark: {e}"));
+        }
+    }
+
+    /// The book and file of the current track, if it is part of an audiobook.
+    fn audiobook_current_book(&self) -> Option<(String, String)> {
+        let file = self.current_song.as_ref()?.file()?;
+        let book = Audiobook::of_file(file, &self.config.audiobook_dir)?;
+        Some((book.to_string_lossy().to_string(), file.to_string()))
+    }
+
+    fn playlist_find_file(&self, file: &str) -> Option<usize> {
+        self.playlist
+            .tracks()
+            .iter()
+            .position(|track| track.file() == Some(file))
+    }
+}
+
+fn file_name(file: &str) -> String {
+    Path::new(file)
+        .file_name()
+        .map_or_else(|| file.to_string(), |f| f.to_string_lossy().to_string())
+}
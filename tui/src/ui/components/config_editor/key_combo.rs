@@ -1179,6 +1179,27 @@ impl KEModifierSelect {
             IdKey::PodcastSearchAddFeed => keys.podcast_search_add_feed.mod_key(),
             IdKey::PodcastRefreshFeed => keys.podcast_refresh_feed.mod_key(),
             IdKey::PodcastRefreshAllFeeds => keys.podcast_refresh_all_feeds.mod_key(),
+            IdKey::GlobalPlayerOutputDevice => keys.global_player_output_device.mod_key(),
+            IdKey::GlobalVisualizerCycle => keys.global_visualizer_cycle.mod_key(),
+            IdKey::GlobalPlayerAbLoopSetA => keys.global_player_ab_loop_set_a.mod_key(),
+            IdKey::GlobalPlayerAbLoopSetB => keys.global_player_ab_loop_set_b.mod_key(),
+            IdKey::GlobalPlayerAbLoopClear => keys.global_player_ab_loop_clear.mod_key(),
+            IdKey::GlobalPlayerChapterNext => keys.global_player_chapter_next.mod_key(),
+            IdKey::GlobalPlayerChapterPrevious => keys.global_player_chapter_previous.mod_key(),
+            IdKey::GlobalAudiobookBookmark => keys.global_audiobook_bookmark.mod_key(),
+            IdKey::GlobalAudiobookShow => keys.global_audiobook_show.mod_key(),
+            IdKey::GlobalPlayerSwitchBackend => keys.global_player_switch_backend.mod_key(),
+            IdKey::GlobalPlayerRadioGoLive => keys.global_player_radio_go_live.mod_key(),
+            IdKey::GlobalPlayerRadioRecord => keys.global_player_radio_record.mod_key(),
+            IdKey::GlobalLayoutRadio => keys.global_layout_radio.mod_key(),
+            IdKey::RadioSearch => keys.radio_search.mod_key(),
+            IdKey::RadioFavorite => keys.radio_favorite.mod_key(),
+            IdKey::RadioDeleteFavorite => keys.radio_delete_favorite.mod_key(),
+            IdKey::RadioSortCycle => keys.radio_sort_cycle.mod_key(),
+            IdKey::GlobalRadioHistory => keys.global_radio_history.mod_key(),
+            IdKey::RadioHistorySearchYoutube => keys.radio_history_search_youtube.mod_key(),
+            IdKey::RadioHistorySearchLyric => keys.radio_history_search_lyric.mod_key(),
+            IdKey::RadioHistoryExport => keys.radio_history_export.mod_key(),
         }
     }
 
@@ -3000,3 +3021,584 @@ impl Component<Msg, NoUserEvent> for ConfigPodcastRefreshAllFeeds {
 //         println!("Hello!");
 //     };
 // }
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerOutputDevice {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerOutputDevice {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Output Device ",
+                IdKey::GlobalPlayerOutputDevice,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerOutputDeviceBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerOutputDeviceBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerOutputDevice {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalVisualizerCycle {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalVisualizerCycle {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Visualizer Cycle ",
+                IdKey::GlobalVisualizerCycle,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalVisualizerCycleBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalVisualizerCycleBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalVisualizerCycle {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerAbLoopSetA {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerAbLoopSetA {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " A-B Loop Set A ",
+                IdKey::GlobalPlayerAbLoopSetA,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopSetABlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopSetABlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerAbLoopSetA {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerAbLoopSetB {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerAbLoopSetB {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " A-B Loop Set B ",
+                IdKey::GlobalPlayerAbLoopSetB,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopSetBBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopSetBBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerAbLoopSetB {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerAbLoopClear {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerAbLoopClear {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " A-B Loop Clear ",
+                IdKey::GlobalPlayerAbLoopClear,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopClearBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerAbLoopClearBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerAbLoopClear {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerChapterNext {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerChapterNext {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Chapter Next ",
+                IdKey::GlobalPlayerChapterNext,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerChapterNextBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerChapterNextBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerChapterNext {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerChapterPrevious {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerChapterPrevious {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Chapter Previous ",
+                IdKey::GlobalPlayerChapterPrevious,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerChapterPreviousBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerChapterPreviousBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerChapterPrevious {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalAudiobookBookmark {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalAudiobookBookmark {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Audiobook Bookmark ",
+                IdKey::GlobalAudiobookBookmark,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalAudiobookBookmarkBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalAudiobookBookmarkBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalAudiobookBookmark {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalAudiobookShow {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalAudiobookShow {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Audiobook Show ",
+                IdKey::GlobalAudiobookShow,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalAudiobookShowBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalAudiobookShowBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalAudiobookShow {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerSwitchBackend {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerSwitchBackend {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Switch Backend ",
+                IdKey::GlobalPlayerSwitchBackend,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerSwitchBackendBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerSwitchBackendBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerSwitchBackend {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerRadioGoLive {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerRadioGoLive {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Go Live ",
+                IdKey::GlobalPlayerRadioGoLive,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerRadioGoLiveBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerRadioGoLiveBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerRadioGoLive {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalPlayerRadioRecord {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalPlayerRadioRecord {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Record ",
+                IdKey::GlobalPlayerRadioRecord,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerRadioRecordBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::GlobalPlayerRadioRecordBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalPlayerRadioRecord {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalLayoutRadio {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalLayoutRadio {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Layout Radio ",
+                IdKey::GlobalLayoutRadio,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalLayoutRadioBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalLayoutRadioBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalLayoutRadio {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioSearch {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioSearch {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Search ",
+                IdKey::RadioSearch,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioSearchBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioSearchBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioSearch {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioFavorite {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioFavorite {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Favorite ",
+                IdKey::RadioFavorite,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioFavoriteBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioFavoriteBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioFavorite {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioDeleteFavorite {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioDeleteFavorite {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Delete Favorite ",
+                IdKey::RadioDeleteFavorite,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::RadioDeleteFavoriteBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioDeleteFavoriteBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioDeleteFavorite {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioSortCycle {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioSortCycle {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio Sort Cycle ",
+                IdKey::RadioSortCycle,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioSortCycleBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioSortCycleBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioSortCycle {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigGlobalRadioHistory {
+    component: KEModifierSelect,
+}
+
+impl ConfigGlobalRadioHistory {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " Radio History ",
+                IdKey::GlobalRadioHistory,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalRadioHistoryBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::GlobalRadioHistoryBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigGlobalRadioHistory {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioHistorySearchYoutube {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioHistorySearchYoutube {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " History Search Youtube ",
+                IdKey::RadioHistorySearchYoutube,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::RadioHistorySearchYoutubeBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::RadioHistorySearchYoutubeBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioHistorySearchYoutube {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioHistorySearchLyric {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioHistorySearchLyric {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " History Search Lyric ",
+                IdKey::RadioHistorySearchLyric,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::RadioHistorySearchLyricBlurDown,
+                )),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(
+                    KFMsg::RadioHistorySearchLyricBlurUp,
+                )),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioHistorySearchLyric {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct ConfigRadioHistoryExport {
+    component: KEModifierSelect,
+}
+
+impl ConfigRadioHistoryExport {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: KEModifierSelect::new(
+                " History Export ",
+                IdKey::RadioHistoryExport,
+                config,
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioHistoryExportBlurDown)),
+                Msg::ConfigEditor(ConfigEditorMsg::KeyFocus(KFMsg::RadioHistoryExportBlurUp)),
+            ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ConfigRadioHistoryExport {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(ev)
+    }
+}
@@ -61,6 +61,7 @@ impl CEHeader {
                     "Themes and Colors",
                     "Keys Global",
                     "Keys Other",
+                    "Keys Player and Radio",
                 ])
                 .foreground(
                     config
@@ -79,6 +80,7 @@ impl CEHeader {
                     ConfigEditorLayout::Color => 1,
                     ConfigEditorLayout::Key1 => 2,
                     ConfigEditorLayout::Key2 => 3,
+                    ConfigEditorLayout::Key3 => 4,
                 }),
         }
     }
@@ -146,6 +146,9 @@ impl Model {
                     Ok(()) => {
                         match self.config.save() {
                             Ok(()) => {
+                                if let Err(e) = termusiclib::http::configure(&self.config) {
+                                    self.mount_error_popup(format!("http settings error: {e:#}"));
+                                }
                                 self.command(&PlayerCmd::ReloadConfig);
                             }
                             Err(e) => {
@@ -772,6 +775,149 @@ impl Model {
                     )))
                     .ok();
             }
+            KFMsg::RadioHistoryExportBlurDown | KFMsg::GlobalVisualizerCycleBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerOutputDevice,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerOutputDeviceBlurDown | KFMsg::GlobalPlayerAbLoopSetABlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalVisualizerCycle,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalVisualizerCycleBlurDown | KFMsg::GlobalPlayerAbLoopSetBBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerAbLoopSetA,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerAbLoopSetABlurDown | KFMsg::GlobalPlayerAbLoopClearBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerAbLoopSetB,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerAbLoopSetBBlurDown | KFMsg::GlobalPlayerChapterNextBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerAbLoopClear,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerAbLoopClearBlurDown | KFMsg::GlobalPlayerChapterPreviousBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerChapterNext,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerChapterNextBlurDown | KFMsg::GlobalAudiobookBookmarkBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerChapterPrevious,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerChapterPreviousBlurDown | KFMsg::GlobalAudiobookShowBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalAudiobookBookmark,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalAudiobookBookmarkBlurDown | KFMsg::GlobalPlayerSwitchBackendBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalAudiobookShow,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalAudiobookShowBlurDown | KFMsg::GlobalPlayerRadioGoLiveBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerSwitchBackend,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerSwitchBackendBlurDown | KFMsg::GlobalPlayerRadioRecordBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerRadioGoLive,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerRadioGoLiveBlurDown | KFMsg::GlobalLayoutRadioBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalPlayerRadioRecord,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalPlayerRadioRecordBlurDown | KFMsg::RadioSearchBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalLayoutRadio,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalLayoutRadioBlurDown | KFMsg::RadioFavoriteBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSearch)))
+                    .ok();
+            }
+            KFMsg::RadioSearchBlurDown | KFMsg::RadioDeleteFavoriteBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioFavorite)))
+                    .ok();
+            }
+            KFMsg::RadioFavoriteBlurDown | KFMsg::RadioSortCycleBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::RadioDeleteFavorite,
+                    )))
+                    .ok();
+            }
+            KFMsg::RadioDeleteFavoriteBlurDown | KFMsg::GlobalRadioHistoryBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::RadioSortCycle,
+                    )))
+                    .ok();
+            }
+            KFMsg::RadioSortCycleBlurDown | KFMsg::RadioHistorySearchYoutubeBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::GlobalRadioHistory,
+                    )))
+                    .ok();
+            }
+            KFMsg::GlobalRadioHistoryBlurDown | KFMsg::RadioHistorySearchLyricBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::RadioHistorySearchYoutube,
+                    )))
+                    .ok();
+            }
+            KFMsg::RadioHistorySearchYoutubeBlurDown | KFMsg::RadioHistoryExportBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::RadioHistorySearchLyric,
+                    )))
+                    .ok();
+            }
+            KFMsg::RadioHistorySearchLyricBlurDown | KFMsg::GlobalPlayerOutputDeviceBlurUp => {
+                self.app
+                    .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                        IdKey::RadioHistoryExport,
+                    )))
+                    .ok();
+            }
         }
     }
 
@@ -855,6 +1001,69 @@ impl Model {
             IdKey::PodcastRefreshAllFeeds => {
                 self.ke_key_config.podcast_refresh_all_feeds = *binding;
             }
+            IdKey::GlobalPlayerOutputDevice => {
+                self.ke_key_config.global_player_output_device = *binding;
+            }
+            IdKey::GlobalVisualizerCycle => {
+                self.ke_key_config.global_visualizer_cycle = *binding;
+            }
+            IdKey::GlobalPlayerAbLoopSetA => {
+                self.ke_key_config.global_player_ab_loop_set_a = *binding;
+            }
+            IdKey::GlobalPlayerAbLoopSetB => {
+                self.ke_key_config.global_player_ab_loop_set_b = *binding;
+            }
+            IdKey::GlobalPlayerAbLoopClear => {
+                self.ke_key_config.global_player_ab_loop_clear = *binding;
+            }
+            IdKey::GlobalPlayerChapterNext => {
+                self.ke_key_config.global_player_chapter_next = *binding;
+            }
+            IdKey::GlobalPlayerChapterPrevious => {
+                self.ke_key_config.global_player_chapter_previous = *binding;
+            }
+            IdKey::GlobalAudiobookBookmark => {
+                self.ke_key_config.global_audiobook_bookmark = *binding;
+            }
+            IdKey::GlobalAudiobookShow => {
+                self.ke_key_config.global_audiobook_show = *binding;
+            }
+            IdKey::GlobalPlayerSwitchBackend => {
+                self.ke_key_config.global_player_switch_backend = *binding;
+            }
+            IdKey::GlobalPlayerRadioGoLive => {
+                self.ke_key_config.global_player_radio_go_live = *binding;
+            }
+            IdKey::GlobalPlayerRadioRecord => {
+                self.ke_key_config.global_player_radio_record = *binding;
+            }
+            IdKey::GlobalLayoutRadio => {
+                self.ke_key_config.global_layout_radio = *binding;
+            }
+            IdKey::RadioSearch => {
+                self.ke_key_config.radio_search = *binding;
+            }
+            IdKey::RadioFavorite => {
+                self.ke_key_config.radio_favorite = *binding;
+            }
+            IdKey::RadioDeleteFavorite => {
+                self.ke_key_config.radio_delete_favorite = *binding;
+            }
+            IdKey::RadioSortCycle => {
+                self.ke_key_config.radio_sort_cycle = *binding;
+            }
+            IdKey::GlobalRadioHistory => {
+                self.ke_key_config.global_radio_history = *binding;
+            }
+            IdKey::RadioHistorySearchYoutube => {
+                self.ke_key_config.radio_history_search_youtube = *binding;
+            }
+            IdKey::RadioHistorySearchLyric => {
+                self.ke_key_config.radio_history_search_lyric = *binding;
+            }
+            IdKey::RadioHistoryExport => {
+                self.ke_key_config.radio_history_export = *binding;
+            }
         }
     }
 
@@ -1,17 +1,23 @@
 use crate::ui::components::{
     AlbumPhotoAlign, CEHeader, CEThemeSelectTable, ConfigCurrentlyPlayingTrackSymbol,
-    ConfigDatabaseAddAll, ConfigGlobalConfig, ConfigGlobalDown, ConfigGlobalGotoBottom,
-    ConfigGlobalGotoTop, ConfigGlobalHelp, ConfigGlobalLayoutDatabase, ConfigGlobalLayoutPodcast,
-    ConfigGlobalLayoutTreeview, ConfigGlobalLeft, ConfigGlobalLyricAdjustBackward,
-    ConfigGlobalLyricAdjustForward, ConfigGlobalLyricCycle, ConfigGlobalPlayerNext,
-    ConfigGlobalPlayerPrevious, ConfigGlobalPlayerSeekBackward, ConfigGlobalPlayerSeekForward,
-    ConfigGlobalPlayerSpeedDown, ConfigGlobalPlayerSpeedUp, ConfigGlobalPlayerToggleGapless,
-    ConfigGlobalPlayerTogglePause, ConfigGlobalQuit, ConfigGlobalRight, ConfigGlobalSavePlaylist,
-    ConfigGlobalUp, ConfigGlobalVolumeDown, ConfigGlobalVolumeUp, ConfigGlobalXywhHide,
-    ConfigGlobalXywhMoveDown, ConfigGlobalXywhMoveLeft, ConfigGlobalXywhMoveRight,
-    ConfigGlobalXywhMoveUp, ConfigGlobalXywhZoomIn, ConfigGlobalXywhZoomOut, ConfigLibraryAddRoot,
-    ConfigLibraryBackground, ConfigLibraryBorder, ConfigLibraryDelete, ConfigLibraryForeground,
-    ConfigLibraryHighlight, ConfigLibraryHighlightSymbol, ConfigLibraryLoadDir, ConfigLibraryPaste,
+    ConfigDatabaseAddAll, ConfigGlobalAudiobookBookmark, ConfigGlobalAudiobookShow,
+    ConfigGlobalConfig, ConfigGlobalDown, ConfigGlobalGotoBottom, ConfigGlobalGotoTop,
+    ConfigGlobalHelp, ConfigGlobalLayoutDatabase, ConfigGlobalLayoutPodcast,
+    ConfigGlobalLayoutRadio, ConfigGlobalLayoutTreeview, ConfigGlobalLeft,
+    ConfigGlobalLyricAdjustBackward, ConfigGlobalLyricAdjustForward, ConfigGlobalLyricCycle,
+    ConfigGlobalPlayerAbLoopClear, ConfigGlobalPlayerAbLoopSetA, ConfigGlobalPlayerAbLoopSetB,
+    ConfigGlobalPlayerChapterNext, ConfigGlobalPlayerChapterPrevious, ConfigGlobalPlayerNext,
+    ConfigGlobalPlayerOutputDevice, ConfigGlobalPlayerPrevious, ConfigGlobalPlayerRadioGoLive,
+    ConfigGlobalPlayerRadioRecord, ConfigGlobalPlayerSeekBackward, ConfigGlobalPlayerSeekForward,
+    ConfigGlobalPlayerSpeedDown, ConfigGlobalPlayerSpeedUp, ConfigGlobalPlayerSwitchBackend,
+    ConfigGlobalPlayerToggleGapless, ConfigGlobalPlayerTogglePause, ConfigGlobalQuit,
+    ConfigGlobalRadioHistory, ConfigGlobalRight, ConfigGlobalSavePlaylist, ConfigGlobalUp,
+    ConfigGlobalVisualizerCycle, ConfigGlobalVolumeDown, ConfigGlobalVolumeUp,
+    ConfigGlobalXywhHide, ConfigGlobalXywhMoveDown, ConfigGlobalXywhMoveLeft,
+    ConfigGlobalXywhMoveRight, ConfigGlobalXywhMoveUp, ConfigGlobalXywhZoomIn,
+    ConfigGlobalXywhZoomOut, ConfigLibraryAddRoot, ConfigLibraryBackground, ConfigLibraryBorder,
+    ConfigLibraryDelete, ConfigLibraryForeground, ConfigLibraryHighlight,
+    ConfigLibraryHighlightSymbol, ConfigLibraryLoadDir, ConfigLibraryPaste,
     ConfigLibraryRemoveRoot, ConfigLibrarySearch, ConfigLibrarySearchYoutube,
     ConfigLibrarySwitchRoot, ConfigLibraryTagEditor, ConfigLibraryTitle, ConfigLibraryYank,
     ConfigLyricBackground, ConfigLyricBorder, ConfigLyricForeground, ConfigLyricTitle,
@@ -23,7 +29,9 @@ use crate::ui::components::{
     ConfigPodcastDeleteFeed, ConfigPodcastEpDeleteFile, ConfigPodcastEpDownload,
     ConfigPodcastMarkAllPlayed, ConfigPodcastMarkPlayed, ConfigPodcastRefreshAllFeeds,
     ConfigPodcastRefreshFeed, ConfigPodcastSearchAddFeed, ConfigProgressBackground,
-    ConfigProgressBorder, ConfigProgressForeground, ConfigProgressTitle, ConfigSavePopup,
+    ConfigProgressBorder, ConfigProgressForeground, ConfigProgressTitle, ConfigRadioDeleteFavorite,
+    ConfigRadioFavorite, ConfigRadioHistoryExport, ConfigRadioHistorySearchLyric,
+    ConfigRadioHistorySearchYoutube, ConfigRadioSearch, ConfigRadioSortCycle, ConfigSavePopup,
     ConfigSeekStep, ExitConfirmation, Footer, GlobalListener, KillDaemon, MusicDir, PlayerPort,
     PlayerUseDiscord, PlayerUseMpris, PlaylistDisplaySymbol, PlaylistRandomAlbum,
     PlaylistRandomTrack, PodcastDir, PodcastMaxRetries, PodcastSimulDownload, SaveLastPosition,
@@ -1555,6 +1563,334 @@ impl Model {
             .is_ok());
     }
 
+    #[allow(clippy::too_many_lines)]
+    pub fn view_config_editor_key3(&mut self) {
+        let select_global_player_output_device_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerOutputDevice),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_visualizer_cycle_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalVisualizerCycle),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_ab_loop_set_a_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetA),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_ab_loop_set_b_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetB),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_ab_loop_clear_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopClear),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_chapter_next_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerChapterNext),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_chapter_previous_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerChapterPrevious),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_audiobook_bookmark_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalAudiobookBookmark),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_audiobook_show_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalAudiobookShow),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_switch_backend_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerSwitchBackend),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_radio_go_live_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerRadioGoLive),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_player_radio_record_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalPlayerRadioRecord),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_layout_radio_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalLayoutRadio),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_search_len = match self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSearch)))
+        {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_favorite_len = match self
+            .app
+            .state(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioFavorite)))
+        {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_delete_favorite_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::RadioDeleteFavorite),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_sort_cycle_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::RadioSortCycle),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_global_radio_history_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::GlobalRadioHistory),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_history_search_youtube_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::RadioHistorySearchYoutube),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_history_search_lyric_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::RadioHistorySearchLyric),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+        let select_radio_history_export_len = match self.app.state(&Id::ConfigEditor(
+            IdConfigEditor::Key(IdKey::RadioHistoryExport),
+        )) {
+            Ok(State::One(_)) => 3,
+            _ => 8,
+        };
+
+        assert!(self
+            .terminal
+            .raw_mut()
+            .draw(|f| {
+                let chunks_main = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(3),
+                            Constraint::Min(3),
+                            Constraint::Length(1),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(f.size());
+
+                let chunks_middle = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                            Constraint::Ratio(1, 4),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_main[1]);
+
+                let chunks_middle_column1 = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(select_global_player_output_device_len),
+                            Constraint::Length(select_global_visualizer_cycle_len),
+                            Constraint::Length(select_global_player_ab_loop_set_a_len),
+                            Constraint::Length(select_global_player_ab_loop_set_b_len),
+                            Constraint::Length(select_global_player_ab_loop_clear_len),
+                            Constraint::Length(select_global_player_chapter_next_len),
+                            Constraint::Length(select_global_player_chapter_previous_len),
+                            Constraint::Length(select_global_audiobook_bookmark_len),
+                            Constraint::Length(select_global_audiobook_show_len),
+                            Constraint::Min(0),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_middle[0]);
+                let chunks_middle_column2 = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(select_global_player_switch_backend_len),
+                            Constraint::Length(select_global_player_radio_go_live_len),
+                            Constraint::Length(select_global_player_radio_record_len),
+                            Constraint::Length(select_global_layout_radio_len),
+                            Constraint::Length(select_radio_search_len),
+                            Constraint::Length(select_radio_favorite_len),
+                            Constraint::Length(select_radio_delete_favorite_len),
+                            Constraint::Length(select_radio_sort_cycle_len),
+                            Constraint::Length(select_global_radio_history_len),
+                            Constraint::Min(0),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_middle[1]);
+                let chunks_middle_column3 = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Length(select_radio_history_search_youtube_len),
+                            Constraint::Length(select_radio_history_search_lyric_len),
+                            Constraint::Length(select_radio_history_export_len),
+                            Constraint::Min(0),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_middle[2]);
+                self.app
+                    .view(&Id::ConfigEditor(IdConfigEditor::Header), f, chunks_main[0]);
+                self.app
+                    .view(&Id::ConfigEditor(IdConfigEditor::Footer), f, chunks_main[2]);
+
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerOutputDevice)),
+                    f,
+                    chunks_middle_column1[0],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalVisualizerCycle)),
+                    f,
+                    chunks_middle_column1[1],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetA)),
+                    f,
+                    chunks_middle_column1[2],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetB)),
+                    f,
+                    chunks_middle_column1[3],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopClear)),
+                    f,
+                    chunks_middle_column1[4],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerChapterNext)),
+                    f,
+                    chunks_middle_column1[5],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerChapterPrevious)),
+                    f,
+                    chunks_middle_column1[6],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalAudiobookBookmark)),
+                    f,
+                    chunks_middle_column1[7],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalAudiobookShow)),
+                    f,
+                    chunks_middle_column1[8],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerSwitchBackend)),
+                    f,
+                    chunks_middle_column2[0],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerRadioGoLive)),
+                    f,
+                    chunks_middle_column2[1],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerRadioRecord)),
+                    f,
+                    chunks_middle_column2[2],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalLayoutRadio)),
+                    f,
+                    chunks_middle_column2[3],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSearch)),
+                    f,
+                    chunks_middle_column2[4],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioFavorite)),
+                    f,
+                    chunks_middle_column2[5],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioDeleteFavorite)),
+                    f,
+                    chunks_middle_column2[6],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSortCycle)),
+                    f,
+                    chunks_middle_column2[7],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalRadioHistory)),
+                    f,
+                    chunks_middle_column2[8],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistorySearchYoutube)),
+                    f,
+                    chunks_middle_column3[0],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistorySearchLyric)),
+                    f,
+                    chunks_middle_column3[1],
+                );
+                self.app.view(
+                    &Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistoryExport)),
+                    f,
+                    chunks_middle_column3[2],
+                );
+                Self::view_config_editor_commons(f, &mut self.app);
+            })
+            .is_ok());
+    }
+
     #[allow(clippy::too_many_lines)]
     pub fn mount_config_editor(&mut self) {
         self.config_layout = ConfigEditorLayout::General;
@@ -2454,6 +2790,174 @@ impl Model {
                 vec![],
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerOutputDevice)),
+                Box::new(ConfigGlobalPlayerOutputDevice::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalVisualizerCycle)),
+                Box::new(ConfigGlobalVisualizerCycle::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetA)),
+                Box::new(ConfigGlobalPlayerAbLoopSetA::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopSetB)),
+                Box::new(ConfigGlobalPlayerAbLoopSetB::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerAbLoopClear)),
+                Box::new(ConfigGlobalPlayerAbLoopClear::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerChapterNext)),
+                Box::new(ConfigGlobalPlayerChapterNext::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerChapterPrevious)),
+                Box::new(ConfigGlobalPlayerChapterPrevious::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalAudiobookBookmark)),
+                Box::new(ConfigGlobalAudiobookBookmark::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalAudiobookShow)),
+                Box::new(ConfigGlobalAudiobookShow::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerSwitchBackend)),
+                Box::new(ConfigGlobalPlayerSwitchBackend::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerRadioGoLive)),
+                Box::new(ConfigGlobalPlayerRadioGoLive::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalPlayerRadioRecord)),
+                Box::new(ConfigGlobalPlayerRadioRecord::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalLayoutRadio)),
+                Box::new(ConfigGlobalLayoutRadio::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSearch)),
+                Box::new(ConfigRadioSearch::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioFavorite)),
+                Box::new(ConfigRadioFavorite::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioDeleteFavorite)),
+                Box::new(ConfigRadioDeleteFavorite::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSortCycle)),
+                Box::new(ConfigRadioSortCycle::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::GlobalRadioHistory)),
+                Box::new(ConfigGlobalRadioHistory::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistorySearchYoutube)),
+                Box::new(ConfigRadioHistorySearchYoutube::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistorySearchLyric)),
+                Box::new(ConfigRadioHistorySearchLyric::new(config)),
+                vec![],
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioHistoryExport)),
+                Box::new(ConfigRadioHistoryExport::new(config)),
+                vec![],
+            )
+            .is_ok());
         self.theme_select_sync();
     }
 
@@ -2465,6 +2969,7 @@ impl Model {
         self.progress_reload();
         self.mount_label_help();
         self.lyric_reload();
+        self.visualizer_reload();
 
         assert!(self
             .app
@@ -2951,6 +3456,107 @@ impl Model {
                 IdKey::PodcastSearchAddFeed,
             )))
             .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerOutputDevice,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalVisualizerCycle,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerAbLoopSetA,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerAbLoopSetB,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerAbLoopClear,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerChapterNext,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerChapterPrevious,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalAudiobookBookmark,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalAudiobookShow,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerSwitchBackend,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerRadioGoLive,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalPlayerRadioRecord,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalLayoutRadio,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioSearch)))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(IdKey::RadioFavorite)))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::RadioDeleteFavorite,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::RadioSortCycle,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::GlobalRadioHistory,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::RadioHistorySearchYoutube,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::RadioHistorySearchLyric,
+            )))
+            .ok();
+        self.app
+            .umount(&Id::ConfigEditor(IdConfigEditor::Key(
+                IdKey::RadioHistoryExport,
+            )))
+            .ok();
         assert!(self
             .app
             .remount(
@@ -2971,7 +3577,8 @@ impl Model {
 
             ConfigEditorLayout::Color => self.config_layout = ConfigEditorLayout::Key1,
             ConfigEditorLayout::Key1 => self.config_layout = ConfigEditorLayout::Key2,
-            ConfigEditorLayout::Key2 => self.config_layout = ConfigEditorLayout::General,
+            ConfigEditorLayout::Key2 => self.config_layout = ConfigEditorLayout::Key3,
+            ConfigEditorLayout::Key3 => self.config_layout = ConfigEditorLayout::General,
         }
 
         let layout = self.config_layout.clone();
@@ -3002,6 +3609,12 @@ impl Model {
                     IdKey::LibraryTagEditor,
                 )))
                 .ok(),
+            ConfigEditorLayout::Key3 => self
+                .app
+                .active(&Id::ConfigEditor(IdConfigEditor::Key(
+                    IdKey::GlobalPlayerOutputDevice,
+                )))
+                .ok(),
         };
     }
 
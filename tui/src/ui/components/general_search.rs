@@ -436,7 +436,7 @@ impl Model {
                     if let Some(file_name_text_span) = line.get(3) {
                         let file_name = &file_name_text_span.content;
                         for (idx, item) in self.playlist.tracks().iter().enumerate() {
-                            if item.file() == Some(file_name) {
+                            if item.location().as_deref() == Some(file_name) {
                                 index = idx;
                                 matched = true;
                             }
@@ -464,7 +464,7 @@ impl Model {
                     if let Some(file_name_text_span) = line.get(3) {
                         let file_name = &file_name_text_span.content;
                         for (idx, item) in self.playlist.tracks().iter().enumerate() {
-                            if item.file() == Some(file_name) {
+                            if item.location().as_deref() == Some(file_name) {
                                 index = idx;
                                 matched = true;
                             }
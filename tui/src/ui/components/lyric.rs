@@ -1,7 +1,8 @@
 use crate::ui::{model::TermusicLayout, Model};
+use termusiclib::chapters::{self, Chapter};
 use termusiclib::config::Settings;
 use termusiclib::podcast::Episode;
-use termusiclib::track::MediaType;
+use termusiclib::track::{MediaType, Track};
 use termusiclib::types::{Id, LyricMsg, Msg};
 
 use anyhow::{anyhow, Result};
@@ -139,6 +140,18 @@ impl Model {
         self.lyric_set_lyric(&lyric_line);
     }
 
+    /// Use the chapters the server fetched for the current track.
+    pub fn chapters_update(&mut self, chapters: Vec<Chapter>) {
+        if let Some(track) = self.current_song.as_mut() {
+            track.set_chapters(chapters.clone());
+        }
+        if let Some(track) = self.playlist.current_track_as_mut() {
+            track.set_chapters(chapters);
+        }
+        self.lyric_chapter = None;
+        self.lyric_update_title();
+    }
+
     pub fn lyric_update_for_podcast_by_current_track(&mut self) {
         let mut need_update = false;
         let mut pod_title = String::new();
@@ -244,6 +257,16 @@ impl Model {
         final_vec.push(PropValue::TextSpan(TextSpan::from("Description:").bold()));
         final_vec.append(&mut lines_textspan);
 
+        if !ep.chapters.is_empty() {
+            final_vec.push(PropValue::TextSpan(TextSpan::from("   ")));
+            final_vec.push(PropValue::TextSpan(TextSpan::from("Chapters:").bold()));
+            final_vec.extend(
+                ep.chapters
+                    .iter()
+                    .map(|chapter| PropValue::TextSpan(TextSpan::from(chapter_line(chapter)))),
+            );
+        }
+
         self.app
             .attr(
                 &Id::Lyric,
@@ -269,6 +292,15 @@ impl Model {
                 return;
             }
 
+            if !song.chapters().is_empty() {
+                let current = chapters::current_index(song.chapters(), self.time_pos);
+                if self.lyric_chapter != Some(current) {
+                    let chapters = song.chapters().to_vec();
+                    self.lyric_set_chapters(&chapters, current);
+                }
+                return;
+            }
+
             let mut line = String::new();
             if song.lyric_frames_is_empty() {
                 self.lyric_set_lyric("No lyrics available.");
@@ -307,7 +339,41 @@ impl Model {
         }
     }
 
+    /// Show the chapters of the current track, the one playing highlighted.
+    fn lyric_set_chapters(&mut self, chapters: &[Chapter], current: Option<usize>) {
+        let symbol = self
+            .config
+            .style_color_symbol
+            .playlist_highlight_symbol
+            .clone();
+        let indent = " ".repeat(unicode_width::UnicodeWidthStr::width(symbol.as_str()));
+        let lines = chapters
+            .iter()
+            .enumerate()
+            .map(|(i, chapter)| {
+                let line = chapter_line(chapter);
+                PropValue::TextSpan(if Some(i) == current {
+                    TextSpan::from(format!("{symbol}{line}")).bold()
+                } else {
+                    TextSpan::from(format!("{indent}{line}"))
+                })
+            })
+            .collect();
+
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Text,
+                AttrValue::Payload(PropPayload::Vec(lines)),
+            )
+            .ok();
+        self.lyric_chapter = Some(current);
+        // show the lyric line again once there are no chapters
+        self.lyric_line.clear();
+    }
+
     fn lyric_set_lyric(&mut self, text: &str) {
+        self.lyric_chapter = None;
         self.app
             .attr(
                 &Id::Lyric,
@@ -353,7 +419,15 @@ impl Model {
                 Some(MediaType::Music) => {
                     let artist = track.artist().unwrap_or("Unknown Artist");
                     let title = track.title().unwrap_or("Unknown Title");
-                    lyric_title = format!(" Lyrics of {artist:^.20} - {title:^.20} ");
+                    let kind = if track.chapters().is_empty() {
+                        "Lyrics"
+                    } else {
+                        "Chapters"
+                    };
+                    lyric_title = format!(" {kind} of {artist:^.20} - {title:^.20} ");
+                }
+                Some(MediaType::Podcast) if !track.chapters().is_empty() => {
+                    lyric_title = " Chapters: ".to_string();
                 }
                 Some(MediaType::Podcast) => {
                     lyric_title = " Details: ".to_string();
@@ -377,3 +451,12 @@ impl Model {
             .ok();
     }
 }
+
+/// A chapter as its start and title.
+fn chapter_line(chapter: &Chapter) -> String {
+    format!(
+        "{}  {}",
+        Track::duration_formatted_short(&chapter.start),
+        chapter.title
+    )
+}
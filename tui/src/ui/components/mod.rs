@@ -22,6 +22,7 @@
  * SOFTWARE.
  */
 // -- modules
+mod audiobook;
 mod config_editor;
 mod database;
 mod general_search;
@@ -32,6 +33,8 @@ mod playlist;
 mod podcast;
 mod popups;
 mod progress;
+mod radio;
+mod radio_history;
 #[allow(
     clippy::match_bool,
     clippy::redundant_closure_for_method_calls,
@@ -39,10 +42,12 @@ mod progress;
     clippy::module_name_repetitions
 )]
 mod tag_editor;
+mod visualizer;
 mod xywh;
 mod youtube_search;
 
 // -- export
+pub use audiobook::{AudiobookBookmarkInputPopup, AudiobookRow, AudiobookTablePopup};
 pub use config_editor::*;
 pub use database::{DBListCriteria, DBListSearchResult, DBListSearchTracks};
 pub use general_search::{GSInputPopup, GSTablePopup, Source};
@@ -56,6 +61,9 @@ pub use popups::{
     SavePlaylistPopup,
 };
 pub use progress::Progress;
+pub use radio::{RadioFavorites, RadioSearchInputPopup, RadioStations};
+pub use radio_history::RadioHistoryTablePopup;
+pub use visualizer::{Visualizer, ATTR_LEVELS, ATTR_WAVEFORM};
 pub use youtube_search::{YSInputPopup, YSTablePopup};
 //Tag Editor Controls,
 pub use tag_editor::*;
@@ -64,7 +72,8 @@ use crate::config::Keys;
 // #[cfg(any(feature = "mpris", feature = "discord"))]
 // use crate::track::Track;
 use crate::ui::{
-    ConfigEditorMsg, GSMsg, Id, IdConfigEditor, IdTagEditor, Model, Msg, PLMsg, XYWHMsg, YSMsg,
+    AudiobookMsg, ConfigEditorMsg, GSMsg, Id, IdConfigEditor, IdTagEditor, Model, Msg, PLMsg,
+    RadioHistoryMsg, XYWHMsg, YSMsg,
 };
 use tui_realm_stdlib::Phantom;
 use tuirealm::event::NoUserEvent;
@@ -152,6 +161,41 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
             {
                 Some(Msg::PlayerSpeedDown)
             }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_ab_loop_set_a.key_event() =>
+            {
+                Some(Msg::PlayerAbLoopSetA)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_ab_loop_set_b.key_event() =>
+            {
+                Some(Msg::PlayerAbLoopSetB)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_ab_loop_clear.key_event() =>
+            {
+                Some(Msg::PlayerAbLoopClear)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_chapter_next.key_event() =>
+            {
+                Some(Msg::PlayerChapterNext)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_chapter_previous.key_event() =>
+            {
+                Some(Msg::PlayerChapterPrevious)
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_audiobook_bookmark.key_event() =>
+            {
+                Some(Msg::Audiobook(AudiobookMsg::BookmarkInputShow))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_audiobook_show.key_event() =>
+            {
+                Some(Msg::Audiobook(AudiobookMsg::TablePopupShow))
+            }
 
             Event::Keyboard(keyevent)
                 if keyevent == self.keys.global_lyric_adjust_forward.key_event() =>
@@ -185,12 +229,50 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
                 Some(Msg::LayoutPodCast)
             }
 
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_layout_radio.key_event() => {
+                Some(Msg::LayoutRadio)
+            }
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_radio_history.key_event() => {
+                Some(Msg::RadioHistory(RadioHistoryMsg::TablePopupShow))
+            }
+
             Event::Keyboard(keyevent)
                 if keyevent == self.keys.global_player_toggle_gapless.key_event() =>
             {
                 Some(Msg::PlayerToggleGapless)
             }
 
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_output_device.key_event() =>
+            {
+                Some(Msg::OutputDevicePopupShow)
+            }
+
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_switch_backend.key_event() =>
+            {
+                Some(Msg::PlayerSwitchBackend)
+            }
+
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_radio_go_live.key_event() =>
+            {
+                Some(Msg::PlayerRadioGoLive)
+            }
+
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_player_radio_record.key_event() =>
+            {
+                Some(Msg::PlayerRadioRecord)
+            }
+
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.global_visualizer_cycle.key_event() =>
+            {
+                Some(Msg::VisualizerCycle)
+            }
+
             Event::Keyboard(keyevent) if keyevent == self.keys.global_config_open.key_event() => {
                 Some(Msg::ConfigEditor(ConfigEditorMsg::Open))
             }
@@ -263,6 +345,34 @@ impl Model {
                 SubEventClause::Keyboard(keys.global_player_speed_down.key_event()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_ab_loop_set_a.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_ab_loop_set_b.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_ab_loop_clear.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_chapter_next.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_chapter_previous.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_audiobook_bookmark.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_audiobook_show.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.global_player_volume_minus_1.key_event()),
                 Self::no_popup_mounted_clause(),
@@ -315,6 +425,26 @@ impl Model {
                 SubEventClause::Keyboard(keys.global_player_toggle_gapless.key_event()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_output_device.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_switch_backend.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_radio_go_live.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_player_radio_record.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_visualizer_cycle.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.global_config_open.key_event()),
                 Self::no_popup_mounted_clause(),
@@ -327,6 +457,14 @@ impl Model {
                 SubEventClause::Keyboard(keys.global_layout_podcast.key_event()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_layout_radio.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.global_radio_history.key_event()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.global_xywh_move_left.key_event()),
                 SubClause::Always,
@@ -369,7 +507,22 @@ impl Model {
             Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmRadioPopup)),
             Box::new(SubClause::Or(
                 Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmInputPopup)),
-                Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                Box::new(SubClause::Or(
+                    Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                    Box::new(SubClause::Or(
+                        Box::new(SubClause::IsMounted(Id::OutputDeviceTablePopup)),
+                        Box::new(SubClause::Or(
+                            Box::new(SubClause::IsMounted(Id::AudiobookTablePopup)),
+                            Box::new(SubClause::Or(
+                                Box::new(SubClause::IsMounted(Id::AudiobookBookmarkInputPopup)),
+                                Box::new(SubClause::Or(
+                                    Box::new(SubClause::IsMounted(Id::RadioSearchInputPopup)),
+                                    Box::new(SubClause::IsMounted(Id::RadioHistoryTablePopup)),
+                                )),
+                            )),
+                        )),
+                    )),
+                )),
             )),
         )))
     }
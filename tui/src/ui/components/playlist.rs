@@ -4,6 +4,7 @@ use anyhow::{anyhow, bail, Result};
 use rand::seq::SliceRandom;
 use std::borrow::Cow;
 use std::path::Path;
+use termusiclib::playlist::cue;
 use termusiclib::sqlite::SearchCriteria;
 use termusiclib::sqlite::TrackForDB;
 use termusiclib::types::{GSMsg, Id, Msg, PLMsg};
@@ -288,7 +289,7 @@ impl Model {
     }
 
     pub fn playlist_add(&mut self, current_node: &str) -> Result<()> {
-        let p: &Path = Path::new(&current_node);
+        let p: &Path = Path::new(cue::location_file(current_node));
         if !p.exists() {
             return Ok(());
         }
@@ -523,7 +524,8 @@ impl Model {
                 let name = record.name().unwrap_or(&noname_string);
                 let artist = record.artist().unwrap_or(name);
                 let title = record.title().unwrap_or("Unknown Title");
-                let file_name = record.file().unwrap_or("no file");
+                let file_name = record.location();
+                let file_name = file_name.as_deref().unwrap_or("no file");
 
                 table
                     .add_col(TextSpan::new(duration_string.as_str()))
@@ -564,7 +566,9 @@ impl Model {
                     if record.title.contains("Unknown Title") {
                         continue;
                     }
-                    if filetype_supported(&record.file) {
+                    if filetype_supported(&record.file)
+                        || cue::parse_location(&record.file).is_some()
+                    {
                         result.push(record.clone());
                         i += 1;
                         if i > quantity - 1 {
@@ -3,7 +3,6 @@ use anyhow::{anyhow, bail, Result};
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use sanitize_filename::{sanitize_with_options, Options};
 use serde_json::Value;
-use std::time::Duration;
 use termusiclib::config::{Keys, Settings};
 use termusiclib::podcast::{download_list, EpData, PodcastFeed, PodcastNoId};
 use termusiclib::track::MediaType;
@@ -374,8 +373,7 @@ impl Model {
         let encoded: String = utf8_percent_encode(search_str, NON_ALPHANUMERIC).to_string();
         let url =
             format!("https://itunes.apple.com/search?media=podcast&entity=podcast&term={encoded}",);
-        let agent = reqwest::blocking::ClientBuilder::new()
-            .connect_timeout(Duration::from_secs(5))
+        let agent = termusiclib::http::blocking_client_builder()
             .build()
             .expect("error build client");
         // let result = agent.get(&url).call()?;
@@ -255,9 +255,45 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Playback speed up/down 10 percent"))
                         .add_row()
+                        .add_col(Self::key(&[
+                            keys.global_player_ab_loop_set_a,
+                            keys.global_player_ab_loop_set_b,
+                        ]))
+                        .add_col(Self::comment("Repeat from A to B, set A/B"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_player_ab_loop_clear]))
+                        .add_col(Self::comment("Clear A-B repeat"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            keys.global_player_chapter_previous,
+                            keys.global_player_chapter_next,
+                        ]))
+                        .add_col(Self::comment("Previous/Next chapter"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_audiobook_show]))
+                        .add_col(Self::comment("Audiobooks in progress and bookmarks"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_audiobook_bookmark]))
+                        .add_col(Self::comment("Bookmark the audiobook position"))
+                        .add_row()
                         .add_col(Self::key(&[keys.global_player_toggle_gapless]))
                         .add_col(Self::comment("Toggle gapless playback"))
                         .add_row()
+                        .add_col(Self::key(&[keys.global_player_output_device]))
+                        .add_col(Self::comment("Select audio output device"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_player_switch_backend]))
+                        .add_col(Self::comment("Switch to the next playback backend"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_player_radio_go_live]))
+                        .add_col(Self::comment("Catch up with a paused or rewound radio"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_player_radio_record]))
+                        .add_col(Self::comment("Record the radio, a file for every song"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_visualizer_cycle]))
+                        .add_col(Self::comment("Cycle visualizer position"))
+                        .add_row()
                         .add_col(Self::key(&[
                             keys.global_lyric_adjust_forward,
                             keys.global_lyric_adjust_backward,
@@ -297,6 +333,9 @@ impl HelpPopup {
                         .add_col(Self::key(&[keys.global_layout_podcast]))
                         .add_col(Self::comment("Switch layout to podcast"))
                         .add_row()
+                        .add_col(Self::key(&[keys.global_layout_radio]))
+                        .add_col(Self::comment("Switch layout to radio"))
+                        .add_row()
                         .add_col(Self::key(&[
                             keys.global_xywh_move_left,
                             keys.global_xywh_move_right,
@@ -415,6 +454,37 @@ impl HelpPopup {
                         .add_row()
                         .add_col(Self::key(&[keys.podcast_episode_delete_file]))
                         .add_col(Self::comment("Episode: delete episode local file"))
+                        .add_row()
+                        .add_col(TextSpan::new("Radio").bold().fg(Color::LightYellow))
+                        .add_row()
+                        .add_col(Self::key(&[keys.radio_search]))
+                        .add_col(Self::comment(
+                            "Search stations by name, tag:, country:, codec:",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[keys.radio_sort_cycle]))
+                        .add_col(Self::comment("Stations: cycle sort order"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_right]))
+                        .add_col(Self::comment("Add station to playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            keys.radio_favorite,
+                            keys.radio_delete_favorite,
+                        ]))
+                        .add_col(Self::comment("Add/delete favorite station"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.global_radio_history]))
+                        .add_col(Self::comment("Show titles the radio played"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            keys.radio_history_search_youtube,
+                            keys.radio_history_search_lyric,
+                        ]))
+                        .add_col(Self::comment("History: search title on youtube/lyric"))
+                        .add_row()
+                        .add_col(Self::key(&[keys.radio_history_export]))
+                        .add_col(Self::comment("History: export as csv"))
                         .build(),
                 ),
             keys: keys.clone(),
@@ -1226,6 +1296,114 @@ impl Component<Msg, NoUserEvent> for PodcastSearchTablePopup {
     }
 }
 
+#[derive(MockComponent)]
+pub struct OutputDeviceTablePopup {
+    component: Table,
+    keys: Keys,
+}
+
+impl OutputDeviceTablePopup {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Magenta),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::Magenta),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(" Enter to select output device: ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["", " Device "])
+                .column_spacing(1)
+                .widths(&[5, 95])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from("Loading..."))
+                        .build(),
+                ),
+            keys: config.keys.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for OutputDeviceTablePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::OutputDevicePopupCloseCancel)
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_quit.key_event() => {
+                return Some(Msg::OutputDevicePopupCloseCancel)
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::OutputDevicePopupCloseOk(index));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
 impl Model {
     pub fn mount_confirm_radio(&mut self) {
         assert!(self
@@ -1348,4 +1526,62 @@ impl Model {
             self.mount_error_popup(format!("update photo error: {e}"));
         }
     }
+
+    pub fn mount_output_device_table(&mut self, current: &str) {
+        assert!(self
+            .app
+            .remount(
+                Id::OutputDeviceTablePopup,
+                Box::new(OutputDeviceTablePopup::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::OutputDeviceTablePopup).is_ok());
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+        self.update_output_device_table(current);
+    }
+
+    pub fn update_output_device_table(&mut self, current: &str) {
+        let symbol = &self
+            .config
+            .style_color_symbol
+            .currently_playing_track_symbol;
+        let mark = |name: &str| {
+            if name == current {
+                symbol.clone()
+            } else {
+                String::new()
+            }
+        };
+        let mut table: TableBuilder = TableBuilder::default();
+        table
+            .add_col(TextSpan::new(mark("")))
+            .add_col(TextSpan::new("system default").bold());
+        for name in &self.output_devices {
+            table.add_row();
+            table
+                .add_col(TextSpan::new(mark(name)))
+                .add_col(TextSpan::new(name));
+        }
+        let table = table.build();
+
+        self.app
+            .attr(
+                &Id::OutputDeviceTablePopup,
+                tuirealm::Attribute::Content,
+                tuirealm::AttrValue::Table(table),
+            )
+            .ok();
+    }
+
+    pub fn umount_output_device_table(&mut self) {
+        if self.app.mounted(&Id::OutputDeviceTablePopup) {
+            assert!(self.app.umount(&Id::OutputDeviceTablePopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+    }
 }
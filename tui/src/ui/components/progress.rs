@@ -8,6 +8,10 @@ use tuirealm::event::NoUserEvent;
 use tuirealm::props::{Alignment, BorderType, Borders, Color, PropPayload, PropValue};
 use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent};
 
+/// How far behind its live edge a radio may play and still be shown as live, as what is
+/// downloaded comes in chunks
+const RADIO_LIVE_MARGIN: Duration = Duration::from_secs(2);
+
 #[derive(MockComponent)]
 pub struct Progress {
     component: ProgressBar,
@@ -154,12 +158,42 @@ impl Model {
                 &Id::Progress,
                 Attribute::Text,
                 AttrValue::String(format!(
-                    "{}    -    {}",
+                    "{}    -    {}{}{}",
                     Track::duration_formatted_short(&self.time_pos),
-                    Track::duration_formatted_short(&total_duration)
+                    Track::duration_formatted_short(&total_duration),
+                    self.progress_radio_live_label(),
+                    self.progress_ab_loop_label(),
                 )),
             )
             .ok();
         // self.force_redraw();
     }
+
+    /// Whether the radio playing is live or how far behind it is, to show after the time of
+    /// what can be gone back to of it, and whether it is recorded.
+    fn progress_radio_live_label(&self) -> String {
+        let live = match self.radio_behind_live {
+            Some(behind) if behind < RADIO_LIVE_MARGIN => "    |    LIVE".to_string(),
+            Some(behind) => format!("    |    -{}", Track::duration_formatted_short(&behind)),
+            None => String::new(),
+        };
+        if self.radio_recording {
+            format!("{live}    |    REC")
+        } else {
+            live
+        }
+    }
+
+    /// The marks of the A-B repeat, to show after the time.
+    fn progress_ab_loop_label(&self) -> String {
+        match (self.ab_loop.a, self.ab_loop.b) {
+            (Some(a), Some(b)) => format!(
+                "    |    A-B: {} - {}",
+                Track::duration_formatted_short(&a),
+                Track::duration_formatted_short(&b)
+            ),
+            (Some(a), None) => format!("    |    A: {} - ?", Track::duration_formatted_short(&a)),
+            _ => String::new(),
+        }
+    }
 }
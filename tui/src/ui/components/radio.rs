@@ -0,0 +1,550 @@
+//! The radio view: favorite stations, and searching the radio-browser directory for more.
+
+use crate::ui::model::Model;
+use termusiclib::config::{Keys, Settings};
+use termusiclib::radio_browser::{Client, Query, Station};
+use termusiclib::types::{Id, Msg, RadioMsg};
+use tui_realm_stdlib::{Input, List, Table};
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
+use tuirealm::props::{Alignment, BorderType, Borders, Color, InputType, TableBuilder, TextSpan};
+use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
+
+#[derive(MockComponent)]
+pub struct RadioFavorites {
+    component: List,
+    keys: Keys,
+}
+
+impl RadioFavorites {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: List::default()
+                .borders(
+                    Borders::default().modifiers(BorderType::Rounded).color(
+                        config
+                            .style_color_symbol
+                            .library_border()
+                            .unwrap_or(Color::Blue),
+                    ),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .title(" Favorite Stations: ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .rows(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from("Empty"))
+                        .build(),
+                ),
+            keys: config.keys.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioFavorites {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            Event::Keyboard(key) if key == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(key) if key == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Home,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent {
+                code: Key::End,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::End)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab,
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::Radio(RadioMsg::FavoritesBlurDown)),
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => return Some(Msg::Radio(RadioMsg::FavoritesBlurUp)),
+
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::FavoritePlay(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.global_right.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::FavoritePlay(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.radio_delete_favorite.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::FavoriteDelete(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.radio_search.key_event() => {
+                return Some(Msg::Radio(RadioMsg::SearchPopupShow));
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct RadioStations {
+    component: Table,
+    keys: Keys,
+}
+
+impl RadioStations {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .borders(
+                    Borders::default().modifiers(BorderType::Rounded).color(
+                        config
+                            .style_color_symbol
+                            .library_border()
+                            .unwrap_or(Color::Blue),
+                    ),
+                )
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Yellow),
+                )
+                .title(" Stations: ", Alignment::Left)
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Name", "Country", "Codec", "kbit/s", "Votes"])
+                .column_spacing(2)
+                .widths(&[50, 20, 10, 10, 10])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from(format!(
+                            "Press {} to search for stations",
+                            config.keys.radio_search
+                        )))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .build(),
+                ),
+            keys: config.keys.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioStations {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Down,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Up,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Move(Direction::Up)),
+            Event::Keyboard(key) if key == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(key) if key == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Home,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent {
+                code: Key::End,
+                modifiers: KeyModifiers::NONE,
+            }) => self.perform(Cmd::GoTo(Position::End)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Tab,
+                modifiers: KeyModifiers::NONE,
+            }) => return Some(Msg::Radio(RadioMsg::StationsBlurDown)),
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab,
+                modifiers: KeyModifiers::SHIFT,
+            }) => return Some(Msg::Radio(RadioMsg::StationsBlurUp)),
+
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter,
+                modifiers: KeyModifiers::NONE,
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::StationAdd(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.global_right.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::StationAdd(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.radio_favorite.key_event() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Radio(RadioMsg::FavoriteAdd(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(key) if key == self.keys.radio_search.key_event() => {
+                return Some(Msg::Radio(RadioMsg::SearchPopupShow));
+            }
+            Event::Keyboard(key) if key == self.keys.radio_sort_cycle.key_event() => {
+                return Some(Msg::Radio(RadioMsg::SortCycle));
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct RadioSearchInputPopup {
+    component: Input,
+}
+
+impl RadioSearchInputPopup {
+    pub fn new(config: &Settings, query: &Query) -> Self {
+        Self {
+            component: Input::default()
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Magenta),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::Magenta),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(query.to_string())
+                .title(
+                    " Search stations, like: jazz tag:smooth country:DE codec:mp3 ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioSearchInputPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Radio(RadioMsg::SearchPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => self.perform(Cmd::Submit),
+            _ => CmdResult::None,
+        };
+        match cmd_result {
+            CmdResult::Submit(State::One(StateValue::String(input_string))) => {
+                Some(Msg::Radio(RadioMsg::SearchPopupCloseOk(input_string)))
+            }
+            _ => Some(Msg::None),
+        }
+    }
+}
+
+impl Model {
+    pub fn mount_radio_search_popup(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioSearchInputPopup,
+                Box::new(RadioSearchInputPopup::new(&self.config, &self.radio_query)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioSearchInputPopup).is_ok());
+    }
+
+    pub fn umount_radio_search_popup(&mut self) {
+        if self.app.mounted(&Id::RadioSearchInputPopup) {
+            assert!(self.app.umount(&Id::RadioSearchInputPopup).is_ok());
+        }
+    }
+
+    /// Search the directory for the stations of `query` in the background, the result comes as
+    /// [`RadioMsg::SearchSuccess`] or [`RadioMsg::SearchError`].
+    pub fn radio_search(&mut self, query: Query) {
+        self.radio_query = query.clone();
+        self.radio_update_stations_title("searching...");
+        let base_url = self.config.radio_browser_url.clone();
+        let tx = self.tx_to_main.clone();
+        std::thread::spawn(move || {
+            let msg = match Client::new(&base_url).and_then(|client| client.search(&query)) {
+                Ok(stations) => RadioMsg::SearchSuccess(stations),
+                Err(e) => RadioMsg::SearchError(format!("{e:#}")),
+            };
+            tx.send(Msg::Radio(msg)).ok();
+        });
+    }
+
+    /// Search again for the same stations, sorted by the next order.
+    pub fn radio_sort_cycle(&mut self) {
+        let mut query = self.radio_query.clone();
+        query.order = query.order.next();
+        self.radio_search(query);
+    }
+
+    fn radio_update_stations_title(&mut self, status: &str) {
+        let query = self.radio_query.to_string();
+        let query = if query.is_empty() { "all" } else { &query };
+        let title = format!(
+            " Stations: {query} by {} ({status}), {} to sort ",
+            self.radio_query.order, self.config.keys.radio_sort_cycle
+        );
+        self.app
+            .attr(
+                &Id::RadioStations,
+                Attribute::Title,
+                AttrValue::Title((title, Alignment::Left)),
+            )
+            .ok();
+    }
+
+    pub fn radio_sync_stations(&mut self, stations: Vec<Station>) {
+        self.radio_stations = stations;
+        let mut table: TableBuilder = TableBuilder::default();
+        for (idx, station) in self.radio_stations.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let bitrate = if station.bitrate == 0 {
+                String::new()
+            } else {
+                station.bitrate.to_string()
+            };
+            let name = if self.radio_is_favorite(station) {
+                TextSpan::new(&station.name).bold()
+            } else {
+                TextSpan::new(&station.name)
+            };
+            table
+                .add_col(name)
+                .add_col(TextSpan::new(&station.country))
+                .add_col(TextSpan::new(&station.codec))
+                .add_col(TextSpan::new(bitrate))
+                .add_col(TextSpan::new(station.votes.to_string()));
+        }
+        if self.radio_stations.is_empty() {
+            table
+                .add_col(TextSpan::from("no stations found"))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""));
+        }
+        self.app
+            .attr(
+                &Id::RadioStations,
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+        let status = format!("{} found", self.radio_stations.len());
+        self.radio_update_stations_title(&status);
+    }
+
+    /// Read the favorites from the database and show them.
+    pub fn radio_sync_favorites(&mut self) {
+        match self.db.get_radio_favorites() {
+            Ok(favorites) => self.radio_favorites = favorites,
+            Err(e) => self.mount_error_popup(format!("error loading favorite stations: {e}")),
+        }
+        let mut table: TableBuilder = TableBuilder::default();
+        for (idx, station) in self.radio_favorites.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table.add_col(TextSpan::new(&station.name));
+        }
+        if self.radio_favorites.is_empty() {
+            table.add_col(TextSpan::from(format!(
+                "no favorites yet, {} on a station to add it",
+                self.config.keys.radio_favorite
+            )));
+        }
+        self.app
+            .attr(
+                &Id::RadioFavorites,
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+
+    fn radio_is_favorite(&self, station: &Station) -> bool {
+        self.radio_favorites
+            .iter()
+            .any(|favorite| favorite.uuid == station.uuid)
+    }
+
+    pub fn radio_favorite_add(&mut self, index: usize) {
+        let Some(station) = self.radio_stations.get(index).cloned() else {
+            return;
+        };
+        if let Err(e) = self.db.add_radio_favorite(&station) {
+            self.mount_error_popup(format!("error adding favorite station: {e}"));
+            return;
+        }
+        self.radio_sync_favorites();
+        let stations = std::mem::take(&mut self.radio_stations);
+        self.radio_sync_stations(stations);
+        self.show_message_timeout_label_help(
+            format!("{} added to favorites", station.name),
+            None,
+            None,
+            None,
+        );
+    }
+
+    pub fn radio_favorite_delete(&mut self, index: usize) {
+        let Some(uuid) = self.radio_favorites.get(index).map(|s| s.uuid.clone()) else {
+            return;
+        };
+        if let Err(e) = self.db.delete_radio_favorite(&uuid) {
+            self.mount_error_popup(format!("error deleting favorite station: {e}"));
+            return;
+        }
+        self.radio_sync_favorites();
+        let stations = std::mem::take(&mut self.radio_stations);
+        self.radio_sync_stations(stations);
+    }
+
+    /// Add `station` to the playlist.
+    pub fn radio_add_to_playlist(&mut self, station: &Station) {
+        self.playlist.add_radio(&station.url, &station.name);
+        if let Err(e) = self.player_sync_playlist() {
+            self.mount_error_popup(format!("sync playlist error: {e}"));
+        }
+        self.playlist_sync();
+        self.show_message_timeout_label_help(
+            format!("{} added to the playlist", station.name),
+            None,
+            None,
+            None,
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! The titles radio stations announced while they played, to look up or export.
+
+use crate::ui::model::Model;
+use std::str::FromStr;
+use std::sync::mpsc;
+use termusiclib::config::{Keys, Settings};
+use termusiclib::radio_history::to_csv;
+use termusiclib::songtag::lrc::Lyric;
+use termusiclib::songtag::search;
+use termusiclib::types::{Id, Msg, RadioHistoryMsg, SearchLyricState};
+use tui_realm_stdlib::Table;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::event::{Key, KeyEvent, NoUserEvent};
+use tuirealm::props::{
+    Alignment, AttrValue, Attribute, BorderType, Borders, Color, PropPayload, PropValue,
+    TableBuilder, TextSpan,
+};
+use tuirealm::{Component, Event, MockComponent, State, StateValue};
+
+/// How many of the latest titles the popup lists, the export has all of them
+const RADIO_HISTORY_SHOWN: u32 = 500;
+
+#[derive(MockComponent)]
+pub struct RadioHistoryTablePopup {
+    component: Table,
+    keys: Keys,
+}
+
+impl RadioHistoryTablePopup {
+    pub fn new(config: &Settings) -> Self {
+        Self {
+            component: Table::default()
+                .background(
+                    config
+                        .style_color_symbol
+                        .library_background()
+                        .unwrap_or(Color::Reset),
+                )
+                .foreground(
+                    config
+                        .style_color_symbol
+                        .library_foreground()
+                        .unwrap_or(Color::Magenta),
+                )
+                .borders(
+                    Borders::default()
+                        .color(
+                            config
+                                .style_color_symbol
+                                .library_border()
+                                .unwrap_or(Color::Magenta),
+                        )
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(
+                        " Radio history: {} search youtube, {} search lyric, {} export csv ",
+                        config.keys.radio_history_search_youtube,
+                        config.keys.radio_history_search_lyric,
+                        config.keys.radio_history_export
+                    ),
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(
+                    config
+                        .style_color_symbol
+                        .library_highlight()
+                        .unwrap_or(Color::LightBlue),
+                )
+                .highlighted_str(&config.style_color_symbol.library_highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&["Played", "Station", "Title"])
+                .column_spacing(2)
+                .widths(&[18, 27, 55])
+                .table(
+                    TableBuilder::default()
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from(""))
+                        .add_col(TextSpan::from("Loading..."))
+                        .build(),
+                ),
+            keys: config.keys.clone(),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RadioHistoryTablePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::RadioHistory(RadioHistoryMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_quit.key_event() => {
+                return Some(Msg::RadioHistory(RadioHistoryMsg::TablePopupCloseCancel))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_down.key_event() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_up.key_event() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_top.key_event() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.global_goto_bottom.key_event() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.radio_history_search_youtube.key_event() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioHistory(RadioHistoryMsg::SearchYoutube(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent)
+                if keyevent == self.keys.radio_history_search_lyric.key_event() =>
+            {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::RadioHistory(RadioHistoryMsg::SearchLyric(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == self.keys.radio_history_export.key_event() => {
+                return Some(Msg::RadioHistory(RadioHistoryMsg::ExportCsv));
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    pub fn mount_radio_history_table(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::RadioHistoryTablePopup,
+                Box::new(RadioHistoryTablePopup::new(&self.config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::RadioHistoryTablePopup).is_ok());
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+        self.radio_history_table_sync();
+    }
+
+    pub fn umount_radio_history_table(&mut self) {
+        if self.app.mounted(&Id::RadioHistoryTablePopup) {
+            assert!(self.app.umount(&Id::RadioHistoryTablePopup).is_ok());
+        }
+        if let Err(e) = self.update_photo() {
+            self.mount_error_popup(format!("update photo error: {e}"));
+        }
+    }
+
+    /// Read the latest titles from the database and show them.
+    pub fn radio_history_table_sync(&mut self) {
+        self.radio_history = match self.db.get_radio_history(Some(RADIO_HISTORY_SHOWN)) {
+            Ok(history) => history,
+            Err(e) => {
+                self.mount_error_popup(format!("error loading radio history: {e}"));
+                Vec::new()
+            }
+        };
+
+        let mut table: TableBuilder = TableBuilder::default();
+        for (idx, title) in self.radio_history.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            table
+                .add_col(TextSpan::new(title.played_at_local()))
+                .add_col(TextSpan::new(&title.station))
+                .add_col(TextSpan::new(&title.title).bold());
+        }
+        if self.radio_history.is_empty() {
+            table
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from(""))
+                .add_col(TextSpan::from("no radio titles heard yet"));
+        }
+
+        self.app
+            .attr(
+                &Id::RadioHistoryTablePopup,
+                Attribute::Content,
+                AttrValue::Table(table.build()),
+            )
+            .ok();
+    }
+
+    /// Search the title at `index` on youtube, to download it.
+    pub fn radio_history_search_youtube(&mut self, index: usize) {
+        let Some(title) = self.radio_history.get(index).map(|t| t.title.clone()) else {
+            return;
+        };
+        self.umount_radio_history_table();
+        self.mount_youtube_search_table();
+        self.youtube_options_search(&title);
+    }
+
+    /// Look for the lyric of the title at `index` with the lyric providers, the first one found
+    /// comes as [`RadioHistoryMsg::LyricFound`].
+    pub fn radio_history_search_lyric(&mut self, index: usize) {
+        let Some(title) = self.radio_history.get(index).map(|t| t.title.clone()) else {
+            return;
+        };
+        self.show_message_timeout_label_help(
+            format!("Searching lyric of {title}..."),
+            None,
+            None,
+            None,
+        );
+        let tx = self.tx_to_main.clone();
+        std::thread::spawn(move || {
+            let (tx_search, rx_search) = mpsc::channel();
+            search(&title, tx_search);
+            let lyric = match rx_search.recv() {
+                Ok(SearchLyricState::Finish(results)) => results
+                    .iter()
+                    .filter_map(|result| result.fetch_lyric().ok())
+                    .find(|lyric| !lyric.trim().is_empty()),
+                Err(_) => None,
+            };
+            let msg = match lyric {
+                Some(lyric) => RadioHistoryMsg::LyricFound(title, lyric),
+                None => RadioHistoryMsg::LyricNotFound(title),
+            };
+            tx.send(Msg::RadioHistory(msg)).ok();
+        });
+    }
+
+    /// Show the lyric found for `title` in the lyric pane, until the radio announces the next one.
+    pub fn radio_history_show_lyric(&mut self, title: &str, lyric: &str) {
+        let text = Lyric::from_str(lyric).map_or_else(|_| lyric.to_string(), |l| l.as_plain_text());
+        let mut lines = vec![
+            PropValue::TextSpan(TextSpan::from(title).bold()),
+            PropValue::TextSpan(TextSpan::from("   ")),
+        ];
+        lines.extend(
+            text.lines()
+                .map(|line| PropValue::TextSpan(TextSpan::from(line))),
+        );
+        self.lyric_chapter = None;
+        self.app
+            .attr(
+                &Id::Lyric,
+                Attribute::Text,
+                AttrValue::Payload(PropPayload::Vec(lines)),
+            )
+            .ok();
+    }
+
+    /// Write the whole history to `radio_history.csv` in the music directory.
+    pub fn radio_history_export(&mut self) {
+        let history = match self.db.get_radio_history(None) {
+            Ok(history) => history,
+            Err(e) => {
+                self.mount_error_popup(format!("error loading radio history: {e}"));
+                return;
+            }
+        };
+        let path = self.path.join("radio_history.csv");
+        match std::fs::write(&path, to_csv(&history)) {
+            Ok(()) => self.show_message_timeout_label_help(
+                format!("{} titles exported to {}", history.len(), path.display()),
+                None,
+                None,
+                None,
+            ),
+            Err(e) => self.mount_error_popup(format!("error exporting radio history: {e}")),
+        }
+    }
+}
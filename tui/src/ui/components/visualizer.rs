@@ -0,0 +1,252 @@
+//! Spectrum bars or an oscilloscope of what the server plays.
+
+use crate::ui::model::Model;
+use termusiclib::config::{Settings, VisualizerStyle};
+use termusiclib::types::{Id, Msg};
+use termusicplayback::player::SpectrumReply;
+use tuirealm::command::{Cmd, CmdResult};
+use tuirealm::event::NoUserEvent;
+use tuirealm::props::{AttrValue, Attribute, Color, PropPayload, PropValue};
+use tuirealm::tui::buffer::Buffer;
+use tuirealm::tui::layout::Rect;
+use tuirealm::tui::style::Style;
+use tuirealm::tui::symbols::Marker;
+use tuirealm::tui::widgets::canvas::{Canvas, Line};
+use tuirealm::tui::widgets::{Block, BorderType, Borders, Widget};
+use tuirealm::{Component, Event, Frame, MockComponent, State};
+
+/// Attribute holding the waveform, [`Attribute::Value`] holds the bands.
+pub const ATTR_WAVEFORM: Attribute = Attribute::Custom("waveform");
+/// Attribute holding the peak and rms level in dB.
+pub const ATTR_LEVELS: Attribute = Attribute::Custom("levels");
+
+/// Eighths of a cell, from empty to full.
+const BAR_SYMBOLS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+pub struct Visualizer {
+    style: VisualizerStyle,
+    title: String,
+    bands: Vec<f32>,
+    waveform: Vec<f32>,
+    /// Peak and rms level in dB
+    levels: Option<(f32, f32)>,
+    border: Color,
+    foreground: Color,
+    background: Color,
+}
+
+impl Visualizer {
+    pub fn new(config: &Settings) -> Self {
+        let colors = &config.style_color_symbol;
+        Self {
+            style: config.visualizer_style,
+            title: " Visualizer ".to_string(),
+            bands: Vec::new(),
+            waveform: Vec::new(),
+            levels: None,
+            border: colors.lyric_border().unwrap_or(Color::Green),
+            foreground: colors.lyric_foreground().unwrap_or(Color::Cyan),
+            background: colors.lyric_background().unwrap_or(Color::Reset),
+        }
+    }
+
+    fn block(&self) -> Block<'_> {
+        let title = match self.levels {
+            Some((peak, rms)) => format!("{}peak {peak:.1} dB rms {rms:.1} dB ", self.title),
+            None => self.title.clone(),
+        };
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.border))
+            .style(Style::default().bg(self.background))
+            .title(title)
+    }
+}
+
+fn floats(value: AttrValue) -> Vec<f32> {
+    match value {
+        AttrValue::Payload(PropPayload::Vec(values)) => values
+            .into_iter()
+            .filter_map(|v| match v {
+                PropValue::F32(v) => Some(v),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl MockComponent for Visualizer {
+    #[allow(clippy::cast_precision_loss)]
+    fn view(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let block = self.block();
+        match self.style {
+            VisualizerStyle::Bars => {
+                let inner = block.inner(area);
+                frame.render_widget(block, area);
+                frame.render_widget(
+                    Bars {
+                        bands: &self.bands,
+                        color: self.foreground,
+                    },
+                    inner,
+                );
+            }
+            VisualizerStyle::Oscilloscope => {
+                let waveform = &self.waveform;
+                let color = self.foreground;
+                let canvas = Canvas::default()
+                    .block(block)
+                    .marker(Marker::Braille)
+                    .background_color(self.background)
+                    .x_bounds([0.0, waveform.len().saturating_sub(1) as f64])
+                    .y_bounds([-1.0, 1.0])
+                    .paint(|ctx| {
+                        for (x, pair) in waveform.windows(2).enumerate() {
+                            ctx.draw(&Line {
+                                x1: x as f64,
+                                y1: f64::from(pair[0]),
+                                x2: (x + 1) as f64,
+                                y2: f64::from(pair[1]),
+                                color,
+                            });
+                        }
+                    });
+                frame.render_widget(canvas, area);
+            }
+        }
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        match attr {
+            Attribute::Title => Some(AttrValue::String(self.title.clone())),
+            _ => None,
+        }
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match attr {
+            Attribute::Value => self.bands = floats(value),
+            ATTR_WAVEFORM => self.waveform = floats(value),
+            ATTR_LEVELS => {
+                self.levels = match value {
+                    AttrValue::Payload(PropPayload::Tup2((
+                        PropValue::F32(peak),
+                        PropValue::F32(rms),
+                    ))) => Some((peak, rms)),
+                    _ => None,
+                };
+            }
+            Attribute::Title => {
+                if let AttrValue::String(title) = value {
+                    self.title = title;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for Visualizer {
+    fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
+
+/// One bar per column, the bands stretched over the width.
+struct Bars<'a> {
+    bands: &'a [f32],
+    color: Color,
+}
+
+impl Widget for Bars<'_> {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.bands.is_empty() || area.width == 0 {
+            return;
+        }
+        for column in 0..area.width {
+            let band = usize::from(column) * self.bands.len() / usize::from(area.width);
+            let eighths = (self.bands[band].clamp(0.0, 1.0) * f32::from(area.height) * 8.0) as u32;
+            for row in 0..area.height {
+                let filled = eighths.saturating_sub(u32::from(row) * 8).min(8);
+                buf.get_mut(area.x + column, area.bottom() - 1 - row)
+                    .set_symbol(BAR_SYMBOLS[filled as usize])
+                    .set_fg(self.color);
+            }
+        }
+    }
+}
+
+impl Model {
+    pub fn visualizer_reload(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::Visualizer,
+                Box::new(Visualizer::new(&self.config)),
+                Vec::new()
+            )
+            .is_ok());
+    }
+
+    /// Show the latest data from the server.
+    pub fn visualizer_update(&mut self, reply: SpectrumReply) {
+        let payload = |values: Vec<f32>| {
+            AttrValue::Payload(PropPayload::Vec(
+                values.into_iter().map(PropValue::F32).collect(),
+            ))
+        };
+        // the loudest channel, silence shows as -100 dB
+        let db = |levels: &[f32]| 20.0 * levels.iter().fold(1e-5_f32, |a, b| a.max(*b)).log10();
+
+        self.app
+            .attr(
+                &Id::Visualizer,
+                ATTR_LEVELS,
+                AttrValue::Payload(PropPayload::Tup2((
+                    PropValue::F32(db(&reply.peak)),
+                    PropValue::F32(db(&reply.rms)),
+                ))),
+            )
+            .ok();
+        self.app
+            .attr(&Id::Visualizer, Attribute::Value, payload(reply.bands))
+            .ok();
+        self.app
+            .attr(&Id::Visualizer, ATTR_WAVEFORM, payload(reply.waveform))
+            .ok();
+        self.redraw = true;
+    }
+
+    /// Show why there is nothing to see, `None` once there is.
+    pub fn visualizer_set_error(&mut self, error: Option<&str>) {
+        let title = match error {
+            Some(error) => format!(" Visualizer: {error} "),
+            None => " Visualizer ".to_string(),
+        };
+        self.app
+            .attr(&Id::Visualizer, Attribute::Title, AttrValue::String(title))
+            .ok();
+    }
+
+    pub fn visualizer_cycle(&mut self) {
+        self.config.visualizer_position = self.config.visualizer_position.next();
+        // the album art makes room for the visualizer and comes back after it
+        self.update_photo().ok();
+        self.force_redraw();
+    }
+}
@@ -30,6 +30,7 @@ use image::io::Reader as ImageReader;
 use image::DynamicImage;
 use lofty::Picture;
 use std::io::Write;
+use termusiclib::config::VisualizerPosition;
 use termusiclib::track::MediaType;
 use termusiclib::types::{DLMsg, Id, IdConfigEditor, IdTagEditor, ImageWrapper, Msg};
 
@@ -73,6 +74,12 @@ impl Model {
         if self.app.mounted(&Id::PodcastSearchTablePopup) {
             return true;
         }
+        if self.app.mounted(&Id::OutputDeviceTablePopup) {
+            return true;
+        }
+        if self.app.mounted(&Id::AudiobookTablePopup) {
+            return true;
+        }
 
         if self.app.mounted(&Id::TagEditor(IdTagEditor::InputTitle)) {
             return true;
@@ -94,6 +101,10 @@ impl Model {
             return true;
         }
 
+        if self.config.visualizer_position == VisualizerPosition::AlbumArt {
+            return true;
+        }
+
         false
     }
 
@@ -142,7 +153,8 @@ impl Model {
                 }
                 let tx = self.tx_to_main.clone();
                 std::thread::spawn(move || -> Result<()> {
-                    match reqwest::blocking::get(&url) {
+                    let client = termusiclib::http::blocking_client_builder().build()?;
+                    match client.get(&url).send() {
                         Ok(result) => {
                             // let reader = BufReader::new(result.bytes().unwrap());
                             let mut reader = result.bytes()?.reader();
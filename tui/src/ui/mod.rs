@@ -28,17 +28,21 @@ mod playback;
 use anyhow::Result;
 use model::{Model, TermusicLayout};
 use playback::Playback;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use sysinfo::System;
-use termusiclib::config::Settings;
+use termusiclib::config::{Settings, VisualizerPosition};
 pub use termusiclib::types::*;
-use termusicplayback::{PlayerCmd, PlayerProgress, Status};
-use tokio::sync::mpsc::{self, UnboundedReceiver};
+use termusicplayback::player::SpectrumReply;
+use termusicplayback::{AbLoop, PlayerCmd, PlayerProgress, Status};
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{self, Receiver, UnboundedReceiver};
 use tuirealm::application::PollStrategy;
 use tuirealm::{Application, Update};
 // -- internal
 
 const FORCED_REDRAW_INTERVAL: Duration = Duration::from_millis(1000);
+/// How long to wait before subscribing to the spectrum again after it failed.
+const SPECTRUM_RETRY_INTERVAL: Duration = Duration::from_secs(5);
 
 // Let's define the messages handled by our app. NOTE: it must derive `PartialEq`
 
@@ -47,6 +51,9 @@ pub struct UI {
     model: Model,
     playback: Playback,
     cmd_rx: UnboundedReceiver<PlayerCmd>,
+    /// Subscribed while the visualizer is shown
+    spectrum_rx: Option<Receiver<SpectrumReply>>,
+    spectrum_last_subscribe: Option<Instant>,
 }
 
 impl UI {
@@ -68,6 +75,8 @@ impl UI {
             model,
             playback,
             cmd_rx,
+            spectrum_rx: None,
+            spectrum_last_subscribe: None,
         })
     }
 
@@ -102,6 +111,7 @@ impl UI {
                 self.model.run();
             }
             self.run_playback().await?;
+            self.run_spectrum().await;
             progress_interval += 1;
             if progress_interval >= 80 {
                 progress_interval = 0;
@@ -164,6 +174,7 @@ impl UI {
             .set_current_track_index(current_track_index);
         self.model.playlist_locate(current_track_index);
         self.model.current_song = self.model.playlist.current_track().cloned();
+        self.model.lyric_chapter = None;
         self.model.update_layout_for_current_track();
         self.model.player_update_current_track_after();
 
@@ -207,6 +218,55 @@ impl UI {
         }
     }
 
+    /// Keep a spectrum subscription while the visualizer is shown and show the latest data.
+    async fn run_spectrum(&mut self) {
+        if self.model.config.visualizer_position == VisualizerPosition::Hidden {
+            // the server stops analyzing once no client is subscribed
+            self.spectrum_rx = None;
+            self.spectrum_last_subscribe = None;
+            return;
+        }
+
+        if self.spectrum_rx.is_none() {
+            if self
+                .spectrum_last_subscribe
+                .map_or(false, |last| last.elapsed() < SPECTRUM_RETRY_INTERVAL)
+            {
+                return;
+            }
+            self.spectrum_last_subscribe = Some(Instant::now());
+            match self.playback.subscribe_spectrum().await {
+                Ok(rx) => {
+                    self.spectrum_rx = Some(rx);
+                    self.model.visualizer_set_error(None);
+                }
+                Err(e) => {
+                    info!("spectrum not available: {e}");
+                    self.model.visualizer_set_error(Some("not available"));
+                    return;
+                }
+            }
+        }
+
+        let Some(rx) = &mut self.spectrum_rx else {
+            return;
+        };
+        let mut latest = None;
+        loop {
+            match rx.try_recv() {
+                Ok(reply) => latest = Some(reply),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.spectrum_rx = None;
+                    break;
+                }
+            }
+        }
+        if let Some(reply) = latest {
+            self.model.visualizer_update(reply);
+        }
+    }
+
     async fn run_playback(&mut self) -> Result<()> {
         if let Ok(cmd) = self.cmd_rx.try_recv() {
             match cmd {
@@ -222,6 +282,12 @@ impl UI {
                 PlayerCmd::GetProgress => {
                     let response = self.playback.get_progress().await?;
                     let pprogress: PlayerProgress = response.progress.unwrap_or_default().into();
+                    self.model.ab_loop = AbLoop {
+                        a: response.ab_loop_a.map(Into::into),
+                        b: response.ab_loop_b.map(Into::into),
+                    };
+                    self.model.radio_behind_live = response.radio_behind_live.map(Into::into);
+                    self.model.radio_recording = response.radio_recording;
                     self.model.progress_update(
                         pprogress.position,
                         pprogress.total_duration.unwrap_or_default(),
@@ -229,13 +295,57 @@ impl UI {
                     if response.current_track_updated {
                         self.handle_current_track_index(response.current_track_index as usize);
                     }
+                    if response.chapters_updated {
+                        let chapters = response.chapters.into_iter().map(Into::into).collect();
+                        self.model.chapters_update(chapters);
+                    }
 
                     self.model.lyric_update_for_radio(&response.radio_title);
+                    if response.backend != self.model.backend {
+                        if !self.model.backend.is_empty() {
+                            self.model.show_message_timeout_label_help(
+                                format!("Switched to the {} backend", response.backend),
+                                None,
+                                None,
+                                None,
+                            );
+                        }
+                        self.model
+                            .playlist
+                            .set_backend(response.backend.parse().ok());
+                        self.model.backend = response.backend;
+                    }
+                    if response.stream_state != self.model.stream_state {
+                        if !response.stream_state.is_empty() {
+                            self.model.show_message_timeout_label_help(
+                                format!("Stream {}", response.stream_state),
+                                None,
+                                None,
+                                None,
+                            );
+                        } else if self.model.stream_state.starts_with("reconnecting") {
+                            self.model.show_message_timeout_label_help(
+                                "Stream reconnected",
+                                None,
+                                None,
+                                None,
+                            );
+                        }
+                        self.model.stream_state = response.stream_state;
+                    }
 
                     self.handle_status(Status::from_u32(response.status));
                 }
 
                 PlayerCmd::CycleLoop => self.playback.cycle_loop().await?,
+                PlayerCmd::AbLoopSetA => self.playback.ab_loop_set_a().await?,
+                PlayerCmd::AbLoopSetB => self.playback.ab_loop_set_b().await?,
+                PlayerCmd::AbLoopClear => self.playback.ab_loop_clear().await?,
+                PlayerCmd::ChapterNext => self.playback.chapter_next().await?,
+                PlayerCmd::ChapterPrevious => self.playback.chapter_previous().await?,
+                PlayerCmd::AudiobookJump(file, position) => {
+                    self.playback.audiobook_jump(file, position).await?;
+                }
                 PlayerCmd::PlaySelected => {
                     self.playback.play_selected().await?;
                     // self.model.playlist.clear_current_track();
@@ -273,6 +383,26 @@ impl UI {
                     self.model.config.player_gapless = self.playback.toggle_gapless().await?;
                     self.model.progress_update_title();
                 }
+                PlayerCmd::SwitchBackend(kind) => {
+                    self.playback.switch_backend(kind).await?;
+                }
+                PlayerCmd::RadioGoLive => {
+                    self.playback.radio_go_live().await?;
+                }
+                PlayerCmd::RadioRecord => {
+                    let recording = self.playback.radio_record().await?;
+                    self.model.radio_recording = recording;
+                    let message = if recording {
+                        format!(
+                            "Recording the radio to {}",
+                            self.model.config.player_radio_record_dir
+                        )
+                    } else {
+                        "Stopped recording the radio".to_string()
+                    };
+                    self.model
+                        .show_message_timeout_label_help(message, None, None, None);
+                }
                 PlayerCmd::VolumeDown => {
                     let volume = self.playback.volume_down().await?;
                     self.model.config.player_volume = volume;
@@ -283,6 +413,12 @@ impl UI {
                     self.model.config.player_volume = volume;
                     self.model.progress_update_title();
                 }
+                PlayerCmd::GetOutputDevices => {
+                    let (devices, current) = self.playback.get_output_devices().await?;
+                    self.model.output_devices = devices;
+                    self.model.mount_output_device_table(&current);
+                }
+                PlayerCmd::SetOutputDevice(name) => self.playback.set_output_device(name).await?,
                 _ => {}
             }
         }
@@ -24,6 +24,7 @@
 mod update;
 mod view;
 mod youtube_options;
+use crate::ui::components::AudiobookRow;
 use crate::ui::Application;
 use termusiclib::sqlite::{DataBase, SearchCriteria};
 use termusiclib::types::{Id, Msg, SearchLyricState, YoutubeOptions};
@@ -40,13 +41,15 @@ use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
 use termusiclib::config::{Keys, StyleColorSymbol};
 use termusiclib::podcast::{db::Database as DBPod, Podcast, PodcastFeed, Threadpool};
+use termusiclib::radio_browser::{Query, Station};
+use termusiclib::radio_history::RadioTitle;
 use termusiclib::songtag::SongTag;
 use termusiclib::sqlite::TrackForDB;
 // use termusiclib::track::MediaType;
 use termusiclib::utils::{get_app_config_path, DownloadTracker};
 // use termusicplayback::{GeneralPlayer, PlayerMsg, PlayerTrait};
 use anyhow::Result;
-use termusicplayback::{PlayerCmd, Playlist};
+use termusicplayback::{AbLoop, PlayerCmd, Playlist};
 use tokio::sync::mpsc::UnboundedSender;
 use tui_realm_treeview::Tree;
 use tuirealm::event::NoUserEvent;
@@ -57,6 +60,7 @@ pub enum TermusicLayout {
     TreeView,
     DataBase,
     Podcast,
+    Radio,
 }
 
 #[derive(PartialEq, Clone, Eq)]
@@ -65,6 +69,7 @@ pub enum ConfigEditorLayout {
     Color,
     Key1,
     Key2,
+    Key3,
 }
 
 pub struct Model {
@@ -84,7 +89,11 @@ pub struct Model {
     pub current_song: Option<Track>,
     pub tageditor_song: Option<Track>,
     pub time_pos: Duration,
+    /// A-B repeat on the current track, as the server reported it
+    pub ab_loop: AbLoop,
     pub lyric_line: String,
+    /// Highlighted chapter of the chapter list in the lyric pane, `None` if it shows no list
+    pub lyric_chapter: Option<Option<usize>>,
     youtube_options: YoutubeOptions,
     #[cfg(feature = "cover")]
     pub ueberzug_instance: UeInstance,
@@ -110,6 +119,25 @@ pub struct Model {
     pub tx_to_main: Sender<Msg>,
     pub rx_to_main: Receiver<Msg>,
     pub podcast_search_vec: Option<Vec<PodcastFeed>>,
+    pub output_devices: Vec<String>,
+    /// The backend the server plays with, empty until the first progress
+    pub backend: String,
+    /// How the connection of the stream playing is doing, empty while it is connected
+    pub stream_state: String,
+    /// How far the radio playing is behind its live edge, `None` unless the backend can go back
+    /// in it
+    pub radio_behind_live: Option<Duration>,
+    /// Whether the radio playing is being recorded
+    pub radio_recording: bool,
+    /// The stations the last search of the radio view found
+    pub radio_stations: Vec<Station>,
+    pub radio_favorites: Vec<Station>,
+    /// What the radio view searched for last
+    pub radio_query: Query,
+    /// What the radio history popup lists
+    pub radio_history: Vec<RadioTitle>,
+    /// What the audiobook popup lists
+    pub audiobook_rows: Vec<AudiobookRow>,
     pub playlist: Playlist,
     pub cmd_tx: UnboundedSender<PlayerCmd>,
 }
@@ -175,7 +203,9 @@ impl Model {
             // current_song: None,
             tageditor_song: None,
             time_pos: Duration::default(),
+            ab_loop: AbLoop::default(),
             lyric_line: String::new(),
+            lyric_chapter: None,
 
             // TODO: Consider making YoutubeOptions async and use async reqwest in YoutubeOptions
             // and avoid this `spawn_blocking` call.
@@ -206,6 +236,16 @@ impl Model {
             rx_to_main,
             download_tracker: DownloadTracker::default(),
             podcast_search_vec: None,
+            output_devices: Vec::new(),
+            backend: String::new(),
+            stream_state: String::new(),
+            radio_behind_live: None,
+            radio_recording: false,
+            radio_stations: Vec::new(),
+            radio_favorites: Vec::new(),
+            radio_query: Query::default(),
+            radio_history: Vec::new(),
+            audiobook_rows: Vec::new(),
             playlist,
             cmd_tx,
             current_song: None,
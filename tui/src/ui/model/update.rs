@@ -1,11 +1,13 @@
 use crate::ui::{model::TermusicLayout, Model};
 use std::thread::{self, sleep};
 use std::time::Duration;
+use termusiclib::radio_browser::Query;
 use termusiclib::sqlite::SearchCriteria;
 // use termusiclib::track::MediaType;
 use termusiclib::track::MediaType;
 use termusiclib::types::{
-    DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Msg, PCMsg, PLMsg, XYWHMsg, YSMsg,
+    AudiobookMsg, DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Msg, PCMsg, PLMsg,
+    RadioHistoryMsg, RadioMsg, XYWHMsg, YSMsg,
 };
 use termusicplayback::PlayerCmd;
 /**
@@ -88,12 +90,34 @@ impl Update<Msg> for Model {
 
                 Msg::PlayerTogglePause
                 | Msg::PlayerToggleGapless
+                | Msg::PlayerSwitchBackend
+                | Msg::PlayerRadioGoLive
+                | Msg::PlayerRadioRecord
                 | Msg::PlayerSpeedUp
                 | Msg::PlayerSpeedDown
                 | Msg::PlayerVolumeUp
                 | Msg::PlayerVolumeDown
                 | Msg::PlayerSeekForward
-                | Msg::PlayerSeekBackward => self.update_player(&msg),
+                | Msg::PlayerSeekBackward
+                | Msg::PlayerAbLoopSetA
+                | Msg::PlayerAbLoopSetB
+                | Msg::PlayerAbLoopClear
+                | Msg::PlayerChapterNext
+                | Msg::PlayerChapterPrevious => self.update_player(&msg),
+
+                Msg::OutputDevicePopupShow
+                | Msg::OutputDevicePopupCloseCancel
+                | Msg::OutputDevicePopupCloseOk(_) => self.update_output_device(&msg),
+
+                Msg::Audiobook(m) => {
+                    self.update_audiobook(&m);
+                    None
+                }
+
+                Msg::VisualizerCycle => {
+                    self.visualizer_cycle();
+                    None
+                }
 
                 Msg::HelpPopupShow => {
                     self.mount_help_popup();
@@ -128,9 +152,10 @@ impl Update<Msg> for Model {
                     }
                     None
                 }
-                Msg::LayoutDataBase | Msg::LayoutTreeView | Msg::LayoutPodCast => {
-                    self.update_layout(&msg)
-                }
+                Msg::LayoutDataBase
+                | Msg::LayoutTreeView
+                | Msg::LayoutPodCast
+                | Msg::LayoutRadio => self.update_layout(&msg),
 
                 Msg::None => None,
                 Msg::SavePlaylistPopupShow => {
@@ -168,6 +193,14 @@ impl Update<Msg> for Model {
                     None
                 }
                 Msg::Podcast(m) => self.update_podcast(&m),
+                Msg::Radio(m) => {
+                    self.update_radio(m);
+                    None
+                }
+                Msg::RadioHistory(m) => {
+                    self.update_radio_history(&m);
+                    None
+                }
                 Msg::LyricMessage(m) => self.update_lyric_textarea(&m),
                 Msg::Download(m) => self.update_download_msg(&m),
                 Msg::Xywh(m) => self.update_xywh_msg(&m),
@@ -201,11 +234,52 @@ impl Model {
                 TermusicLayout::TreeView => self.app.active(&Id::Library).ok(),
                 TermusicLayout::DataBase => self.app.active(&Id::DBListCriteria).ok(),
                 TermusicLayout::Podcast => self.app.active(&Id::Podcast).ok(),
+                TermusicLayout::Radio => self.app.active(&Id::RadioFavorites).ok(),
             },
         };
         None
     }
 
+    fn update_radio(&mut self, msg: RadioMsg) {
+        match msg {
+            RadioMsg::FavoritesBlurDown | RadioMsg::StationsBlurUp => {
+                self.app.active(&Id::RadioStations).ok();
+            }
+            RadioMsg::StationsBlurDown => {
+                self.app.active(&Id::Playlist).ok();
+            }
+            RadioMsg::FavoritesBlurUp => {
+                self.app.active(&Id::Lyric).ok();
+            }
+            RadioMsg::SearchPopupShow => self.mount_radio_search_popup(),
+            RadioMsg::SearchPopupCloseCancel => self.umount_radio_search_popup(),
+            RadioMsg::SearchPopupCloseOk(input) => {
+                self.umount_radio_search_popup();
+                let mut query = Query::parse(&input);
+                query.order = self.radio_query.order;
+                self.radio_search(query);
+            }
+            RadioMsg::SearchSuccess(stations) => self.radio_sync_stations(stations),
+            RadioMsg::SearchError(e) => {
+                self.radio_sync_stations(Vec::new());
+                self.mount_error_popup(format!("error searching stations: {e}"));
+            }
+            RadioMsg::SortCycle => self.radio_sort_cycle(),
+            RadioMsg::StationAdd(index) => {
+                if let Some(station) = self.radio_stations.get(index).cloned() {
+                    self.radio_add_to_playlist(&station);
+                }
+            }
+            RadioMsg::FavoriteAdd(index) => self.radio_favorite_add(index),
+            RadioMsg::FavoritePlay(index) => {
+                if let Some(station) = self.radio_favorites.get(index).cloned() {
+                    self.radio_add_to_playlist(&station);
+                }
+            }
+            RadioMsg::FavoriteDelete(index) => self.radio_favorite_delete(index),
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     fn update_podcast(&mut self, msg: &PCMsg) -> Option<Msg> {
         match msg {
@@ -411,9 +485,9 @@ impl Model {
                 self.player_toggle_pause();
             }
             Msg::PlayerSeekForward => {
-                if self.is_radio() {
+                if self.is_radio() && self.radio_behind_live.is_none() {
                     self.show_message_timeout_label_help(
-                        "seek is not available for live radio",
+                        "seek is not available for this live radio",
                         None,
                         None,
                         None,
@@ -423,9 +497,9 @@ impl Model {
                 self.command(&PlayerCmd::SeekForward);
             }
             Msg::PlayerSeekBackward => {
-                if self.is_radio() {
+                if self.is_radio() && self.radio_behind_live.is_none() {
                     self.show_message_timeout_label_help(
-                        "seek is not available for live radio",
+                        "seek is not available for this live radio",
                         None,
                         None,
                         None,
@@ -449,6 +523,110 @@ impl Model {
             Msg::PlayerToggleGapless => {
                 self.command(&PlayerCmd::ToggleGapless);
             }
+            Msg::PlayerSwitchBackend => {
+                self.command(&PlayerCmd::SwitchBackend(None));
+            }
+            Msg::PlayerRadioGoLive => {
+                if self.radio_behind_live.is_none() {
+                    self.show_message_timeout_label_help(
+                        "going live is only available while playing a radio with the rusty backend",
+                        None,
+                        None,
+                        None,
+                    );
+                    return None;
+                }
+                self.command(&PlayerCmd::RadioGoLive);
+            }
+            Msg::PlayerRadioRecord if !self.is_radio() && !self.radio_recording => {
+                self.show_message_timeout_label_help(
+                    "recording is only available while playing a radio",
+                    None,
+                    None,
+                    None,
+                );
+            }
+            Msg::PlayerRadioRecord => {
+                self.command(&PlayerCmd::RadioRecord);
+            }
+            Msg::PlayerAbLoopSetA | Msg::PlayerAbLoopSetB if self.is_radio() => {
+                self.show_message_timeout_label_help(
+                    "A-B repeat is not available for live radio",
+                    None,
+                    None,
+                    None,
+                );
+            }
+            Msg::PlayerAbLoopSetA => {
+                self.command(&PlayerCmd::AbLoopSetA);
+            }
+            Msg::PlayerAbLoopSetB => {
+                self.command(&PlayerCmd::AbLoopSetB);
+            }
+            Msg::PlayerAbLoopClear => {
+                self.command(&PlayerCmd::AbLoopClear);
+            }
+            Msg::PlayerChapterNext => {
+                self.command(&PlayerCmd::ChapterNext);
+            }
+            Msg::PlayerChapterPrevious => {
+                self.command(&PlayerCmd::ChapterPrevious);
+            }
+            _ => {}
+        }
+        None
+    }
+    fn update_audiobook(&mut self, msg: &AudiobookMsg) {
+        match msg {
+            AudiobookMsg::BookmarkInputShow => self.mount_audiobook_bookmark_input(),
+            AudiobookMsg::BookmarkInputCloseCancel => self.umount_audiobook_bookmark_input(),
+            AudiobookMsg::BookmarkInputCloseOk(name) => {
+                self.umount_audiobook_bookmark_input();
+                self.audiobook_add_bookmark(name);
+            }
+            AudiobookMsg::TablePopupShow => self.mount_audiobook_table(),
+            AudiobookMsg::TablePopupCloseCancel => self.umount_audiobook_table(),
+            AudiobookMsg::TablePopupCloseOk(index) => self.audiobook_jump(*index),
+            AudiobookMsg::TablePopupDelete(index) => self.audiobook_delete(*index),
+        }
+    }
+
+    fn update_radio_history(&mut self, msg: &RadioHistoryMsg) {
+        match msg {
+            RadioHistoryMsg::TablePopupShow => self.mount_radio_history_table(),
+            RadioHistoryMsg::TablePopupCloseCancel => self.umount_radio_history_table(),
+            RadioHistoryMsg::SearchYoutube(index) => self.radio_history_search_youtube(*index),
+            RadioHistoryMsg::SearchLyric(index) => self.radio_history_search_lyric(*index),
+            RadioHistoryMsg::LyricFound(title, lyric) => {
+                self.umount_radio_history_table();
+                self.radio_history_show_lyric(title, lyric);
+            }
+            RadioHistoryMsg::LyricNotFound(title) => {
+                self.mount_error_popup(format!("no lyric found for {title}"));
+            }
+            RadioHistoryMsg::ExportCsv => self.radio_history_export(),
+        }
+    }
+
+    fn update_output_device(&mut self, msg: &Msg) -> Option<Msg> {
+        match msg {
+            Msg::OutputDevicePopupShow => {
+                self.command(&PlayerCmd::GetOutputDevices);
+            }
+            Msg::OutputDevicePopupCloseCancel => self.umount_output_device_table(),
+            Msg::OutputDevicePopupCloseOk(index) => {
+                // the first row is the system default device
+                let name = if *index == 0 {
+                    String::new()
+                } else if let Some(name) = self.output_devices.get(index - 1) {
+                    name.clone()
+                } else {
+                    return None;
+                };
+                self.umount_output_device_table();
+                self.config.player_output_device = name.clone();
+                self.command(&PlayerCmd::SetOutputDevice(name));
+            }
             _ => {}
         }
         None
@@ -544,6 +722,31 @@ impl Model {
                 self.playlist_switch_layout();
                 None
             }
+
+            Msg::LayoutRadio => {
+                let mut need_to_set_focus = true;
+                for id in [
+                    Id::RadioFavorites,
+                    Id::RadioStations,
+                    Id::Playlist,
+                    Id::Lyric,
+                ] {
+                    if let Ok(Some(AttrValue::Flag(true))) = self.app.query(&id, Attribute::Focus) {
+                        need_to_set_focus = false;
+                    }
+                }
+                if need_to_set_focus {
+                    self.app.active(&Id::RadioStations).ok();
+                }
+
+                self.layout = TermusicLayout::Radio;
+                self.radio_sync_favorites();
+                if self.radio_stations.is_empty() {
+                    self.radio_search(self.radio_query.clone());
+                }
+                self.playlist_switch_layout();
+                None
+            }
             _ => None,
         }
     }
@@ -793,6 +996,7 @@ impl Model {
                 TermusicLayout::TreeView => assert!(self.app.active(&Id::Library).is_ok()),
                 TermusicLayout::DataBase => assert!(self.app.active(&Id::DBListCriteria).is_ok()),
                 TermusicLayout::Podcast => assert!(self.app.active(&Id::Lyric).is_ok()),
+                TermusicLayout::Radio => assert!(self.app.active(&Id::Lyric).is_ok()),
             },
             PLMsg::NextSong => {
                 self.command(&PlayerCmd::SkipNext);
@@ -827,6 +1031,7 @@ impl Model {
                     assert!(self.app.active(&Id::DBListSearchTracks).is_ok());
                 }
                 TermusicLayout::Podcast => assert!(self.app.active(&Id::Episode).is_ok()),
+                TermusicLayout::Radio => assert!(self.app.active(&Id::RadioStations).is_ok()),
             },
         }
     }
@@ -963,7 +1168,8 @@ impl Model {
                     self.update_layout(&Msg::LayoutPodCast);
                 }
                 Some(MediaType::Music | MediaType::LiveRadio) => match self.layout {
-                    TermusicLayout::TreeView | TermusicLayout::DataBase => {}
+                    TermusicLayout::TreeView | TermusicLayout::DataBase | TermusicLayout::Radio => {
+                    }
                     TermusicLayout::Podcast => {
                         self.update_layout(&Msg::LayoutTreeView);
                     }
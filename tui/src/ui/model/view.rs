@@ -1,8 +1,9 @@
 use crate::ui::components::{
     DBListCriteria, DBListSearchResult, DBListSearchTracks, DownloadSpinner, EpisodeList,
     ErrorPopup, FeedsList, GSInputPopup, GSTablePopup, GlobalListener, HelpPopup, LabelSpan, Lyric,
-    MessagePopup, MusicLibrary, Playlist, PodcastAddPopup, Progress, QuitPopup,
-    SavePlaylistConfirm, SavePlaylistPopup, Source, YSInputPopup, YSTablePopup,
+    MessagePopup, MusicLibrary, Playlist, PodcastAddPopup, Progress, QuitPopup, RadioFavorites,
+    RadioStations, SavePlaylistConfirm, SavePlaylistPopup, Source, Visualizer, YSInputPopup,
+    YSTablePopup,
 };
 use crate::ui::model::{ConfigEditorLayout, Model, TermusicLayout};
 use crate::ui::Application;
@@ -31,7 +32,7 @@ use std::time::{Duration, Instant};
  * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
  * SOFTWARE.
  */
-use termusiclib::config::Settings;
+use termusiclib::config::{Settings, VisualizerPosition};
 use termusiclib::types::{DBMsg, Id, IdConfigEditor, IdTagEditor, Msg, PCMsg};
 use termusiclib::utils::{
     draw_area_in_absolute, draw_area_in_relative, draw_area_top_right_absolute, get_parent_folder,
@@ -40,7 +41,7 @@ use termusiclib::VERSION;
 use tui_realm_treeview::Tree;
 use tuirealm::event::NoUserEvent;
 use tuirealm::props::{AttrValue, Attribute, Color, PropPayload, PropValue, TextSpan};
-use tuirealm::tui::layout::{Constraint, Direction, Layout};
+use tuirealm::tui::layout::{Constraint, Direction, Layout, Rect};
 use tuirealm::tui::widgets::Clear;
 use tuirealm::EventListenerCfg;
 use tuirealm::{Frame, State, StateValue};
@@ -108,6 +109,9 @@ impl Model {
         assert!(app
             .mount(Id::Lyric, Box::new(Lyric::new(config)), vec![])
             .is_ok());
+        assert!(app
+            .mount(Id::Visualizer, Box::new(Visualizer::new(config)), vec![])
+            .is_ok());
 
         assert!(app
             .mount(
@@ -132,6 +136,20 @@ impl Model {
                 vec![]
             )
             .is_ok());
+        assert!(app
+            .mount(
+                Id::RadioFavorites,
+                Box::new(RadioFavorites::new(config)),
+                vec![]
+            )
+            .is_ok());
+        assert!(app
+            .mount(
+                Id::RadioStations,
+                Box::new(RadioStations::new(config)),
+                vec![]
+            )
+            .is_ok());
         assert!(app
             .mount(
                 Id::DownloadSpinner,
@@ -169,6 +187,7 @@ impl Model {
                     ConfigEditorLayout::Color => self.view_config_editor_color(),
                     ConfigEditorLayout::Key1 => self.view_config_editor_key1(),
                     ConfigEditorLayout::Key2 => self.view_config_editor_key2(),
+                    ConfigEditorLayout::Key3 => self.view_config_editor_key3(),
                 }
                 return;
             }
@@ -177,6 +196,7 @@ impl Model {
                 TermusicLayout::TreeView => self.view_layout_treeview(),
                 TermusicLayout::DataBase => self.view_layout_database(),
                 TermusicLayout::Podcast => self.view_layout_podcast(),
+                TermusicLayout::Radio => self.view_layout_radio(),
             }
         }
     }
@@ -218,14 +238,67 @@ impl Model {
                 self.app.view(&Id::Podcast, f, chunks_left[0]);
                 self.app.view(&Id::Episode, f, chunks_left[1]);
                 self.app.view(&Id::Playlist, f, chunks_right[0]);
-                self.app.view(&Id::Lyric, f, chunks_right[1]);
+                Self::view_lyric_pane(
+                    f,
+                    &mut self.app,
+                    self.config.visualizer_position,
+                    chunks_right[1],
+                );
                 self.app.view(&Id::Progress, f, chunks_main[1]);
                 self.app.view(&Id::Label, f, chunks_main[2]);
+                Self::view_visualizer_album_art(f, &mut self.app, &self.config);
+
+                Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
+            })
+            .is_ok());
+    }
+    pub fn view_layout_radio(&mut self) {
+        assert!(self
+            .terminal
+            .raw_mut()
+            .draw(|f| {
+                let chunks_main = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints([Constraint::Min(2), Constraint::Length(1)].as_ref())
+                    .split(f.size());
+                let chunks_left = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(0)
+                    .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)].as_ref())
+                    .split(chunks_main[0]);
+                let chunks_right = Layout::default()
+                    .direction(Direction::Vertical)
+                    .margin(0)
+                    .constraints(
+                        [
+                            Constraint::Percentage(45),
+                            Constraint::Min(2),
+                            Constraint::Length(3),
+                            Constraint::Length(4),
+                        ]
+                        .as_ref(),
+                    )
+                    .split(chunks_left[1]);
+
+                self.app.view(&Id::RadioFavorites, f, chunks_left[0]);
+                self.app.view(&Id::RadioStations, f, chunks_right[0]);
+                self.app.view(&Id::Playlist, f, chunks_right[1]);
+                self.app.view(&Id::Progress, f, chunks_right[2]);
+                Self::view_lyric_pane(
+                    f,
+                    &mut self.app,
+                    self.config.visualizer_position,
+                    chunks_right[3],
+                );
+                self.app.view(&Id::Label, f, chunks_main[1]);
+                Self::view_visualizer_album_art(f, &mut self.app, &self.config);
 
                 Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
             })
             .is_ok());
     }
+
     pub fn view_layout_database(&mut self) {
         assert!(self
             .terminal
@@ -276,7 +349,13 @@ impl Model {
 
                 self.app.view(&Id::Playlist, f, chunks_right[0]);
                 self.app.view(&Id::Progress, f, chunks_right[1]);
-                self.app.view(&Id::Lyric, f, chunks_right[2]);
+                Self::view_lyric_pane(
+                    f,
+                    &mut self.app,
+                    self.config.visualizer_position,
+                    chunks_right[2],
+                );
+                Self::view_visualizer_album_art(f, &mut self.app, &self.config);
                 Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
             })
             .is_ok());
@@ -313,14 +392,66 @@ impl Model {
                 self.app.view(&Id::Library, f, chunks_left[0]);
                 self.app.view(&Id::Playlist, f, chunks_right[0]);
                 self.app.view(&Id::Progress, f, chunks_right[1]);
-                self.app.view(&Id::Lyric, f, chunks_right[2]);
+                Self::view_lyric_pane(
+                    f,
+                    &mut self.app,
+                    self.config.visualizer_position,
+                    chunks_right[2],
+                );
                 self.app.view(&Id::Label, f, chunks_main[1]);
+                Self::view_visualizer_album_art(f, &mut self.app, &self.config);
 
                 Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
             })
             .is_ok());
     }
 
+    /// The lyric pane, with the visualizer in its place or beside it.
+    fn view_lyric_pane(
+        f: &mut Frame<'_>,
+        app: &mut Application<Id, Msg, NoUserEvent>,
+        position: VisualizerPosition,
+        area: Rect,
+    ) {
+        match position {
+            VisualizerPosition::Lyric => app.view(&Id::Visualizer, f, area),
+            VisualizerPosition::BesideLyric => {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .margin(0)
+                    .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)].as_ref())
+                    .split(area);
+                app.view(&Id::Lyric, f, chunks[0]);
+                app.view(&Id::Visualizer, f, chunks[1]);
+            }
+            VisualizerPosition::Hidden | VisualizerPosition::AlbumArt => {
+                app.view(&Id::Lyric, f, area);
+            }
+        }
+    }
+
+    /// The visualizer where the album art would be, if it takes its place.
+    fn view_visualizer_album_art(
+        f: &mut Frame<'_>,
+        app: &mut Application<Id, Msg, NoUserEvent>,
+        config: &Settings,
+    ) {
+        if config.visualizer_position != VisualizerPosition::AlbumArt {
+            return;
+        }
+        let size = f.size();
+        let (x, y, width, height) = config
+            .album_photo_xywh
+            .square_area(u32::from(size.width), u32::from(size.height));
+        let cells = |v: u32| u16::try_from(v).unwrap_or(u16::MAX);
+        let area = Rect::new(cells(x), cells(y), cells(width), cells(height)).intersection(size);
+        if area.width < 3 || area.height < 3 {
+            return;
+        }
+        f.render_widget(Clear, area);
+        app.view(&Id::Visualizer, f, area);
+    }
+
     #[allow(clippy::too_many_lines)]
     fn view_layout_commons(
         f: &mut Frame<'_>,
@@ -410,6 +541,22 @@ impl Model {
             let popup = draw_area_in_relative(f.size(), 65, 68);
             f.render_widget(Clear, popup);
             app.view(&Id::PodcastSearchTablePopup, f, popup);
+        } else if app.mounted(&Id::AudiobookTablePopup) {
+            let popup = draw_area_in_relative(f.size(), 65, 68);
+            f.render_widget(Clear, popup);
+            app.view(&Id::AudiobookTablePopup, f, popup);
+        } else if app.mounted(&Id::RadioHistoryTablePopup) {
+            let popup = draw_area_in_relative(f.size(), 75, 68);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioHistoryTablePopup, f, popup);
+        } else if app.mounted(&Id::AudiobookBookmarkInputPopup) {
+            let popup = draw_area_in_absolute(f.size(), 50, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::AudiobookBookmarkInputPopup, f, popup);
+        } else if app.mounted(&Id::OutputDeviceTablePopup) {
+            let popup = draw_area_in_relative(f.size(), 50, 50);
+            f.render_widget(Clear, popup);
+            app.view(&Id::OutputDeviceTablePopup, f, popup);
         } else if app.mounted(&Id::SavePlaylistPopup) {
             let popup = draw_area_in_absolute(f.size(), 76, 6);
             f.render_widget(Clear, popup);
@@ -427,6 +574,10 @@ impl Model {
             let popup = draw_area_in_absolute(f.size(), 65, 3);
             f.render_widget(Clear, popup);
             app.view(&Id::PodcastAddPopup, f, popup);
+        } else if app.mounted(&Id::RadioSearchInputPopup) {
+            let popup = draw_area_in_absolute(f.size(), 65, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::RadioSearchInputPopup, f, popup);
         }
         if app.mounted(&Id::MessagePopup) {
             let popup = draw_area_top_right_absolute(f.size(), 25, 4);
@@ -688,6 +839,20 @@ impl Model {
                                 .library_highlight()
                                 .unwrap_or(Color::Cyan))
                             .bold(),
+                        TextSpan::new(" Radio: ")
+                            .fg(self
+                                .config
+                                .style_color_symbol
+                                .library_foreground()
+                                .unwrap_or(Color::Blue))
+                            .bold(),
+                        TextSpan::new(format!("<{}>", self.config.keys.global_layout_radio))
+                            .fg(self
+                                .config
+                                .style_color_symbol
+                                .library_highlight()
+                                .unwrap_or(Color::Cyan))
+                            .bold(),
                     ]
                 )),
                 Vec::default(),
@@ -1,12 +1,19 @@
 use anyhow::Result;
+use std::time::Duration;
+use termusiclib::config::BackendKind;
 use termusicplayback::player::music_player_client::MusicPlayerClient;
 use termusicplayback::player::{
-    CycleLoopRequest, GetProgressRequest, GetProgressResponse, PlaySelectedRequest,
-    ReloadConfigRequest, ReloadPlaylistRequest, SeekBackwardRequest, SeekForwardRequest,
-    SkipNextRequest, SkipPreviousRequest, SpeedDownRequest, SpeedUpRequest, ToggleGaplessRequest,
-    TogglePauseRequest, VolumeDownRequest, VolumeUpRequest,
+    AbLoopClearRequest, AbLoopSetARequest, AbLoopSetBRequest, AudiobookJumpRequest,
+    ChapterNextRequest, ChapterPreviousRequest, CycleLoopRequest, GetOutputDevicesRequest,
+    GetProgressRequest, GetProgressResponse, PlaySelectedRequest, RadioGoLiveRequest,
+    RadioRecordRequest, ReloadConfigRequest, ReloadPlaylistRequest, SeekBackwardRequest,
+    SeekForwardRequest, SetOutputDeviceRequest, SkipNextRequest, SkipPreviousRequest,
+    SpectrumReply, SpeedDownRequest, SpeedUpRequest, SubscribeSpectrumRequest,
+    SwitchBackendRequest, ToggleGaplessRequest, TogglePauseRequest, VolumeDownRequest,
+    VolumeUpRequest,
 };
 use termusicplayback::{PlayerProgress, Status};
+use tokio::sync::mpsc;
 use tonic::transport::Channel;
 
 pub struct Playback {
@@ -70,6 +77,45 @@ impl Playback {
         Ok(())
     }
 
+    pub async fn ab_loop_set_a(&mut self) -> Result<()> {
+        let request = tonic::Request::new(AbLoopSetARequest {});
+        self.client.ab_loop_set_a(request).await?;
+        Ok(())
+    }
+
+    pub async fn ab_loop_set_b(&mut self) -> Result<()> {
+        let request = tonic::Request::new(AbLoopSetBRequest {});
+        self.client.ab_loop_set_b(request).await?;
+        Ok(())
+    }
+
+    pub async fn ab_loop_clear(&mut self) -> Result<()> {
+        let request = tonic::Request::new(AbLoopClearRequest {});
+        self.client.ab_loop_clear(request).await?;
+        Ok(())
+    }
+
+    pub async fn chapter_next(&mut self) -> Result<()> {
+        let request = tonic::Request::new(ChapterNextRequest {});
+        self.client.chapter_next(request).await?;
+        Ok(())
+    }
+
+    pub async fn chapter_previous(&mut self) -> Result<()> {
+        let request = tonic::Request::new(ChapterPreviousRequest {});
+        self.client.chapter_previous(request).await?;
+        Ok(())
+    }
+
+    pub async fn audiobook_jump(&mut self, file: String, position: Duration) -> Result<()> {
+        let request = tonic::Request::new(AudiobookJumpRequest {
+            file,
+            position: Some(position.into()),
+        });
+        self.client.audiobook_jump(request).await?;
+        Ok(())
+    }
+
     pub async fn speed_up(&mut self) -> Result<i32> {
         let request = tonic::Request::new(SpeedUpRequest {});
         let response = self.client.speed_up(request).await?;
@@ -94,6 +140,32 @@ impl Playback {
         Ok(response.gapless)
     }
 
+    pub async fn switch_backend(&mut self, kind: Option<BackendKind>) -> Result<()> {
+        let request = tonic::Request::new(SwitchBackendRequest {
+            backend: kind.map(|kind| kind.to_string()).unwrap_or_default(),
+        });
+        let response = self.client.switch_backend(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn radio_go_live(&mut self) -> Result<()> {
+        let request = tonic::Request::new(RadioGoLiveRequest {});
+        let response = self.client.radio_go_live(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn radio_record(&mut self) -> Result<bool> {
+        let request = tonic::Request::new(RadioRecordRequest {});
+        let response = self.client.radio_record(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.recording)
+    }
+
     pub async fn seek_forward(&mut self) -> Result<PlayerProgress> {
         let request = tonic::Request::new(SeekForwardRequest {});
         let response = self.client.seek_forward(request).await?;
@@ -139,4 +211,35 @@ impl Playback {
         info!("Got response from server: {:?}", response);
         Ok(())
     }
+
+    pub async fn get_output_devices(&mut self) -> Result<(Vec<String>, String)> {
+        let request = tonic::Request::new(GetOutputDevicesRequest {});
+        let response = self.client.get_output_devices(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok((response.devices, response.current))
+    }
+
+    pub async fn set_output_device(&mut self, name: String) -> Result<()> {
+        let request = tonic::Request::new(SetOutputDeviceRequest { name });
+        let response = self.client.set_output_device(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    /// Receive the spectrum of what is playing until the receiver is dropped.
+    pub async fn subscribe_spectrum(&mut self) -> Result<mpsc::Receiver<SpectrumReply>> {
+        let request = tonic::Request::new(SubscribeSpectrumRequest {});
+        let mut stream = self.client.subscribe_spectrum(request).await?.into_inner();
+        let (tx, rx) = mpsc::channel(2);
+        tokio::spawn(async move {
+            while let Ok(Some(reply)) = stream.message().await {
+                if tx.send(reply).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
 }